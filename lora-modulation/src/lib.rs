@@ -17,6 +17,14 @@ pub enum Bandwidth {
     _125KHz,
     _250KHz,
     _500KHz,
+    /// 2.4 GHz band only (SX128x)
+    _203KHz,
+    /// 2.4 GHz band only (SX128x)
+    _406KHz,
+    /// 2.4 GHz band only (SX128x)
+    _812KHz,
+    /// 2.4 GHz band only (SX128x)
+    _1625KHz,
 }
 
 impl Bandwidth {
@@ -32,8 +40,21 @@ impl Bandwidth {
             Bandwidth::_125KHz => 125000u32,
             Bandwidth::_250KHz => 250000u32,
             Bandwidth::_500KHz => 500000u32,
+            Bandwidth::_203KHz => 203125u32,
+            Bandwidth::_406KHz => 406250u32,
+            Bandwidth::_812KHz => 812500u32,
+            Bandwidth::_1625KHz => 1625000u32,
         }
     }
+
+    /// Whether the bandwidth belongs to the 2.4 GHz LoRa modulation of SX128x radios, which uses
+    /// different time on air and low data rate optimization rules
+    pub const fn is_2g4(self) -> bool {
+        matches!(
+            self,
+            Bandwidth::_203KHz | Bandwidth::_406KHz | Bandwidth::_812KHz | Bandwidth::_1625KHz
+        )
+    }
 }
 
 impl From<Bandwidth> for u32 {
@@ -119,13 +140,17 @@ impl BaseBandModulationParams {
     /// Create a set of parameters, possible forcing low data rate optimization on or off.
     /// Low data rate optimization is determined automatically
     /// based on `sf` and `bw` according to Semtech's datasheets for SX126x/SX127x
-    /// (enabled if symbol length is >= 16.38ms)
+    /// (enabled if symbol length is >= 16.38ms). SX128x always use it with SF11 and SF12.
     pub const fn new(sf: SpreadingFactor, bw: Bandwidth, cr: CodingRate) -> Self {
         let t_sym_us = 2u32.pow(sf.factor()) * 1_000_000 / bw.hz();
         // according to SX127x 4.1.1.6 it's 16ms
         // SX126x says it's 16.38ms
         // probably it's 16.384ms which is SF11@125kHz
-        let ldro = t_sym_us >= 16_384;
+        let ldro = if bw.is_2g4() {
+            sf.factor() >= 11
+        } else {
+            t_sym_us >= 16_384
+        };
         Self { sf, bw, cr, ldro, t_sym_us }
     }
 
@@ -145,6 +170,9 @@ impl BaseBandModulationParams {
         explicit_header: bool,
         len: u8,
     ) -> u32 {
+        if self.bw.is_2g4() {
+            return self.time_on_air_2g4_us(preamble, explicit_header, len);
+        }
         let sf = self.sf.factor() as i32;
         let t_sym_us = self.t_sym_us;
 
@@ -177,6 +205,48 @@ impl BaseBandModulationParams {
             Some(preamble) => (4 * preamble as u32 + 17 + 4 * payload_symb_nb) * t_sym_us / 4,
         }
     }
+
+    // Time on air according to the SX1280/SX1281 datasheet, section 7.4.4, computed from the exact
+    // symbol duration as the 2.4 GHz bandwidths do not divide it into whole microseconds.
+    const fn time_on_air_2g4_us(
+        &self,
+        preamble: Option<u8>,
+        explicit_header: bool,
+        len: u8,
+    ) -> u32 {
+        let sf = self.sf.factor() as i32;
+        let cr = self.cr.denom() as i32;
+        let header = if explicit_header {
+            20
+        } else {
+            0
+        };
+        // SF5 and SF6 use a longer sync sequence but no extra payload symbols
+        let (sync_quarter_symbols, extra_bits) = if sf < 7 {
+            (25, 0)
+        } else {
+            (17, 8)
+        };
+        let bits_per_symbol = if self.ldro {
+            4 * (sf - 2)
+        } else {
+            4 * sf
+        };
+
+        let bits = 8 * len as i32 + 16 - 4 * sf + extra_bits + header;
+        let big_ratio = if bits > 0 {
+            (bits + bits_per_symbol - 1) / bits_per_symbol
+        } else {
+            0
+        };
+        let payload_symb_nb = (8 + big_ratio * cr) as u64;
+
+        let quarter_symbols = match preamble {
+            None => 4 * payload_symb_nb,
+            Some(preamble) => 4 * preamble as u64 + sync_quarter_symbols + 4 * payload_symb_nb,
+        };
+        (quarter_symbols * (1 << sf) * 1_000_000 / (4 * self.bw.hz() as u64)) as u32
+    }
 }
 
 #[cfg(test)]
@@ -214,6 +284,36 @@ mod tests {
         params.time_on_air_us(Some(8), true, LORAWAN_OVERHEAD + app_payload_length)
     }
 
+    #[test]
+    fn time_on_air_2g4() {
+        // 44.25 symbols of 157.5 us
+        let sf7bw812 = BaseBandModulationParams::new(
+            SpreadingFactor::_7,
+            Bandwidth::_812KHz,
+            CodingRate::_4_5,
+        );
+        assert!(!sf7bw812.ldro);
+        assert_eq!(6_971, sf7bw812.time_on_air_us(Some(12), true, 10));
+        assert_eq!(4_411, sf7bw812.time_on_air_us(None, true, 10));
+
+        // SF5/SF6 use a longer sync sequence: 46.25 symbols of 19.7 us
+        let sf5bw1625 = BaseBandModulationParams::new(
+            SpreadingFactor::_5,
+            Bandwidth::_1625KHz,
+            CodingRate::_4_5,
+        );
+        assert_eq!(910, sf5bw1625.time_on_air_us(Some(12), false, 10));
+
+        // LDRO is used with SF11 and SF12 regardless of the symbol duration: 39.25 symbols of 1260 us
+        let sf11bw1625 = BaseBandModulationParams::new(
+            SpreadingFactor::_11,
+            Bandwidth::_1625KHz,
+            CodingRate::_4_5,
+        );
+        assert!(sf11bw1625.ldro);
+        assert_eq!(49_467, sf11bw1625.time_on_air_us(Some(12), true, 10));
+    }
+
     // data for time-on-air tests is verified against:
     // * https://www.thethingsnetwork.org/airtime-calculator
     // * https://avbentem.github.io/airtime-calculator/ttn/
@@ -0,0 +1,160 @@
+use lorawan_device::nb_device::radio::{Event, PhyRxTx, Response, RxQuality};
+use lorawan_device::Timings;
+
+use super::{block_on, DelayNs, LoRa};
+use crate::lorawan_radio::{prepare_for_rx, prepare_for_tx, Error, RxPacketParams};
+use crate::mod_params::RadioError;
+use crate::mod_traits::{IrqState, RadioKind};
+
+const DEFAULT_RX_WINDOW_OFFSET: i32 = -50;
+const DEFAULT_RX_WINDOW_DURATION: u32 = 1050;
+
+/// Blocking LoRaWAN radio implementation for [`lorawan_device::nb_device`].
+///
+/// Transmissions block until they are complete, `Response::TxDone` carries the time provided by
+/// `now_ms` at that point. Receptions are started in continuous mode and closed by
+/// `Event::CancelRx`; in between, the application polls for a received frame by passing
+/// `Event::Phy(())` to the device, e.g. from a super-loop or once DIO1 has been raised.
+///
+/// The const generic P is the max power the radio may be instructed to transmit at.
+/// The const generic G is the antenna gain and board loss in dBi.
+pub struct LorawanRadio<RK, DLY, CLK, const P: u8, const G: i8 = 0>
+where
+    RK: RadioKind,
+    DLY: DelayNs,
+    CLK: FnMut() -> u32,
+{
+    lora: LoRa<RK, DLY>,
+    now_ms: CLK,
+    rx_pkt_params: Option<RxPacketParams>,
+    rx_buffer: [u8; 256],
+    rx_len: usize,
+    rx_window_offset_ms: i32,
+    rx_window_duration_ms: u32,
+}
+
+impl<RK, DLY, CLK, const P: u8, const G: i8> LorawanRadio<RK, DLY, CLK, P, G>
+where
+    RK: RadioKind,
+    DLY: DelayNs,
+    CLK: FnMut() -> u32,
+{
+    /// Create the radio from a blocking LoRa physical layer and a millisecond clock, which must
+    /// use the same timebase as the timeouts requested by `nb_device`.
+    pub fn new(lora: LoRa<RK, DLY>, now_ms: CLK) -> Self {
+        Self {
+            lora,
+            now_ms,
+            rx_pkt_params: None,
+            rx_buffer: [0; 256],
+            rx_len: 0,
+            rx_window_offset_ms: DEFAULT_RX_WINDOW_OFFSET,
+            rx_window_duration_ms: DEFAULT_RX_WINDOW_DURATION,
+        }
+    }
+
+    /// Set the offset of RX windows in milliseconds, typically negative to open them early.
+    pub fn set_rx_window_offset_ms(&mut self, offset: i32) {
+        self.rx_window_offset_ms = offset;
+    }
+
+    /// Set how long RX windows are kept open in milliseconds.
+    pub fn set_rx_window_duration_ms(&mut self, duration: u32) {
+        self.rx_window_duration_ms = duration;
+    }
+
+    /// Release the LoRa physical layer
+    pub fn release(self) -> LoRa<RK, DLY> {
+        self.lora
+    }
+
+    fn poll_rx(&mut self) -> Result<Response<Self>, Error> {
+        let Some(rx_pkt_params) = &self.rx_pkt_params else {
+            return Ok(Response::Idle);
+        };
+        match self.lora.get_irq_state() {
+            Ok(Some(IrqState::Done)) => {
+                let result = block_on(rx_pkt_params.get_rx_result(&mut self.lora.lora, &mut self.rx_buffer));
+                self.lora.clear_irq_status()?;
+                let (len, status) = result?;
+                self.rx_len = len as usize;
+                Ok(Response::RxDone(RxQuality::new(status.rssi, status.snr as i8)))
+            }
+            Ok(Some(IrqState::PreambleReceived) | None) => Ok(Response::Rxing),
+            // The window is closed by CancelRx
+            Err(RadioError::ReceiveTimeout) => {
+                self.lora.clear_irq_status()?;
+                Ok(Response::Rxing)
+            }
+            Err(err) => {
+                self.lora.clear_irq_status()?;
+                Err(err.into())
+            }
+        }
+    }
+}
+
+impl<RK, DLY, CLK, const P: u8, const G: i8> Timings for LorawanRadio<RK, DLY, CLK, P, G>
+where
+    RK: RadioKind,
+    DLY: DelayNs,
+    CLK: FnMut() -> u32,
+{
+    fn get_rx_window_offset_ms(&self) -> i32 {
+        self.rx_window_offset_ms
+    }
+
+    fn get_rx_window_duration_ms(&self) -> u32 {
+        self.rx_window_duration_ms
+    }
+}
+
+impl<RK, DLY, CLK, const P: u8, const G: i8> PhyRxTx for LorawanRadio<RK, DLY, CLK, P, G>
+where
+    RK: RadioKind,
+    DLY: DelayNs,
+    CLK: FnMut() -> u32,
+{
+    type PhyEvent = ();
+    type PhyError = Error;
+    type PhyResponse = ();
+
+    const ANTENNA_GAIN: i8 = G;
+
+    const MAX_RADIO_POWER: u8 = P;
+
+    fn get_mut_radio(&mut self) -> &mut Self {
+        self
+    }
+
+    fn get_received_packet(&mut self) -> &mut [u8] {
+        &mut self.rx_buffer[..self.rx_len]
+    }
+
+    fn handle_event(&mut self, event: Event<'_, Self>) -> Result<Response<Self>, Self::PhyError> {
+        match event {
+            Event::TxRequest(config, buffer) => {
+                self.rx_pkt_params = None;
+                block_on(prepare_for_tx(&mut self.lora.lora, &config, buffer))?;
+                self.lora.tx()?;
+                Ok(Response::TxDone((self.now_ms)()))
+            }
+            Event::RxRequest(config) => {
+                let rx_pkt_params = block_on(prepare_for_rx(
+                    &mut self.lora.lora,
+                    &config,
+                    lorawan_device::nb_device::radio::RxMode::Continuous,
+                ))?;
+                self.lora.start_rx()?;
+                self.rx_pkt_params = Some(rx_pkt_params);
+                Ok(Response::Rxing)
+            }
+            Event::CancelRx => {
+                self.rx_pkt_params = None;
+                self.lora.enter_standby()?;
+                Ok(Response::Idle)
+            }
+            Event::Phy(()) => self.poll_rx(),
+        }
+    }
+}
@@ -0,0 +1,895 @@
+//! Blocking API for RTOS and bare-metal (super-loop) users.
+//!
+//! The chip specific logic of [`crate::sx126x`] and [`crate::sx127x`] is shared with the async
+//! API: the adapters of this module turn blocking `embedded-hal` peripherals into their
+//! `embedded-hal-async` counterparts, so that every future of a [`RadioKind`] built on top of them
+//! completes on its first poll. [`LoRa`] then drives these futures to completion with
+//! [`block_on`].
+//!
+//! ```ignore
+//! let iv = GenericSx126xInterfaceVariant::new(reset, PollingPin::new(dio1), PollingPin::new(busy), None, None)?;
+//! let sx1262 = Sx126x::new(BlockingSpi::new(spi), iv, config);
+//! let mut lora = lora_phy::blocking::LoRa::new(sx1262, true, delay)?;
+//! ```
+//!
+//! [`GenericSx126xInterfaceVariant`](crate::iv::GenericSx126xInterfaceVariant) and
+//! [`GenericSx127xInterfaceVariant`](crate::iv::GenericSx127xInterfaceVariant) busy-wait on their
+//! pins when those are wrapped into a [`PollingPin`].
+
+#[cfg(feature = "lorawan-radio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lorawan-radio")))]
+mod lorawan_radio;
+#[cfg(feature = "lorawan-radio")]
+pub use lorawan_radio::LorawanRadio;
+
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::InputPin;
+use embedded_hal::spi::Operation;
+
+use crate::mod_params::*;
+use crate::mod_traits::{IrqState, RadioKind};
+
+/// Run a future to completion by polling it in a busy loop.
+///
+/// Futures of radio kinds built on [`BlockingSpi`], [`BlockingDelay`] and [`PollingPin`] complete
+/// on their first poll. Other futures are polled until they are ready, without ever sleeping.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+fn noop_waker() -> Waker {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW, |_| {}, |_| {}, |_| {});
+    const RAW: RawWaker = RawWaker::new(core::ptr::null(), &VTABLE);
+    // SAFETY: the vtable functions do not access the (null) data pointer
+    unsafe { Waker::from_raw(RAW) }
+}
+
+/// Provides [`embedded_hal_async::spi::SpiDevice`] on top of a blocking
+/// [`embedded_hal::spi::SpiDevice`].
+pub struct BlockingSpi<SPI>(SPI);
+
+impl<SPI> BlockingSpi<SPI> {
+    /// Wrap a blocking SPI device
+    pub fn new(spi: SPI) -> Self {
+        Self(spi)
+    }
+
+    /// Release the blocking SPI device
+    pub fn release(self) -> SPI {
+        self.0
+    }
+}
+
+impl<SPI: embedded_hal::spi::ErrorType> embedded_hal::spi::ErrorType for BlockingSpi<SPI> {
+    type Error = SPI::Error;
+}
+
+impl<SPI: embedded_hal::spi::SpiDevice<u8>> embedded_hal_async::spi::SpiDevice<u8> for BlockingSpi<SPI> {
+    async fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        self.0.transaction(operations)
+    }
+}
+
+/// Provides [`embedded_hal_async::delay::DelayNs`] on top of a blocking
+/// [`embedded_hal::delay::DelayNs`].
+pub struct BlockingDelay<DLY>(DLY);
+
+impl<DLY> BlockingDelay<DLY> {
+    /// Wrap a blocking delay
+    pub fn new(delay: DLY) -> Self {
+        Self(delay)
+    }
+
+    /// Release the blocking delay
+    pub fn release(self) -> DLY {
+        self.0
+    }
+}
+
+impl<DLY: DelayNs> embedded_hal_async::delay::DelayNs for BlockingDelay<DLY> {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.0.delay_ns(ns)
+    }
+
+    async fn delay_us(&mut self, us: u32) {
+        self.0.delay_us(us)
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.0.delay_ms(ms)
+    }
+}
+
+/// Provides [`embedded_hal_async::digital::Wait`] by busy-polling an [`InputPin`], e.g. the BUSY
+/// and DIO1 lines of an [`InterfaceVariant`](crate::mod_traits::InterfaceVariant).
+pub struct PollingPin<PIN>(PIN);
+
+impl<PIN> PollingPin<PIN> {
+    /// Wrap an input pin
+    pub fn new(pin: PIN) -> Self {
+        Self(pin)
+    }
+
+    /// Release the input pin
+    pub fn release(self) -> PIN {
+        self.0
+    }
+}
+
+impl<PIN: embedded_hal::digital::ErrorType> embedded_hal::digital::ErrorType for PollingPin<PIN> {
+    type Error = PIN::Error;
+}
+
+impl<PIN: InputPin> embedded_hal_async::digital::Wait for PollingPin<PIN> {
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        while self.0.is_low()? {}
+        Ok(())
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        while self.0.is_high()? {}
+        Ok(())
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for_low().await?;
+        self.wait_for_high().await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for_high().await?;
+        self.wait_for_low().await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        let high = self.0.is_high()?;
+        while self.0.is_high()? == high {}
+        Ok(())
+    }
+}
+
+/// Blocking counterpart of [`crate::LoRa`], providing the same physical layer API
+pub struct LoRa<RK, DLY>
+where
+    RK: RadioKind,
+    DLY: DelayNs,
+{
+    pub(crate) lora: crate::LoRa<RK, BlockingDelay<DLY>>,
+}
+
+impl<RK, DLY> LoRa<RK, DLY>
+where
+    RK: RadioKind,
+    DLY: DelayNs,
+{
+    /// Build and return a new instance of the LoRa physical layer API with a specified sync word
+    pub fn with_syncword(radio_kind: RK, sync_word: u8, delay: DLY) -> Result<Self, RadioError> {
+        let lora = block_on(crate::LoRa::with_syncword(
+            radio_kind,
+            sync_word,
+            BlockingDelay::new(delay),
+        ))?;
+        Ok(Self { lora })
+    }
+
+    /// Build and return a new instance of the LoRa physical layer API to control an initialized
+    /// LoRa radio for LoRaWAN public or private network.
+    pub fn new(radio_kind: RK, enable_public_network: bool, delay: DLY) -> Result<Self, RadioError> {
+        let lora = block_on(crate::LoRa::new(
+            radio_kind,
+            enable_public_network,
+            BlockingDelay::new(delay),
+        ))?;
+        Ok(Self { lora })
+    }
+
+    /// Wait for an IRQ event to occur
+    pub fn wait_for_irq(&mut self) -> Result<(), RadioError> {
+        block_on(self.lora.wait_for_irq())
+    }
+
+    /// Process an IRQ event and return the new state of the radio
+    pub fn process_irq_event(&mut self) -> Result<Option<IrqState>, RadioError> {
+        block_on(self.lora.process_irq_event())
+    }
+
+    /// Create modulation parameters for a communication channel
+    pub fn create_modulation_params(
+        &mut self,
+        spreading_factor: SpreadingFactor,
+        bandwidth: Bandwidth,
+        coding_rate: CodingRate,
+        frequency_in_hz: u32,
+    ) -> Result<ModulationParams, RadioError> {
+        self.lora
+            .create_modulation_params(spreading_factor, bandwidth, coding_rate, frequency_in_hz)
+    }
+
+    /// Create packet parameters for a transmit operation on a communication channel
+    pub fn create_tx_packet_params(
+        &mut self,
+        preamble_length: u16,
+        implicit_header: bool,
+        crc_on: bool,
+        iq_inverted: bool,
+        modulation_params: &ModulationParams,
+    ) -> Result<PacketParams, RadioError> {
+        self.lora
+            .create_tx_packet_params(preamble_length, implicit_header, crc_on, iq_inverted, modulation_params)
+    }
+
+    /// Create packet parameters for a receive operation on a communication channel
+    pub fn create_rx_packet_params(
+        &mut self,
+        preamble_length: u16,
+        implicit_header: bool,
+        max_payload_length: u8,
+        crc_on: bool,
+        iq_inverted: bool,
+        modulation_params: &ModulationParams,
+    ) -> Result<PacketParams, RadioError> {
+        self.lora.create_rx_packet_params(
+            preamble_length,
+            implicit_header,
+            max_payload_length,
+            crc_on,
+            iq_inverted,
+            modulation_params,
+        )
+    }
+
+    /// Create FSK modulation parameters for a communication channel, where `rx_bandwidth` is the
+    /// single-sideband receiver bandwidth in Hz
+    pub fn create_fsk_modulation_params(
+        &mut self,
+        bitrate: u32,
+        fdev: u32,
+        pulse_shape: PulseShape,
+        rx_bandwidth: u32,
+        frequency_in_hz: u32,
+    ) -> Result<FskModulationParams, RadioError> {
+        self.lora
+            .create_fsk_modulation_params(bitrate, fdev, pulse_shape, rx_bandwidth, frequency_in_hz)
+    }
+
+    /// Create FSK packet parameters for a transmit operation on a communication channel
+    pub fn create_fsk_tx_packet_params(
+        &mut self,
+        preamble_length: u16,
+        sync_word: &[u8],
+        variable_length: bool,
+        crc_on: bool,
+        whitening: bool,
+    ) -> Result<FskPacketParams, RadioError> {
+        self.lora
+            .create_fsk_tx_packet_params(preamble_length, sync_word, variable_length, crc_on, whitening)
+    }
+
+    /// Create FSK packet parameters for a receive operation on a communication channel
+    pub fn create_fsk_rx_packet_params(
+        &mut self,
+        preamble_length: u16,
+        sync_word: &[u8],
+        variable_length: bool,
+        max_payload_length: u8,
+        crc_on: bool,
+        whitening: bool,
+    ) -> Result<FskPacketParams, RadioError> {
+        self.lora.create_fsk_rx_packet_params(
+            preamble_length,
+            sync_word,
+            variable_length,
+            max_payload_length,
+            crc_on,
+            whitening,
+        )
+    }
+
+    /// Initialize the radio for LoRa physical layer communications
+    pub fn init(&mut self) -> Result<(), RadioError> {
+        block_on(self.lora.init())
+    }
+
+    /// Calibrate the image rejection of the radio for a frequency. This is done automatically
+    /// before operating on a frequency outside of the last calibrated band, or after a temperature
+    /// drift reported through [`LoRa::set_temperature()`].
+    pub fn calibrate_image(&mut self, frequency_in_hz: u32) -> Result<(), RadioError> {
+        block_on(self.lora.calibrate_image(frequency_in_hz))
+    }
+
+    /// Report the current temperature (°C) of the radio, as measured by the application. The image
+    /// is calibrated again before the next operation once the temperature has drifted by 10 °C or
+    /// more since the last calibration.
+    pub fn set_temperature(&mut self, temperature_celsius: i16) {
+        self.lora.set_temperature(temperature_celsius)
+    }
+
+    /// Offset (Hz) applied to the channel frequencies to compensate the drift of the radio crystal
+    pub fn frequency_offset(&self) -> i32 {
+        self.lora.frequency_offset()
+    }
+
+    /// Set the offset (Hz) applied to the channel frequencies of subsequent operations
+    pub fn set_frequency_offset(&mut self, offset_in_hz: i32) {
+        self.lora.set_frequency_offset(offset_in_hz)
+    }
+
+    /// Automatic frequency correction: shift the channel frequencies of subsequent operations by
+    /// the frequency error of a received packet, so that the radio follows the transmitter
+    pub fn correct_frequency(&mut self, packet_status: &PacketStatus) {
+        self.lora.correct_frequency(packet_status)
+    }
+
+    /// Abort the ongoing operation: return the radio to standby and clear its IRQs
+    pub fn abort(&mut self) -> Result<(), RadioError> {
+        block_on(self.lora.abort())
+    }
+
+    /// Place the LoRa physical layer in standby mode
+    pub fn enter_standby(&mut self) -> Result<(), RadioError> {
+        block_on(self.lora.enter_standby())
+    }
+
+    /// Place the LoRa physical layer in low power mode, specifying cold or
+    /// warm start (if chip supports it)
+    pub fn sleep(&mut self, warm_start_if_possible: bool) -> Result<(), RadioError> {
+        block_on(self.lora.sleep(warm_start_if_possible))
+    }
+
+    /// Prepare the radio for a transmit operation
+    pub fn prepare_for_tx(
+        &mut self,
+        mdltn_params: &ModulationParams,
+        tx_pkt_params: &mut PacketParams,
+        output_power: i32,
+        buffer: &[u8],
+    ) -> Result<(), RadioError> {
+        block_on(
+            self.lora
+                .prepare_for_tx(mdltn_params, tx_pkt_params, output_power, buffer),
+        )
+    }
+
+    /// Prepare the radio for an FSK transmit operation
+    pub fn prepare_for_fsk_tx(
+        &mut self,
+        mdltn_params: &FskModulationParams,
+        tx_pkt_params: &mut FskPacketParams,
+        output_power: i32,
+        buffer: &[u8],
+    ) -> Result<(), RadioError> {
+        block_on(
+            self.lora
+                .prepare_for_fsk_tx(mdltn_params, tx_pkt_params, output_power, buffer),
+        )
+    }
+
+    /// Execute a transmit operation, returning once it is complete
+    ///
+    /// The IRQ status is polled every millisecond, and the transmission fails with
+    /// [`RadioError::TransmitTimeout`] once the TX timeout (see [`LoRa::set_tx_timeout_margin`])
+    /// expires, including on the sx127x which has no hardware TX timeout. The radio is then
+    /// returned to standby, or reset if it does not respond.
+    pub fn tx(&mut self) -> Result<(), RadioError> {
+        block_on(self.lora.transmit(true))
+    }
+
+    /// Set the margin added to the time on air of a packet to obtain its TX timeout, or `None` to
+    /// transmit without timeout. The margin defaults to 100 ms and applies to packets prepared
+    /// afterwards.
+    pub fn set_tx_timeout_margin(&mut self, margin_in_ms: Option<u32>) {
+        self.lora.set_tx_timeout_margin(margin_in_ms)
+    }
+
+    /// Configure radio for a receive operation
+    pub fn prepare_for_rx(
+        &mut self,
+        listen_mode: RxMode,
+        mdltn_params: &ModulationParams,
+        rx_pkt_params: &PacketParams,
+    ) -> Result<(), RadioError> {
+        block_on(self.lora.prepare_for_rx(listen_mode, mdltn_params, rx_pkt_params))
+    }
+
+    /// Configure radio for an FSK receive operation
+    pub fn prepare_for_fsk_rx(
+        &mut self,
+        listen_mode: RxMode,
+        mdltn_params: &FskModulationParams,
+        rx_pkt_params: &FskPacketParams,
+    ) -> Result<(), RadioError> {
+        block_on(self.lora.prepare_for_fsk_rx(listen_mode, mdltn_params, rx_pkt_params))
+    }
+
+    /// Switch radio to receive mode (prepared via [`LoRa::prepare_for_rx`]).
+    /// Call [`LoRa::complete_rx`] to wait and handle result.
+    pub fn start_rx(&mut self) -> Result<(), RadioError> {
+        block_on(self.lora.start_rx())
+    }
+
+    /// Wait for a previously started receive to complete
+    pub fn complete_rx(
+        &mut self,
+        packet_params: &PacketParams,
+        receiving_buffer: &mut [u8],
+    ) -> Result<(u8, PacketStatus), RadioError> {
+        block_on(self.lora.complete_rx(packet_params, receiving_buffer))
+    }
+
+    /// Wait for a previously started FSK receive to complete
+    pub fn complete_fsk_rx(
+        &mut self,
+        packet_params: &FskPacketParams,
+        receiving_buffer: &mut [u8],
+    ) -> Result<(u8, PacketStatus), RadioError> {
+        block_on(self.lora.complete_fsk_rx(packet_params, receiving_buffer))
+    }
+
+    /// Returns the current IRQ state, which allows polling for the completion of an operation
+    /// without blocking
+    pub fn get_irq_state(&mut self) -> Result<Option<IrqState>, RadioError> {
+        block_on(self.lora.get_irq_state())
+    }
+
+    /// Clears the IRQ status
+    pub fn clear_irq_status(&mut self) -> Result<(), RadioError> {
+        block_on(self.lora.clear_irq_status())
+    }
+
+    /// Extracts the received payload and packet status after a completed RX IRQ event.
+    /// Should be called after receiving `IrqState::Done`.
+    pub fn get_rx_result(
+        &mut self,
+        packet_params: &PacketParams,
+        receiving_buffer: &mut [u8],
+    ) -> Result<(u8, PacketStatus), RadioError> {
+        block_on(self.lora.get_rx_result(packet_params, receiving_buffer))
+    }
+
+    /// Start reception and wait for its completion by calling
+    /// [`LoRa::start_rx`]  and [`LoRa::complete_rx`] in succession.
+    pub fn rx(
+        &mut self,
+        packet_params: &PacketParams,
+        receiving_buffer: &mut [u8],
+    ) -> Result<(u8, PacketStatus), RadioError> {
+        block_on(self.lora.rx(packet_params, receiving_buffer))
+    }
+
+    /// Extracts the received FSK payload and packet status after a completed RX IRQ event.
+    /// Should be called after receiving `IrqState::Done`.
+    pub fn get_fsk_rx_result(
+        &mut self,
+        packet_params: &FskPacketParams,
+        receiving_buffer: &mut [u8],
+    ) -> Result<(u8, PacketStatus), RadioError> {
+        block_on(self.lora.get_fsk_rx_result(packet_params, receiving_buffer))
+    }
+
+    /// Start FSK reception and wait for its completion by calling
+    /// [`LoRa::start_rx`]  and [`LoRa::complete_fsk_rx`] in succession.
+    pub fn fsk_rx(
+        &mut self,
+        packet_params: &FskPacketParams,
+        receiving_buffer: &mut [u8],
+    ) -> Result<(u8, PacketStatus), RadioError> {
+        block_on(self.lora.fsk_rx(packet_params, receiving_buffer))
+    }
+
+    /// Start listening to a given frequency and [`Bandwidth`]
+    pub fn listen(&mut self, frequency_in_hz: u32, bandwidth: Bandwidth) -> Result<(), RadioError> {
+        block_on(self.lora.listen(frequency_in_hz, bandwidth))
+    }
+
+    /// Get the current RSSI
+    pub fn get_rssi(&mut self) -> Result<i16, RadioError> {
+        block_on(self.lora.get_rssi())
+    }
+
+    /// Get the packet statistics (received packets, CRC and header errors) since the last reset.
+    /// The sx127x and sx128x count them in software.
+    pub fn get_packet_stats(&mut self) -> Result<PacketStats, RadioError> {
+        block_on(self.lora.get_packet_stats())
+    }
+
+    /// Reset the packet statistics
+    pub fn reset_packet_stats(&mut self) -> Result<(), RadioError> {
+        block_on(self.lora.reset_packet_stats())
+    }
+
+    /// Get the hardware errors (calibrations, oscillator start, PLL lock, PA ramp) reported by the
+    /// radio. The sx127x and sx128x do not report errors.
+    pub fn get_device_errors(&mut self) -> Result<DeviceErrors, RadioError> {
+        block_on(self.lora.get_device_errors())
+    }
+
+    /// Clear the hardware errors reported by the radio
+    pub fn clear_device_errors(&mut self) -> Result<(), RadioError> {
+        block_on(self.lora.clear_device_errors())
+    }
+
+    /// Prepare the radio for a channel activity detection (CAD) operation
+    pub fn prepare_for_cad(&mut self, mdltn_params: &ModulationParams) -> Result<(), RadioError> {
+        block_on(self.lora.prepare_for_cad(mdltn_params))
+    }
+
+    /// Start channel activity detection (CAD) operation and return the result
+    pub fn cad(&mut self, mdltn_params: &ModulationParams) -> Result<bool, RadioError> {
+        block_on(self.lora.cad(mdltn_params))
+    }
+
+    /// Place radio in continuous wave mode, generally for regulatory testing
+    ///
+    /// Presumes that init() is called before this function
+    pub fn continuous_wave(&mut self, mdltn_params: &ModulationParams, output_power: i32) -> Result<(), RadioError> {
+        block_on(self.lora.continuous_wave(mdltn_params, output_power))
+    }
+
+    /// Wait for the given number of milliseconds using the delay provided to the constructor
+    pub fn delay_ms(&mut self, ms: u32) {
+        self.lora.delay.0.delay_ms(ms)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::iv::GenericSx126xInterfaceVariant;
+    use crate::sx126x::{self, Sx1262, Sx126x};
+    use core::convert::Infallible;
+    use embedded_hal::digital::OutputPin;
+
+    /// Records the opcode of SPI transactions and answers GetIrqStatus with `irq_status`
+    pub(crate) struct Spi {
+        pub(crate) opcodes: [u8; 128],
+        pub(crate) len: usize,
+        pub(crate) irq_status: [u8; 2],
+    }
+
+    impl Spi {
+        pub(crate) fn new() -> Self {
+            Self {
+                opcodes: [0; 128],
+                len: 0,
+                irq_status: [0; 2],
+            }
+        }
+
+        pub(crate) fn opcodes(&self) -> &[u8] {
+            &self.opcodes[..self.len]
+        }
+    }
+
+    impl embedded_hal::spi::ErrorType for &mut Spi {
+        type Error = Infallible;
+    }
+
+    impl embedded_hal::spi::SpiDevice for &mut Spi {
+        fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+            let opcode = match operations.first() {
+                Some(Operation::Write(data)) => data[0],
+                _ => 0,
+            };
+            if self.len < self.opcodes.len() {
+                self.opcodes[self.len] = opcode;
+                self.len += 1;
+            }
+            for op in operations.iter_mut() {
+                if let Operation::Read(buf) = op {
+                    buf.fill(0);
+                }
+            }
+            // GetIrqStatus
+            if let (0x12, Some(Operation::Read(buf))) = (opcode, operations.last_mut()) {
+                buf.copy_from_slice(&self.irq_status);
+            }
+            Ok(())
+        }
+    }
+
+    /// Input pin which is always low and output pin ignoring any change
+    pub(crate) struct Pin;
+
+    impl embedded_hal::digital::ErrorType for Pin {
+        type Error = Infallible;
+    }
+
+    impl InputPin for Pin {
+        fn is_high(&mut self) -> Result<bool, Infallible> {
+            Ok(false)
+        }
+
+        fn is_low(&mut self) -> Result<bool, Infallible> {
+            Ok(true)
+        }
+    }
+
+    impl OutputPin for Pin {
+        fn set_low(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    pub(crate) struct Delay;
+
+    impl DelayNs for Delay {
+        fn delay_ns(&mut self, _ns: u32) {}
+    }
+
+    pub(crate) type TestRadioKind<'a> =
+        Sx126x<BlockingSpi<&'a mut Spi>, GenericSx126xInterfaceVariant<Pin, PollingPin<Pin>>, Sx1262>;
+
+    pub(crate) fn radio_kind(spi: &mut Spi) -> TestRadioKind<'_> {
+        let iv =
+            GenericSx126xInterfaceVariant::new(Pin, PollingPin::new(Pin), PollingPin::new(Pin), None, None).unwrap();
+        let config = sx126x::Config {
+            chip: Sx1262,
+            tcxo_ctrl: None,
+            use_dcdc: false,
+            rx_boost: false,
+        };
+        Sx126x::new(BlockingSpi::new(spi), iv, config)
+    }
+
+    #[test]
+    fn blocking_sx126x_init() {
+        let mut spi = Spi::new();
+        {
+            let mut lora = LoRa::new(radio_kind(&mut spi), true, Delay).unwrap();
+            // No IRQ is pending
+            assert!(matches!(lora.get_irq_state(), Ok(None)));
+            lora.sleep(false).unwrap();
+        }
+
+        // SetStandby during initialization, SetSleep last
+        assert!(spi.opcodes().contains(&0x80));
+        assert_eq!(spi.opcodes().last(), Some(&0x84));
+    }
+
+    #[test]
+    fn image_calibration_follows_band_and_temperature() {
+        let mut spi = Spi::new();
+        {
+            let mut lora = LoRa::new(radio_kind(&mut spi), true, Delay).unwrap();
+            let params = |lora: &mut LoRa<_, _>, frequency_in_hz| {
+                lora.create_modulation_params(
+                    SpreadingFactor::_7,
+                    Bandwidth::_125KHz,
+                    CodingRate::_4_5,
+                    frequency_in_hz,
+                )
+                .unwrap()
+            };
+            // 868.1 and 869.525 MHz share a band, 433.175 MHz needs a new calibration
+            for frequency_in_hz in [868_100_000, 869_525_000, 433_175_000] {
+                let mdltn_params = params(&mut lora, frequency_in_hz);
+                lora.prepare_for_cad(&mdltn_params).unwrap();
+            }
+            // A drift below 10 °C keeps the calibration, a larger one invalidates it
+            lora.set_temperature(20);
+            lora.set_temperature(25);
+            let mdltn_params = params(&mut lora, 433_175_000);
+            lora.prepare_for_cad(&mdltn_params).unwrap();
+            lora.set_temperature(31);
+            lora.prepare_for_cad(&mdltn_params).unwrap();
+            // Manual calibration
+            lora.calibrate_image(433_175_000).unwrap();
+        }
+
+        let calibrations = spi.opcodes().iter().filter(|&&opcode| opcode == 0x98).count();
+        assert_eq!(calibrations, 4);
+    }
+
+    #[test]
+    fn tx_timeout_from_time_on_air() {
+        let mut spi = Spi::new();
+        let mut lora = LoRa::new(radio_kind(&mut spi), true, Delay).unwrap();
+        let mdltn_params = lora
+            .create_modulation_params(SpreadingFactor::_7, Bandwidth::_125KHz, CodingRate::_4_5, 868_100_000)
+            .unwrap();
+        let mut tx_pkt_params = lora
+            .create_tx_packet_params(8, false, true, false, &mdltn_params)
+            .unwrap();
+        // 56.576 ms on air plus the default margin
+        lora.prepare_for_tx(&mdltn_params, &mut tx_pkt_params, 14, &[0u8; 20])
+            .unwrap();
+        assert_eq!(lora.lora.tx_timeout_ms, 157);
+
+        lora.set_tx_timeout_margin(None);
+        lora.prepare_for_tx(&mdltn_params, &mut tx_pkt_params, 14, &[0u8; 20])
+            .unwrap();
+        assert_eq!(lora.lora.tx_timeout_ms, 0);
+    }
+
+    #[test]
+    fn blocking_tx_times_out_without_irq() {
+        let mut spi = Spi::new();
+        {
+            let mut lora = LoRa::new(radio_kind(&mut spi), true, Delay).unwrap();
+            let mdltn_params = lora
+                .create_modulation_params(SpreadingFactor::_7, Bandwidth::_125KHz, CodingRate::_4_5, 868_100_000)
+                .unwrap();
+            let mut tx_pkt_params = lora
+                .create_tx_packet_params(8, false, true, false, &mdltn_params)
+                .unwrap();
+            lora.prepare_for_tx(&mdltn_params, &mut tx_pkt_params, 14, &[0u8; 20])
+                .unwrap();
+            // The DIO1 pin stays low and TxDone is never reported
+            assert_eq!(lora.tx(), Err(RadioError::TransmitTimeout));
+            assert!(lora.lora.radio_mode == RadioMode::Standby);
+        }
+
+        let mut spi = Spi::new();
+        // TxDone
+        spi.irq_status = [0x00, 0x01];
+        let mut lora = LoRa::new(radio_kind(&mut spi), true, Delay).unwrap();
+        let mdltn_params = lora
+            .create_modulation_params(SpreadingFactor::_7, Bandwidth::_125KHz, CodingRate::_4_5, 868_100_000)
+            .unwrap();
+        let mut tx_pkt_params = lora
+            .create_tx_packet_params(8, false, true, false, &mdltn_params)
+            .unwrap();
+        lora.prepare_for_tx(&mdltn_params, &mut tx_pkt_params, 14, &[0u8; 20])
+            .unwrap();
+        assert_eq!(lora.tx(), Ok(()));
+    }
+
+    #[test]
+    fn tx_watchdog_expires() {
+        let timer = pin!(async {});
+        assert_eq!(
+            block_on(crate::with_timeout(core::future::pending::<()>(), timer)),
+            None
+        );
+        let timer = pin!(core::future::pending::<()>());
+        assert_eq!(block_on(crate::with_timeout(async { 1 }, timer)), Some(1));
+    }
+
+    /// Wait pin completing each wait on its second poll, which gives the futures driving the
+    /// radio an await point at every busy and IRQ wait
+    struct YieldingPin;
+
+    impl embedded_hal::digital::ErrorType for YieldingPin {
+        type Error = Infallible;
+    }
+
+    impl embedded_hal_async::digital::Wait for YieldingPin {
+        async fn wait_for_high(&mut self) -> Result<(), Infallible> {
+            yield_now().await;
+            Ok(())
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+            yield_now().await;
+            Ok(())
+        }
+
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> {
+            yield_now().await;
+            Ok(())
+        }
+
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> {
+            yield_now().await;
+            Ok(())
+        }
+
+        async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> {
+            yield_now().await;
+            Ok(())
+        }
+    }
+
+    async fn yield_now() {
+        let mut yielded = false;
+        core::future::poll_fn(|_| {
+            if yielded {
+                Poll::Ready(())
+            } else {
+                yielded = true;
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    type YieldingRadioKind<'a> =
+        Sx126x<BlockingSpi<&'a mut Spi>, GenericSx126xInterfaceVariant<Pin, YieldingPin>, Sx1262>;
+
+    /// Async LoRa answering TxDone, RxDone and CADDone to every GetIrqStatus
+    fn yielding_lora(spi: &mut Spi) -> crate::LoRa<YieldingRadioKind<'_>, BlockingDelay<Delay>> {
+        spi.irq_status = [0x00, 0x83];
+        let iv = GenericSx126xInterfaceVariant::new(Pin, YieldingPin, YieldingPin, None, None).unwrap();
+        let config = sx126x::Config {
+            chip: Sx1262,
+            tcxo_ctrl: None,
+            use_dcdc: false,
+            rx_boost: false,
+        };
+        let radio_kind = Sx126x::new(BlockingSpi::new(spi), iv, config);
+        let mut lora = block_on(crate::LoRa::new(radio_kind, true, BlockingDelay::new(Delay))).unwrap();
+        // The test delay completes at once, which would expire the TX watchdog
+        lora.set_tx_timeout_margin(None);
+        lora
+    }
+
+    /// Poll a future `polls` times at most, then drop it. Returns whether it completed.
+    fn poll_then_drop<F: Future>(future: F, polls: usize) -> bool {
+        let mut future = pin!(future);
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        (0..polls).any(|_| future.as_mut().poll(&mut cx).is_ready())
+    }
+
+    #[test]
+    fn cancelled_operations_leave_radio_consistent() {
+        let mut buf = [0u8; 16];
+        // Cancel at every await point until the operation completes
+        for polls in 1..100 {
+            let mut spi = Spi::new();
+            let mut lora = yielding_lora(&mut spi);
+            let mdltn_params = lora
+                .create_modulation_params(SpreadingFactor::_7, Bandwidth::_125KHz, CodingRate::_4_5, 868_100_000)
+                .unwrap();
+            let mut tx_pkt_params = lora
+                .create_tx_packet_params(8, false, true, false, &mdltn_params)
+                .unwrap();
+            let rx_pkt_params = lora
+                .create_rx_packet_params(8, false, 16, true, false, &mdltn_params)
+                .unwrap();
+
+            // A dropped transmission is aborted, and has to be prepared again
+            block_on(lora.prepare_for_tx(&mdltn_params, &mut tx_pkt_params, 14, &[0u8; 4])).unwrap();
+            let tx_completed = poll_then_drop(lora.tx(), polls);
+            if !tx_completed {
+                assert!(lora.interrupted);
+                block_on(lora.tx()).unwrap_err();
+                assert!(!lora.interrupted);
+                assert!(lora.radio_mode == RadioMode::Standby);
+                block_on(lora.prepare_for_tx(&mdltn_params, &mut tx_pkt_params, 14, &[0u8; 4])).unwrap();
+                block_on(lora.tx()).unwrap();
+            }
+            assert!(lora.radio_mode == RadioMode::Standby);
+
+            // A dropped reception is restarted by the next one
+            block_on(lora.prepare_for_rx(RxMode::Continuous, &mdltn_params, &rx_pkt_params)).unwrap();
+            let rx_completed = poll_then_drop(lora.rx(&rx_pkt_params, &mut buf), polls);
+            if !rx_completed {
+                block_on(lora.complete_rx(&rx_pkt_params, &mut buf)).unwrap();
+            }
+            assert!(!lora.interrupted);
+            assert!(lora.radio_mode == RadioMode::Receive(RxMode::Continuous));
+
+            // A dropped CAD is aborted before the next operation
+            block_on(lora.prepare_for_cad(&mdltn_params)).unwrap();
+            let cad_completed = poll_then_drop(lora.cad(&mdltn_params), polls);
+            if !cad_completed {
+                block_on(lora.abort()).unwrap();
+                assert!(!lora.interrupted);
+                assert!(lora.radio_mode == RadioMode::Standby);
+                block_on(lora.prepare_for_cad(&mdltn_params)).unwrap();
+                assert!(!block_on(lora.cad(&mdltn_params)).unwrap());
+            }
+
+            if tx_completed && rx_completed && cad_completed {
+                return;
+            }
+        }
+        panic!("operations did not complete");
+    }
+}
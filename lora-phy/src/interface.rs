@@ -92,4 +92,40 @@ where
 
         Ok(status[0])
     }
+
+    // Send a command, then read its response in a separate transaction once the radio is no
+    // longer busy, returning the status preceding the response.
+    pub async fn read_response(&mut self, write_buffer: &[u8], read_buffer: &mut [u8]) -> Result<u8, RadioError> {
+        self.spi.write(write_buffer).await.map_err(|_| SPI)?;
+        self.iv.wait_on_busy().await?;
+
+        let mut status = [0u8];
+        {
+            let mut ops = [Operation::Read(&mut status), Operation::Read(read_buffer)];
+
+            self.spi.transaction(&mut ops).await.map_err(|_| SPI)?;
+        }
+
+        self.iv.wait_on_busy().await?;
+
+        trace!(
+            "read_response: cmd={=[u8]:02x}, len={}, status={:02x}, buf={=[u8]:02x}",
+            write_buffer,
+            read_buffer.len(),
+            status[0],
+            read_buffer
+        );
+
+        Ok(status[0])
+    }
+
+    // Write a buffer to the radio, replacing it by the bytes read at the same time.
+    pub async fn transfer(&mut self, buffer: &mut [u8]) -> Result<(), RadioError> {
+        trace!("transfer: {=[u8]:02x}", buffer);
+        self.spi.transfer_in_place(buffer).await.map_err(|_| SPI)?;
+        self.iv.wait_on_busy().await?;
+        trace!("transfer read: {=[u8]:02x}", buffer);
+
+        Ok(())
+    }
 }
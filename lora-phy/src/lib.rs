@@ -12,13 +12,21 @@ pub(crate) mod fmt;
 
 #[cfg(feature = "lorawan-radio")]
 #[cfg_attr(docsrs, doc(cfg(feature = "lorawan-radio")))]
-/// Provides an implementation of the async LoRaWAN device trait.
+/// Provides implementations of the async and `nb_device` LoRaWAN radio traits.
 pub mod lorawan_radio;
 
+pub mod blocking;
 /// The read/write interface between an embedded framework/MCU combination and a LoRa chip
 pub(crate) mod interface;
 /// InterfaceVariant implementations using `embedded-hal`.
 pub mod iv;
+/// Specific implementation to support Semtech LR11xx chips
+pub mod lr11xx;
+#[cfg(any(test, feature = "mock"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+/// Scripted SPI device, interface variant and simulated radio to test the driver and
+/// applications on the host
+pub mod mock;
 /// Parameters used across the lora-phy crate to support various use cases
 pub mod mod_params;
 /// Traits implemented externally or internally to support control of LoRa chips
@@ -27,11 +35,18 @@ pub mod mod_traits;
 pub mod sx126x;
 /// Specific implementation to support Semtech Sx127x chips
 pub mod sx127x;
+/// Specific implementation to support Semtech Sx128x chips
+pub mod sx128x;
 
 pub use crate::mod_params::RxMode;
 
+use core::future::{poll_fn, Future};
+use core::pin::{pin, Pin};
+use core::task::Poll;
+
 pub use embedded_hal_async::delay::DelayNs;
 use interface::*;
+use lora_modulation::BaseBandModulationParams;
 use mod_params::*;
 use mod_traits::*;
 
@@ -41,6 +56,15 @@ const LORAWAN_PUBLIC_SYNCWORD: u8 = 0x34;
 /// Sync word for private LoRaWAN networks
 const LORAWAN_PRIVATE_SYNCWORD: u8 = 0x12;
 
+/// Temperature drift (°C) since the last image calibration after which the image is calibrated again
+const IMAGE_CALIBRATION_TEMPERATURE_DELTA: i16 = 10;
+
+/// Default margin added to the time on air of a packet to obtain its TX timeout
+const DEFAULT_TX_TIMEOUT_MARGIN_MS: u32 = 100;
+
+/// Time given to the radio to return to standby after a TX timeout, before resetting it
+const TX_RECOVERY_TIMEOUT_MS: u32 = 10;
+
 /// Provides the physical layer API to support LoRa chips
 pub struct LoRa<RK, DLY>
 where
@@ -52,7 +76,16 @@ where
     radio_mode: RadioMode,
     sync_word: u8,
     cold_start: bool,
-    calibrate_image: bool,
+    calibrated_band: Option<u32>,
+    temperature: Option<i16>,
+    calibration_temperature: Option<i16>,
+    frequency_offset: i32,
+    tx_timeout_margin_ms: Option<u32>,
+    // TX timeout of the prepared packet, 0 for none
+    tx_timeout_ms: u32,
+    // Set while an operation drives the radio, left set when its future is dropped
+    interrupted: bool,
+    fsk: bool,
 }
 
 impl<RK, DLY> LoRa<RK, DLY>
@@ -68,7 +101,14 @@ where
             radio_mode: RadioMode::Sleep,
             sync_word,
             cold_start: true,
-            calibrate_image: true,
+            calibrated_band: None,
+            temperature: None,
+            calibration_temperature: None,
+            frequency_offset: 0,
+            tx_timeout_margin_ms: Some(DEFAULT_TX_TIMEOUT_MARGIN_MS),
+            tx_timeout_ms: 0,
+            interrupted: false,
+            fsk: false,
         };
         lora.init().await?;
 
@@ -96,11 +136,14 @@ where
 
     /// Process an IRQ event and return the new state of the radio
     ///
-    /// # Warning
-    /// This function is not safe to drop or cancel, as it calls `process_irq_event`, which must run to completion to avoid radio lockups.
-    /// Do not call this function within a select branch or in any context where it may be prematurely canceled.
+    /// # Cancellation
+    /// If this future is dropped, the radio is returned to standby with its IRQs cleared before the
+    /// next operation (see [`LoRa::tx`]).
     pub async fn process_irq_event(&mut self) -> Result<Option<IrqState>, RadioError> {
-        self.radio_kind.process_irq_event(self.radio_mode, None, false).await
+        self.interrupted = true;
+        let irq_state = self.radio_kind.process_irq_event(self.radio_mode, None, false).await;
+        self.interrupted = false;
+        irq_state
     }
 
     /// Create modulation parameters for a communication channel
@@ -154,6 +197,55 @@ where
         )
     }
 
+    /// Create FSK modulation parameters for a communication channel, where `rx_bandwidth` is the
+    /// single-sideband receiver bandwidth in Hz
+    pub fn create_fsk_modulation_params(
+        &mut self,
+        bitrate: u32,
+        fdev: u32,
+        pulse_shape: PulseShape,
+        rx_bandwidth: u32,
+        frequency_in_hz: u32,
+    ) -> Result<FskModulationParams, RadioError> {
+        self.radio_kind
+            .create_fsk_modulation_params(bitrate, fdev, pulse_shape, rx_bandwidth, frequency_in_hz)
+    }
+
+    /// Create FSK packet parameters for a transmit operation on a communication channel, with
+    /// the preamble length in bytes and a sync word of up to 8 bytes
+    pub fn create_fsk_tx_packet_params(
+        &mut self,
+        preamble_length: u16,
+        sync_word: &[u8],
+        variable_length: bool,
+        crc_on: bool,
+        whitening: bool,
+    ) -> Result<FskPacketParams, RadioError> {
+        self.radio_kind
+            .create_fsk_packet_params(preamble_length, sync_word, variable_length, 0, crc_on, whitening)
+    }
+
+    /// Create FSK packet parameters for a receive operation on a communication channel, with
+    /// the preamble length in bytes and a sync word of up to 8 bytes
+    pub fn create_fsk_rx_packet_params(
+        &mut self,
+        preamble_length: u16,
+        sync_word: &[u8],
+        variable_length: bool,
+        max_payload_length: u8,
+        crc_on: bool,
+        whitening: bool,
+    ) -> Result<FskPacketParams, RadioError> {
+        self.radio_kind.create_fsk_packet_params(
+            preamble_length,
+            sync_word,
+            variable_length,
+            max_payload_length,
+            crc_on,
+            whitening,
+        )
+    }
+
     /// Initialize the radio for LoRa physical layer communications
     pub async fn init(&mut self) -> Result<(), RadioError> {
         self.cold_start = true;
@@ -169,18 +261,77 @@ where
         self.radio_kind.set_tx_power_and_ramp_time(0, None, false).await?;
         self.radio_kind.set_irq_params(Some(self.radio_mode)).await?;
         self.cold_start = false;
-        self.calibrate_image = true;
+        self.calibrated_band = None;
+        self.fsk = false;
         Ok(())
     }
 
+    /// Calibrate the image rejection of the radio for a frequency. This is done automatically
+    /// before operating on a frequency outside of the last calibrated band, or after a temperature
+    /// drift reported through [`LoRa::set_temperature()`].
+    pub async fn calibrate_image(&mut self, frequency_in_hz: u32) -> Result<(), RadioError> {
+        self.prepare_standby().await?;
+        self.do_calibrate_image(frequency_in_hz).await
+    }
+
+    async fn do_calibrate_image(&mut self, frequency_in_hz: u32) -> Result<(), RadioError> {
+        self.radio_kind.calibrate_image(frequency_in_hz).await?;
+        self.calibrated_band = Some(self.radio_kind.image_calibration_band(frequency_in_hz));
+        self.calibration_temperature = self.temperature;
+        Ok(())
+    }
+
+    /// Report the current temperature (°C) of the radio, as measured by the application. The image
+    /// is calibrated again before the next operation once the temperature has drifted by 10 °C or
+    /// more since the last calibration.
+    pub fn set_temperature(&mut self, temperature_celsius: i16) {
+        match self.calibration_temperature {
+            Some(reference) => {
+                if (temperature_celsius - reference).abs() >= IMAGE_CALIBRATION_TEMPERATURE_DELTA {
+                    self.calibrated_band = None;
+                }
+            }
+            // first report since a calibration done at an unknown temperature
+            None if self.calibrated_band.is_some() => self.calibration_temperature = Some(temperature_celsius),
+            None => {}
+        }
+        self.temperature = Some(temperature_celsius);
+    }
+
+    /// Offset (Hz) applied to the channel frequencies to compensate the drift of the radio crystal
+    pub fn frequency_offset(&self) -> i32 {
+        self.frequency_offset
+    }
+
+    /// Set the offset (Hz) applied to the channel frequencies of subsequent operations
+    pub fn set_frequency_offset(&mut self, offset_in_hz: i32) {
+        self.frequency_offset = offset_in_hz;
+    }
+
+    /// Automatic frequency correction: shift the channel frequencies of subsequent operations by
+    /// the frequency error of a received packet, so that the radio follows the transmitter
+    pub fn correct_frequency(&mut self, packet_status: &PacketStatus) {
+        if let Some(frequency_error) = packet_status.frequency_error {
+            self.frequency_offset = self.frequency_offset.saturating_add(frequency_error);
+        }
+    }
+
+    async fn set_channel(&mut self, frequency_in_hz: u32) -> Result<(), RadioError> {
+        self.radio_kind
+            .set_channel(frequency_in_hz.saturating_add_signed(self.frequency_offset))
+            .await
+    }
+
     /// Place the LoRa physical layer in standby mode
     pub async fn enter_standby(&mut self) -> Result<(), RadioError> {
+        self.recover_interrupted().await?;
         self.radio_kind.set_standby().await
     }
 
     /// Place the LoRa physical layer in low power mode, specifying cold or
     /// warm start (if chip supports it)
     pub async fn sleep(&mut self, warm_start_if_possible: bool) -> Result<(), RadioError> {
+        self.recover_interrupted().await?;
         if self.radio_mode != RadioMode::Sleep {
             self.radio_kind.ensure_ready(self.radio_mode).await?;
             self.radio_kind
@@ -202,7 +353,7 @@ where
         output_power: i32,
         buffer: &[u8],
     ) -> Result<(), RadioError> {
-        self.prepare_modem(mdltn_params.frequency_in_hz).await?;
+        self.prepare_modem(mdltn_params.frequency_in_hz, false).await?;
 
         self.radio_kind.set_modulation_params(mdltn_params).await?;
         self.radio_kind
@@ -216,7 +367,48 @@ where
 
         tx_pkt_params.set_payload_length(buffer.len())?;
         self.radio_kind.set_packet_params(tx_pkt_params).await?;
-        self.radio_kind.set_channel(mdltn_params.frequency_in_hz).await?;
+        let time_on_air_us = BaseBandModulationParams::new(
+            mdltn_params.spreading_factor,
+            mdltn_params.bandwidth,
+            mdltn_params.coding_rate,
+        )
+        .time_on_air_us(
+            Some(tx_pkt_params.preamble_length.min(u8::MAX as u16) as u8),
+            !tx_pkt_params.implicit_header,
+            tx_pkt_params.payload_length,
+        );
+        self.tx_timeout_ms = self.tx_timeout_ms(time_on_air_us);
+        self.set_channel(mdltn_params.frequency_in_hz).await?;
+        self.radio_kind.set_payload(buffer).await?;
+        self.radio_mode = RadioMode::Transmit;
+        self.radio_kind.set_irq_params(Some(self.radio_mode)).await?;
+        Ok(())
+    }
+
+    /// Prepare the radio for an FSK transmit operation, executed by [`LoRa::tx`]
+    pub async fn prepare_for_fsk_tx(
+        &mut self,
+        mdltn_params: &FskModulationParams,
+        tx_pkt_params: &mut FskPacketParams,
+        output_power: i32,
+        buffer: &[u8],
+    ) -> Result<(), RadioError> {
+        self.prepare_modem(mdltn_params.frequency_in_hz, true).await?;
+
+        self.radio_kind
+            .set_tx_power_and_ramp_time(output_power, None, true)
+            .await?;
+        self.radio_kind.set_fsk_modulation_params(mdltn_params).await?;
+        self.radio_kind.ensure_ready(self.radio_mode).await?;
+        if self.radio_mode != RadioMode::Standby {
+            self.radio_kind.set_standby().await?;
+            self.radio_mode = RadioMode::Standby;
+        }
+
+        tx_pkt_params.set_payload_length(buffer.len())?;
+        self.radio_kind.set_fsk_packet_params(tx_pkt_params).await?;
+        self.tx_timeout_ms = self.tx_timeout_ms(tx_pkt_params.time_on_air_us(mdltn_params.bitrate));
+        self.set_channel(mdltn_params.frequency_in_hz).await?;
         self.radio_kind.set_payload(buffer).await?;
         self.radio_mode = RadioMode::Transmit;
         self.radio_kind.set_irq_params(Some(self.radio_mode)).await?;
@@ -225,33 +417,130 @@ where
 
     /// Execute a transmit operation
     ///
-    /// # Warning
-    /// This function is not safe to drop or cancel, as it calls `process_irq_event`, which must run to completion to avoid radio lockups.
-    /// Do not call this function within a select branch or in any context where it may be prematurely canceled.
+    /// # Cancellation
+    /// This future may be dropped, e.g. by a `select` timeout. The radio is then returned to standby
+    /// with its IRQs cleared before the next operation, or by [`LoRa::abort`]. The dropped
+    /// transmission, like a dropped CAD, has to be prepared again.
+    ///
+    /// The transmission fails with [`RadioError::TransmitTimeout`] once the TX timeout (see
+    /// [`LoRa::set_tx_timeout_margin`]) expires, after returning the radio to standby or resetting
+    /// it. Radios without a hardware TX timeout (sx127x) rely on a watchdog running on the delay,
+    /// which requires an asynchronous IRQ pin. The blocking API polls the IRQ status instead.
     pub async fn tx(&mut self) -> Result<(), RadioError> {
+        self.transmit(false).await
+    }
+
+    // Execute a transmit operation, waiting for its end on the IRQ pin, or by polling the IRQ status
+    // against the TX timeout if `poll_irq_status` (blocking API, where waiting on the IRQ pin cannot
+    // be interrupted by the watchdog)
+    pub(crate) async fn transmit(&mut self, poll_irq_status: bool) -> Result<(), RadioError> {
+        self.recover_interrupted().await?;
         if let RadioMode::Transmit = self.radio_mode {
-            self.radio_kind.do_tx().await?;
-            loop {
-                self.wait_for_irq().await?;
-                match self.radio_kind.process_irq_event(self.radio_mode, None, true).await {
-                    Ok(Some(IrqState::Done | IrqState::PreambleReceived)) => {
-                        self.radio_mode = RadioMode::Standby;
-                        return Ok(());
+            self.interrupted = true;
+            self.radio_kind.do_tx(self.tx_timeout_ms).await?;
+            let result = if poll_irq_status {
+                self.poll_tx_done().await
+            } else {
+                let mut watchdog = pin!(self.delay.delay_ms(self.tx_timeout_ms));
+                loop {
+                    let irq = if self.tx_timeout_ms == 0 {
+                        Some(self.radio_kind.await_irq().await)
+                    } else {
+                        with_timeout(self.radio_kind.await_irq(), watchdog.as_mut()).await
+                    };
+                    match irq {
+                        Some(irq) => irq?,
+                        None => break Err(RadioError::TransmitTimeout),
                     }
-                    Ok(None) => continue,
-                    Err(err) => {
-                        self.radio_kind.ensure_ready(self.radio_mode).await?;
-                        self.radio_kind.set_standby().await?;
-                        self.radio_mode = RadioMode::Standby;
-                        return Err(err);
+                    match self.radio_kind.process_irq_event(self.radio_mode, None, true).await {
+                        Ok(Some(IrqState::Done | IrqState::PreambleReceived)) => break Ok(()),
+                        Ok(None) => continue,
+                        Err(err) => break Err(err),
                     }
                 }
-            }
+            };
+            let result = match result {
+                Ok(()) => {
+                    self.radio_mode = RadioMode::Standby;
+                    Ok(())
+                }
+                Err(RadioError::TransmitTimeout) => {
+                    warn!("TX timeout after {} ms", self.tx_timeout_ms);
+                    self.recover_from_tx_timeout().await?;
+                    Err(RadioError::TransmitTimeout)
+                }
+                Err(err) => {
+                    self.radio_kind.ensure_ready(self.radio_mode).await?;
+                    self.radio_kind.set_standby().await?;
+                    self.radio_mode = RadioMode::Standby;
+                    Err(err)
+                }
+            };
+            self.interrupted = false;
+            result
         } else {
             Err(RadioError::InvalidRadioMode)
         }
     }
 
+    // Poll the IRQ status every millisecond until the end of the transmission or the TX timeout
+    async fn poll_tx_done(&mut self) -> Result<(), RadioError> {
+        let mut elapsed_ms = 0;
+        loop {
+            match self.radio_kind.get_irq_state(self.radio_mode, None).await {
+                Ok(None) => {
+                    if self.tx_timeout_ms != 0 && elapsed_ms >= self.tx_timeout_ms {
+                        return Err(RadioError::TransmitTimeout);
+                    }
+                    self.delay.delay_ms(1).await;
+                    elapsed_ms += 1;
+                }
+                irq_state => {
+                    self.radio_kind.clear_irq_status().await?;
+                    return irq_state.map(|_| ());
+                }
+            }
+        }
+    }
+
+    /// Set the margin added to the time on air of a packet to obtain its TX timeout, or `None` to
+    /// transmit without timeout. The margin defaults to 100 ms and applies to packets prepared
+    /// afterwards.
+    pub fn set_tx_timeout_margin(&mut self, margin_in_ms: Option<u32>) {
+        self.tx_timeout_margin_ms = margin_in_ms;
+    }
+
+    fn tx_timeout_ms(&self, time_on_air_us: u32) -> u32 {
+        match self.tx_timeout_margin_ms {
+            Some(margin_in_ms) => time_on_air_us.div_ceil(1000).saturating_add(margin_in_ms),
+            None => 0,
+        }
+    }
+
+    // Return the radio to standby after a TX timeout, resetting it when it does not respond
+    async fn recover_from_tx_timeout(&mut self) -> Result<(), RadioError> {
+        let standby = {
+            let radio_kind = &mut self.radio_kind;
+            let radio_mode = self.radio_mode;
+            let standby = async {
+                radio_kind.ensure_ready(radio_mode).await?;
+                radio_kind.set_standby().await
+            };
+            let timer = pin!(self.delay.delay_ms(TX_RECOVERY_TIMEOUT_MS));
+            with_timeout(standby, timer).await
+        };
+        match standby {
+            Some(Ok(())) => {
+                self.radio_mode = RadioMode::Standby;
+                Ok(())
+            }
+            _ => {
+                warn!("radio unresponsive after TX timeout, resetting it");
+                self.init().await
+            }
+        }
+    }
+
     /// Configure radio for a receive operation
     pub async fn prepare_for_rx(
         &mut self,
@@ -260,11 +549,30 @@ where
         rx_pkt_params: &PacketParams,
     ) -> Result<(), RadioError> {
         trace!("RX mode: {}", listen_mode);
-        self.prepare_modem(mdltn_params.frequency_in_hz).await?;
+        self.prepare_modem(mdltn_params.frequency_in_hz, false).await?;
 
         self.radio_kind.set_modulation_params(mdltn_params).await?;
         self.radio_kind.set_packet_params(rx_pkt_params).await?;
-        self.radio_kind.set_channel(mdltn_params.frequency_in_hz).await?;
+        self.set_channel(mdltn_params.frequency_in_hz).await?;
+        self.radio_mode = listen_mode.into();
+        self.radio_kind.set_irq_params(Some(self.radio_mode)).await?;
+        Ok(())
+    }
+
+    /// Configure radio for an FSK receive operation, started by [`LoRa::start_rx`]. For
+    /// [`RxMode::Single`], the timeout is given in bits.
+    pub async fn prepare_for_fsk_rx(
+        &mut self,
+        listen_mode: RxMode,
+        mdltn_params: &FskModulationParams,
+        rx_pkt_params: &FskPacketParams,
+    ) -> Result<(), RadioError> {
+        trace!("FSK RX mode: {}", listen_mode);
+        self.prepare_modem(mdltn_params.frequency_in_hz, true).await?;
+
+        self.radio_kind.set_fsk_modulation_params(mdltn_params).await?;
+        self.radio_kind.set_fsk_packet_params(rx_pkt_params).await?;
+        self.set_channel(mdltn_params.frequency_in_hz).await?;
         self.radio_mode = listen_mode.into();
         self.radio_kind.set_irq_params(Some(self.radio_mode)).await?;
         Ok(())
@@ -273,6 +581,7 @@ where
     /// Switch radio to receive mode (prepared via [`LoRa::prepare_for_rx`]).
     /// Call [`LoRa::complete_rx`] to wait and handle result.
     pub async fn start_rx(&mut self) -> Result<(), RadioError> {
+        self.recover_interrupted().await?;
         if let RadioMode::Receive(listen_mode) = self.radio_mode {
             self.radio_kind.do_rx(listen_mode).await
         } else {
@@ -282,26 +591,55 @@ where
 
     /// Wait for a previously started receive to complete
     ///
-    /// # Warning
-    /// This function is not safe to drop or cancel, as it calls `process_irq_event`, which must run to completion to avoid radio lockups.
-    /// Do not call this function within a select branch or in any context where it may be prematurely canceled.
+    /// # Cancellation
+    /// This future may be dropped, e.g. by a `select` timeout. The radio is then returned to standby
+    /// with its IRQs cleared, and the reception is restarted by the next [`LoRa::start_rx`] or
+    /// [`LoRa::complete_rx`].
     pub async fn complete_rx(
         &mut self,
         packet_params: &PacketParams,
         receiving_buffer: &mut [u8],
     ) -> Result<(u8, PacketStatus), RadioError> {
-        if let RadioMode::Receive(_) = self.radio_mode {
+        self.wait_for_rx_done().await?;
+        let received_len = self.radio_kind.get_rx_payload(packet_params, receiving_buffer).await?;
+        let rx_pkt_status = self.radio_kind.get_rx_packet_status().await?;
+        self.interrupted = false;
+        Ok((received_len, rx_pkt_status))
+    }
+
+    /// Wait for a previously started FSK receive to complete
+    ///
+    /// # Cancellation
+    /// This future may be dropped like [`LoRa::complete_rx`].
+    pub async fn complete_fsk_rx(
+        &mut self,
+        packet_params: &FskPacketParams,
+        receiving_buffer: &mut [u8],
+    ) -> Result<(u8, PacketStatus), RadioError> {
+        self.wait_for_rx_done().await?;
+        let received_len = self
+            .radio_kind
+            .get_fsk_rx_payload(packet_params, receiving_buffer)
+            .await?;
+        let rx_pkt_status = self.radio_kind.get_rx_packet_status().await?;
+        self.interrupted = false;
+        Ok((received_len, rx_pkt_status))
+    }
+
+    // Wait for the end of a reception, leaving the operation marked as interrupted until the
+    // payload is read
+    async fn wait_for_rx_done(&mut self) -> Result<(), RadioError> {
+        if let RadioMode::Receive(listen_mode) = self.radio_mode {
+            if self.interrupted {
+                // restart the reception dropped before
+                self.recover_interrupted().await?;
+                self.radio_kind.do_rx(listen_mode).await?;
+            }
+            self.interrupted = true;
             loop {
                 match self.radio_kind.process_irq_event(self.radio_mode, None, true).await {
-                    Ok(Some(actual_state)) => match actual_state {
-                        IrqState::PreambleReceived => (),
-                        IrqState::Done => {
-                            let received_len = self.radio_kind.get_rx_payload(packet_params, receiving_buffer).await?;
-                            let rx_pkt_status = self.radio_kind.get_rx_packet_status().await?;
-                            return Ok((received_len, rx_pkt_status));
-                        }
-                    },
-                    Ok(None) => (),
+                    Ok(Some(IrqState::PreambleReceived)) | Ok(None) => (),
+                    Ok(Some(IrqState::Done)) => return Ok(()),
                     Err(err) => {
                         // if in rx continuous mode, allow the caller to determine whether to keep receiving
                         if self.radio_mode != RadioMode::Receive(RxMode::Continuous) {
@@ -309,6 +647,7 @@ where
                             self.radio_kind.set_standby().await?;
                             self.radio_mode = RadioMode::Standby;
                         }
+                        self.interrupted = false;
                         return Err(err);
                     }
                 }
@@ -345,6 +684,36 @@ where
         }
     }
 
+    /// Extracts the received FSK payload and packet status after a completed RX IRQ event.
+    /// Should be called after receiving `IrqState::Done`.
+    pub async fn get_fsk_rx_result(
+        &mut self,
+        packet_params: &FskPacketParams,
+        receiving_buffer: &mut [u8],
+    ) -> Result<(u8, PacketStatus), RadioError> {
+        if let RadioMode::Receive(_) = self.radio_mode {
+            let received_len = self
+                .radio_kind
+                .get_fsk_rx_payload(packet_params, receiving_buffer)
+                .await?;
+            let rx_pkt_status = self.radio_kind.get_rx_packet_status().await?;
+            Ok((received_len, rx_pkt_status))
+        } else {
+            Err(RadioError::InvalidRadioMode)
+        }
+    }
+
+    /// Start FSK reception and wait for its completion by calling
+    /// [`LoRa::start_rx`]  and [`LoRa::complete_fsk_rx`] in succession.
+    pub async fn fsk_rx(
+        &mut self,
+        packet_params: &FskPacketParams,
+        receiving_buffer: &mut [u8],
+    ) -> Result<(u8, PacketStatus), RadioError> {
+        self.start_rx().await?;
+        self.complete_fsk_rx(packet_params, receiving_buffer).await
+    }
+
     /// Start reception and wait for its completion by calling
     /// [`LoRa::start_rx`]  and [`LoRa::complete_rx`] in succession.
     pub async fn rx(
@@ -358,9 +727,9 @@ where
 
     /// Start listening to a given frequency and [`Bandwidth`]
     pub async fn listen(&mut self, frequency_in_hz: u32, bandwidth: Bandwidth) -> Result<(), RadioError> {
-        self.prepare_modem(frequency_in_hz).await?;
+        self.prepare_modem(frequency_in_hz, false).await?;
 
-        self.radio_kind.set_channel(frequency_in_hz).await?;
+        self.set_channel(frequency_in_hz).await?;
         // We need to set the bandwidth, otherwise sx126x doesn't return
         // reasonable RSSI results. All other params are irrelevant with
         // regard to listening to measure RSSI.
@@ -382,12 +751,34 @@ where
         self.radio_kind.get_rssi().await
     }
 
+    /// Get the packet statistics (received packets, CRC and header errors) since the last reset.
+    /// The sx127x and sx128x count them in software.
+    pub async fn get_packet_stats(&mut self) -> Result<PacketStats, RadioError> {
+        self.radio_kind.get_packet_stats().await
+    }
+
+    /// Reset the packet statistics
+    pub async fn reset_packet_stats(&mut self) -> Result<(), RadioError> {
+        self.radio_kind.reset_packet_stats().await
+    }
+
+    /// Get the hardware errors (calibrations, oscillator start, PLL lock, PA ramp) reported by the
+    /// radio. The sx127x and sx128x do not report errors.
+    pub async fn get_device_errors(&mut self) -> Result<DeviceErrors, RadioError> {
+        self.radio_kind.get_device_errors().await
+    }
+
+    /// Clear the hardware errors reported by the radio
+    pub async fn clear_device_errors(&mut self) -> Result<(), RadioError> {
+        self.radio_kind.clear_device_errors().await
+    }
+
     /// Prepare the radio for a channel activity detection (CAD) operation
     pub async fn prepare_for_cad(&mut self, mdltn_params: &ModulationParams) -> Result<(), RadioError> {
-        self.prepare_modem(mdltn_params.frequency_in_hz).await?;
+        self.prepare_modem(mdltn_params.frequency_in_hz, false).await?;
 
         self.radio_kind.set_modulation_params(mdltn_params).await?;
-        self.radio_kind.set_channel(mdltn_params.frequency_in_hz).await?;
+        self.set_channel(mdltn_params.frequency_in_hz).await?;
         self.radio_mode = RadioMode::ChannelActivityDetection;
         self.radio_kind.set_irq_params(Some(self.radio_mode)).await?;
         Ok(())
@@ -395,15 +786,16 @@ where
 
     /// Start channel activity detection (CAD) operation and return the result
     ///
-    /// # Warning
-    /// This function is not safe to drop or cancel, as it calls `process_irq_event`, which must run to completion to avoid radio lockups.
-    /// Do not call this function within a select branch or in any context where it may be prematurely canceled.
+    /// # Cancellation
+    /// This future may be dropped like [`LoRa::tx`].
     pub async fn cad(&mut self, mdltn_params: &ModulationParams) -> Result<bool, RadioError> {
+        self.recover_interrupted().await?;
         if self.radio_mode == RadioMode::ChannelActivityDetection {
+            self.interrupted = true;
             self.radio_kind.do_cad(mdltn_params).await?;
             self.wait_for_irq().await?;
             let mut cad_activity_detected = false;
-            match self
+            let result = match self
                 .radio_kind
                 .process_irq_event(self.radio_mode, Some(&mut cad_activity_detected), true)
                 .await
@@ -416,7 +808,9 @@ where
                     Err(err)
                 }
                 Ok(_) => unreachable!(),
-            }
+            };
+            self.interrupted = false;
+            result
         } else {
             Err(RadioError::InvalidRadioMode)
         }
@@ -432,7 +826,7 @@ where
         mdltn_params: &ModulationParams,
         output_power: i32,
     ) -> Result<(), RadioError> {
-        self.prepare_modem(mdltn_params.frequency_in_hz).await?;
+        self.prepare_modem(mdltn_params.frequency_in_hz, false).await?;
 
         let tx_pkt_params = self
             .radio_kind
@@ -448,13 +842,45 @@ where
             self.radio_kind.set_standby().await?;
             self.radio_mode = RadioMode::Standby;
         }
-        self.radio_kind.set_channel(mdltn_params.frequency_in_hz).await?;
+        self.set_channel(mdltn_params.frequency_in_hz).await?;
         self.radio_mode = RadioMode::Transmit;
         self.radio_kind.set_irq_params(Some(self.radio_mode)).await?;
         self.radio_kind.set_tx_continuous_wave_mode().await
     }
 
-    async fn prepare_modem(&mut self, frequency_in_hz: u32) -> Result<(), RadioError> {
+    /// Abort the ongoing operation, or the one whose future was dropped: return the radio to standby
+    /// and clear its IRQs
+    pub async fn abort(&mut self) -> Result<(), RadioError> {
+        if self.radio_mode == RadioMode::Sleep {
+            return Ok(());
+        }
+        self.interrupted = true;
+        self.recover_interrupted().await?;
+        self.radio_mode = RadioMode::Standby;
+        Ok(())
+    }
+
+    // Return the radio to standby with its IRQs cleared after an operation was dropped. A dropped
+    // reception stays prepared so that it can be restarted.
+    async fn recover_interrupted(&mut self) -> Result<(), RadioError> {
+        if self.interrupted {
+            debug!(
+                "recovering from an interrupted operation in radio mode {}",
+                self.radio_mode
+            );
+            self.radio_kind.ensure_ready(self.radio_mode).await?;
+            self.radio_kind.set_standby().await?;
+            self.radio_kind.clear_irq_status().await?;
+            if !matches!(self.radio_mode, RadioMode::Receive(_)) {
+                self.radio_mode = RadioMode::Standby;
+            }
+            self.interrupted = false;
+        }
+        Ok(())
+    }
+
+    async fn prepare_standby(&mut self) -> Result<(), RadioError> {
+        self.recover_interrupted().await?;
         self.radio_kind.ensure_ready(self.radio_mode).await?;
         if self.radio_mode != RadioMode::Standby {
             self.radio_kind.set_standby().await?;
@@ -464,12 +890,40 @@ where
         if self.cold_start {
             self.do_cold_start().await?;
         }
+        Ok(())
+    }
 
-        if self.calibrate_image {
-            self.radio_kind.calibrate_image(frequency_in_hz).await?;
-            self.calibrate_image = false;
+    async fn prepare_modem(&mut self, frequency_in_hz: u32, fsk: bool) -> Result<(), RadioError> {
+        self.prepare_standby().await?;
+
+        if self.calibrated_band != Some(self.radio_kind.image_calibration_band(frequency_in_hz)) {
+            self.do_calibrate_image(frequency_in_hz).await?;
+        }
+
+        if self.fsk != fsk {
+            if fsk {
+                self.radio_kind.init_fsk().await?;
+            } else {
+                self.radio_kind.init_lora(self.sync_word).await?;
+            }
+            self.fsk = fsk;
         }
 
         Ok(())
     }
 }
+
+// Run a future until the timer completes, returning None if the timer completes first
+async fn with_timeout<F: Future, T: Future>(future: F, mut timer: Pin<&mut T>) -> Option<F::Output> {
+    let mut future = pin!(future);
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = future.as_mut().poll(cx) {
+            return Poll::Ready(Some(output));
+        }
+        if timer.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(None);
+        }
+        Poll::Pending
+    })
+    .await
+}
@@ -0,0 +1,274 @@
+#![allow(missing_docs)]
+
+pub mod nb;
+
+use super::mod_params::{FskModulationParams, FskPacketParams, PacketParams, PacketStatus, PulseShape, RadioError};
+use super::mod_traits::RadioKind;
+use super::{DelayNs, LoRa, RxMode};
+
+use lora_modulation::BaseBandModulationParams;
+use lorawan_device::async_device::{
+    radio::{FskModulation, PhyRxTx, RfConfig, RxConfig, RxMode as LorawanRxMode, RxQuality, RxStatus, TxConfig},
+    Timings,
+};
+
+const DEFAULT_RX_WINDOW_LEAD_TIME: u32 = 50;
+
+/// LoRaWAN radio implementation.
+///
+/// The const generic P is the max power the radio may be instructed to transmit at.
+/// The const generic G is the antenna gain and board loss in dBi.
+pub struct LorawanRadio<RK, DLY, const P: u8, const G: i8 = 0>
+where
+    RK: RadioKind,
+    DLY: DelayNs,
+{
+    pub(crate) lora: LoRa<RK, DLY>,
+    rx_pkt_params: Option<RxPacketParams>,
+    rx_window_lead_time: u32,
+    rx_window_buffer: u32,
+}
+
+impl<RK, DLY, const P: u8, const G: i8> From<LoRa<RK, DLY>> for LorawanRadio<RK, DLY, P, G>
+where
+    RK: RadioKind,
+    DLY: DelayNs,
+{
+    fn from(lora: LoRa<RK, DLY>) -> Self {
+        Self {
+            lora,
+            rx_pkt_params: None,
+            rx_window_lead_time: DEFAULT_RX_WINDOW_LEAD_TIME,
+            rx_window_buffer: DEFAULT_RX_WINDOW_LEAD_TIME,
+        }
+    }
+}
+
+impl<RK, DLY, const P: u8, const G: i8> LorawanRadio<RK, DLY, P, G>
+where
+    RK: RadioKind,
+    DLY: DelayNs,
+{
+    pub fn set_rx_window_lead_time(&mut self, lt: u32) {
+        self.rx_window_lead_time = lt;
+    }
+    pub fn set_rx_window_buffer(&mut self, buffer: u32) {
+        self.rx_window_buffer = buffer;
+    }
+}
+
+/// Provide the timing values
+impl<RK, DLY, const P: u8, const G: i8> Timings for LorawanRadio<RK, DLY, P, G>
+where
+    RK: RadioKind,
+    DLY: DelayNs,
+{
+    fn get_rx_window_buffer(&self) -> u32 {
+        self.rx_window_lead_time
+    }
+
+    fn get_rx_window_lead_time_ms(&self) -> u32 {
+        self.rx_window_lead_time
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum Error {
+    Radio(RadioError),
+    NoRxParams,
+}
+
+impl From<RadioError> for Error {
+    fn from(err: RadioError) -> Self {
+        Error::Radio(err)
+    }
+}
+
+/// Provide the LoRa physical layer rx/tx interface
+impl<RK, DLY, const P: u8, const G: i8> PhyRxTx for LorawanRadio<RK, DLY, P, G>
+where
+    RK: RadioKind,
+    DLY: DelayNs,
+{
+    type PhyError = Error;
+
+    const ANTENNA_GAIN: i8 = G;
+
+    const MAX_RADIO_POWER: u8 = P;
+
+    async fn tx(&mut self, config: TxConfig, buffer: &[u8]) -> Result<u32, Self::PhyError> {
+        prepare_for_tx(&mut self.lora, &config, buffer).await?;
+        self.lora.tx().await?;
+        Ok(0)
+    }
+
+    async fn setup_rx(&mut self, config: RxConfig) -> Result<(), Self::PhyError> {
+        let rx_pkt_params = prepare_for_rx(&mut self.lora, &config.rf, config.mode).await?;
+        self.rx_pkt_params = Some(rx_pkt_params);
+        Ok(())
+    }
+
+    async fn rx_single(&mut self, buf: &mut [u8]) -> Result<RxStatus, Self::PhyError> {
+        if let Some(rx_params) = &self.rx_pkt_params {
+            match rx_params.rx(&mut self.lora, buf).await {
+                Ok((len, q)) => Ok(RxStatus::Rx(len as usize, RxQuality::new(q.rssi, q.snr as i8))),
+                Err(RadioError::ReceiveTimeout) => Ok(RxStatus::RxTimeout),
+                Err(err) => Err(err.into()),
+            }
+        } else {
+            Err(Error::NoRxParams)
+        }
+    }
+    async fn rx_continuous(&mut self, receiving_buffer: &mut [u8]) -> Result<(usize, RxQuality), Self::PhyError> {
+        if let Some(rx_params) = &self.rx_pkt_params {
+            match rx_params.rx(&mut self.lora, receiving_buffer).await {
+                Ok((received_len, rx_pkt_status)) => {
+                    Ok((
+                        received_len as usize,
+                        RxQuality::new(rx_pkt_status.rssi, rx_pkt_status.snr as i8), // downcast snr
+                    ))
+                }
+                Err(err) => Err(err.into()),
+            }
+        } else {
+            Err(Error::NoRxParams)
+        }
+    }
+    async fn low_power(&mut self) -> Result<(), Self::PhyError> {
+        self.lora.sleep(false).await.map_err(|e| e.into())
+    }
+    async fn tx_continuous_wave(&mut self, config: TxConfig, timeout_ms: u32) -> Result<(), Self::PhyError> {
+        let mdltn_params = self.lora.create_modulation_params(
+            config.rf.bb.sf,
+            config.rf.bb.bw,
+            config.rf.bb.cr,
+            config.rf.frequency,
+        )?;
+        self.lora.continuous_wave(&mdltn_params, config.pw.into()).await?;
+        self.lora.delay.delay_ms(timeout_ms).await;
+        self.lora.enter_standby().await.map_err(|e| e.into())
+    }
+}
+
+/// Packet parameters of the modulation used for a reception
+pub(crate) enum RxPacketParams {
+    LoRa(PacketParams),
+    Fsk(FskPacketParams),
+}
+
+impl RxPacketParams {
+    pub(crate) async fn rx<RK: RadioKind, DLY: DelayNs>(
+        &self,
+        lora: &mut LoRa<RK, DLY>,
+        receiving_buffer: &mut [u8],
+    ) -> Result<(u8, PacketStatus), RadioError> {
+        match self {
+            RxPacketParams::LoRa(pkt_params) => lora.rx(pkt_params, receiving_buffer).await,
+            RxPacketParams::Fsk(pkt_params) => lora.fsk_rx(pkt_params, receiving_buffer).await,
+        }
+    }
+
+    pub(crate) async fn get_rx_result<RK: RadioKind, DLY: DelayNs>(
+        &self,
+        lora: &mut LoRa<RK, DLY>,
+        receiving_buffer: &mut [u8],
+    ) -> Result<(u8, PacketStatus), RadioError> {
+        match self {
+            RxPacketParams::LoRa(pkt_params) => lora.get_rx_result(pkt_params, receiving_buffer).await,
+            RxPacketParams::Fsk(pkt_params) => lora.get_fsk_rx_result(pkt_params, receiving_buffer).await,
+        }
+    }
+}
+
+/// Prepare a LoRaWAN transmission, using FSK if the data rate requires it
+pub(crate) async fn prepare_for_tx<RK: RadioKind, DLY: DelayNs>(
+    lora: &mut LoRa<RK, DLY>,
+    config: &TxConfig,
+    buffer: &[u8],
+) -> Result<(), RadioError> {
+    if let Some(fsk) = config.rf.fsk {
+        let mdltn_params = create_fsk_modulation_params(lora, fsk, config.rf.frequency)?;
+        let mut tx_pkt_params = lora.create_fsk_tx_packet_params(
+            FskModulation::PREAMBLE_LENGTH.into(),
+            &FskModulation::SYNC_WORD,
+            true,
+            true,
+            true,
+        )?;
+        lora.prepare_for_fsk_tx(&mdltn_params, &mut tx_pkt_params, config.pw.into(), buffer)
+            .await
+    } else {
+        let mdltn_params =
+            lora.create_modulation_params(config.rf.bb.sf, config.rf.bb.bw, config.rf.bb.cr, config.rf.frequency)?;
+        let mut tx_pkt_params = lora.create_tx_packet_params(8, false, true, false, &mdltn_params)?;
+        lora.prepare_for_tx(&mdltn_params, &mut tx_pkt_params, config.pw.into(), buffer)
+            .await
+    }
+}
+
+/// Prepare a LoRaWAN reception, using FSK if the data rate requires it
+pub(crate) async fn prepare_for_rx<RK: RadioKind, DLY: DelayNs>(
+    lora: &mut LoRa<RK, DLY>,
+    rf: &RfConfig,
+    mode: LorawanRxMode,
+) -> Result<RxPacketParams, RadioError> {
+    if let Some(fsk) = rf.fsk {
+        let mdltn_params = create_fsk_modulation_params(lora, fsk, rf.frequency)?;
+        let rx_pkt_params = lora.create_fsk_rx_packet_params(
+            FskModulation::PREAMBLE_LENGTH.into(),
+            &FskModulation::SYNC_WORD,
+            true,
+            255,
+            true,
+            true,
+        )?;
+        lora.prepare_for_fsk_rx(RxMode::from_fsk(mode, fsk), &mdltn_params, &rx_pkt_params)
+            .await?;
+        Ok(RxPacketParams::Fsk(rx_pkt_params))
+    } else {
+        let mdltn_params = lora.create_modulation_params(rf.bb.sf, rf.bb.bw, rf.bb.cr, rf.frequency)?;
+        let rx_pkt_params = lora.create_rx_packet_params(8, false, 255, true, true, &mdltn_params)?;
+        lora.prepare_for_rx(RxMode::from(mode, rf.bb), &mdltn_params, &rx_pkt_params)
+            .await?;
+        Ok(RxPacketParams::LoRa(rx_pkt_params))
+    }
+}
+
+fn create_fsk_modulation_params<RK: RadioKind, DLY: DelayNs>(
+    lora: &mut LoRa<RK, DLY>,
+    fsk: FskModulation,
+    frequency_in_hz: u32,
+) -> Result<FskModulationParams, RadioError> {
+    // Carson's rule for the single-sideband receiver bandwidth
+    let rx_bandwidth = fsk.fdev + fsk.bitrate / 2;
+    lora.create_fsk_modulation_params(fsk.bitrate, fsk.fdev, PulseShape::Bt1_0, rx_bandwidth, frequency_in_hz)
+}
+
+impl RxMode {
+    fn from_fsk(mode: LorawanRxMode, fsk: FskModulation) -> Self {
+        match mode {
+            LorawanRxMode::Continuous => RxMode::Continuous,
+            LorawanRxMode::Single { ms } => {
+                // The FSK timeout is given in bits, covering the preamble and the sync word
+                const PREAMBLE_BITS: u32 =
+                    (FskModulation::PREAMBLE_LENGTH as u32 + FskModulation::SYNC_WORD.len() as u32) * 8;
+                let num_bits = PREAMBLE_BITS + ms.saturating_mul(fsk.bitrate) / 1000;
+                RxMode::Single(num_bits.min(u16::MAX as u32) as u16)
+            }
+        }
+    }
+
+    fn from(mode: LorawanRxMode, bb: BaseBandModulationParams) -> Self {
+        match mode {
+            LorawanRxMode::Continuous => RxMode::Continuous,
+            LorawanRxMode::Single { ms } => {
+                // Since both sx126x and sx127x have a preamble-based timeout, we translate
+                // the additional millisecond delay into symbols and add it to the amount of preamble symbols.
+                const PREAMBLE_SYMBOLS: u16 = 13; // 12.25
+                let num_symbols = PREAMBLE_SYMBOLS + bb.delay_in_symbols(ms);
+                RxMode::Single(num_symbols)
+            }
+        }
+    }
+}
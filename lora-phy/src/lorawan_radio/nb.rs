@@ -0,0 +1,268 @@
+//! Interrupt-driven LoRaWAN radio implementation for [`lorawan_device::nb_device`].
+//!
+//! Operations are started when `nb_device` requests them and complete once the application passes
+//! [`PhyEvent::Irq`] to the device, typically after DIO1 has been raised:
+//!
+//! ```ignore
+//! // DIO1 interrupt handler
+//! let event = radio::Event::Phy(PhyEvent::Irq { timestamp_ms: now_ms() });
+//! device.handle_event(nb_device::Event::RadioEvent(event))
+//! ```
+//!
+//! While transmitting, the event must only be passed once DIO1 has been raised, as `nb_device`
+//! expects the transmission to be complete. [`IrqLorawanRadio::irq_pending`] allows to poll for it.
+//!
+//! The futures of the [`RadioKind`] are run with [`block_on`], hence it should be built on
+//! blocking peripherals (see [`crate::blocking`]).
+use lorawan_device::nb_device::radio::{Event, PhyRxTx, Response, RxQuality};
+use lorawan_device::Timings;
+
+use super::{prepare_for_rx, prepare_for_tx, Error, LorawanRxMode, RxPacketParams};
+use crate::blocking::block_on;
+use crate::mod_params::{RadioError, RadioMode};
+use crate::mod_traits::{IrqState, RadioKind};
+use crate::{DelayNs, LoRa};
+
+const DEFAULT_RX_WINDOW_OFFSET: i32 = -50;
+const DEFAULT_RX_WINDOW_DURATION: u32 = 1050;
+
+/// Radio specific events passed to `nb_device`
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum PhyEvent {
+    /// The radio raised its IRQ line at `timestamp_ms`, which must use the same timebase as the
+    /// timeouts requested by `nb_device`.
+    Irq { timestamp_ms: u32 },
+}
+
+/// Interrupt-driven LoRaWAN radio for `nb_device`, completing operations on [`PhyEvent::Irq`]
+/// rather than by polling the radio like [`crate::blocking::LorawanRadio`].
+///
+/// The const generic P is the max power the radio may be instructed to transmit at.
+/// The const generic G is the antenna gain and board loss in dBi.
+pub struct IrqLorawanRadio<RK, DLY, const P: u8, const G: i8 = 0>
+where
+    RK: RadioKind,
+    DLY: DelayNs,
+{
+    lora: LoRa<RK, DLY>,
+    rx_pkt_params: Option<RxPacketParams>,
+    rx_buffer: [u8; 256],
+    rx_len: usize,
+    rx_window_offset_ms: i32,
+    rx_window_duration_ms: u32,
+}
+
+impl<RK, DLY, const P: u8, const G: i8> From<LoRa<RK, DLY>> for IrqLorawanRadio<RK, DLY, P, G>
+where
+    RK: RadioKind,
+    DLY: DelayNs,
+{
+    fn from(lora: LoRa<RK, DLY>) -> Self {
+        Self {
+            lora,
+            rx_pkt_params: None,
+            rx_buffer: [0; 256],
+            rx_len: 0,
+            rx_window_offset_ms: DEFAULT_RX_WINDOW_OFFSET,
+            rx_window_duration_ms: DEFAULT_RX_WINDOW_DURATION,
+        }
+    }
+}
+
+impl<RK, DLY, const P: u8, const G: i8> IrqLorawanRadio<RK, DLY, P, G>
+where
+    RK: RadioKind,
+    DLY: DelayNs,
+{
+    pub fn set_rx_window_offset_ms(&mut self, offset: i32) {
+        self.rx_window_offset_ms = offset;
+    }
+    pub fn set_rx_window_duration_ms(&mut self, duration: u32) {
+        self.rx_window_duration_ms = duration;
+    }
+
+    /// Whether the radio has an IRQ pending for the ongoing operation, allowing to poll the radio
+    /// instead of relying on an interrupt handler.
+    pub fn irq_pending(&mut self) -> Result<bool, Error> {
+        match block_on(self.lora.radio_kind.get_irq_state(self.lora.radio_mode, None)) {
+            Ok(state) => Ok(state.is_some()),
+            Err(RadioError::ReceiveTimeout | RadioError::TransmitTimeout) => Ok(true),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn handle_irq(&mut self, timestamp_ms: u32) -> Result<Response<Self>, Error> {
+        let radio_mode = self.lora.radio_mode;
+        let irq_state = block_on(self.lora.radio_kind.process_irq_event(radio_mode, None, true));
+        match (radio_mode, irq_state) {
+            (RadioMode::Transmit, Ok(Some(IrqState::Done))) => {
+                self.lora.radio_mode = RadioMode::Standby;
+                Ok(Response::TxDone(timestamp_ms))
+            }
+            (RadioMode::Transmit, Ok(_)) => Ok(Response::Txing),
+            (RadioMode::Receive(_), Ok(Some(IrqState::Done))) => {
+                let Some(rx_pkt_params) = &self.rx_pkt_params else {
+                    return Ok(Response::Idle);
+                };
+                let (len, status) = block_on(rx_pkt_params.get_rx_result(&mut self.lora, &mut self.rx_buffer))?;
+                self.rx_len = len as usize;
+                Ok(Response::RxDone(RxQuality::new(status.rssi, status.snr as i8)))
+            }
+            // The window is closed by CancelRx
+            (RadioMode::Receive(_), Ok(_) | Err(RadioError::ReceiveTimeout)) => Ok(Response::Rxing),
+            (_, Ok(_)) => Ok(Response::Idle),
+            (_, Err(err)) => {
+                block_on(self.lora.enter_standby())?;
+                self.lora.radio_mode = RadioMode::Standby;
+                Err(err.into())
+            }
+        }
+    }
+}
+
+impl<RK, DLY, const P: u8, const G: i8> Timings for IrqLorawanRadio<RK, DLY, P, G>
+where
+    RK: RadioKind,
+    DLY: DelayNs,
+{
+    fn get_rx_window_offset_ms(&self) -> i32 {
+        self.rx_window_offset_ms
+    }
+
+    fn get_rx_window_duration_ms(&self) -> u32 {
+        self.rx_window_duration_ms
+    }
+}
+
+impl<RK, DLY, const P: u8, const G: i8> PhyRxTx for IrqLorawanRadio<RK, DLY, P, G>
+where
+    RK: RadioKind,
+    DLY: DelayNs,
+{
+    type PhyEvent = PhyEvent;
+    type PhyError = Error;
+    type PhyResponse = ();
+
+    const ANTENNA_GAIN: i8 = G;
+
+    const MAX_RADIO_POWER: u8 = P;
+
+    fn get_mut_radio(&mut self) -> &mut Self {
+        self
+    }
+
+    fn get_received_packet(&mut self) -> &mut [u8] {
+        &mut self.rx_buffer[..self.rx_len]
+    }
+
+    fn handle_event(&mut self, event: Event<'_, Self>) -> Result<Response<Self>, Self::PhyError> {
+        match event {
+            Event::TxRequest(config, buffer) => {
+                self.rx_pkt_params = None;
+                block_on(prepare_for_tx(&mut self.lora, &config, buffer))?;
+                block_on(self.lora.radio_kind.do_tx(self.lora.tx_timeout_ms))?;
+                Ok(Response::Txing)
+            }
+            Event::RxRequest(config) => {
+                let rx_pkt_params = block_on(prepare_for_rx(&mut self.lora, &config, LorawanRxMode::Continuous))?;
+                block_on(self.lora.start_rx())?;
+                self.rx_pkt_params = Some(rx_pkt_params);
+                Ok(Response::Rxing)
+            }
+            Event::CancelRx => {
+                self.rx_pkt_params = None;
+                block_on(self.lora.enter_standby())?;
+                self.lora.radio_mode = RadioMode::Standby;
+                Ok(Response::Idle)
+            }
+            Event::Phy(PhyEvent::Irq { timestamp_ms }) => self.handle_irq(timestamp_ms),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocking::tests::{radio_kind, Delay, Spi, TestRadioKind};
+    use crate::blocking::BlockingDelay;
+    use lora_modulation::BaseBandModulationParams;
+    use lorawan_device::nb_device::radio::{Bandwidth, CodingRate, FskModulation, RfConfig, SpreadingFactor, TxConfig};
+
+    type Radio<'a> = IrqLorawanRadio<TestRadioKind<'a>, BlockingDelay<Delay>, 14>;
+
+    fn rf_config() -> RfConfig {
+        RfConfig {
+            frequency: 868_100_000,
+            bb: BaseBandModulationParams::new(SpreadingFactor::_7, Bandwidth::_125KHz, CodingRate::_4_5),
+            max_payload_len: 242,
+            fsk: None,
+        }
+    }
+
+    fn irq<'a>(radio: &mut Radio<'a>, timestamp_ms: u32) -> Result<Response<Radio<'a>>, Error> {
+        radio.handle_event(Event::Phy(PhyEvent::Irq { timestamp_ms }))
+    }
+
+    #[test]
+    fn tx_done_on_irq() {
+        let mut spi = Spi::new();
+        // TxDone
+        spi.irq_status = [0x00, 0x01];
+        let lora = block_on(LoRa::new(radio_kind(&mut spi), true, BlockingDelay::new(Delay))).unwrap();
+        let mut radio: Radio<'_> = lora.into();
+        let config = TxConfig {
+            pw: 14,
+            rf: rf_config(),
+        };
+        assert!(matches!(
+            radio.handle_event(Event::TxRequest(config, &[1, 2, 3])),
+            Ok(Response::Txing)
+        ));
+        assert!(radio.irq_pending().unwrap());
+        assert!(matches!(irq(&mut radio, 1234), Ok(Response::TxDone(1234))));
+    }
+
+    #[test]
+    fn rx_without_irq() {
+        let mut spi = Spi::new();
+        let lora = block_on(LoRa::new(radio_kind(&mut spi), true, BlockingDelay::new(Delay))).unwrap();
+        let mut radio: Radio<'_> = lora.into();
+        assert!(matches!(
+            radio.handle_event(Event::RxRequest(rf_config())),
+            Ok(Response::Rxing)
+        ));
+        assert!(!radio.irq_pending().unwrap());
+        // Spurious IRQ keeps receiving
+        assert!(matches!(irq(&mut radio, 0), Ok(Response::Rxing)));
+        assert!(matches!(radio.handle_event(Event::CancelRx), Ok(Response::Idle)));
+        assert!(matches!(irq(&mut radio, 0), Ok(Response::Idle)));
+    }
+
+    #[test]
+    fn fsk_tx_switches_packet_type() {
+        let mut spi = Spi::new();
+        {
+            let lora = block_on(LoRa::new(radio_kind(&mut spi), true, BlockingDelay::new(Delay))).unwrap();
+            let mut radio: Radio<'_> = lora.into();
+            let config = TxConfig {
+                pw: 14,
+                rf: RfConfig {
+                    fsk: Some(FskModulation::LORAWAN_50KBPS),
+                    ..rf_config()
+                },
+            };
+            assert!(matches!(
+                radio.handle_event(Event::TxRequest(config, &[1, 2, 3])),
+                Ok(Response::Txing)
+            ));
+        }
+
+        // SetPacketType to LoRa on init, then to GFSK before SetModulationParams and SetTx
+        let opcodes = spi.opcodes();
+        assert_eq!(opcodes.iter().filter(|&&op| op == 0x8a).count(), 2);
+        let gfsk = opcodes.iter().rposition(|&op| op == 0x8a).unwrap();
+        assert!(opcodes[gfsk..].contains(&0x8b));
+        assert_eq!(opcodes.last(), Some(&0x83));
+    }
+}
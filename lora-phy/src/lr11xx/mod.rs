@@ -0,0 +1,1131 @@
+mod radio_kind_params;
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::spi::*;
+use lora_modulation::BaseBandModulationParams;
+pub use radio_kind_params::TcxoCtrlVoltage;
+use radio_kind_params::*;
+
+use crate::mod_params::*;
+use crate::mod_traits::IrqState;
+use crate::{InterfaceVariant, RadioKind, SpiInterface};
+mod variant;
+pub use variant::*;
+
+// Time required for the TCXO to wakeup [ms].
+const BRD_TCXO_WAKEUP_TIME: u32 = 5;
+
+// Frequency of the RTC counting the timeouts
+const LR11XX_RTC_FREQ: u64 = 32768;
+
+// SetRx timeout argument for enabling continuous mode
+const RX_CONTINUOUS_TIMEOUT: u32 = 0xffffff;
+
+// Supported frequency ranges (Hz), the last two requiring the high frequency front end
+const SUB_GHZ_FREQUENCIES: core::ops::RangeInclusive<u32> = 150_000_000..=960_000_000;
+const S_BAND_FREQUENCIES: core::ops::RangeInclusive<u32> = 1_900_000_000..=2_100_000_000;
+const ISM_2G4_FREQUENCIES: core::ops::RangeInclusive<u32> = 2_400_000_000..=2_500_000_000;
+
+// GFSK bitrate limits (bps)
+const LR11XX_FSK_MIN_BITRATE: u32 = 600;
+const LR11XX_FSK_MAX_BITRATE: u32 = 300_000;
+
+// GFSK CRC-16 (CCITT) initial value and polynomial
+const FSK_CRC_CCITT_INIT: u32 = 0x1D0F;
+const FSK_CRC_CCITT_POLYNOMIAL: u32 = 0x1021;
+
+// GFSK whitening initial value
+const FSK_WHITENING_INIT: u16 = 0x01FF;
+
+/// DIOs driving the RF switch of the board in each radio state, given as masks of
+/// [`RfSwitchConfig::DIO5`] to [`RfSwitchConfig::DIO10`]
+#[derive(Clone, Copy, Default)]
+pub struct RfSwitchConfig {
+    /// DIOs controlled by the radio, the others are left untouched
+    pub enable: u8,
+    /// DIOs set in standby mode
+    pub standby: u8,
+    /// DIOs set while receiving
+    pub rx: u8,
+    /// DIOs set while transmitting with the low power PA
+    pub tx: u8,
+    /// DIOs set while transmitting with the high power PA
+    pub tx_hp: u8,
+    /// DIOs set while transmitting with the high frequency PA
+    pub tx_hf: u8,
+    /// DIOs set during GNSS scans (Lr1110 and Lr1120)
+    pub gnss: u8,
+    /// DIOs set during Wi-Fi scans (Lr1110 and Lr1120)
+    pub wifi: u8,
+}
+
+#[allow(missing_docs)]
+impl RfSwitchConfig {
+    pub const DIO5: u8 = 1 << 0;
+    pub const DIO6: u8 = 1 << 1;
+    pub const DIO7: u8 = 1 << 2;
+    pub const DIO8: u8 = 1 << 3;
+    pub const DIO10: u8 = 1 << 4;
+}
+
+/// Configuration for LR11xx-based boards
+///
+/// The IRQ line of the [`InterfaceVariant`] is DIO9, e.g. passed as `dio1` of
+/// [`GenericSx126xInterfaceVariant`](crate::iv::GenericSx126xInterfaceVariant).
+pub struct Config<C: Lr11xxVariant + Sized> {
+    /// LoRa chip variant on this board
+    pub chip: C,
+    /// Board is using a TCXO powered by the VTCXO pin.
+    ///
+    /// The TCXO configuration must match your board's hardware, otherwise transmission & receiving
+    /// functions will hang indefinitely.
+    pub tcxo_ctrl: Option<TcxoCtrlVoltage>,
+    /// Whether board is using optional DCDC in addition to LDO
+    pub use_dcdc: bool,
+    /// Whether to use the high power PA for sub-GHz transmissions (the high frequency PA is
+    /// always used above 1.5 GHz)
+    pub use_high_power_pa: bool,
+    /// RF switch controlled by the radio DIOs, if any
+    pub rf_switch: Option<RfSwitchConfig>,
+    /// Whether to boost receive
+    pub rx_boost: bool,
+}
+
+/// Base for the RadioKind implementation for the LoRa chip kind and board type
+pub struct Lr11xx<SPI, IV, C: Lr11xxVariant + Sized> {
+    intf: SpiInterface<SPI, IV>,
+    config: Config<C>,
+    packet_type: PacketType,
+    fsk_bitrate: u32,
+    frequency_in_hz: u32,
+}
+
+impl<SPI, IV, C> Lr11xx<SPI, IV, C>
+where
+    SPI: SpiDevice<u8>,
+    IV: InterfaceVariant,
+    C: Lr11xxVariant,
+{
+    /// Create an instance of the RadioKind implementation for the LoRa chip kind and board type
+    pub fn new(spi: SPI, iv: IV, config: Config<C>) -> Self {
+        let intf = SpiInterface::new(spi, iv);
+        Self {
+            intf,
+            config,
+            packet_type: PacketType::LoRa,
+            fsk_bitrate: 0,
+            frequency_in_hz: 0,
+        }
+    }
+
+    // Utility functions
+    async fn write_command(&mut self, op_code: OpCode, params: &[u8]) -> Result<(), RadioError> {
+        self.intf.write_with_payload(&op_code.bytes(), params, false).await
+    }
+
+    // Send a command with up to two parameters and read its response
+    async fn read_command(&mut self, op_code: OpCode, params: &[u8], response: &mut [u8]) -> Result<(), RadioError> {
+        let mut cmd = [0x00u8; 4];
+        cmd[..2].copy_from_slice(&op_code.bytes());
+        cmd[2..2 + params.len()].copy_from_slice(params);
+        let status = self.intf.read_response(&cmd[..2 + params.len()], response).await?;
+        if CommandStatus::is_error(status) {
+            return Err(RadioError::OpError(status));
+        }
+        Ok(())
+    }
+
+    // Frequency band limits of CalibImage in 4 MHz steps, none above the sub-GHz range
+    fn image_calibration_value(frequency_in_hz: u32) -> Option<[u8; 2]> {
+        if frequency_in_hz > *SUB_GHZ_FREQUENCIES.end() {
+            None
+        } else if frequency_in_hz > 900_000_000 {
+            Some([0xE1, 0xE9])
+        } else if frequency_in_hz > 850_000_000 {
+            Some([0xD7, 0xDB])
+        } else if frequency_in_hz > 770_000_000 {
+            Some([0xC1, 0xC5])
+        } else if frequency_in_hz > 460_000_000 {
+            Some([0x75, 0x81])
+        } else if frequency_in_hz > 425_000_000 {
+            Some([0x6B, 0x6F])
+        } else {
+            Some([0x00, 0x00])
+        }
+    }
+
+    fn check_frequency(&self, frequency_in_hz: u32) -> Result<(), RadioError> {
+        let high_frequency =
+            S_BAND_FREQUENCIES.contains(&frequency_in_hz) || ISM_2G4_FREQUENCIES.contains(&frequency_in_hz);
+        if SUB_GHZ_FREQUENCIES.contains(&frequency_in_hz)
+            || (high_frequency && self.config.chip.supports_high_frequency())
+        {
+            Ok(())
+        } else {
+            Err(RadioError::InvalidConfiguration)
+        }
+    }
+
+    async fn set_pa_config(
+        &mut self,
+        pa_sel: PaSel,
+        pa_supply: PaSupply,
+        pa_duty_cycle: u8,
+        pa_hp_sel: u8,
+    ) -> Result<(), RadioError> {
+        let pa_config = [pa_sel as u8, pa_supply as u8, pa_duty_cycle, pa_hp_sel];
+        self.write_command(OpCode::SetPaConfig, &pa_config).await
+    }
+
+    fn timeout(timeout: u32) -> [u8; 3] {
+        [(timeout >> 16) as u8, (timeout >> 8) as u8, timeout as u8]
+    }
+
+    // Convert a duration in ms into RTC steps (30.52 us)
+    fn ms_to_rtc_steps(ms: u32) -> u32 {
+        (ms as u64 * LR11XX_RTC_FREQ / 1000) as u32
+    }
+
+    // Convert 15.625 us steps, as used by the duty cycle parameters, into RTC steps (30.52 us)
+    fn duty_cycle_to_rtc_steps(steps: u32) -> u32 {
+        (steps as u64 * LR11XX_RTC_FREQ / 64_000).min(RX_CONTINUOUS_TIMEOUT as u64) as u32
+    }
+
+    // Convert a number of bits at the current GFSK bitrate into a SetRx timeout (RTC steps)
+    fn fsk_bits_to_timeout(&self, bits: u16) -> u32 {
+        let timeout = (bits as u64 * LR11XX_RTC_FREQ).div_ceil(self.fsk_bitrate.max(1) as u64);
+        timeout.min(RX_CONTINUOUS_TIMEOUT as u64 - 1) as u32
+    }
+
+    async fn read_payload(
+        &mut self,
+        payload_length: u8,
+        offset: u8,
+        receiving_buffer: &mut [u8],
+    ) -> Result<u8, RadioError> {
+        if (payload_length as usize) > receiving_buffer.len() {
+            Err(RadioError::PayloadSizeMismatch(
+                payload_length as usize,
+                receiving_buffer.len(),
+            ))
+        } else {
+            self.read_command(
+                OpCode::ReadBuffer8,
+                &[offset, payload_length],
+                &mut receiving_buffer[..payload_length as usize],
+            )
+            .await?;
+            Ok(payload_length)
+        }
+    }
+}
+
+impl<SPI, IV, C> RadioKind for Lr11xx<SPI, IV, C>
+where
+    SPI: SpiDevice<u8>,
+    IV: InterfaceVariant,
+    C: Lr11xxVariant,
+{
+    async fn init_lora(&mut self, sync_word: u8) -> Result<(), RadioError> {
+        // DC-DC regulator setup (default is LDO)
+        if self.config.use_dcdc {
+            self.write_command(OpCode::SetRegMode, &[RegulatorMode::UseDCDC.value()])
+                .await?;
+        }
+        // DIOs acting as RF switch
+        if let Some(rf_switch) = self.config.rf_switch {
+            let rf_switch_config = [
+                rf_switch.enable,
+                rf_switch.standby,
+                rf_switch.rx,
+                rf_switch.tx,
+                rf_switch.tx_hp,
+                rf_switch.tx_hf,
+                rf_switch.gnss,
+                rf_switch.wifi,
+            ];
+            self.write_command(OpCode::SetDioAsRfSwitch, &rf_switch_config).await?;
+        }
+
+        // VTCXO supplying the TCXO
+        if let Some(voltage) = self.config.tcxo_ctrl {
+            // The HF_XOSC_START error is raised at POR or at wake-up from Sleep mode in cold-start
+            // condition, as the chip is not yet aware of being clocked by a TCXO
+            self.write_command(OpCode::ClearErrors, &[]).await?;
+
+            let [t1, t2, t3] = Self::timeout(Self::ms_to_rtc_steps(BRD_TCXO_WAKEUP_TIME));
+            self.write_command(OpCode::SetTcxoMode, &[voltage.value() & 0x07, t1, t2, t3])
+                .await?;
+            // Re-run all calibrations now that chip knows that it's running from TCXO
+            self.write_command(OpCode::Calibrate, &[0b0011_1111]).await?;
+        }
+
+        // Enable LoRa packet engine...
+        self.write_command(OpCode::SetPacketType, &[PacketType::LoRa.value()])
+            .await?;
+        // ...and network syncword
+        self.write_command(OpCode::SetLoRaSyncWord, &[sync_word]).await?;
+        self.packet_type = PacketType::LoRa;
+        Ok(())
+    }
+
+    async fn init_fsk(&mut self) -> Result<(), RadioError> {
+        self.write_command(OpCode::SetPacketType, &[PacketType::GFSK.value()])
+            .await?;
+        self.packet_type = PacketType::GFSK;
+        Ok(())
+    }
+
+    fn create_modulation_params(
+        &self,
+        spreading_factor: SpreadingFactor,
+        bandwidth: Bandwidth,
+        coding_rate: CodingRate,
+        frequency_in_hz: u32,
+    ) -> Result<ModulationParams, RadioError> {
+        // Parameter validation
+        spreading_factor_value(spreading_factor)?;
+        bandwidth_value(bandwidth)?;
+        coding_rate_value(coding_rate)?;
+        self.check_frequency(frequency_in_hz)?;
+        if ((bandwidth == Bandwidth::_250KHz) || (bandwidth == Bandwidth::_500KHz)) && (frequency_in_hz < 400_000_000) {
+            return Err(RadioError::InvalidBandwidthForFrequency);
+        }
+        if bandwidth.is_2g4() && !ISM_2G4_FREQUENCIES.contains(&frequency_in_hz) {
+            return Err(RadioError::InvalidBandwidthForFrequency);
+        }
+
+        let low_data_rate_optimize = BaseBandModulationParams::new(spreading_factor, bandwidth, coding_rate).ldro as u8;
+        Ok(ModulationParams {
+            spreading_factor,
+            bandwidth,
+            coding_rate,
+            low_data_rate_optimize,
+            frequency_in_hz,
+        })
+    }
+
+    fn create_packet_params(
+        &self,
+        mut preamble_length: u16,
+        implicit_header: bool,
+        payload_length: u8,
+        crc_on: bool,
+        iq_inverted: bool,
+        modulation_params: &ModulationParams,
+    ) -> Result<PacketParams, RadioError> {
+        if ((modulation_params.spreading_factor == SpreadingFactor::_5)
+            || (modulation_params.spreading_factor == SpreadingFactor::_6))
+            && (preamble_length < 12)
+        {
+            preamble_length = 12;
+        }
+
+        Ok(PacketParams {
+            preamble_length,
+            implicit_header,
+            payload_length,
+            crc_on,
+            iq_inverted,
+        })
+    }
+
+    fn create_fsk_modulation_params(
+        &self,
+        bitrate: u32,
+        fdev: u32,
+        pulse_shape: PulseShape,
+        rx_bandwidth: u32,
+        frequency_in_hz: u32,
+    ) -> Result<FskModulationParams, RadioError> {
+        // Parameter validation
+        if !(LR11XX_FSK_MIN_BITRATE..=LR11XX_FSK_MAX_BITRATE).contains(&bitrate) {
+            return Err(RadioError::InvalidConfiguration);
+        }
+        fsk_bandwidth_value(rx_bandwidth)?;
+        self.check_frequency(frequency_in_hz)?;
+
+        Ok(FskModulationParams {
+            bitrate,
+            fdev,
+            pulse_shape,
+            rx_bandwidth,
+            frequency_in_hz,
+        })
+    }
+
+    fn create_fsk_packet_params(
+        &self,
+        preamble_length: u16,
+        sync_word: &[u8],
+        variable_length: bool,
+        payload_length: u8,
+        crc_on: bool,
+        whitening: bool,
+    ) -> Result<FskPacketParams, RadioError> {
+        FskPacketParams::new(
+            preamble_length,
+            sync_word,
+            variable_length,
+            payload_length,
+            crc_on,
+            whitening,
+        )
+    }
+
+    async fn reset(&mut self, delay: &mut impl DelayNs) -> Result<(), RadioError> {
+        self.intf.iv.reset(delay).await
+    }
+
+    // Wakeup the radio if it is in Sleep or ReceiveDutyCycle mode; otherwise, ensure it is not busy.
+    async fn ensure_ready(&mut self, mode: RadioMode) -> Result<(), RadioError> {
+        match mode {
+            RadioMode::Sleep | RadioMode::Receive(RxMode::DutyCycle(_)) => {
+                self.intf.write(&OpCode::GetStatus.bytes(), false).await?;
+            }
+            _ => self.intf.iv.wait_on_busy().await?,
+        }
+        Ok(())
+    }
+
+    // Use standby mode RC (not XOSC).
+    async fn set_standby(&mut self) -> Result<(), RadioError> {
+        self.write_command(OpCode::SetStandby, &[StandbyMode::RC.value()])
+            .await?;
+        self.intf.iv.disable_rf_switch().await
+    }
+
+    async fn set_sleep(&mut self, warm_start_if_possible: bool, delay: &mut impl DelayNs) -> Result<(), RadioError> {
+        self.intf.iv.disable_rf_switch().await?;
+        let sleep_params = SleepParams {
+            wakeup_rtc: false,
+            retention: warm_start_if_possible,
+        };
+        // Sleep time is unused without RTC wakeup
+        let sleep_params_and_time = [sleep_params.value(), 0x00u8, 0x00u8, 0x00u8, 0x00u8];
+        self.intf
+            .write_with_payload(&OpCode::SetSleep.bytes(), &sleep_params_and_time, true)
+            .await?;
+        delay.delay_ms(2).await;
+
+        Ok(())
+    }
+
+    // The LR11xx has no configurable buffer base addresses: payloads are written at the start of the
+    // buffer and the offset of received payloads is reported by GetRxBufferStatus.
+    async fn set_tx_rx_buffer_base_address(
+        &mut self,
+        tx_base_addr: usize,
+        rx_base_addr: usize,
+    ) -> Result<(), RadioError> {
+        if tx_base_addr > 255 || rx_base_addr > 255 {
+            return Err(RadioError::InvalidBaseAddress(tx_base_addr, rx_base_addr));
+        }
+        Ok(())
+    }
+
+    // Set parameters associated with power for a send operation.
+    //   output_power            desired RF output power (dBm)
+    //   mdltn_params            needed for the PA selection, otherwise the last channel frequency is used
+    //   is_tx_prep              indicates which ramp up time to use
+    async fn set_tx_power_and_ramp_time(
+        &mut self,
+        output_power: i32,
+        mdltn_params: Option<&ModulationParams>,
+        is_tx_prep: bool,
+    ) -> Result<(), RadioError> {
+        let tx_params_power;
+        let ramp_time = match is_tx_prep {
+            true => RampTime::Ramp48Us,   // for instance, prior to TX or CAD
+            false => RampTime::Ramp208Us, // for instance, on initialization
+        };
+        let frequency_in_hz = mdltn_params.map_or(self.frequency_in_hz, |m_p| m_p.frequency_in_hz);
+
+        if frequency_in_hz > *SUB_GHZ_FREQUENCIES.end() {
+            const HIGH_FREQUENCY_MIN: i32 = -18;
+            const HIGH_FREQUENCY_MAX: i32 = 13;
+            // Clamp power between [-18, 13] dBm
+            tx_params_power = output_power.clamp(HIGH_FREQUENCY_MIN, HIGH_FREQUENCY_MAX) as i8;
+            self.set_pa_config(PaSel::HighFrequency, PaSupply::Internal, 0x00, 0x00)
+                .await?;
+        } else if self.config.use_high_power_pa {
+            const HIGH_POWER_MIN: i32 = -9;
+            const HIGH_POWER_MAX: i32 = 22;
+            // Clamp power between [-9, 22] dBm
+            let txp = output_power.clamp(HIGH_POWER_MIN, HIGH_POWER_MAX);
+            // Optimal settings for +22 dBm, lower output powers are reached through SetTxParams
+            self.set_pa_config(PaSel::HighPower, PaSupply::Vbat, 0x04, 0x07).await?;
+            tx_params_power = txp as i8;
+        } else {
+            const LOW_POWER_MIN: i32 = -17;
+            const LOW_POWER_MAX: i32 = 15;
+            // Clamp power between [-17, 15] dBm
+            let txp = output_power.clamp(LOW_POWER_MIN, LOW_POWER_MAX);
+            match txp {
+                LOW_POWER_MAX => {
+                    self.set_pa_config(PaSel::LowPower, PaSupply::Internal, 0x07, 0x00)
+                        .await?;
+                    tx_params_power = 14;
+                }
+                _ => {
+                    self.set_pa_config(PaSel::LowPower, PaSupply::Internal, 0x04, 0x00)
+                        .await?;
+                    tx_params_power = txp as i8;
+                }
+            }
+        }
+        self.write_command(OpCode::SetTxParams, &[tx_params_power as u8, ramp_time.value()])
+            .await
+    }
+
+    async fn set_modulation_params(&mut self, mdltn_params: &ModulationParams) -> Result<(), RadioError> {
+        let spreading_factor_val = spreading_factor_value(mdltn_params.spreading_factor)?;
+        let bandwidth_val = bandwidth_value(mdltn_params.bandwidth)?;
+        let coding_rate_val = coding_rate_value(mdltn_params.coding_rate)?;
+        debug!(
+            "sf = {}, bw = {}, cr = {}",
+            spreading_factor_val, bandwidth_val, coding_rate_val
+        );
+        let mod_params = [
+            spreading_factor_val,
+            bandwidth_val,
+            coding_rate_val,
+            mdltn_params.low_data_rate_optimize,
+        ];
+        self.write_command(OpCode::SetModulationParams, &mod_params).await
+    }
+
+    async fn set_packet_params(&mut self, pkt_params: &PacketParams) -> Result<(), RadioError> {
+        let [preamble_msb, preamble_lsb] = pkt_params.preamble_length.to_be_bytes();
+        let pkt_params = [
+            preamble_msb,
+            preamble_lsb,
+            pkt_params.implicit_header as u8,
+            pkt_params.payload_length,
+            pkt_params.crc_on as u8,
+            pkt_params.iq_inverted as u8,
+        ];
+        self.write_command(OpCode::SetPacketParams, &pkt_params).await
+    }
+
+    async fn set_fsk_modulation_params(&mut self, mdltn_params: &FskModulationParams) -> Result<(), RadioError> {
+        let bandwidth_val = fsk_bandwidth_value(mdltn_params.rx_bandwidth)?;
+        debug!(
+            "bitrate = {}, fdev = {}, bw = {}",
+            mdltn_params.bitrate, mdltn_params.fdev, bandwidth_val
+        );
+        let [b1, b2, b3, b4] = mdltn_params.bitrate.to_be_bytes();
+        let [f1, f2, f3, f4] = mdltn_params.fdev.to_be_bytes();
+        let mod_params = [
+            b1,
+            b2,
+            b3,
+            b4,
+            pulse_shape_value(mdltn_params.pulse_shape),
+            bandwidth_val,
+            f1,
+            f2,
+            f3,
+            f4,
+        ];
+        self.write_command(OpCode::SetModulationParams, &mod_params).await?;
+        self.fsk_bitrate = mdltn_params.bitrate;
+        Ok(())
+    }
+
+    async fn set_fsk_packet_params(&mut self, pkt_params: &FskPacketParams) -> Result<(), RadioError> {
+        let [preamble_msb, preamble_lsb] = pkt_params.preamble_length.saturating_mul(8).to_be_bytes();
+        // The preamble detector length may not exceed the preamble length
+        let preamble_detector = match pkt_params.preamble_length {
+            0 => 0x00u8, // off
+            1 => 0x04u8, // 8 bits
+            _ => 0x05u8, // 16 bits
+        };
+        let crc_type = if pkt_params.crc_on { 0x06u8 } else { 0x01u8 }; // 2 bytes inverted or off
+        let fsk_pkt_params = [
+            preamble_msb,
+            preamble_lsb,
+            preamble_detector,
+            pkt_params.sync_word_length * 8,
+            0x00u8, // no address filtering
+            pkt_params.variable_length as u8,
+            pkt_params.payload_length,
+            crc_type,
+            pkt_params.whitening as u8,
+        ];
+        self.write_command(OpCode::SetPacketParams, &fsk_pkt_params).await?;
+        self.write_command(OpCode::SetGfskSyncWord, &pkt_params.sync_word)
+            .await?;
+
+        if pkt_params.crc_on {
+            let [i1, i2, i3, i4] = FSK_CRC_CCITT_INIT.to_be_bytes();
+            let [p1, p2, p3, p4] = FSK_CRC_CCITT_POLYNOMIAL.to_be_bytes();
+            self.write_command(OpCode::SetGfskCrcParams, &[i1, i2, i3, i4, p1, p2, p3, p4])
+                .await?;
+        }
+
+        if pkt_params.whitening {
+            self.write_command(OpCode::SetGfskWhitParams, &FSK_WHITENING_INIT.to_be_bytes())
+                .await?;
+        }
+        Ok(())
+    }
+
+    // Calibrate the image rejection based on the given frequency (sub-GHz only)
+    async fn calibrate_image(&mut self, frequency_in_hz: u32) -> Result<(), RadioError> {
+        match Self::image_calibration_value(frequency_in_hz) {
+            Some(cal_freq) => self.write_command(OpCode::CalibImage, &cal_freq).await,
+            None => Ok(()),
+        }
+    }
+
+    fn image_calibration_band(&self, frequency_in_hz: u32) -> u32 {
+        Self::image_calibration_value(frequency_in_hz).map_or(u32::MAX, |cal_freq| u16::from_be_bytes(cal_freq) as u32)
+    }
+
+    async fn set_channel(&mut self, frequency_in_hz: u32) -> Result<(), RadioError> {
+        debug!("channel = {}", frequency_in_hz);
+        self.write_command(OpCode::SetRfFrequency, &frequency_in_hz.to_be_bytes())
+            .await?;
+        self.frequency_in_hz = frequency_in_hz;
+        Ok(())
+    }
+
+    async fn set_payload(&mut self, payload: &[u8]) -> Result<(), RadioError> {
+        self.write_command(OpCode::WriteBuffer8, payload).await
+    }
+
+    async fn do_tx(&mut self, timeout_in_ms: u32) -> Result<(), RadioError> {
+        self.intf.iv.enable_rf_switch_tx().await?;
+
+        // 0 disables the timeout
+        let timeout = Self::ms_to_rtc_steps(timeout_in_ms).min(0x00FF_FFFF);
+        self.write_command(OpCode::SetTx, &Self::timeout(timeout)).await
+    }
+
+    async fn do_rx(&mut self, rx_mode: RxMode) -> Result<(), RadioError> {
+        self.intf.iv.enable_rf_switch_rx().await?;
+
+        // Stop the Rx timer on preamble detection
+        self.write_command(OpCode::StopTimeoutOnPreamble, &[0x01u8]).await?;
+
+        let num_symbols = match rx_mode {
+            RxMode::DutyCycle(_) | RxMode::Continuous => 0,
+            RxMode::Single(n) => n,
+        };
+        // The GFSK packet engine uses the timeout of SetRx instead
+        let single_timeout = if self.packet_type == PacketType::GFSK {
+            self.fsk_bits_to_timeout(num_symbols)
+        } else {
+            let symbols = num_symbols.min(u8::MAX as u16) as u8;
+            self.write_command(OpCode::SetLoRaSynchTimeout, &[symbols]).await?;
+            0
+        };
+
+        self.write_command(OpCode::SetRxBoosted, &[self.config.rx_boost as u8])
+            .await?;
+
+        match rx_mode {
+            RxMode::DutyCycle(args) => {
+                let [r1, r2, r3] = Self::timeout(Self::duty_cycle_to_rtc_steps(args.rx_time));
+                let [s1, s2, s3] = Self::timeout(Self::duty_cycle_to_rtc_steps(args.sleep_time));
+                // Receive mode during the listening periods
+                self.write_command(OpCode::SetRxDutyCycle, &[r1, r2, r3, s1, s2, s3, 0x00u8])
+                    .await
+            }
+            RxMode::Single(_) => self.write_command(OpCode::SetRx, &Self::timeout(single_timeout)).await,
+            RxMode::Continuous => {
+                self.write_command(OpCode::SetRx, &Self::timeout(RX_CONTINUOUS_TIMEOUT))
+                    .await
+            }
+        }
+    }
+
+    async fn get_rx_payload(
+        &mut self,
+        rx_pkt_params: &PacketParams,
+        receiving_buffer: &mut [u8],
+    ) -> Result<u8, RadioError> {
+        let mut rx_buffer_status = [0x00u8; 2];
+        self.read_command(OpCode::GetRxBufferStatus, &[], &mut rx_buffer_status)
+            .await?;
+
+        let payload_length = if rx_pkt_params.implicit_header {
+            rx_pkt_params.payload_length
+        } else {
+            rx_buffer_status[0]
+        };
+        self.read_payload(payload_length, rx_buffer_status[1], receiving_buffer)
+            .await
+    }
+
+    async fn get_fsk_rx_payload(
+        &mut self,
+        _rx_pkt_params: &FskPacketParams,
+        receiving_buffer: &mut [u8],
+    ) -> Result<u8, RadioError> {
+        let mut rx_buffer_status = [0x00u8; 2];
+        self.read_command(OpCode::GetRxBufferStatus, &[], &mut rx_buffer_status)
+            .await?;
+
+        // The length is also reported for fixed length packets
+        self.read_payload(rx_buffer_status[0], rx_buffer_status[1], receiving_buffer)
+            .await
+    }
+
+    async fn get_rx_packet_status(&mut self) -> Result<PacketStatus, RadioError> {
+        if self.packet_type == PacketType::GFSK {
+            // RssiSync, RssiAvg, RxLen and Status; there is no SNR for GFSK
+            let mut pkt_status = [0x00u8; 4];
+            self.read_command(OpCode::GetPacketStatus, &[], &mut pkt_status).await?;
+            let rssi = ((-(pkt_status[0] as i32)) >> 1) as i16;
+            return Ok(PacketStatus {
+                rssi,
+                snr: 0,
+                signal_rssi: rssi,
+                frequency_error: None,
+            });
+        }
+
+        // RssiPkt, SnrPkt and SignalRssiPkt
+        let mut pkt_status = [0x00u8; 3];
+        self.read_command(OpCode::GetPacketStatus, &[], &mut pkt_status).await?;
+        let rssi = ((-(pkt_status[0] as i32)) >> 1) as i16;
+        let snr = (((pkt_status[1] as i8) + 2) >> 2) as i16;
+        let signal_rssi = ((-(pkt_status[2] as i32)) >> 1) as i16;
+
+        // The frequency error is not reported by the LR11xx commands
+        Ok(PacketStatus {
+            rssi,
+            snr,
+            signal_rssi,
+            frequency_error: None,
+        })
+    }
+
+    async fn get_packet_stats(&mut self) -> Result<PacketStats, RadioError> {
+        // NbPktReceived, NbPktCrcError, NbPktHeaderErr and NbPktFalseSync
+        let mut stats = [0x00u8; 8];
+        self.read_command(OpCode::GetStats, &[], &mut stats).await?;
+        Ok(PacketStats {
+            received: u16::from_be_bytes([stats[0], stats[1]]),
+            crc_errors: u16::from_be_bytes([stats[2], stats[3]]),
+            header_errors: u16::from_be_bytes([stats[4], stats[5]]),
+        })
+    }
+
+    async fn reset_packet_stats(&mut self) -> Result<(), RadioError> {
+        self.write_command(OpCode::ResetStats, &[]).await
+    }
+
+    async fn get_device_errors(&mut self) -> Result<DeviceErrors, RadioError> {
+        let mut errors = [0x00u8; 2];
+        self.read_command(OpCode::GetErrors, &[], &mut errors).await?;
+        Ok(device_errors(u16::from_be_bytes(errors)))
+    }
+
+    async fn clear_device_errors(&mut self) -> Result<(), RadioError> {
+        self.write_command(OpCode::ClearErrors, &[]).await
+    }
+
+    async fn get_rssi(&mut self) -> Result<i16, RadioError> {
+        let mut response = [0x00u8; 1];
+        self.read_command(OpCode::GetRssiInst, &[], &mut response).await?;
+        let rssi = ((-(response[0] as i32)) >> 1) as i16;
+        Ok(rssi)
+    }
+
+    async fn do_cad(&mut self, mdltn_params: &ModulationParams) -> Result<(), RadioError> {
+        self.intf.iv.enable_rf_switch_rx().await?;
+
+        self.write_command(OpCode::SetRxBoosted, &[self.config.rx_boost as u8])
+            .await?;
+
+        // See:
+        //  https://lora-developers.semtech.com/documentation/tech-papers-and-guides/channel-activity-detection-ensuring-your-lora-packets-are-sent/how-to-ensure-your-lora-packets-are-sent-properly
+        // for default values used here.
+        let spreading_factor_val = spreading_factor_value(mdltn_params.spreading_factor)?;
+        let [t1, t2, t3] = Self::timeout(0);
+        let cad_params = [
+            CADSymbols::_8.value(),      // number of symbols for detection
+            spreading_factor_val + 13u8, // limit for detection of SNR peak
+            10u8,                        // minimum symbol recognition
+            0x00u8,                      // CAD exit mode without listen-before-send or subsequent receive processing
+            t1,                          // no timeout
+            t2,
+            t3,
+        ];
+        self.write_command(OpCode::SetCadParams, &cad_params).await?;
+        self.write_command(OpCode::SetCad, &[]).await
+    }
+
+    // Set the IRQs routed to DIO9
+    async fn set_irq_params(&mut self, radio_mode: Option<RadioMode>) -> Result<(), RadioError> {
+        let irq_mask: u32 = match radio_mode {
+            Some(RadioMode::Standby) | Some(RadioMode::Receive(_)) => IrqMask::All.value(),
+            Some(RadioMode::Transmit) => IrqMask::TxDone.value() | IrqMask::RxTxTimeout.value(),
+            Some(RadioMode::ChannelActivityDetection) => {
+                IrqMask::CADDone.value() | IrqMask::CADActivityDetected.value()
+            }
+            _ => IrqMask::None.value(),
+        };
+
+        let [m1, m2, m3, m4] = irq_mask.to_be_bytes();
+        // Nothing is routed to DIO11
+        let masks = [m1, m2, m3, m4, 0x00u8, 0x00u8, 0x00u8, 0x00u8];
+        self.write_command(OpCode::SetDioIrqParams, &masks).await
+    }
+
+    async fn set_tx_continuous_wave_mode(&mut self) -> Result<(), RadioError> {
+        self.intf.iv.enable_rf_switch_tx().await?;
+        self.write_command(OpCode::SetTxCw, &[]).await
+    }
+
+    async fn await_irq(&mut self) -> Result<(), RadioError> {
+        self.intf.iv.await_irq().await
+    }
+
+    async fn get_irq_state(
+        &mut self,
+        radio_mode: RadioMode,
+        cad_activity_detected: Option<&mut bool>,
+    ) -> Result<Option<IrqState>, RadioError> {
+        // Stat1, Stat2 and the IRQ status are shifted out while the command is sent
+        let [op1, op2] = OpCode::GetStatus.bytes();
+        let mut status = [op1, op2, 0x00u8, 0x00u8, 0x00u8, 0x00u8];
+        self.intf.transfer(&mut status).await?;
+        let irq_flags = u32::from_be_bytes([status[2], status[3], status[4], status[5]]);
+
+        debug!(
+            "process_irq satisfied: irq_flags = 0x{:x} in radio mode {}",
+            irq_flags, radio_mode
+        );
+
+        if IrqMask::CommandError.is_set(irq_flags) || IrqMask::Error.is_set(irq_flags) {
+            debug!("Error in radio mode {}", radio_mode);
+        }
+        if IrqMask::PreambleDetected.is_set(irq_flags) {
+            debug!("PreambleDetected in radio mode {}", radio_mode);
+        }
+        if IrqMask::SyncwordHeaderValid.is_set(irq_flags) {
+            debug!("SyncwordHeaderValid in radio mode {}", radio_mode);
+        }
+
+        match radio_mode {
+            RadioMode::Transmit => {
+                if IrqMask::TxDone.is_set(irq_flags) {
+                    return Ok(Some(IrqState::Done));
+                }
+                if IrqMask::RxTxTimeout.is_set(irq_flags) {
+                    return Err(RadioError::TransmitTimeout);
+                }
+            }
+            RadioMode::Receive(_) => {
+                if IrqMask::HeaderError.is_set(irq_flags) {
+                    debug!("HeaderError in radio mode {}", radio_mode);
+                }
+                if IrqMask::CRCError.is_set(irq_flags) {
+                    debug!("CRCError in radio mode {}", radio_mode);
+                }
+                if IrqMask::RxDone.is_set(irq_flags) {
+                    debug!("RxDone in radio mode {}", radio_mode);
+                    return Ok(Some(IrqState::Done));
+                }
+                if IrqMask::RxTxTimeout.is_set(irq_flags) {
+                    return Err(RadioError::ReceiveTimeout);
+                }
+                if IrqMask::PreambleDetected.is_set(irq_flags) || IrqMask::SyncwordHeaderValid.is_set(irq_flags) {
+                    return Ok(Some(IrqState::PreambleReceived));
+                }
+            }
+            RadioMode::ChannelActivityDetection => {
+                if IrqMask::CADDone.is_set(irq_flags) {
+                    if let Some(detected) = cad_activity_detected {
+                        *detected = IrqMask::CADActivityDetected.is_set(irq_flags);
+                    }
+                    return Ok(Some(IrqState::Done));
+                }
+            }
+            RadioMode::Sleep | RadioMode::Standby | RadioMode::Listen => {
+                warn!("IRQ during sleep/standby/listen?");
+            }
+            RadioMode::FrequencySynthesis => {
+                warn!("IRQ during frequency synthesis?");
+            }
+        }
+
+        // If none of the specific conditions are met, return None to indicate no IRQ state change.
+        Ok(None)
+    }
+
+    async fn clear_irq_status(&mut self) -> Result<(), RadioError> {
+        // clear all interrupts
+        self.write_command(OpCode::ClearIrq, &[0xffu8, 0xffu8, 0xffu8, 0xffu8])
+            .await
+    }
+
+    /// Process the radio IRQ. Log unexpected interrupts. Packets from other
+    /// devices can cause unexpected interrupts.
+    ///
+    /// NB! Do not await this future in a select branch as interrupting it
+    /// mid-flow could cause radio lock up.
+    async fn process_irq_event(
+        &mut self,
+        radio_mode: RadioMode,
+        cad_activity_detected: Option<&mut bool>,
+        clear_interrupts: bool,
+    ) -> Result<Option<IrqState>, RadioError> {
+        let irq_state = self.get_irq_state(radio_mode, cad_activity_detected).await;
+
+        if clear_interrupts {
+            self.clear_irq_status().await?;
+        }
+
+        irq_state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocking::block_on;
+    use crate::mock::{read, write, MockDelay, MockIv, MockSpi, Transaction};
+
+    fn config<C: Lr11xxVariant>(chip: C) -> Config<C> {
+        Config {
+            chip,
+            tcxo_ctrl: None,
+            use_dcdc: false,
+            use_high_power_pa: true,
+            rf_switch: None,
+            rx_boost: false,
+        }
+    }
+
+    fn lr1121(spi: &mut MockSpi) -> Lr11xx<&mut MockSpi, MockIv, Lr1121> {
+        Lr11xx::new(spi, MockIv, config(Lr1121))
+    }
+
+    #[test]
+    fn init_with_tcxo_and_rf_switch() {
+        const TRANSACTIONS: &[Transaction] = &[
+            // SetRegMode (DC-DC)
+            write(&[0x01, 0x10, 0x01]),
+            // SetDioAsRfSwitch
+            write(&[0x01, 0x12, 0x03, 0x00, 0x01, 0x03, 0x02, 0x00, 0x00, 0x00]),
+            // ClearErrors, SetTcxoMode (1.8 V, 5 ms) and Calibrate
+            write(&[0x01, 0x0E]),
+            write(&[0x01, 0x17, 0x02, 0x00, 0x00, 0xA3]),
+            write(&[0x01, 0x0F, 0x3F]),
+            // SetPacketType (LoRa) and SetLoRaSyncWord
+            write(&[0x02, 0x0E, 0x02]),
+            write(&[0x02, 0x2B, 0x34]),
+        ];
+        let mut spi = MockSpi::new(TRANSACTIONS);
+        let config = Config {
+            tcxo_ctrl: Some(TcxoCtrlVoltage::Ctrl1V8),
+            use_dcdc: true,
+            rf_switch: Some(RfSwitchConfig {
+                enable: RfSwitchConfig::DIO5 | RfSwitchConfig::DIO6,
+                rx: RfSwitchConfig::DIO5,
+                tx: RfSwitchConfig::DIO5 | RfSwitchConfig::DIO6,
+                tx_hp: RfSwitchConfig::DIO6,
+                ..Default::default()
+            }),
+            ..config(Lr1110)
+        };
+        let mut radio = Lr11xx::new(&mut spi, MockIv, config);
+        block_on(radio.init_lora(0x34)).unwrap();
+        spi.assert_done();
+    }
+
+    #[test]
+    fn lora_tx() {
+        const TRANSACTIONS: &[Transaction] = &[
+            // SetModulationParams (SF12, BW125, CR4/5, LDRO)
+            write(&[0x02, 0x0F, 0x0C, 0x04, 0x01, 0x01]),
+            // SetPaConfig (HP PA on VBAT) and SetTxParams (14 dBm, 48 us)
+            write(&[0x02, 0x15, 0x01, 0x01, 0x04, 0x07]),
+            write(&[0x02, 0x11, 0x0E, 0x02]),
+            // SetPacketParams (8 symbols, explicit header, 3 bytes, CRC, standard IQ)
+            write(&[0x02, 0x10, 0x00, 0x08, 0x00, 0x03, 0x01, 0x00]),
+            // SetRfFrequency (868.1 MHz)
+            write(&[0x02, 0x0B, 0x33, 0xBE, 0x27, 0xA0]),
+            // WriteBuffer8
+            write(&[0x01, 0x09, 0x01, 0x02, 0x03]),
+            // SetTx without timeout
+            write(&[0x02, 0x0A, 0x00, 0x00, 0x00]),
+            // GetStatus: Stat1, Stat2 and TxDone
+            Transaction {
+                write: &[0x01, 0x00, 0x00, 0x00, 0x00, 0x00],
+                read: &[0x05, 0x00, 0x00, 0x00, 0x00, 0x04],
+            },
+            // ClearIrq
+            write(&[0x01, 0x14, 0xFF, 0xFF, 0xFF, 0xFF]),
+        ];
+        let mut spi = MockSpi::new(TRANSACTIONS);
+        let mut radio = lr1121(&mut spi);
+        let mdltn_params = radio
+            .create_modulation_params(SpreadingFactor::_12, Bandwidth::_125KHz, CodingRate::_4_5, 868_100_000)
+            .unwrap();
+        let mut pkt_params = radio
+            .create_packet_params(8, false, 0, true, false, &mdltn_params)
+            .unwrap();
+        pkt_params.set_payload_length(3).unwrap();
+        block_on(async {
+            radio.set_modulation_params(&mdltn_params).await?;
+            radio.set_tx_power_and_ramp_time(14, Some(&mdltn_params), true).await?;
+            radio.set_packet_params(&pkt_params).await?;
+            radio.set_channel(868_100_000).await?;
+            radio.set_payload(&[1, 2, 3]).await?;
+            radio.do_tx(0).await?;
+            let state = radio.process_irq_event(RadioMode::Transmit, None, true).await?;
+            assert!(state == Some(IrqState::Done));
+            Ok::<(), RadioError>(())
+        })
+        .unwrap();
+        spi.assert_done();
+    }
+
+    #[test]
+    fn lora_rx() {
+        const TRANSACTIONS: &[Transaction] = &[
+            // StopTimeoutOnPreamble, SetLoRaSynchTimeout (16 symbols), SetRxBoosted (off)
+            write(&[0x02, 0x17, 0x01]),
+            write(&[0x02, 0x1B, 0x10]),
+            write(&[0x02, 0x27, 0x00]),
+            // SetRx in single mode
+            write(&[0x02, 0x09, 0x00, 0x00, 0x00]),
+            // GetStatus: RxDone
+            Transaction {
+                write: &[0x01, 0x00, 0x00, 0x00, 0x00, 0x00],
+                read: &[0x05, 0x00, 0x00, 0x00, 0x00, 0x08],
+            },
+            // GetRxBufferStatus: 3 bytes at offset 0x10
+            write(&[0x02, 0x03]),
+            read(&[0x06, 0x03, 0x10]),
+            // ReadBuffer8
+            write(&[0x01, 0x0A, 0x10, 0x03]),
+            read(&[0x06, 0xAA, 0xBB, 0xCC]),
+            // GetPacketStatus: -40 dBm, 7 dB SNR
+            write(&[0x02, 0x04]),
+            read(&[0x06, 0x50, 0x1C, 0x52]),
+        ];
+        let mut spi = MockSpi::new(TRANSACTIONS);
+        let mut radio = lr1121(&mut spi);
+        let mdltn_params = radio
+            .create_modulation_params(SpreadingFactor::_7, Bandwidth::_125KHz, CodingRate::_4_5, 868_100_000)
+            .unwrap();
+        let pkt_params = radio
+            .create_packet_params(8, false, 255, true, true, &mdltn_params)
+            .unwrap();
+        let mut buf = [0u8; 255];
+        block_on(async {
+            let rx_mode = RadioMode::Receive(RxMode::Single(16));
+            radio.do_rx(RxMode::Single(16)).await?;
+            assert!(radio.get_irq_state(rx_mode, None).await? == Some(IrqState::Done));
+            assert_eq!(radio.get_rx_payload(&pkt_params, &mut buf).await?, 3);
+            let status = radio.get_rx_packet_status().await?;
+            assert_eq!((status.rssi, status.snr), (-40, 7));
+            Ok::<(), RadioError>(())
+        })
+        .unwrap();
+        assert_eq!(buf[..3], [0xAA, 0xBB, 0xCC]);
+        spi.assert_done();
+    }
+
+    #[test]
+    fn cad_and_rssi() {
+        const TRANSACTIONS: &[Transaction] = &[
+            // SetRxBoosted (off), SetCadParams (8 symbols, SF7) and SetCad
+            write(&[0x02, 0x27, 0x00]),
+            write(&[0x02, 0x0D, 0x08, 0x14, 0x0A, 0x00, 0x00, 0x00, 0x00]),
+            write(&[0x02, 0x18]),
+            // GetStatus: CadDone and CadDetected
+            Transaction {
+                write: &[0x01, 0x00, 0x00, 0x00, 0x00, 0x00],
+                read: &[0x05, 0x00, 0x00, 0x00, 0x03, 0x00],
+            },
+            // GetRssiInst: -90 dBm
+            write(&[0x02, 0x05]),
+            read(&[0x06, 0xB4]),
+            // GetRssiInst failing with a parameter error
+            write(&[0x02, 0x05]),
+            read(&[0x02, 0x00]),
+        ];
+        let mut spi = MockSpi::new(TRANSACTIONS);
+        let mut radio = lr1121(&mut spi);
+        let mdltn_params = radio
+            .create_modulation_params(SpreadingFactor::_7, Bandwidth::_125KHz, CodingRate::_4_5, 868_100_000)
+            .unwrap();
+        let mut detected = false;
+        block_on(async {
+            radio.do_cad(&mdltn_params).await?;
+            let state = radio
+                .get_irq_state(RadioMode::ChannelActivityDetection, Some(&mut detected))
+                .await?;
+            assert!(state == Some(IrqState::Done));
+            assert_eq!(radio.get_rssi().await?, -90);
+            assert_eq!(radio.get_rssi().await, Err(RadioError::OpError(0x02)));
+            Ok::<(), RadioError>(())
+        })
+        .unwrap();
+        assert!(detected);
+        spi.assert_done();
+    }
+
+    #[test]
+    fn sleep_with_retention() {
+        const TRANSACTIONS: &[Transaction] = &[
+            // SetSleep with retention, without RTC wakeup
+            write(&[0x01, 0x1B, 0x01, 0x00, 0x00, 0x00, 0x00]),
+            // Wakeup
+            write(&[0x01, 0x00]),
+        ];
+        let mut spi = MockSpi::new(TRANSACTIONS);
+        let mut radio = lr1121(&mut spi);
+        block_on(async {
+            radio.set_sleep(true, &mut MockDelay).await?;
+            radio.ensure_ready(RadioMode::Sleep).await
+        })
+        .unwrap();
+        spi.assert_done();
+    }
+
+    #[test]
+    fn high_frequency() {
+        const TRANSACTIONS: &[Transaction] = &[
+            // SetPaConfig (HF PA) and SetTxParams (clamped to 13 dBm)
+            write(&[0x02, 0x15, 0x02, 0x00, 0x00, 0x00]),
+            write(&[0x02, 0x11, 0x0D, 0x02]),
+        ];
+        let mut spi = MockSpi::new(TRANSACTIONS);
+        {
+            let radio = Lr11xx::new(&mut spi, MockIv, config(Lr1110));
+            assert!(radio
+                .create_modulation_params(SpreadingFactor::_7, Bandwidth::_500KHz, CodingRate::_4_5, 2_450_000_000)
+                .is_err());
+        }
+        let mut radio = lr1121(&mut spi);
+        // The 2.4 GHz LoRa bandwidths are limited to the ISM band
+        assert!(radio
+            .create_modulation_params(SpreadingFactor::_7, Bandwidth::_812KHz, CodingRate::_4_5, 868_100_000)
+            .is_err());
+        assert!(radio
+            .create_modulation_params(
+                SpreadingFactor::_7,
+                Bandwidth::_1625KHz,
+                CodingRate::_4_5,
+                2_450_000_000
+            )
+            .is_err());
+        radio
+            .create_modulation_params(SpreadingFactor::_7, Bandwidth::_812KHz, CodingRate::_4_5, 2_450_000_000)
+            .unwrap();
+        let mdltn_params = radio
+            .create_modulation_params(SpreadingFactor::_7, Bandwidth::_500KHz, CodingRate::_4_5, 2_450_000_000)
+            .unwrap();
+        block_on(radio.set_tx_power_and_ramp_time(20, Some(&mdltn_params), true)).unwrap();
+        spi.assert_done();
+    }
+}
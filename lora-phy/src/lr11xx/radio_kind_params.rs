@@ -0,0 +1,327 @@
+use crate::mod_params::*;
+
+#[derive(Clone, Copy, PartialEq)]
+#[allow(dead_code)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum PacketType {
+    None = 0x00,
+    GFSK = 0x01,
+    LoRa = 0x02,
+}
+
+impl PacketType {
+    pub const fn value(self) -> u8 {
+        self as u8
+    }
+}
+
+/// IRQ sources, reported by GetStatus and routed to DIO9 by SetDioIrqParams
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+pub enum IrqMask {
+    None = 0x0000_0000,
+    TxDone = 0x0000_0004,
+    RxDone = 0x0000_0008,
+    PreambleDetected = 0x0000_0010,
+    SyncwordHeaderValid = 0x0000_0020,
+    HeaderError = 0x0000_0040,
+    CRCError = 0x0000_0080,
+    CADDone = 0x0000_0100,
+    CADActivityDetected = 0x0000_0200,
+    RxTxTimeout = 0x0000_0400,
+    CommandError = 0x0040_0000,
+    Error = 0x0080_0000,
+    All = 0x03FF_FFFC,
+}
+
+impl IrqMask {
+    pub fn value(self) -> u32 {
+        self as u32
+    }
+
+    pub fn is_set(self, mask: u32) -> bool {
+        self.value() & mask == self.value()
+    }
+}
+
+/// Commands are sent as two bytes opcodes, MSB first
+#[derive(Clone, Copy, PartialEq)]
+#[allow(dead_code)]
+pub enum OpCode {
+    // System
+    GetStatus = 0x0100,
+    GetVersion = 0x0101,
+    WriteBuffer8 = 0x0109,
+    ReadBuffer8 = 0x010A,
+    ClearRxBuffer = 0x010B,
+    GetErrors = 0x010D,
+    ClearErrors = 0x010E,
+    Calibrate = 0x010F,
+    SetRegMode = 0x0110,
+    CalibImage = 0x0111,
+    SetDioAsRfSwitch = 0x0112,
+    SetDioIrqParams = 0x0113,
+    ClearIrq = 0x0114,
+    SetTcxoMode = 0x0117,
+    SetSleep = 0x011B,
+    SetStandby = 0x011C,
+    SetFs = 0x011D,
+    // Radio
+    ResetStats = 0x0200,
+    GetStats = 0x0201,
+    GetPacketType = 0x0202,
+    GetRxBufferStatus = 0x0203,
+    GetPacketStatus = 0x0204,
+    GetRssiInst = 0x0205,
+    SetGfskSyncWord = 0x0206,
+    SetRx = 0x0209,
+    SetTx = 0x020A,
+    SetRfFrequency = 0x020B,
+    SetCadParams = 0x020D,
+    SetPacketType = 0x020E,
+    SetModulationParams = 0x020F,
+    SetPacketParams = 0x0210,
+    SetTxParams = 0x0211,
+    SetRxTxFallbackMode = 0x0213,
+    SetRxDutyCycle = 0x0214,
+    SetPaConfig = 0x0215,
+    StopTimeoutOnPreamble = 0x0217,
+    SetCad = 0x0218,
+    SetTxCw = 0x0219,
+    SetLoRaSynchTimeout = 0x021B,
+    SetGfskCrcParams = 0x0224,
+    SetGfskWhitParams = 0x0225,
+    SetRxBoosted = 0x0227,
+    SetLoRaSyncWord = 0x022B,
+}
+
+impl OpCode {
+    pub fn bytes(self) -> [u8; 2] {
+        (self as u16).to_be_bytes()
+    }
+}
+
+/// Command status reported in bits 3:1 of the Stat1 byte
+#[derive(Clone, Copy, PartialEq)]
+#[allow(dead_code)]
+pub enum CommandStatus {
+    Fail = 0x00,
+    ParameterError = 0x01,
+    Ok = 0x02,
+    Data = 0x03,
+}
+
+impl CommandStatus {
+    pub fn is_error(stat1: u8) -> bool {
+        let status = (stat1 >> 1) & 0x07;
+        status == CommandStatus::Fail as u8 || status == CommandStatus::ParameterError as u8
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct SleepParams {
+    pub wakeup_rtc: bool, // get out of sleep mode once the sleep time has elapsed
+    pub retention: bool,  // retain the configuration for a warm start
+}
+
+impl SleepParams {
+    pub fn value(self) -> u8 {
+        ((self.wakeup_rtc as u8) << 1) | (self.retention as u8)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[allow(dead_code)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum StandbyMode {
+    RC = 0x00,
+    XOSC = 0x01,
+}
+
+impl StandbyMode {
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+}
+
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+pub enum RegulatorMode {
+    UseLDO = 0x00,
+    UseDCDC = 0x01,
+}
+
+impl RegulatorMode {
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Power amplifier selection of SetPaConfig
+#[derive(Clone, Copy, PartialEq)]
+pub enum PaSel {
+    LowPower = 0x00,
+    HighPower = 0x01,
+    HighFrequency = 0x02,
+}
+
+/// Power amplifier supply of SetPaConfig
+#[derive(Clone, Copy)]
+pub enum PaSupply {
+    Internal = 0x00,
+    Vbat = 0x01,
+}
+
+/// Supply voltage of the TCXO, driven by the VTCXO pin
+#[derive(Clone, Copy)]
+#[allow(missing_docs)]
+pub enum TcxoCtrlVoltage {
+    Ctrl1V6 = 0x00,
+    Ctrl1V7 = 0x01,
+    Ctrl1V8 = 0x02,
+    Ctrl2V2 = 0x03,
+    Ctrl2V4 = 0x04,
+    Ctrl2V7 = 0x05,
+    Ctrl3V0 = 0x06,
+    Ctrl3V3 = 0x07,
+}
+
+#[allow(missing_docs)]
+impl TcxoCtrlVoltage {
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+}
+
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+#[allow(clippy::enum_variant_names)]
+pub enum RampTime {
+    Ramp16Us = 0x00,
+    Ramp48Us = 0x02,
+    Ramp80Us = 0x04,
+    Ramp208Us = 0x0C,
+    Ramp304Us = 0x0F,
+}
+
+impl RampTime {
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+}
+
+pub fn spreading_factor_value(spreading_factor: SpreadingFactor) -> Result<u8, RadioError> {
+    match spreading_factor {
+        SpreadingFactor::_5 => Ok(0x05),
+        SpreadingFactor::_6 => Ok(0x06),
+        SpreadingFactor::_7 => Ok(0x07),
+        SpreadingFactor::_8 => Ok(0x08),
+        SpreadingFactor::_9 => Ok(0x09),
+        SpreadingFactor::_10 => Ok(0x0A),
+        SpreadingFactor::_11 => Ok(0x0B),
+        SpreadingFactor::_12 => Ok(0x0C),
+    }
+}
+
+pub fn bandwidth_value(bandwidth: Bandwidth) -> Result<u8, RadioError> {
+    match bandwidth {
+        Bandwidth::_7KHz => Err(RadioError::UnavailableBandwidth),
+        Bandwidth::_10KHz => Ok(0x08),
+        Bandwidth::_15KHz => Ok(0x01),
+        Bandwidth::_20KHz => Ok(0x09),
+        Bandwidth::_31KHz => Ok(0x02),
+        Bandwidth::_41KHz => Ok(0x0A),
+        Bandwidth::_62KHz => Ok(0x03),
+        Bandwidth::_125KHz => Ok(0x04),
+        Bandwidth::_250KHz => Ok(0x05),
+        Bandwidth::_500KHz => Ok(0x06),
+        // 2.4 GHz band only
+        Bandwidth::_203KHz => Ok(0x0D),
+        Bandwidth::_406KHz => Ok(0x0E),
+        Bandwidth::_812KHz => Ok(0x0F),
+        Bandwidth::_1625KHz => Err(RadioError::UnavailableBandwidth),
+    }
+}
+
+pub fn coding_rate_value(coding_rate: CodingRate) -> Result<u8, RadioError> {
+    match coding_rate {
+        CodingRate::_4_5 => Ok(0x01),
+        CodingRate::_4_6 => Ok(0x02),
+        CodingRate::_4_7 => Ok(0x03),
+        CodingRate::_4_8 => Ok(0x04),
+    }
+}
+
+pub fn pulse_shape_value(pulse_shape: PulseShape) -> u8 {
+    match pulse_shape {
+        PulseShape::None => 0x00,
+        PulseShape::Bt0_3 => 0x08,
+        PulseShape::Bt0_5 => 0x09,
+        PulseShape::Bt0_7 => 0x0A,
+        PulseShape::Bt1_0 => 0x0B,
+    }
+}
+
+// GFSK receiver bandwidths (double-sideband, Hz) in ascending order
+const FSK_BANDWIDTHS: [(u32, u8); 21] = [
+    (4_800, 0x1F),
+    (5_800, 0x17),
+    (7_300, 0x0F),
+    (9_700, 0x1E),
+    (11_700, 0x16),
+    (14_600, 0x0E),
+    (19_500, 0x1D),
+    (23_400, 0x15),
+    (29_300, 0x0D),
+    (39_000, 0x1C),
+    (46_900, 0x14),
+    (58_600, 0x0C),
+    (78_200, 0x1B),
+    (93_800, 0x13),
+    (117_300, 0x0B),
+    (156_200, 0x1A),
+    (187_200, 0x12),
+    (234_300, 0x0A),
+    (312_000, 0x19),
+    (373_600, 0x11),
+    (467_000, 0x09),
+];
+
+/// Smallest GFSK receiver bandwidth covering the given single-sideband bandwidth
+pub fn fsk_bandwidth_value(rx_bandwidth: u32) -> Result<u8, RadioError> {
+    FSK_BANDWIDTHS
+        .iter()
+        .find(|(bw, _)| *bw >= rx_bandwidth.saturating_mul(2))
+        .map(|(_, val)| *val)
+        .ok_or(RadioError::UnavailableBandwidth)
+}
+
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+pub enum CADSymbols {
+    _1 = 0x01,
+    _2 = 0x02,
+    _4 = 0x04,
+    _8 = 0x08,
+    _16 = 0x10,
+}
+
+impl CADSymbols {
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+}
+
+// Decode the bits of GetErrors
+pub fn device_errors(errors: u16) -> DeviceErrors {
+    DeviceErrors {
+        lf_rc_calibration: errors & 0x0001 != 0,
+        hf_rc_calibration: errors & 0x0002 != 0,
+        adc_calibration: errors & 0x0104 != 0, // ADC_CALIB_ERR or RX_ADC_OFFSET_ERR
+        pll_calibration: errors & 0x0008 != 0,
+        image_calibration: errors & 0x0010 != 0,
+        xosc_start: errors & 0x0020 != 0, // HF_XOSC_START_ERR
+        pll_lock: errors & 0x0080 != 0,
+        pa_ramp: false,
+    }
+}
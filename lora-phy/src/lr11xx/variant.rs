@@ -0,0 +1,32 @@
+/// Implement this trait on your custom variant or use provided impls
+pub trait Lr11xxVariant {
+    /// whether the chip has the high frequency front end (1.9 to 2.1 GHz and 2.4 GHz ISM band)
+    fn supports_high_frequency(&self) -> bool;
+}
+
+/// Lr1110 only operates in the sub-GHz bands
+pub struct Lr1110;
+
+impl Lr11xxVariant for Lr1110 {
+    fn supports_high_frequency(&self) -> bool {
+        false
+    }
+}
+
+/// Lr1120 adds the high frequency front end to the Lr1110
+pub struct Lr1120;
+
+impl Lr11xxVariant for Lr1120 {
+    fn supports_high_frequency(&self) -> bool {
+        true
+    }
+}
+
+/// Lr1121 is a transceiver-only Lr1120 (no GNSS and Wi-Fi scanning)
+pub struct Lr1121;
+
+impl Lr11xxVariant for Lr1121 {
+    fn supports_high_frequency(&self) -> bool {
+        true
+    }
+}
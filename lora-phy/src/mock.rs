@@ -0,0 +1,717 @@
+use core::convert::Infallible;
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::spi::{ErrorType, Operation, SpiDevice};
+use lora_modulation::BaseBandModulationParams;
+
+use crate::mod_params::*;
+use crate::mod_traits::{InterfaceVariant, IrqState, RadioKind};
+
+/// SPI transaction expected from the driver: bytes written by the host and bytes read back
+pub struct Transaction {
+    /// Bytes the driver is expected to write (command and parameters, or register address and data)
+    pub write: &'static [u8],
+    /// Bytes returned to the driver, following the written ones
+    pub read: &'static [u8],
+}
+
+/// Transaction only writing bytes
+pub const fn write(write: &'static [u8]) -> Transaction {
+    Transaction { write, read: &[] }
+}
+
+/// Transaction only reading bytes
+pub const fn read(read: &'static [u8]) -> Transaction {
+    Transaction { write: &[], read }
+}
+
+/// Transaction writing a command or register address, then reading the response
+pub const fn transfer(write: &'static [u8], read: &'static [u8]) -> Transaction {
+    Transaction { write, read }
+}
+
+/// SPI device replaying a script of transactions: the bytes written by the driver are checked
+/// against the script, which provides the bytes read back. Mismatches panic.
+pub struct MockSpi {
+    transactions: &'static [Transaction],
+    next: usize,
+}
+
+impl MockSpi {
+    /// Create a device expecting the given transactions, in order
+    pub const fn new(transactions: &'static [Transaction]) -> Self {
+        Self { transactions, next: 0 }
+    }
+
+    /// Number of transactions performed so far
+    pub fn performed(&self) -> usize {
+        self.next
+    }
+
+    /// Assert that all the transactions of the script were performed
+    pub fn assert_done(&self) {
+        assert_eq!(self.next, self.transactions.len(), "transactions left");
+    }
+}
+
+impl ErrorType for &mut MockSpi {
+    type Error = Infallible;
+}
+
+impl SpiDevice for &mut MockSpi {
+    async fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+        let index = self.next;
+        let expected = self.transactions.get(index).expect("unexpected transaction");
+        self.next += 1;
+        let (mut written, mut read) = (0, 0);
+        for op in operations.iter_mut() {
+            let (write_buf, read_buf): (Option<&[u8]>, Option<&mut [u8]>) = match op {
+                Operation::Write(data) => (Some(data), None),
+                Operation::Read(buf) => (None, Some(buf)),
+                Operation::TransferInPlace(buf) => {
+                    let end = written + buf.len();
+                    assert_eq!(
+                        Some(&buf[..]),
+                        expected.write.get(written..end),
+                        "transaction {}",
+                        index
+                    );
+                    written = end;
+                    (None, Some(buf))
+                }
+                Operation::Transfer(buf, data) => (Some(data), Some(buf)),
+                Operation::DelayNs(_) => (None, None),
+            };
+            if let Some(data) = write_buf {
+                let end = written + data.len();
+                assert_eq!(Some(data), expected.write.get(written..end), "transaction {}", index);
+                written = end;
+            }
+            if let Some(buf) = read_buf {
+                let end = read + buf.len();
+                buf.copy_from_slice(expected.read.get(read..end).expect("short read"));
+                read = end;
+            }
+        }
+        assert_eq!(written, expected.write.len(), "transaction {}", index);
+        assert_eq!(read, expected.read.len(), "transaction {}", index);
+        Ok(())
+    }
+}
+
+/// Interface variant of a radio which is never busy and whose IRQ line is always raised, to be
+/// used with [`MockSpi`] scripting the IRQ status reads
+pub struct MockIv;
+
+impl InterfaceVariant for MockIv {
+    async fn reset(&mut self, _delay: &mut impl DelayNs) -> Result<(), RadioError> {
+        Ok(())
+    }
+    async fn wait_on_busy(&mut self) -> Result<(), RadioError> {
+        Ok(())
+    }
+    async fn await_irq(&mut self) -> Result<(), RadioError> {
+        Ok(())
+    }
+    async fn enable_rf_switch_rx(&mut self) -> Result<(), RadioError> {
+        Ok(())
+    }
+    async fn enable_rf_switch_tx(&mut self) -> Result<(), RadioError> {
+        Ok(())
+    }
+    async fn disable_rf_switch(&mut self) -> Result<(), RadioError> {
+        Ok(())
+    }
+}
+
+/// Delay returning immediately, so that simulated time elapses instantly
+pub struct MockDelay;
+
+impl DelayNs for MockDelay {
+    async fn delay_ns(&mut self, _ns: u32) {}
+}
+
+// Interrupt raised by the simulated radio
+#[derive(Clone, Copy, PartialEq)]
+enum Irq {
+    TxDone,
+    RxDone,
+    RxTimeout,
+    CadDone(bool),
+}
+
+// Symbols of a channel activity detection
+const CAD_SYMBOLS: u32 = 2;
+
+/// Radio simulated in software, raising its IRQs after the time the operations take on air
+/// (packet time on air, RX timeout, CAD duration) elapsed on its delay. Packets received by the
+/// simulated radio and the channel activity are scripted by the test.
+pub struct SimulatedRadio<DLY> {
+    delay: DLY,
+    mode: RadioMode,
+    fsk: bool,
+    frequency_in_hz: u32,
+    lora_params: Option<(SpreadingFactor, Bandwidth, CodingRate)>,
+    preamble_length: u16,
+    implicit_header: bool,
+    fsk_bitrate: u32,
+    fsk_overhead_bytes: u32,
+    tx_buffer: [u8; 255],
+    tx_len: usize,
+    transmitted: bool,
+    tx_stalled: bool,
+    queued: Option<([u8; 255], usize, PacketStatus)>,
+    rx_buffer: [u8; 255],
+    rx_len: usize,
+    rx_status: PacketStatus,
+    cad_activity: bool,
+    rssi: i16,
+    pending: Option<(Irq, u32)>,
+    irq: Option<Irq>,
+    elapsed_us: u64,
+    packet_stats: PacketStats,
+}
+
+impl<DLY: DelayNs> SimulatedRadio<DLY> {
+    /// Create a simulated radio waiting for its IRQs on `delay`
+    pub fn new(delay: DLY) -> Self {
+        Self {
+            delay,
+            mode: RadioMode::Sleep,
+            fsk: false,
+            frequency_in_hz: 0,
+            lora_params: None,
+            preamble_length: 0,
+            implicit_header: false,
+            fsk_bitrate: 0,
+            fsk_overhead_bytes: 0,
+            tx_buffer: [0; 255],
+            tx_len: 0,
+            transmitted: false,
+            tx_stalled: false,
+            queued: None,
+            rx_buffer: [0; 255],
+            rx_len: 0,
+            rx_status: PacketStatus {
+                rssi: 0,
+                snr: 0,
+                signal_rssi: 0,
+                frequency_error: None,
+            },
+            cad_activity: false,
+            rssi: -120,
+            pending: None,
+            irq: None,
+            elapsed_us: 0,
+            packet_stats: PacketStats::default(),
+        }
+    }
+
+    /// Queue a packet received by the next reception, or by the ongoing one
+    pub fn queue_rx_packet(&mut self, payload: &[u8], status: PacketStatus) {
+        let mut buffer = [0; 255];
+        buffer[..payload.len()].copy_from_slice(payload);
+        self.queued = Some((buffer, payload.len(), status));
+        if let RadioMode::Receive(_) = self.mode {
+            if !matches!(self.pending, Some((Irq::RxDone, _))) {
+                self.pending = Some((Irq::RxDone, self.time_on_air_us(payload.len())));
+            }
+        }
+    }
+
+    /// Set whether subsequent channel activity detections find activity
+    pub fn set_cad_activity(&mut self, activity_detected: bool) {
+        self.cad_activity = activity_detected;
+    }
+
+    /// Set the RSSI measured on the channel
+    pub fn set_rssi(&mut self, rssi: i16) {
+        self.rssi = rssi;
+    }
+
+    /// Make subsequent transmissions hang without raising an IRQ, as a radio with a faulty
+    /// oscillator would
+    pub fn set_tx_stalled(&mut self, stalled: bool) {
+        self.tx_stalled = stalled;
+    }
+
+    /// Payload of the last completed transmission
+    pub fn transmitted(&self) -> Option<&[u8]> {
+        self.transmitted.then_some(&self.tx_buffer[..self.tx_len])
+    }
+
+    /// Frequency (Hz) of the channel set last
+    pub fn frequency_in_hz(&self) -> u32 {
+        self.frequency_in_hz
+    }
+
+    /// Mode of the simulated radio
+    pub fn mode(&self) -> RadioMode {
+        self.mode
+    }
+
+    /// Time (µs) simulated while waiting for IRQs
+    pub fn elapsed_us(&self) -> u64 {
+        self.elapsed_us
+    }
+
+    fn time_on_air_us(&self, payload_length: usize) -> u32 {
+        if self.fsk {
+            let bits = 8 * (self.fsk_overhead_bytes as u64 + payload_length as u64);
+            return (bits * 1_000_000 / self.fsk_bitrate.max(1) as u64) as u32;
+        }
+        match self.lora_params {
+            Some((spreading_factor, bandwidth, coding_rate)) => {
+                BaseBandModulationParams::new(spreading_factor, bandwidth, coding_rate).time_on_air_us(
+                    Some(self.preamble_length.min(u8::MAX as u16) as u8),
+                    !self.implicit_header,
+                    payload_length as u8,
+                )
+            }
+            None => 0,
+        }
+    }
+
+    fn symbol_time_us(spreading_factor: SpreadingFactor, bandwidth: Bandwidth) -> u32 {
+        (1u64 << spreading_factor.factor()) as u32 * 1_000_000 / bandwidth.hz()
+    }
+
+    fn rx_timeout_us(&self, symbols: u16) -> u32 {
+        if self.fsk {
+            // the timeout is given in bits
+            return (symbols as u64 * 1_000_000 / self.fsk_bitrate.max(1) as u64) as u32;
+        }
+        match self.lora_params {
+            Some((spreading_factor, bandwidth, _)) => {
+                symbols as u32 * Self::symbol_time_us(spreading_factor, bandwidth)
+            }
+            None => 0,
+        }
+    }
+
+    fn stop(&mut self) {
+        self.mode = RadioMode::Standby;
+        self.pending = None;
+    }
+}
+
+impl<DLY: DelayNs> RadioKind for SimulatedRadio<DLY> {
+    async fn init_lora(&mut self, _sync_word: u8) -> Result<(), RadioError> {
+        self.fsk = false;
+        Ok(())
+    }
+
+    async fn init_fsk(&mut self) -> Result<(), RadioError> {
+        self.fsk = true;
+        Ok(())
+    }
+
+    fn create_modulation_params(
+        &self,
+        spreading_factor: SpreadingFactor,
+        bandwidth: Bandwidth,
+        coding_rate: CodingRate,
+        frequency_in_hz: u32,
+    ) -> Result<ModulationParams, RadioError> {
+        // same threshold as the sx126x and sx127x (16.38 ms symbols)
+        let low_data_rate_optimize = (Self::symbol_time_us(spreading_factor, bandwidth) >= 16_384) as u8;
+        Ok(ModulationParams {
+            spreading_factor,
+            bandwidth,
+            coding_rate,
+            low_data_rate_optimize,
+            frequency_in_hz,
+        })
+    }
+
+    fn create_packet_params(
+        &self,
+        preamble_length: u16,
+        implicit_header: bool,
+        payload_length: u8,
+        crc_on: bool,
+        iq_inverted: bool,
+        _modulation_params: &ModulationParams,
+    ) -> Result<PacketParams, RadioError> {
+        Ok(PacketParams {
+            preamble_length,
+            implicit_header,
+            payload_length,
+            crc_on,
+            iq_inverted,
+        })
+    }
+
+    fn create_fsk_modulation_params(
+        &self,
+        bitrate: u32,
+        fdev: u32,
+        pulse_shape: PulseShape,
+        rx_bandwidth: u32,
+        frequency_in_hz: u32,
+    ) -> Result<FskModulationParams, RadioError> {
+        Ok(FskModulationParams {
+            bitrate,
+            fdev,
+            pulse_shape,
+            rx_bandwidth,
+            frequency_in_hz,
+        })
+    }
+
+    fn create_fsk_packet_params(
+        &self,
+        preamble_length: u16,
+        sync_word: &[u8],
+        variable_length: bool,
+        payload_length: u8,
+        crc_on: bool,
+        whitening: bool,
+    ) -> Result<FskPacketParams, RadioError> {
+        FskPacketParams::new(
+            preamble_length,
+            sync_word,
+            variable_length,
+            payload_length,
+            crc_on,
+            whitening,
+        )
+    }
+
+    async fn reset(&mut self, _delay: &mut impl DelayNs) -> Result<(), RadioError> {
+        self.stop();
+        self.fsk = false;
+        self.irq = None;
+        Ok(())
+    }
+
+    async fn ensure_ready(&mut self, _mode: RadioMode) -> Result<(), RadioError> {
+        if self.mode == RadioMode::Sleep {
+            self.mode = RadioMode::Standby;
+        }
+        Ok(())
+    }
+
+    async fn set_standby(&mut self) -> Result<(), RadioError> {
+        self.stop();
+        Ok(())
+    }
+
+    async fn set_sleep(&mut self, _warm_start_if_possible: bool, _delay: &mut impl DelayNs) -> Result<(), RadioError> {
+        self.stop();
+        self.mode = RadioMode::Sleep;
+        Ok(())
+    }
+
+    async fn set_tx_rx_buffer_base_address(
+        &mut self,
+        _tx_base_addr: usize,
+        _rx_base_addr: usize,
+    ) -> Result<(), RadioError> {
+        Ok(())
+    }
+
+    async fn set_tx_power_and_ramp_time(
+        &mut self,
+        _output_power: i32,
+        _mdltn_params: Option<&ModulationParams>,
+        _is_tx_prep: bool,
+    ) -> Result<(), RadioError> {
+        Ok(())
+    }
+
+    async fn set_modulation_params(&mut self, mdltn_params: &ModulationParams) -> Result<(), RadioError> {
+        self.lora_params = Some((
+            mdltn_params.spreading_factor,
+            mdltn_params.bandwidth,
+            mdltn_params.coding_rate,
+        ));
+        Ok(())
+    }
+
+    async fn set_packet_params(&mut self, pkt_params: &PacketParams) -> Result<(), RadioError> {
+        self.preamble_length = pkt_params.preamble_length;
+        self.implicit_header = pkt_params.implicit_header;
+        Ok(())
+    }
+
+    async fn set_fsk_modulation_params(&mut self, mdltn_params: &FskModulationParams) -> Result<(), RadioError> {
+        self.fsk_bitrate = mdltn_params.bitrate;
+        Ok(())
+    }
+
+    async fn set_fsk_packet_params(&mut self, pkt_params: &FskPacketParams) -> Result<(), RadioError> {
+        self.fsk_overhead_bytes = pkt_params.preamble_length as u32
+            + pkt_params.sync_word_length as u32
+            + pkt_params.variable_length as u32
+            + 2 * pkt_params.crc_on as u32;
+        Ok(())
+    }
+
+    async fn calibrate_image(&mut self, _frequency_in_hz: u32) -> Result<(), RadioError> {
+        Ok(())
+    }
+
+    fn image_calibration_band(&self, _frequency_in_hz: u32) -> u32 {
+        0
+    }
+
+    async fn set_channel(&mut self, frequency_in_hz: u32) -> Result<(), RadioError> {
+        self.frequency_in_hz = frequency_in_hz;
+        Ok(())
+    }
+
+    async fn set_payload(&mut self, payload: &[u8]) -> Result<(), RadioError> {
+        if payload.len() > self.tx_buffer.len() {
+            return Err(RadioError::PayloadSizeUnexpected(payload.len()));
+        }
+        self.tx_buffer[..payload.len()].copy_from_slice(payload);
+        self.tx_len = payload.len();
+        self.transmitted = false;
+        Ok(())
+    }
+
+    async fn do_tx(&mut self, _timeout_in_ms: u32) -> Result<(), RadioError> {
+        self.mode = RadioMode::Transmit;
+        self.irq = None;
+        self.pending = if self.tx_stalled {
+            None
+        } else {
+            Some((Irq::TxDone, self.time_on_air_us(self.tx_len)))
+        };
+        Ok(())
+    }
+
+    async fn do_rx(&mut self, rx_mode: RxMode) -> Result<(), RadioError> {
+        self.mode = RadioMode::Receive(rx_mode);
+        self.irq = None;
+        self.pending = match (&self.queued, rx_mode) {
+            (Some((_, len, _)), _) => Some((Irq::RxDone, self.time_on_air_us(*len))),
+            (None, RxMode::Single(symbols)) if symbols > 0 => Some((Irq::RxTimeout, self.rx_timeout_us(symbols))),
+            (None, _) => None,
+        };
+        Ok(())
+    }
+
+    async fn get_rx_payload(
+        &mut self,
+        _rx_pkt_params: &PacketParams,
+        receiving_buffer: &mut [u8],
+    ) -> Result<u8, RadioError> {
+        if self.rx_len > receiving_buffer.len() {
+            return Err(RadioError::PayloadSizeMismatch(self.rx_len, receiving_buffer.len()));
+        }
+        receiving_buffer[..self.rx_len].copy_from_slice(&self.rx_buffer[..self.rx_len]);
+        Ok(self.rx_len as u8)
+    }
+
+    async fn get_fsk_rx_payload(
+        &mut self,
+        _rx_pkt_params: &FskPacketParams,
+        receiving_buffer: &mut [u8],
+    ) -> Result<u8, RadioError> {
+        if self.rx_len > receiving_buffer.len() {
+            return Err(RadioError::PayloadSizeMismatch(self.rx_len, receiving_buffer.len()));
+        }
+        receiving_buffer[..self.rx_len].copy_from_slice(&self.rx_buffer[..self.rx_len]);
+        Ok(self.rx_len as u8)
+    }
+
+    async fn get_rx_packet_status(&mut self) -> Result<PacketStatus, RadioError> {
+        Ok(self.rx_status)
+    }
+
+    async fn get_packet_stats(&mut self) -> Result<PacketStats, RadioError> {
+        Ok(self.packet_stats)
+    }
+
+    async fn reset_packet_stats(&mut self) -> Result<(), RadioError> {
+        self.packet_stats = PacketStats::default();
+        Ok(())
+    }
+
+    async fn get_device_errors(&mut self) -> Result<DeviceErrors, RadioError> {
+        Ok(DeviceErrors::default())
+    }
+
+    async fn clear_device_errors(&mut self) -> Result<(), RadioError> {
+        Ok(())
+    }
+
+    async fn get_rssi(&mut self) -> Result<i16, RadioError> {
+        Ok(self.rssi)
+    }
+
+    async fn do_cad(&mut self, mdltn_params: &ModulationParams) -> Result<(), RadioError> {
+        self.mode = RadioMode::ChannelActivityDetection;
+        self.irq = None;
+        let symbol_time_us = Self::symbol_time_us(mdltn_params.spreading_factor, mdltn_params.bandwidth);
+        self.pending = Some((Irq::CadDone(self.cad_activity), CAD_SYMBOLS * symbol_time_us));
+        Ok(())
+    }
+
+    async fn set_irq_params(&mut self, _radio_mode: Option<RadioMode>) -> Result<(), RadioError> {
+        Ok(())
+    }
+
+    async fn set_tx_continuous_wave_mode(&mut self) -> Result<(), RadioError> {
+        self.mode = RadioMode::Transmit;
+        self.pending = None;
+        Ok(())
+    }
+
+    async fn await_irq(&mut self) -> Result<(), RadioError> {
+        if self.irq.is_some() {
+            // the IRQ line stays raised until the IRQ status is cleared
+            return Ok(());
+        }
+        let Some((irq, after_us)) = self.pending else {
+            return core::future::pending().await;
+        };
+        self.delay.delay_us(after_us).await;
+        self.elapsed_us += after_us as u64;
+        self.pending = None;
+        self.irq = Some(irq);
+        match irq {
+            Irq::TxDone => {
+                self.transmitted = true;
+                self.mode = RadioMode::Standby;
+            }
+            Irq::RxDone => {
+                if let Some((buffer, len, status)) = self.queued.take() {
+                    self.rx_buffer = buffer;
+                    self.rx_len = len;
+                    self.rx_status = status;
+                }
+                if let RadioMode::Receive(RxMode::Single(_)) = self.mode {
+                    self.mode = RadioMode::Standby;
+                }
+            }
+            Irq::RxTimeout | Irq::CadDone(_) => self.mode = RadioMode::Standby,
+        }
+        Ok(())
+    }
+
+    async fn process_irq_event(
+        &mut self,
+        radio_mode: RadioMode,
+        cad_activity_detected: Option<&mut bool>,
+        clear_interrupts: bool,
+    ) -> Result<Option<IrqState>, RadioError> {
+        let irq_state = self.get_irq_state(radio_mode, cad_activity_detected).await;
+        if clear_interrupts {
+            self.clear_irq_status().await?;
+        }
+        irq_state
+    }
+
+    async fn get_irq_state(
+        &mut self,
+        radio_mode: RadioMode,
+        cad_activity_detected: Option<&mut bool>,
+    ) -> Result<Option<IrqState>, RadioError> {
+        match (self.irq, radio_mode) {
+            (Some(Irq::TxDone), RadioMode::Transmit) => Ok(Some(IrqState::Done)),
+            (Some(Irq::RxDone), RadioMode::Receive(_)) => Ok(Some(IrqState::Done)),
+            (Some(Irq::RxTimeout), RadioMode::Receive(_)) => Err(RadioError::ReceiveTimeout),
+            (Some(Irq::CadDone(activity_detected)), RadioMode::ChannelActivityDetection) => {
+                if let Some(cad_activity_detected) = cad_activity_detected {
+                    *cad_activity_detected = activity_detected;
+                }
+                Ok(Some(IrqState::Done))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn clear_irq_status(&mut self) -> Result<(), RadioError> {
+        self.packet_stats.count(self.irq == Some(Irq::RxDone), false, false);
+        self.irq = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocking::block_on;
+    use crate::LoRa;
+
+    fn lora() -> LoRa<SimulatedRadio<MockDelay>, MockDelay> {
+        block_on(LoRa::new(SimulatedRadio::new(MockDelay), true, MockDelay)).unwrap()
+    }
+
+    fn status(rssi: i16) -> PacketStatus {
+        PacketStatus {
+            rssi,
+            snr: 7,
+            signal_rssi: rssi,
+            frequency_error: Some(-1200),
+        }
+    }
+
+    #[test]
+    fn simulated_tx_rx_and_cad() {
+        let mut lora = lora();
+        let mdltn_params = lora
+            .create_modulation_params(SpreadingFactor::_7, Bandwidth::_125KHz, CodingRate::_4_5, 868_100_000)
+            .unwrap();
+        let mut tx_pkt_params = lora
+            .create_tx_packet_params(8, false, true, false, &mdltn_params)
+            .unwrap();
+        let rx_pkt_params = lora
+            .create_rx_packet_params(8, false, 255, true, true, &mdltn_params)
+            .unwrap();
+        let radio = |lora: &LoRa<SimulatedRadio<MockDelay>, MockDelay>| -> (u64, RadioMode) {
+            (lora.radio_kind.elapsed_us(), lora.radio_kind.mode())
+        };
+
+        block_on(lora.prepare_for_tx(&mdltn_params, &mut tx_pkt_params, 14, &[0xA5; 20])).unwrap();
+        block_on(lora.tx()).unwrap();
+        assert_eq!(lora.radio_kind.transmitted(), Some(&[0xA5; 20][..]));
+        assert_eq!(lora.radio_kind.frequency_in_hz(), 868_100_000);
+        // 56.576 ms on air
+        assert!(radio(&lora) == (56_576, RadioMode::Standby));
+
+        // Nothing received within 8 symbols of 1.024 ms
+        let mut buffer = [0; 255];
+        block_on(lora.prepare_for_rx(RxMode::Single(8), &mdltn_params, &rx_pkt_params)).unwrap();
+        assert_eq!(
+            block_on(lora.rx(&rx_pkt_params, &mut buffer)).err(),
+            Some(RadioError::ReceiveTimeout)
+        );
+        assert!(radio(&lora) == (64_768, RadioMode::Standby));
+
+        lora.radio_kind.queue_rx_packet(&[1, 2, 3], status(-80));
+        block_on(lora.prepare_for_rx(RxMode::Single(8), &mdltn_params, &rx_pkt_params)).unwrap();
+        let (len, packet_status) = block_on(lora.rx(&rx_pkt_params, &mut buffer)).unwrap();
+        assert_eq!(&buffer[..len as usize], &[1, 2, 3]);
+        assert_eq!(packet_status.rssi, -80);
+        assert_eq!(block_on(lora.get_packet_stats()).unwrap().received, 1);
+
+        lora.radio_kind.set_cad_activity(true);
+        block_on(lora.prepare_for_cad(&mdltn_params)).unwrap();
+        assert_eq!(block_on(lora.cad(&mdltn_params)), Ok(true));
+    }
+
+    #[test]
+    fn stalled_tx_times_out() {
+        let mut lora = lora();
+        lora.radio_kind.set_tx_stalled(true);
+        let mdltn_params = lora
+            .create_modulation_params(SpreadingFactor::_9, Bandwidth::_125KHz, CodingRate::_4_5, 868_100_000)
+            .unwrap();
+        let mut tx_pkt_params = lora
+            .create_tx_packet_params(8, false, true, false, &mdltn_params)
+            .unwrap();
+        block_on(lora.prepare_for_tx(&mdltn_params, &mut tx_pkt_params, 14, &[0; 10])).unwrap();
+        assert_eq!(block_on(lora.tx()), Err(RadioError::TransmitTimeout));
+        assert!(lora.radio_kind.transmitted().is_none());
+        assert!(lora.radio_kind.mode() == RadioMode::Standby);
+
+        // The radio is usable again
+        lora.radio_kind.set_tx_stalled(false);
+        block_on(lora.prepare_for_tx(&mdltn_params, &mut tx_pkt_params, 14, &[0; 10])).unwrap();
+        assert_eq!(block_on(lora.tx()), Ok(()));
+    }
+}
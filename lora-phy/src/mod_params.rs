@@ -33,10 +33,72 @@ pub enum RadioError {
 /// Status for a received packet
 #[derive(Clone, Copy)]
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
-#[allow(missing_docs)]
 pub struct PacketStatus {
+    /// RSSI of the packet (dBm)
     pub rssi: i16,
+    /// SNR of the packet (dB)
     pub snr: i16,
+    /// RSSI of the LoRa signal after despreading (dBm), the packet RSSI on radios not estimating it
+    pub signal_rssi: i16,
+    /// Carrier frequency offset (Hz) of the received signal relative to the channel frequency,
+    /// positive when the transmitter is above it. None when the radio does not estimate it.
+    pub frequency_error: Option<i32>,
+}
+
+/// Packet statistics of the radio since the last reset
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct PacketStats {
+    /// Packets received, including those with errors
+    pub received: u16,
+    /// Packets received with a CRC error
+    pub crc_errors: u16,
+    /// LoRa packets received with an invalid header (packets with a length error in FSK on sx126x)
+    pub header_errors: u16,
+}
+
+impl PacketStats {
+    // Count a reception event for radios keeping the statistics in software
+    pub(crate) fn count(&mut self, rx_done: bool, crc_error: bool, header_error: bool) {
+        if rx_done {
+            self.received = self.received.wrapping_add(1);
+        }
+        if crc_error {
+            self.crc_errors = self.crc_errors.wrapping_add(1);
+        }
+        if header_error {
+            self.header_errors = self.header_errors.wrapping_add(1);
+        }
+    }
+}
+
+/// Hardware errors reported by the radio since they were last cleared
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct DeviceErrors {
+    /// Calibration of the low frequency RC oscillator failed
+    pub lf_rc_calibration: bool,
+    /// Calibration of the high frequency RC oscillator failed
+    pub hf_rc_calibration: bool,
+    /// Calibration of the PLL failed
+    pub pll_calibration: bool,
+    /// Calibration of the ADC failed
+    pub adc_calibration: bool,
+    /// Image calibration failed
+    pub image_calibration: bool,
+    /// The crystal oscillator failed to start
+    pub xosc_start: bool,
+    /// The PLL failed to lock
+    pub pll_lock: bool,
+    /// The power amplifier ramp failed (sx126x)
+    pub pa_ramp: bool,
+}
+
+impl DeviceErrors {
+    /// Whether any error is reported
+    pub fn any(&self) -> bool {
+        *self != Self::default()
+    }
 }
 
 /// The state of the radio
@@ -74,6 +136,8 @@ pub enum RxMode {
     /// Preamble length as symbols is configured via following registers:
     /// * sx126x: uses `SetLoRaSymbNumTimeout(0 < n < 255)` + `SetStopRxTimerOnPreamble(1)`
     /// * sx127x: uses `RegSymbTimeout (4 < n < 1023)`
+    ///
+    /// With FSK modulation, the timeout is given in bits instead.
     // TODO: Single mode with time-based timeout is available on sx126x, but not sx127x
     Single(u16),
     /// Continuous Rx mode to listen for incoming packets continuously
@@ -110,6 +174,82 @@ impl PacketParams {
     }
 }
 
+/// Gaussian filter applied to FSK modulation, by bandwidth-time product
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[allow(missing_docs)]
+pub enum PulseShape {
+    None,
+    Bt0_3,
+    Bt0_5,
+    Bt0_7,
+    Bt1_0,
+}
+
+/// FSK modulation parameters for a send and/or receive communication channel
+pub struct FskModulationParams {
+    pub(crate) bitrate: u32,
+    pub(crate) fdev: u32,
+    pub(crate) pulse_shape: PulseShape,
+    pub(crate) rx_bandwidth: u32, // single-sideband receiver bandwidth in Hz
+    pub(crate) frequency_in_hz: u32,
+}
+
+/// FSK packet parameters for a send or receive communication channel
+pub struct FskPacketParams {
+    pub(crate) preamble_length: u16, // number of bytes in the preamble
+    pub(crate) sync_word: [u8; 8],
+    pub(crate) sync_word_length: u8,
+    pub(crate) variable_length: bool, // if the length is variable, it is transmitted as the first byte of the packet
+    pub(crate) payload_length: u8,
+    pub(crate) crc_on: bool, // CRC-16 (CCITT)
+    pub(crate) whitening: bool,
+}
+
+impl FskPacketParams {
+    pub(crate) fn new(
+        preamble_length: u16,
+        sync_word: &[u8],
+        variable_length: bool,
+        payload_length: u8,
+        crc_on: bool,
+        whitening: bool,
+    ) -> Result<Self, RadioError> {
+        if sync_word.len() > 8 {
+            return Err(RadioError::InvalidConfiguration);
+        }
+        let mut sync_word_buf = [0u8; 8];
+        sync_word_buf[..sync_word.len()].copy_from_slice(sync_word);
+        Ok(Self {
+            preamble_length,
+            sync_word: sync_word_buf,
+            sync_word_length: sync_word.len() as u8,
+            variable_length,
+            payload_length,
+            crc_on,
+            whitening,
+        })
+    }
+
+    pub(crate) fn set_payload_length(&mut self, payload_length: usize) -> Result<(), RadioError> {
+        if payload_length > 255 {
+            return Err(RadioError::PayloadSizeUnexpected(payload_length));
+        }
+        self.payload_length = payload_length as u8;
+        Ok(())
+    }
+
+    // Time on air of the packet at the given bitrate
+    pub(crate) fn time_on_air_us(&self, bitrate: u32) -> u32 {
+        let bytes = self.preamble_length as u32
+            + self.sync_word_length as u32
+            + self.variable_length as u32
+            + self.payload_length as u32
+            + 2 * self.crc_on as u32;
+        (8 * bytes as u64 * 1_000_000 / bitrate.max(1) as u64) as u32
+    }
+}
+
 /// Receive duty cycle parameters
 #[derive(Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
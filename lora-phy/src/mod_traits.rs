@@ -33,8 +33,10 @@ pub enum IrqState {
 /// LoRa physical layer API
 #[allow(async_fn_in_trait)]
 pub trait RadioKind {
-    /// Initialize lora radio
+    /// Initialize lora radio, switching the LoRa chip to the LoRa packet engine
     async fn init_lora(&mut self, sync_word: u8) -> Result<(), RadioError>;
+    /// Switch the LoRa chip to the (G)FSK packet engine
+    async fn init_fsk(&mut self) -> Result<(), RadioError>;
     /// Create modulation parameters specific to the LoRa chip kind and type
     fn create_modulation_params(
         &self,
@@ -53,6 +55,25 @@ pub trait RadioKind {
         iq_inverted: bool,
         modulation_params: &ModulationParams,
     ) -> Result<PacketParams, RadioError>;
+    /// Create FSK modulation parameters specific to the LoRa chip kind and type
+    fn create_fsk_modulation_params(
+        &self,
+        bitrate: u32,
+        fdev: u32,
+        pulse_shape: PulseShape,
+        rx_bandwidth: u32,
+        frequency_in_hz: u32,
+    ) -> Result<FskModulationParams, RadioError>;
+    /// Create FSK packet parameters specific to the LoRa chip kind and type
+    fn create_fsk_packet_params(
+        &self,
+        preamble_length: u16,
+        sync_word: &[u8],
+        variable_length: bool,
+        payload_length: u8,
+        crc_on: bool,
+        whitening: bool,
+    ) -> Result<FskPacketParams, RadioError>;
     /// Reset the loRa chip
     async fn reset(&mut self, delay: &mut impl DelayNs) -> Result<(), RadioError>;
     /// Ensure the LoRa chip is in the appropriate state to allow operation requests
@@ -78,14 +99,22 @@ pub trait RadioKind {
     async fn set_modulation_params(&mut self, mdltn_params: &ModulationParams) -> Result<(), RadioError>;
     /// Set the LoRa chip packet parameters prior to sending or receiving packets
     async fn set_packet_params(&mut self, pkt_params: &PacketParams) -> Result<(), RadioError>;
+    /// Set the LoRa chip FSK modulation parameters prior to using a communication channel
+    async fn set_fsk_modulation_params(&mut self, mdltn_params: &FskModulationParams) -> Result<(), RadioError>;
+    /// Set the LoRa chip FSK packet parameters prior to sending or receiving packets
+    async fn set_fsk_packet_params(&mut self, pkt_params: &FskPacketParams) -> Result<(), RadioError>;
     /// Set the LoRa chip to support a given communication channel frequency
     async fn calibrate_image(&mut self, frequency_in_hz: u32) -> Result<(), RadioError>;
+    /// Identify the image calibration band covering a frequency; the image is calibrated again
+    /// when the operating frequency moves to another band
+    fn image_calibration_band(&self, frequency_in_hz: u32) -> u32;
     /// Set the frequency for a communication channel
     async fn set_channel(&mut self, frequency_in_hz: u32) -> Result<(), RadioError>;
     /// Set a payload for a subsequent send operation
     async fn set_payload(&mut self, payload: &[u8]) -> Result<(), RadioError>;
-    /// Perform a transmit operation
-    async fn do_tx(&mut self) -> Result<(), RadioError>;
+    /// Perform a transmit operation, aborted by the radio after `timeout_in_ms` (0 for none) if it
+    /// supports a TX timeout
+    async fn do_tx(&mut self, timeout_in_ms: u32) -> Result<(), RadioError>;
     /// Set up to perform a receive operation (single-shot, continuous, or duty cycle)
     async fn do_rx(&mut self, rx_mode: RxMode) -> Result<(), RadioError>;
     /// Get an available packet made available as the result of a receive operation
@@ -94,8 +123,22 @@ pub trait RadioKind {
         rx_pkt_params: &PacketParams,
         receiving_buffer: &mut [u8],
     ) -> Result<u8, RadioError>;
+    /// Get an available packet made available as the result of an FSK receive operation
+    async fn get_fsk_rx_payload(
+        &mut self,
+        rx_pkt_params: &FskPacketParams,
+        receiving_buffer: &mut [u8],
+    ) -> Result<u8, RadioError>;
     /// Get the RSSI and SNR for the packet made available as the result of a receive operation
     async fn get_rx_packet_status(&mut self) -> Result<PacketStatus, RadioError>;
+    /// Get the packet statistics since the last reset, counted in software by radios lacking them
+    async fn get_packet_stats(&mut self) -> Result<PacketStats, RadioError>;
+    /// Reset the packet statistics
+    async fn reset_packet_stats(&mut self) -> Result<(), RadioError>;
+    /// Get the hardware errors reported by the radio; radios without error reporting report none
+    async fn get_device_errors(&mut self) -> Result<DeviceErrors, RadioError>;
+    /// Clear the hardware errors reported by the radio
+    async fn clear_device_errors(&mut self) -> Result<(), RadioError>;
     /// Get the current RSSI
     async fn get_rssi(&mut self) -> Result<i16, RadioError>;
     /// Perform a channel activity detection operation
@@ -32,7 +32,19 @@ const BRD_TCXO_WAKEUP_TIME: u32 = 10;
 // SetRx timeout argument for enabling continuous mode
 const RX_CONTINUOUS_TIMEOUT: u32 = 0xffffff;
 
+// GFSK bitrate limits (bps)
+const SX126X_FSK_MIN_BITRATE: u32 = 600;
+const SX126X_FSK_MAX_BITRATE: u32 = 300_000;
+
+// GFSK CRC-16 (CCITT) initial value and polynomial
+const FSK_CRC_CCITT_INIT: u16 = 0x1D0F;
+const FSK_CRC_CCITT_POLYNOMIAL: u16 = 0x1021;
+
+// GFSK whitening initial value
+const FSK_WHITENING_INIT: u16 = 0x01FF;
+
 /// Power amplifier selection
+#[derive(Clone, Copy, PartialEq, Debug)]
 #[repr(u8)]
 pub enum DeviceSel {
     /// Low power, power amplifier, used by sx1261
@@ -49,8 +61,8 @@ pub struct Config<C: Sx126xVariant + Sized> {
     ///
     /// The TCXO configuration must match your board's hardware.
     /// If your board does not have a TCXO (Temperature-Compensated Crystal Oscillator),
-    /// set `tcxo_ctrl` to `None`. An incorrect setting will cause transmission & receiving
-    /// functions (e.g., `lora.tx()`, `lora.rx()`) to hang indefinitely.
+    /// set `tcxo_ctrl` to `None`. An incorrect setting will cause transmissions to time out
+    /// (`lora.tx()` returns `RadioError::TransmitTimeout`) and receptions (`lora.rx()`) to hang.
     pub tcxo_ctrl: Option<TcxoCtrlVoltage>,
     /// Whether board is using optional DCDC in addition to LDO
     pub use_dcdc: bool,
@@ -62,6 +74,10 @@ pub struct Config<C: Sx126xVariant + Sized> {
 pub struct Sx126x<SPI, IV, C: Sx126xVariant + Sized> {
     intf: SpiInterface<SPI, IV>,
     config: Config<C>,
+    packet_type: PacketType,
+    fsk_bitrate: u32,
+    // LoRa bandwidth of the current modulation parameters, scaling the frequency error
+    bandwidth_in_hz: u32,
 }
 
 impl<SPI, IV, C> Sx126x<SPI, IV, C>
@@ -73,7 +89,13 @@ where
     /// Create an instance of the RadioKind implementation for the LoRa chip kind and board type
     pub fn new(spi: SPI, iv: IV, config: Config<C>) -> Self {
         let intf = SpiInterface::new(spi, iv);
-        Self { intf, config }
+        Self {
+            intf,
+            config,
+            packet_type: PacketType::LoRa,
+            fsk_bitrate: 0,
+            bandwidth_in_hz: 0,
+        }
     }
 
     // Utility functions
@@ -149,6 +171,23 @@ where
         Ok(())
     }
 
+    async fn write_register_u16(&mut self, register: Register, value: u16) -> Result<(), RadioError> {
+        let register_and_value = [
+            OpCode::WriteRegister.value(),
+            register.addr1(),
+            register.addr2(),
+            (value >> 8) as u8,
+            value as u8,
+        ];
+        self.intf.write(&register_and_value, false).await
+    }
+
+    // Convert a number of bits at the current GFSK bitrate into a SetRx timeout (15.625 us steps)
+    fn fsk_bits_to_timeout(&self, bits: u16) -> u32 {
+        let timeout = (bits as u64 * 64_000).div_ceil(self.fsk_bitrate.max(1) as u64);
+        timeout.min(RX_CONTINUOUS_TIMEOUT as u64 - 1) as u32
+    }
+
     async fn set_pa_config(&mut self, pa_duty_cycle: u8, hp_max: u8, device_sel: DeviceSel) -> Result<(), RadioError> {
         const PA_LUT_RESERVED: u8 = 0x01;
         let op_code_and_pa_config = [
@@ -282,6 +321,7 @@ where
             word[1],
         ];
         self.intf.write(&lora_syncword_set, false).await?;
+        self.packet_type = PacketType::LoRa;
 
         self.set_tx_rx_buffer_base_address(0, 0).await?;
         // Update register list to support warm starts from sleep mode
@@ -289,6 +329,14 @@ where
         Ok(())
     }
 
+    async fn init_fsk(&mut self) -> Result<(), RadioError> {
+        self.intf
+            .write(&[OpCode::SetPacketType.value(), PacketType::GFSK.value()], false)
+            .await?;
+        self.packet_type = PacketType::GFSK;
+        self.set_tx_rx_buffer_base_address(0, 0).await
+    }
+
     fn create_modulation_params(
         &self,
         spreading_factor: SpreadingFactor,
@@ -300,6 +348,7 @@ where
         spreading_factor_value(spreading_factor)?;
         bandwidth_value(bandwidth)?;
         coding_rate_value(coding_rate)?;
+        self.config.chip.check_modulation_params(spreading_factor, bandwidth)?;
         if ((bandwidth == Bandwidth::_250KHz) || (bandwidth == Bandwidth::_500KHz)) && (frequency_in_hz < 400_000_000) {
             return Err(RadioError::InvalidBandwidthForFrequency);
         }
@@ -345,6 +394,48 @@ where
         })
     }
 
+    fn create_fsk_modulation_params(
+        &self,
+        bitrate: u32,
+        fdev: u32,
+        pulse_shape: PulseShape,
+        rx_bandwidth: u32,
+        frequency_in_hz: u32,
+    ) -> Result<FskModulationParams, RadioError> {
+        // Parameter validation
+        if !(SX126X_FSK_MIN_BITRATE..=SX126X_FSK_MAX_BITRATE).contains(&bitrate) {
+            return Err(RadioError::InvalidConfiguration);
+        }
+        fsk_bandwidth_value(rx_bandwidth)?;
+
+        Ok(FskModulationParams {
+            bitrate,
+            fdev,
+            pulse_shape,
+            rx_bandwidth,
+            frequency_in_hz,
+        })
+    }
+
+    fn create_fsk_packet_params(
+        &self,
+        preamble_length: u16,
+        sync_word: &[u8],
+        variable_length: bool,
+        payload_length: u8,
+        crc_on: bool,
+        whitening: bool,
+    ) -> Result<FskPacketParams, RadioError> {
+        FskPacketParams::new(
+            preamble_length,
+            sync_word,
+            variable_length,
+            payload_length,
+            crc_on,
+            whitening,
+        )
+    }
+
     async fn reset(&mut self, delay: &mut impl DelayNs) -> Result<(), RadioError> {
         self.intf.iv.reset(delay).await
     }
@@ -408,54 +499,24 @@ where
         mdltn_params: Option<&ModulationParams>,
         is_tx_prep: bool,
     ) -> Result<(), RadioError> {
-        let tx_params_power;
         let ramp_time = match is_tx_prep {
             true => RampTime::Ramp40Us,   // for instance, prior to TX or CAD
             false => RampTime::Ramp200Us, // for instance, on initialization
         };
 
-        match self.config.chip.get_device_sel() {
+        let pa_config = self.config.chip.pa_config(output_power);
+        match pa_config.device_sel {
             DeviceSel::LowPowerPA => {
-                const LOW_POWER_MIN: i32 = -17;
-                const LOW_POWER_MAX: i32 = 15;
-                // Clamp power between [-17, 15] dBm
-                let txp = output_power.clamp(LOW_POWER_MIN, LOW_POWER_MAX);
-
-                if txp == 15 {
+                // 15 dBm is only available above 400 MHz
+                if output_power >= 15 {
                     if let Some(m_p) = mdltn_params {
                         if m_p.frequency_in_hz < 400_000_000 {
                             return Err(RadioError::InvalidOutputPowerForFrequency);
                         }
                     }
                 }
-
-                // For SX1261:
-                // if f < 400 MHz, paDutyCycle should not be higher than 0x04,
-                // if f > 400 Mhz, paDutyCycle should not be higher than 0x07.
-                // From Table 13-21: PA Operating Modes with Optimal Settings
-                match txp {
-                    LOW_POWER_MAX => {
-                        self.set_pa_config(0x06, 0x00, DeviceSel::LowPowerPA).await?;
-                        tx_params_power = 14;
-                    }
-                    11..=14 => {
-                        self.set_pa_config(0x04, 0x00, DeviceSel::LowPowerPA).await?;
-                        tx_params_power = txp as u8;
-                    }
-                    // 10 and less
-                    LOW_POWER_MIN..=10 => {
-                        self.set_pa_config(0x01, 0x00, DeviceSel::LowPowerPA).await?;
-                        // table indicates 10 dBm => txp = 13, therefore we add 3 to values below 10
-                        tx_params_power = txp as u8 + 3;
-                    }
-                    _ => unreachable!("Invalid output power value for low power PA!"),
-                }
             }
             DeviceSel::HighPowerPA => {
-                const HIGH_POWER_MIN: i32 = -9;
-                const HIGH_POWER_MAX: i32 = 22;
-                // Clamp power between [-9, 22] dBm
-                let txp = output_power.clamp(HIGH_POWER_MIN, HIGH_POWER_MAX);
                 // Provide better resistance of the SX1262 Tx to antenna mismatch (see DS_SX1261-2_V1.2 datasheet chapter 15.2)
                 let mut tx_clamp_cfg = [0x00u8];
                 self.intf
@@ -477,36 +538,12 @@ where
                     tx_clamp_cfg[0],
                 ];
                 self.intf.write(&register_and_tx_clamp_cfg, false).await?;
-
-                // From Table 13-21: PA Operating Modes with Optimal Settings
-                match txp {
-                    21..=HIGH_POWER_MAX => {
-                        self.set_pa_config(0x04, 0x07, DeviceSel::HighPowerPA).await?;
-                        tx_params_power = 22;
-                    }
-                    18..=20 => {
-                        self.set_pa_config(0x03, 0x05, DeviceSel::HighPowerPA).await?;
-                        // table indicates 20 dBm => txp = 22, therefore we add 2 to this range
-                        tx_params_power = txp as u8 + 2;
-                    }
-                    15..=17 => {
-                        self.set_pa_config(0x02, 0x03, DeviceSel::HighPowerPA).await?;
-                        // table indicates 17 dBm => txp = 22, therefore we add 5 to this range
-                        tx_params_power = txp as u8 + 5;
-                    }
-                    HIGH_POWER_MIN..=14 => {
-                        self.set_pa_config(0x02, 0x02, DeviceSel::HighPowerPA).await?;
-                        // table indicates 14 dBm => txp = 22, therefore we should add 8 to this range
-                        // this however seems to be wrong when looking at the reference driver
-                        // https://github.com/STMicroelectronics/STM32CubeWL/blob/139e8d28bcec6af78dec8b52a9b9f9057868cc2e/Middlewares/Third_Party/SubGHz_Phy/stm32_radio_driver/radio_driver.c#L675
-                        tx_params_power = txp as u8;
-                    }
-                    _ => {
-                        unreachable!("Invalid output power value for high power PA!")
-                    }
-                }
             }
         }
+        self.set_pa_config(pa_config.pa_duty_cycle, pa_config.hp_max, pa_config.device_sel)
+            .await?;
+
+        let tx_params_power = pa_config.tx_power as u8;
         let op_code_and_tx_params = [OpCode::SetTxParams.value(), tx_params_power, ramp_time.value()];
         self.intf.write(&op_code_and_tx_params, false).await
     }
@@ -519,6 +556,7 @@ where
             "sf = {}, bw = {}, cr = {}",
             spreading_factor_val, bandwidth_val, coding_rate_val
         );
+        self.bandwidth_in_hz = u32::from(mdltn_params.bandwidth);
         let op_code_and_mod_params = [
             OpCode::SetModulationParams.value(),
             spreading_factor_val,
@@ -602,31 +640,99 @@ where
         Ok(())
     }
 
-    // Calibrate the image rejection based on the given frequency
-    async fn calibrate_image(&mut self, frequency_in_hz: u32) -> Result<(), RadioError> {
-        let mut cal_freq = [0x00u8, 0x00u8];
-
-        if frequency_in_hz > 900000000 {
-            cal_freq[0] = 0xE1;
-            cal_freq[1] = 0xE9;
-        } else if frequency_in_hz > 850000000 {
-            cal_freq[0] = 0xD7;
-            cal_freq[1] = 0xDB;
-        } else if frequency_in_hz > 770000000 {
-            cal_freq[0] = 0xC1;
-            cal_freq[1] = 0xC5;
-        } else if frequency_in_hz > 460000000 {
-            cal_freq[0] = 0x75;
-            cal_freq[1] = 0x81;
-        } else if frequency_in_hz > 425000000 {
-            cal_freq[0] = 0x6B;
-            cal_freq[1] = 0x6F;
+    async fn set_fsk_modulation_params(&mut self, mdltn_params: &FskModulationParams) -> Result<(), RadioError> {
+        let bitrate = (32 * SX126X_XTAL_FREQ) / mdltn_params.bitrate;
+        let fdev = Self::convert_freq_in_hz_to_pll_step(mdltn_params.fdev);
+        let bandwidth_val = fsk_bandwidth_value(mdltn_params.rx_bandwidth)?;
+        debug!(
+            "bitrate = {}, fdev = {}, bw = {}",
+            mdltn_params.bitrate, mdltn_params.fdev, bandwidth_val
+        );
+        let op_code_and_mod_params = [
+            OpCode::SetModulationParams.value(),
+            Self::timeout_1(bitrate),
+            Self::timeout_2(bitrate),
+            Self::timeout_3(bitrate),
+            pulse_shape_value(mdltn_params.pulse_shape),
+            bandwidth_val,
+            Self::timeout_1(fdev),
+            Self::timeout_2(fdev),
+            Self::timeout_3(fdev),
+        ];
+        self.intf.write(&op_code_and_mod_params, false).await?;
+        self.fsk_bitrate = mdltn_params.bitrate;
+        Ok(())
+    }
+
+    async fn set_fsk_packet_params(&mut self, pkt_params: &FskPacketParams) -> Result<(), RadioError> {
+        let preamble_bits = pkt_params.preamble_length.saturating_mul(8);
+        // The preamble detector length may not exceed the preamble length
+        let preamble_detector = match pkt_params.preamble_length {
+            0 => 0x00u8, // off
+            1 => 0x04u8, // 8 bits
+            _ => 0x05u8, // 16 bits
+        };
+        let crc_type = if pkt_params.crc_on { 0x06u8 } else { 0x01u8 }; // 2 bytes inverted or off
+        let op_code_and_pkt_params = [
+            OpCode::SetPacketParams.value(),
+            (preamble_bits >> 8) as u8,
+            (preamble_bits & 0xFF) as u8,
+            preamble_detector,
+            pkt_params.sync_word_length * 8,
+            0x00u8, // no address filtering
+            pkt_params.variable_length as u8,
+            pkt_params.payload_length,
+            crc_type,
+            pkt_params.whitening as u8,
+        ];
+        self.intf.write(&op_code_and_pkt_params, false).await?;
+
+        let register = [
+            OpCode::WriteRegister.value(),
+            Register::Syncword.addr1(),
+            Register::Syncword.addr2(),
+        ];
+        self.intf
+            .write_with_payload(&register, &pkt_params.sync_word, false)
+            .await?;
+
+        if pkt_params.crc_on {
+            self.write_register_u16(Register::CrcInit, FSK_CRC_CCITT_INIT).await?;
+            self.write_register_u16(Register::CrcPolynomial, FSK_CRC_CCITT_POLYNOMIAL)
+                .await?;
+        }
+
+        if pkt_params.whitening {
+            // Only the least significant bit of the first register belongs to the initial value
+            let mut whitening_msb = [0x00u8];
+            self.intf
+                .read(
+                    &[
+                        OpCode::ReadRegister.value(),
+                        Register::WhiteningInit.addr1(),
+                        Register::WhiteningInit.addr2(),
+                        0x00u8,
+                    ],
+                    &mut whitening_msb,
+                )
+                .await?;
+            let value = (((whitening_msb[0] & 0xFE) as u16) << 8) | FSK_WHITENING_INIT;
+            self.write_register_u16(Register::WhiteningInit, value).await?;
         }
+        Ok(())
+    }
 
+    // Calibrate the image rejection based on the given frequency
+    async fn calibrate_image(&mut self, frequency_in_hz: u32) -> Result<(), RadioError> {
+        let cal_freq = self.config.chip.image_calibration_band(frequency_in_hz);
         let op_code_and_cal_freq = [OpCode::CalibrateImage.value(), cal_freq[0], cal_freq[1]];
         self.intf.write(&op_code_and_cal_freq, false).await
     }
 
+    fn image_calibration_band(&self, frequency_in_hz: u32) -> u32 {
+        u16::from_be_bytes(self.config.chip.image_calibration_band(frequency_in_hz)) as u32
+    }
+
     async fn set_channel(&mut self, frequency_in_hz: u32) -> Result<(), RadioError> {
         debug!("channel = {}", frequency_in_hz);
         let freq_in_pll_steps = Self::convert_freq_in_hz_to_pll_step(frequency_in_hz);
@@ -645,15 +751,16 @@ where
         self.intf.write_with_payload(&op_code_and_offset, payload, false).await
     }
 
-    async fn do_tx(&mut self) -> Result<(), RadioError> {
+    async fn do_tx(&mut self, timeout_in_ms: u32) -> Result<(), RadioError> {
         self.intf.iv.enable_rf_switch_tx().await?;
 
-        // Disable timeout
+        // Timeout in steps of 15.625 us, 0 disables it
+        let timeout = timeout_in_ms.saturating_mul(64).min(0x00FF_FFFF);
         let cmd = [
             OpCode::SetTx.value(),
-            Self::timeout_1(0),
-            Self::timeout_2(0),
-            Self::timeout_3(0),
+            Self::timeout_1(timeout),
+            Self::timeout_2(timeout),
+            Self::timeout_3(timeout),
         ];
         self.intf.write(&cmd, false).await
     }
@@ -669,7 +776,13 @@ where
             RxMode::DutyCycle(_) | RxMode::Continuous => 0,
             RxMode::Single(n) => n,
         };
-        self.set_lora_symbol_num_timeout(num_symbols).await?;
+        // The GFSK packet engine uses the timeout of SetRx instead
+        let single_timeout = if self.packet_type == PacketType::GFSK {
+            self.fsk_bits_to_timeout(num_symbols)
+        } else {
+            self.set_lora_symbol_num_timeout(num_symbols).await?;
+            0
+        };
 
         let rx_gain = if self.config.rx_boost { 0x96 } else { 0x94 };
         let register_and_rx_gain = [
@@ -696,9 +809,9 @@ where
             RxMode::Single(_) => {
                 let op = [
                     OpCode::SetRx.value(),
-                    Self::timeout_1(0),
-                    Self::timeout_2(0),
-                    Self::timeout_3(0),
+                    Self::timeout_1(single_timeout),
+                    Self::timeout_2(single_timeout),
+                    Self::timeout_3(single_timeout),
                 ];
                 self.intf.write(&op, false).await
             }
@@ -762,6 +875,38 @@ where
         }
     }
 
+    async fn get_fsk_rx_payload(
+        &mut self,
+        _rx_pkt_params: &FskPacketParams,
+        receiving_buffer: &mut [u8],
+    ) -> Result<u8, RadioError> {
+        let op_code = [OpCode::GetRxBufferStatus.value()];
+        let mut rx_buffer_status = [0x00u8; 2];
+        let read_status = self.intf.read_with_status(&op_code, &mut rx_buffer_status).await?;
+        if OpStatusErrorMask::is_error(read_status) {
+            return Err(RadioError::OpError(read_status));
+        }
+
+        // The length is also reported for fixed length packets
+        let payload_length = rx_buffer_status[0];
+        let offset = rx_buffer_status[1];
+
+        if (payload_length as usize) > receiving_buffer.len() {
+            Err(RadioError::PayloadSizeMismatch(
+                payload_length as usize,
+                receiving_buffer.len(),
+            ))
+        } else {
+            self.intf
+                .read(
+                    &[OpCode::ReadBuffer.value(), offset, 0x00u8],
+                    &mut receiving_buffer[..payload_length as usize],
+                )
+                .await?;
+            Ok(payload_length)
+        }
+    }
+
     async fn get_rx_packet_status(&mut self) -> Result<PacketStatus, RadioError> {
         let op_code = [OpCode::GetPacketStatus.value()];
         let mut pkt_status = [0x00u8; 3];
@@ -769,12 +914,83 @@ where
         if OpStatusErrorMask::is_error(read_status) {
             return Err(RadioError::OpError(read_status));
         }
+        if self.packet_type == PacketType::GFSK {
+            // RxStatus, RssiSync and RssiAvg; there is no SNR for GFSK
+            let rssi = ((-(pkt_status[1] as i32)) >> 1) as i16;
+            return Ok(PacketStatus {
+                rssi,
+                snr: 0,
+                signal_rssi: rssi,
+                frequency_error: None,
+            });
+        }
         // check this ???
         let rssi = ((-(pkt_status[0] as i32)) >> 1) as i16;
         let snr = (((pkt_status[1] as i8) + 2) >> 2) as i16;
-        let _signal_rssi = ((-(pkt_status[2] as i32)) >> 1) as i16; // unused currently
+        let signal_rssi = ((-(pkt_status[2] as i32)) >> 1) as i16;
+
+        let mut freq_error = [0x00u8; 3];
+        self.intf
+            .read(
+                &[
+                    OpCode::ReadRegister.value(),
+                    Register::FreqError.addr1(),
+                    Register::FreqError.addr2(),
+                    0x00u8,
+                ],
+                &mut freq_error,
+            )
+            .await?;
+        let frequency_error = frequency_error_value(freq_error, self.bandwidth_in_hz);
 
-        Ok(PacketStatus { rssi, snr })
+        Ok(PacketStatus {
+            rssi,
+            snr,
+            signal_rssi,
+            frequency_error: Some(frequency_error),
+        })
+    }
+
+    async fn get_packet_stats(&mut self) -> Result<PacketStats, RadioError> {
+        let op_code = [OpCode::GetStats.value()];
+        let mut stats = [0x00u8; 6];
+        let read_status = self.intf.read_with_status(&op_code, &mut stats).await?;
+        if OpStatusErrorMask::is_error(read_status) {
+            return Err(RadioError::OpError(read_status));
+        }
+        Ok(PacketStats {
+            received: u16::from_be_bytes([stats[0], stats[1]]),
+            crc_errors: u16::from_be_bytes([stats[2], stats[3]]),
+            header_errors: u16::from_be_bytes([stats[4], stats[5]]),
+        })
+    }
+
+    async fn reset_packet_stats(&mut self) -> Result<(), RadioError> {
+        let op_code_and_stats = [
+            OpCode::ResetStats.value(),
+            0x00u8,
+            0x00u8,
+            0x00u8,
+            0x00u8,
+            0x00u8,
+            0x00u8,
+        ];
+        self.intf.write(&op_code_and_stats, false).await
+    }
+
+    async fn get_device_errors(&mut self) -> Result<DeviceErrors, RadioError> {
+        let op_code = [OpCode::GetDeviceErrors.value()];
+        let mut op_error = [0x00u8; 2];
+        let read_status = self.intf.read_with_status(&op_code, &mut op_error).await?;
+        if OpStatusErrorMask::is_error(read_status) {
+            return Err(RadioError::OpError(read_status));
+        }
+        Ok(device_errors(u16::from_be_bytes(op_error)))
+    }
+
+    async fn clear_device_errors(&mut self) -> Result<(), RadioError> {
+        let op_code_and_params = [OpCode::ClearDeviceErrors.value(), 0x00u8, 0x00u8];
+        self.intf.write(&op_code_and_params, false).await
     }
 
     async fn get_rssi(&mut self) -> Result<i16, RadioError> {
@@ -977,7 +1193,9 @@ where
             self.clear_irq_status().await?;
         }
 
-        if let (RadioMode::Receive(RxMode::Single(_)), Ok(Some(IrqState::Done))) = (radio_mode, &irq_state) {
+        if let (RadioMode::Receive(RxMode::Single(_)), Ok(Some(IrqState::Done)), PacketType::LoRa) =
+            (radio_mode, &irq_state, self.packet_type)
+        {
             self.handle_implicit_header_mode().await?;
         }
 
@@ -988,6 +1206,8 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::blocking::block_on;
+    use crate::mock::{transfer, write, MockIv, MockSpi, Transaction};
 
     #[test]
     // -17 (0xEF) to +14 (0x0E) dBm by step of 1 dB if low power PA is selected
@@ -1007,4 +1227,126 @@ mod tests {
         // sx126x 0x1424 corresponds to sx127 0x12
         assert_eq!(convert_sync_word(0x12), [0x14, 0x24]);
     }
+
+    #[test]
+    fn llcc68_modulation_params() {
+        assert!(Llcc68
+            .check_modulation_params(SpreadingFactor::_9, Bandwidth::_125KHz)
+            .is_ok());
+        assert!(Llcc68
+            .check_modulation_params(SpreadingFactor::_11, Bandwidth::_500KHz)
+            .is_ok());
+        assert_eq!(
+            Llcc68.check_modulation_params(SpreadingFactor::_10, Bandwidth::_125KHz),
+            Err(RadioError::UnavailableSpreadingFactor)
+        );
+        assert_eq!(
+            Llcc68.check_modulation_params(SpreadingFactor::_12, Bandwidth::_500KHz),
+            Err(RadioError::UnavailableSpreadingFactor)
+        );
+        assert_eq!(
+            Llcc68.check_modulation_params(SpreadingFactor::_7, Bandwidth::_62KHz),
+            Err(RadioError::UnavailableBandwidth)
+        );
+        assert!(Sx1262
+            .check_modulation_params(SpreadingFactor::_12, Bandwidth::_62KHz)
+            .is_ok());
+    }
+
+    #[test]
+    fn image_calibration_bands() {
+        assert_eq!(Sx1262.image_calibration_band(868_100_000), [0xD7, 0xDB]);
+        assert_eq!(Sx1268.image_calibration_band(470_300_000), [0x75, 0x81]);
+        assert_eq!(Sx1268.image_calibration_band(433_175_000), [0x6B, 0x6F]);
+        assert_eq!(Sx1268.image_calibration_band(412_000_000), [0x66, 0x6A]);
+    }
+
+    #[test]
+    fn pa_config() {
+        let pa_config = Sx1262.pa_config(30);
+        assert_eq!(
+            (pa_config.pa_duty_cycle, pa_config.hp_max, pa_config.tx_power),
+            (0x04, 0x07, 22)
+        );
+        let pa_config = Llcc68.pa_config(17);
+        assert_eq!(
+            (pa_config.pa_duty_cycle, pa_config.hp_max, pa_config.tx_power),
+            (0x02, 0x03, 22)
+        );
+        let pa_config = Sx1261.pa_config(-20);
+        assert_eq!(pa_config.device_sel, DeviceSel::LowPowerPA);
+        assert_eq!((pa_config.pa_duty_cycle, pa_config.tx_power), (0x01, -14));
+    }
+
+    #[test]
+    fn device_errors_decoding() {
+        assert!(!device_errors(0x0000).any());
+        let errors = device_errors(0x0140);
+        assert!(errors.pll_lock && errors.pa_ramp);
+        assert_eq!(
+            DeviceErrors {
+                pll_lock: false,
+                pa_ramp: false,
+                ..errors
+            },
+            DeviceErrors::default()
+        );
+    }
+
+    #[test]
+    fn tx_and_single_rx_commands() {
+        const TRANSACTIONS: &[Transaction] = &[
+            // SetTx with a 250 ms timeout
+            write(&[0x83, 0x00, 0x3E, 0x80]),
+            // SetStopRxTimerOnPreamble, SetLoRaSymbNumTimeout (8 symbols) and SynchTimeout
+            write(&[0x9F, 0x01]),
+            write(&[0xA0, 0x08]),
+            write(&[0x0D, 0x07, 0x06, 0x20]),
+            // RxGain (power saving) and SetRx without timer
+            write(&[0x0D, 0x08, 0xAC, 0x94]),
+            write(&[0x82, 0x00, 0x00, 0x00]),
+        ];
+        let mut spi = MockSpi::new(TRANSACTIONS);
+        let config = Config {
+            chip: Sx1262,
+            tcxo_ctrl: None,
+            use_dcdc: false,
+            rx_boost: false,
+        };
+        let mut radio = Sx126x::new(&mut spi, MockIv, config);
+        block_on(radio.do_tx(250)).unwrap();
+        block_on(radio.do_rx(RxMode::Single(8))).unwrap();
+        spi.assert_done();
+    }
+
+    #[test]
+    fn lora_packet_status() {
+        const TRANSACTIONS: &[Transaction] = &[
+            // SetModulationParams: SF7, 125 kHz, 4/5, no low data rate optimization
+            write(&[0x8B, 0x07, 0x04, 0x01, 0x00]),
+            // TxModulation read and update for a bandwidth below 500 kHz
+            transfer(&[0x1D, 0x08, 0x89, 0x00], &[0x00]),
+            write(&[0x0D, 0x08, 0x89, 0x04]),
+            // GetPacketStatus: RssiPkt, SnrPkt and SignalRssiPkt
+            transfer(&[0x14], &[0x24, 0xA0, 0x1C, 0xA8]),
+            // Frequency error estimation: -8259 steps
+            transfer(&[0x1D, 0x07, 0x6B, 0x00], &[0x0F, 0xDF, 0xBD]),
+        ];
+        let mut spi = MockSpi::new(TRANSACTIONS);
+        let config = Config {
+            chip: Sx1262,
+            tcxo_ctrl: None,
+            use_dcdc: false,
+            rx_boost: false,
+        };
+        let mut radio = Sx126x::new(&mut spi, MockIv, config);
+        let mdltn_params = radio
+            .create_modulation_params(SpreadingFactor::_7, Bandwidth::_125KHz, CodingRate::_4_5, 868_100_000)
+            .unwrap();
+        block_on(radio.set_modulation_params(&mdltn_params)).unwrap();
+        let status = block_on(radio.get_rx_packet_status()).unwrap();
+        assert_eq!((status.rssi, status.snr, status.signal_rssi), (-80, 7, -84));
+        assert_eq!(status.frequency_error, Some(-1000));
+        spi.assert_done();
+    }
 }
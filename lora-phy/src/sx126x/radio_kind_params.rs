@@ -50,6 +50,9 @@ pub enum Register {
     /// Number of symbols given as SX126X_REG_LR_SYNCH_TIMEOUT[7:3] * 2 ^ (2*SX126X_REG_LR_SYNCH_TIMEOUT[2:0] + 1)
     /// Info from SDK (not present in user manual).
     SynchTimeout = 0x0706,
+    WhiteningInit = 0x06B8,         // GFSK whitening initial value (9 bits)
+    CrcInit = 0x06BC,               // GFSK CRC initial value
+    CrcPolynomial = 0x06BE,         // GFSK CRC polynomial
     Syncword = 0x06C0,              // Syncword values
     LoRaSyncword = 0x0740,          // LoRa Syncword value
     GeneratedRandomNumber = 0x0819, //32-bit generated random number
@@ -66,6 +69,7 @@ pub enum Register {
     TxClampCfg = 0x08D8,   // better resistance to antenna mismatch (see DS_SX1261-2_V1.2 datasheet chapter 15.2)
     RTCCtrl = 0x0902,      // RTC control
     EvtClr = 0x0944,       // event clear
+    FreqError = 0x076B,    // LoRa frequency error estimation (20 bits), valid after a reception
 }
 
 impl Register {
@@ -276,6 +280,9 @@ pub fn bandwidth_value(bandwidth: Bandwidth) -> Result<u8, RadioError> {
         Bandwidth::_125KHz => Ok(0x04),
         Bandwidth::_250KHz => Ok(0x05),
         Bandwidth::_500KHz => Ok(0x06),
+        Bandwidth::_203KHz | Bandwidth::_406KHz | Bandwidth::_812KHz | Bandwidth::_1625KHz => {
+            Err(RadioError::UnavailableBandwidth)
+        }
     }
 }
 
@@ -288,6 +295,50 @@ pub fn coding_rate_value(coding_rate: CodingRate) -> Result<u8, RadioError> {
     }
 }
 
+pub fn pulse_shape_value(pulse_shape: PulseShape) -> u8 {
+    match pulse_shape {
+        PulseShape::None => 0x00,
+        PulseShape::Bt0_3 => 0x08,
+        PulseShape::Bt0_5 => 0x09,
+        PulseShape::Bt0_7 => 0x0A,
+        PulseShape::Bt1_0 => 0x0B,
+    }
+}
+
+// GFSK receiver bandwidths (double-sideband, Hz) in ascending order
+const FSK_BANDWIDTHS: [(u32, u8); 21] = [
+    (4_800, 0x1F),
+    (5_800, 0x17),
+    (7_300, 0x0F),
+    (9_700, 0x1E),
+    (11_700, 0x16),
+    (14_600, 0x0E),
+    (19_500, 0x1D),
+    (23_400, 0x15),
+    (29_300, 0x0D),
+    (39_000, 0x1C),
+    (46_900, 0x14),
+    (58_600, 0x0C),
+    (78_200, 0x1B),
+    (93_800, 0x13),
+    (117_300, 0x0B),
+    (156_200, 0x1A),
+    (187_200, 0x12),
+    (234_300, 0x0A),
+    (312_000, 0x19),
+    (373_600, 0x11),
+    (467_000, 0x09),
+];
+
+/// Smallest GFSK receiver bandwidth covering the given single-sideband bandwidth
+pub fn fsk_bandwidth_value(rx_bandwidth: u32) -> Result<u8, RadioError> {
+    FSK_BANDWIDTHS
+        .iter()
+        .find(|(bw, _)| *bw >= rx_bandwidth.saturating_mul(2))
+        .map(|(_, val)| *val)
+        .ok_or(RadioError::UnavailableBandwidth)
+}
+
 #[derive(Clone, Copy)]
 pub enum CADSymbols {
     _1 = 0x00,
@@ -302,3 +353,25 @@ impl CADSymbols {
         self as u8
     }
 }
+
+// Decode the OpError bits of GetDeviceErrors
+pub fn device_errors(op_error: u16) -> DeviceErrors {
+    DeviceErrors {
+        lf_rc_calibration: op_error & 0x0001 != 0, // RC64K_CALIB_ERR
+        hf_rc_calibration: op_error & 0x0002 != 0, // RC13M_CALIB_ERR
+        pll_calibration: op_error & 0x0004 != 0,
+        adc_calibration: op_error & 0x0008 != 0,
+        image_calibration: op_error & 0x0010 != 0,
+        xosc_start: op_error & 0x0020 != 0,
+        pll_lock: op_error & 0x0040 != 0,
+        pa_ramp: op_error & 0x0100 != 0,
+    }
+}
+
+// Frequency error (Hz) of the 20-bit two's complement frequency error estimation, scaled by
+// 1.55 Hz per step at a 1.6 MHz bandwidth (as SX126x::getFrequencyError of RadioLib, the register
+// is not documented in DS_SX1261-2_V2.1)
+pub fn frequency_error_value(freq_error: [u8; 3], bandwidth_in_hz: u32) -> i32 {
+    let raw = ((u32::from_be_bytes([0, freq_error[0], freq_error[1], freq_error[2]]) << 12) as i32) >> 12;
+    (raw as i64 * bandwidth_in_hz as i64 * 31 / 32_000_000) as i32
+}
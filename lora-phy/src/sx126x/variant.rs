@@ -1,4 +1,55 @@
 use super::DeviceSel;
+use crate::mod_params::*;
+
+/// Power amplifier settings (SetPaConfig and SetTxParams) producing a given output power
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PaConfig {
+    /// paDutyCycle of SetPaConfig
+    pub pa_duty_cycle: u8,
+    /// hpMax of SetPaConfig
+    pub hp_max: u8,
+    /// deviceSel of SetPaConfig
+    pub device_sel: DeviceSel,
+    /// power of SetTxParams
+    pub tx_power: i8,
+}
+
+impl PaConfig {
+    /// Optimal settings for the output power (dBm) clamped to the power amplifier range,
+    /// from Table 13-21: PA Operating Modes with Optimal Settings
+    pub fn optimal(device_sel: DeviceSel, output_power: i32) -> Self {
+        let (pa_duty_cycle, hp_max, tx_power) = match device_sel {
+            DeviceSel::LowPowerPA => {
+                // For SX1261:
+                // if f < 400 MHz, paDutyCycle should not be higher than 0x04,
+                // if f > 400 Mhz, paDutyCycle should not be higher than 0x07.
+                match output_power.clamp(-17, 15) {
+                    15 => (0x06, 0x00, 14),
+                    txp @ 11..=14 => (0x04, 0x00, txp),
+                    // table indicates 10 dBm => txp = 13, therefore we add 3 to values below 10
+                    txp => (0x01, 0x00, txp + 3),
+                }
+            }
+            DeviceSel::HighPowerPA => match output_power.clamp(-9, 22) {
+                21..=22 => (0x04, 0x07, 22),
+                // table indicates 20 dBm => txp = 22, therefore we add 2 to this range
+                txp @ 18..=20 => (0x03, 0x05, txp + 2),
+                // table indicates 17 dBm => txp = 22, therefore we add 5 to this range
+                txp @ 15..=17 => (0x02, 0x03, txp + 5),
+                // table indicates 14 dBm => txp = 22, therefore we should add 8 to this range
+                // this however seems to be wrong when looking at the reference driver
+                // https://github.com/STMicroelectronics/STM32CubeWL/blob/139e8d28bcec6af78dec8b52a9b9f9057868cc2e/Middlewares/Third_Party/SubGHz_Phy/stm32_radio_driver/radio_driver.c#L675
+                txp => (0x02, 0x02, txp),
+            },
+        };
+        Self {
+            pa_duty_cycle,
+            hp_max,
+            device_sel,
+            tx_power: tx_power as i8,
+        }
+    }
+}
 
 /// Implement this trait on your custom variant or use provided impls
 pub trait Sx126xVariant {
@@ -9,6 +60,37 @@ pub trait Sx126xVariant {
     fn use_dio2_as_rfswitch(&self) -> bool {
         true
     }
+
+    /// Reject the spreading factor and bandwidth combinations the chip does not support
+    fn check_modulation_params(
+        &self,
+        _spreading_factor: SpreadingFactor,
+        _bandwidth: Bandwidth,
+    ) -> Result<(), RadioError> {
+        Ok(())
+    }
+
+    /// Image calibration band (CalibrateImage freq1 and freq2, in 4 MHz steps) of the frequency
+    fn image_calibration_band(&self, frequency_in_hz: u32) -> [u8; 2] {
+        if frequency_in_hz > 900_000_000 {
+            [0xE1, 0xE9]
+        } else if frequency_in_hz > 850_000_000 {
+            [0xD7, 0xDB]
+        } else if frequency_in_hz > 770_000_000 {
+            [0xC1, 0xC5]
+        } else if frequency_in_hz > 460_000_000 {
+            [0x75, 0x81]
+        } else if frequency_in_hz > 425_000_000 {
+            [0x6B, 0x6F]
+        } else {
+            [0x00, 0x00]
+        }
+    }
+
+    /// Power amplifier settings for the requested output power (dBm)
+    fn pa_config(&self, output_power: i32) -> PaConfig {
+        PaConfig::optimal(self.get_device_sel(), output_power)
+    }
 }
 
 /// Sx1261 uses only LowPowerPA
@@ -28,6 +110,54 @@ impl Sx126xVariant for Sx1262 {
     }
 }
 
+/// Sx1268 is the 410 to 810 MHz counterpart of the Sx1262, using only HighPowerPA
+pub struct Sx1268;
+
+impl Sx126xVariant for Sx1268 {
+    fn get_device_sel(&self) -> super::DeviceSel {
+        super::DeviceSel::HighPowerPA
+    }
+
+    fn image_calibration_band(&self, frequency_in_hz: u32) -> [u8; 2] {
+        if frequency_in_hz > 770_000_000 {
+            [0xC1, 0xC5] // 779 - 787 MHz
+        } else if frequency_in_hz > 460_000_000 {
+            [0x75, 0x81] // 470 - 510 MHz
+        } else if frequency_in_hz > 425_000_000 {
+            [0x6B, 0x6F] // 430 - 440 MHz
+        } else {
+            [0x66, 0x6A] // 410 - 425 MHz
+        }
+    }
+}
+
+/// Llcc68 uses only HighPowerPA and supports a subset of the Sx1262 spreading factors
+/// and bandwidths: up to SF9 at 125 kHz, SF10 at 250 kHz and SF11 at 500 kHz
+pub struct Llcc68;
+
+impl Sx126xVariant for Llcc68 {
+    fn get_device_sel(&self) -> super::DeviceSel {
+        super::DeviceSel::HighPowerPA
+    }
+
+    fn check_modulation_params(
+        &self,
+        spreading_factor: SpreadingFactor,
+        bandwidth: Bandwidth,
+    ) -> Result<(), RadioError> {
+        let max_spreading_factor = match bandwidth {
+            Bandwidth::_125KHz => SpreadingFactor::_9,
+            Bandwidth::_250KHz => SpreadingFactor::_10,
+            Bandwidth::_500KHz => SpreadingFactor::_11,
+            _ => return Err(RadioError::UnavailableBandwidth),
+        };
+        if spreading_factor.factor() > max_spreading_factor.factor() {
+            return Err(RadioError::UnavailableSpreadingFactor);
+        }
+        Ok(())
+    }
+}
+
 /// Stm32wl variant.
 pub struct Stm32wl {
     /// select which output to use. (Switching is not supported)
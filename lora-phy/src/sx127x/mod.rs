@@ -2,7 +2,7 @@ mod radio_kind_params;
 mod sx1272;
 pub use sx1272::Sx1272;
 mod sx1276;
-pub use sx1276::Sx1276;
+pub use sx1276::{Rfm95, Rfm96, Rfm97, Rfm98, Sx1276, Sx1276Family, Sx1277, Sx1278, Sx1279};
 
 use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::spi::*;
@@ -25,6 +25,13 @@ const SX1276_RSSI_OFFSET_LF: i16 = -164;
 const SX1276_RSSI_OFFSET_HF: i16 = -157;
 const SX1276_RF_MID_BAND_THRESH: u32 = 525_000_000;
 
+// Selects the low frequency band registers in RegOpMode (sx1276 family)
+const LOW_FREQUENCY_MODE_ON: u8 = 0x08;
+
+// FSK bitrate limits (bps)
+const SX127X_FSK_MIN_BITRATE: u32 = 1_200;
+const SX127X_FSK_MAX_BITRATE: u32 = 300_000;
+
 // Frequency synthesizer step for frequency calculation (Hz)
 // FXOSC (32 MHz) * 1000000 (Hz/MHz) / 524288 (2^19)
 const SCALE: u32 = 8;
@@ -57,7 +64,8 @@ pub struct Config<C: Sx127xVariant> {
     pub chip: C,
     /// Whether board is using crystal oscillator or external clock
     pub tcxo_used: bool,
-    /// Whether to use PA_BOOST for transmit instead of RFO (sx1272) or RFO_LF (sx1276).
+    /// Whether to use PA_BOOST for transmit instead of RFO (sx1272) or RFO_LF/RFO_HF depending on
+    /// the band (sx1276 family).
     /// NB! Depends on board layout.
     pub tx_boost: bool,
     /// Whether to boost receive
@@ -69,6 +77,14 @@ pub struct Sx127x<SPI, IV, C: Sx127xVariant + Sized> {
     intf: SpiInterface<SPI, IV>,
     config: Config<C>,
     data: C::Data,
+    fsk: bool,
+    fsk_variable_length: bool,
+    low_frequency_mode: bool,
+    // LoRa bandwidth of the current modulation parameters, scaling the frequency error
+    bandwidth_in_hz: u32,
+    // LoRa IRQ flags read last, counted into the packet statistics once cleared
+    irq_flags: u8,
+    packet_stats: PacketStats,
 }
 
 impl<SPI, IV, C> Sx127x<SPI, IV, C>
@@ -84,6 +100,12 @@ where
             intf,
             config,
             data: Default::default(),
+            fsk: false,
+            fsk_variable_length: false,
+            low_frequency_mode: false,
+            bandwidth_in_hz: 0,
+            irq_flags: 0,
+            packet_stats: PacketStats::default(),
         }
     }
 
@@ -100,6 +122,77 @@ where
         Ok(read_buffer[0])
     }
 
+    async fn write_fsk_register(&mut self, register: FskRegister, value: u8) -> Result<(), RadioError> {
+        let write_buffer = [register.write_addr(), value];
+        self.intf.write(&write_buffer, false).await
+    }
+
+    async fn read_fsk_register(&mut self, register: FskRegister) -> Result<u8, RadioError> {
+        let write_buffer = [register.read_addr()];
+        let mut read_buffer = [0x00u8];
+        self.intf.read(&write_buffer, &mut read_buffer).await?;
+        Ok(read_buffer[0])
+    }
+
+    // RegOpMode value of a mode, selecting the registers of the current frequency band
+    fn op_mode_value(&self, mode: u8) -> u8 {
+        if self.low_frequency_mode {
+            mode | LOW_FREQUENCY_MODE_ON
+        } else {
+            mode
+        }
+    }
+
+    async fn write_op_mode(&mut self, mode: u8) -> Result<(), RadioError> {
+        self.write_register(Register::RegOpMode, self.op_mode_value(mode)).await
+    }
+
+    // Write the operating mode, keeping the current modem
+    async fn set_mode(&mut self, lora_mode: LoRaMode, fsk_mode: FskMode) -> Result<(), RadioError> {
+        let mode = if self.fsk { fsk_mode.value() } else { lora_mode.value() };
+        self.write_op_mode(mode).await
+    }
+
+    fn check_frequency(frequency_in_hz: u32) -> Result<(), RadioError> {
+        if C::frequency_range().contains(&frequency_in_hz) {
+            Ok(())
+        } else {
+            Err(RadioError::InvalidConfiguration)
+        }
+    }
+
+    async fn get_fsk_irq_state(&mut self, radio_mode: RadioMode) -> Result<Option<IrqState>, RadioError> {
+        let irq_flags_1 = self.read_fsk_register(FskRegister::RegIrqFlags1).await?;
+        let irq_flags_2 = self.read_fsk_register(FskRegister::RegIrqFlags2).await?;
+        match radio_mode {
+            RadioMode::Transmit => {
+                if FskIrqFlags2::PacketSent.is_set_in(irq_flags_2) {
+                    debug!("PacketSent in radio mode {}", radio_mode);
+                    return Ok(Some(IrqState::Done));
+                }
+            }
+            RadioMode::Receive(_) => {
+                if FskIrqFlags2::PayloadReady.is_set_in(irq_flags_2) {
+                    debug!("PayloadReady in radio mode {}", radio_mode);
+                    return Ok(Some(IrqState::Done));
+                }
+                if FskIrqFlags1::Timeout.is_set_in(irq_flags_1) {
+                    debug!("Timeout in radio mode {}", radio_mode);
+                    return Err(RadioError::ReceiveTimeout);
+                }
+                if FskIrqFlags1::PreambleDetect.is_set_in(irq_flags_1)
+                    || FskIrqFlags1::SyncAddressMatch.is_set_in(irq_flags_1)
+                {
+                    return Ok(Some(IrqState::PreambleReceived));
+                }
+            }
+            _ => {
+                warn!("FSK IRQ in radio mode {}?", radio_mode);
+            }
+        }
+        Ok(None)
+    }
+
     async fn read_buffer(&mut self, register: Register, buf: &mut [u8]) -> Result<(), RadioError> {
         self.intf.read(&[register.read_addr()], buf).await
     }
@@ -133,6 +226,14 @@ where
     C: Sx127xVariant,
 {
     async fn init_lora(&mut self, sync_word: u8) -> Result<(), RadioError> {
+        if self.fsk {
+            // The modem can only be switched in sleep mode
+            self.write_op_mode(FskMode::Sleep.value()).await?;
+            self.write_op_mode(LoRaMode::Sleep.value()).await?;
+            self.write_op_mode(LoRaMode::Standby.value()).await?;
+            self.fsk = false;
+        }
+
         if self.config.tcxo_used {
             self.write_register(C::reg_txco(), TCXO_FOR_OSCILLATOR).await?;
         }
@@ -146,6 +247,27 @@ where
         Ok(())
     }
 
+    async fn init_fsk(&mut self) -> Result<(), RadioError> {
+        if !self.fsk {
+            // The modem can only be switched in sleep mode
+            self.write_op_mode(LoRaMode::Sleep.value()).await?;
+            self.write_op_mode(FskMode::Sleep.value()).await?;
+            self.write_op_mode(FskMode::Standby.value()).await?;
+            self.fsk = true;
+        }
+
+        // AFC and AGC on, receiver triggered by preamble detection
+        self.write_fsk_register(FskRegister::RegRxConfig, 0x1e).await?;
+        // Preamble detector on, 2 bytes, 10 chips tolerance
+        self.write_fsk_register(FskRegister::RegPreambleDetect, 0xaa).await?;
+        self.write_fsk_register(FskRegister::RegRxTimeout1, 0x00).await?;
+        self.write_fsk_register(FskRegister::RegRxTimeout3, 0x00).await?;
+        // Packet mode
+        self.write_fsk_register(FskRegister::RegPacketConfig2, 0x40).await?;
+        // Start transmitting as soon as the FIFO is not empty
+        self.write_fsk_register(FskRegister::RegFifoThresh, 0x8f).await
+    }
+
     fn create_modulation_params(
         &self,
         spreading_factor: SpreadingFactor,
@@ -155,8 +277,12 @@ where
     ) -> Result<ModulationParams, RadioError> {
         // Parameter validation
         spreading_factor_value(spreading_factor)?;
+        if spreading_factor.factor() > C::max_spreading_factor().factor() {
+            return Err(RadioError::UnavailableSpreadingFactor);
+        }
         coding_rate_value(coding_rate)?;
         C::bandwidth_value(bandwidth)?;
+        Self::check_frequency(frequency_in_hz)?;
         if ((bandwidth == Bandwidth::_250KHz) || (bandwidth == Bandwidth::_500KHz)) && (frequency_in_hz < 400_000_000) {
             return Err(RadioError::InvalidBandwidthForFrequency);
         }
@@ -201,8 +327,53 @@ where
         })
     }
 
+    fn create_fsk_modulation_params(
+        &self,
+        bitrate: u32,
+        fdev: u32,
+        pulse_shape: PulseShape,
+        rx_bandwidth: u32,
+        frequency_in_hz: u32,
+    ) -> Result<FskModulationParams, RadioError> {
+        // Parameter validation
+        if !(SX127X_FSK_MIN_BITRATE..=SX127X_FSK_MAX_BITRATE).contains(&bitrate) {
+            return Err(RadioError::InvalidConfiguration);
+        }
+        pulse_shape_value(pulse_shape)?;
+        fsk_bandwidth_value(rx_bandwidth)?;
+        Self::check_frequency(frequency_in_hz)?;
+
+        Ok(FskModulationParams {
+            bitrate,
+            fdev,
+            pulse_shape,
+            rx_bandwidth,
+            frequency_in_hz,
+        })
+    }
+
+    fn create_fsk_packet_params(
+        &self,
+        preamble_length: u16,
+        sync_word: &[u8],
+        variable_length: bool,
+        payload_length: u8,
+        crc_on: bool,
+        whitening: bool,
+    ) -> Result<FskPacketParams, RadioError> {
+        FskPacketParams::new(
+            preamble_length,
+            sync_word,
+            variable_length,
+            payload_length,
+            crc_on,
+            whitening,
+        )
+    }
+
     async fn reset(&mut self, delay: &mut impl DelayNs) -> Result<(), RadioError> {
         self.intf.iv.reset(delay).await?;
+        self.fsk = false;
         self.set_sleep(false, delay).await?; // ensure sleep mode is entered so that the LoRa mode bit is set
         Ok(())
     }
@@ -212,15 +383,19 @@ where
     }
 
     async fn set_standby(&mut self) -> Result<(), RadioError> {
-        self.write_register(Register::RegOpMode, LoRaMode::Standby.value())
-            .await?;
+        self.set_mode(LoRaMode::Standby, FskMode::Standby).await?;
         self.intf.iv.disable_rf_switch().await
     }
 
     async fn set_sleep(&mut self, _warm_start_if_possible: bool, _delay: &mut impl DelayNs) -> Result<(), RadioError> {
         // Warm start is unavailable for sx127x
         self.intf.iv.disable_rf_switch().await?;
-        let buf = [Register::RegOpMode.write_addr(), LoRaMode::Sleep.value()];
+        let mode = if self.fsk {
+            FskMode::Sleep.value()
+        } else {
+            LoRaMode::Sleep.value()
+        };
+        let buf = [Register::RegOpMode.write_addr(), self.op_mode_value(mode)];
         // NB! Switching to sleep mode is "sleep" command...
         self.intf.write(&buf, true).await?;
 
@@ -261,7 +436,11 @@ where
             false => RampTime::Ramp250Us, // for instance, on initialization
         };
 
-        let val = C::ramp_value(ramp_time);
+        let mut val = C::ramp_value(ramp_time);
+        if self.fsk {
+            // Keep the FSK modulation shaping
+            val |= self.read_register(Register::RegPaRamp).await? & 0x60;
+        }
         self.write_register(Register::RegPaRamp, val).await
     }
 
@@ -273,6 +452,7 @@ where
             "sf = {}, bw = {}, cr_denom = {}",
             sf_val, bw_val, coding_rate_denominator_val
         );
+        self.bandwidth_in_hz = u32::from(mdltn_params.bandwidth);
         // Configure LoRa optimization (0x31) and detection threshold registers (0x37)
         let (opt, thr) = match mdltn_params.spreading_factor {
             SpreadingFactor::_6 => (0x05, 0x0c),
@@ -324,14 +504,74 @@ where
         Ok(())
     }
 
+    async fn set_fsk_modulation_params(&mut self, mdltn_params: &FskModulationParams) -> Result<(), RadioError> {
+        let bitrate = 32_000_000 / mdltn_params.bitrate;
+        // Fdev = Fstep * value, with Fstep = FXOSC / 2^19
+        let fdev = ((mdltn_params.fdev as u64 * (1 << 19) + 16_000_000) / 32_000_000) as u32;
+        let bandwidth_val = fsk_bandwidth_value(mdltn_params.rx_bandwidth)?;
+        debug!(
+            "bitrate = {}, fdev = {}, bw = {}",
+            mdltn_params.bitrate, mdltn_params.fdev, bandwidth_val
+        );
+        self.write_fsk_register(FskRegister::RegBitrateMsb, (bitrate >> 8) as u8)
+            .await?;
+        self.write_fsk_register(FskRegister::RegBitrateLsb, bitrate as u8)
+            .await?;
+        self.write_fsk_register(FskRegister::RegFdevMsb, ((fdev >> 8) & 0x3f) as u8)
+            .await?;
+        self.write_fsk_register(FskRegister::RegFdevLsb, fdev as u8).await?;
+        self.write_fsk_register(FskRegister::RegRxBw, bandwidth_val).await?;
+        self.write_fsk_register(FskRegister::RegAfcBw, bandwidth_val).await?;
+
+        let pa_ramp = self.read_register(Register::RegPaRamp).await?;
+        let shaping = pulse_shape_value(mdltn_params.pulse_shape)?;
+        self.write_register(Register::RegPaRamp, (pa_ramp & 0x9f) | shaping)
+            .await
+    }
+
+    async fn set_fsk_packet_params(&mut self, pkt_params: &FskPacketParams) -> Result<(), RadioError> {
+        self.write_fsk_register(FskRegister::RegPreambleMsb, (pkt_params.preamble_length >> 8) as u8)
+            .await?;
+        self.write_fsk_register(FskRegister::RegPreambleLsb, pkt_params.preamble_length as u8)
+            .await?;
+
+        // Restart the receiver without waiting for the PLL after a packet
+        let mut sync_config = 0x40u8;
+        if pkt_params.sync_word_length > 0 {
+            sync_config |= 0x10 | (pkt_params.sync_word_length - 1);
+            let sync_word = &pkt_params.sync_word[..pkt_params.sync_word_length as usize];
+            self.intf
+                .write_with_payload(&[FskRegister::RegSyncValue1.write_addr()], sync_word, false)
+                .await?;
+        }
+        self.write_fsk_register(FskRegister::RegSyncConfig, sync_config).await?;
+
+        // PacketFormat [7], DcFree [6:5] (whitening) and CrcOn [4], with a CCITT CRC
+        let packet_config_1 = ((pkt_params.variable_length as u8) << 7)
+            | ((pkt_params.whitening as u8) << 6)
+            | ((pkt_params.crc_on as u8) << 4);
+        self.write_fsk_register(FskRegister::RegPacketConfig1, packet_config_1)
+            .await?;
+        self.write_fsk_register(FskRegister::RegPayloadLength, pkt_params.payload_length)
+            .await?;
+        self.fsk_variable_length = pkt_params.variable_length;
+        Ok(())
+    }
+
     // Calibrate the image rejection based on the given frequency
     async fn calibrate_image(&mut self, _frequency_in_hz: u32) -> Result<(), RadioError> {
         // An automatic process, but can set bit ImageCalStart in RegImageCal, when the device is in Standby mode.
         Ok(())
     }
 
+    fn image_calibration_band(&self, frequency_in_hz: u32) -> u32 {
+        C::low_frequency_mode(frequency_in_hz) as u32
+    }
+
     async fn set_channel(&mut self, frequency_in_hz: u32) -> Result<(), RadioError> {
         debug!("channel = {}", frequency_in_hz);
+        Self::check_frequency(frequency_in_hz)?;
+        self.low_frequency_mode = C::low_frequency_mode(frequency_in_hz);
         let frf = freq_to_pll_step(frequency_in_hz);
         self.write_register(Register::RegFrfMsb, ((frf & 0x00FF0000) >> 16) as u8)
             .await?;
@@ -341,6 +581,12 @@ where
     }
 
     async fn set_payload(&mut self, payload: &[u8]) -> Result<(), RadioError> {
+        if self.fsk {
+            if self.fsk_variable_length {
+                self.write_register(Register::RegFifo, payload.len() as u8).await?;
+            }
+            return self.write_buffer(Register::RegFifo, payload).await;
+        }
         self.write_register(Register::RegFifoAddrPtr, 0x00u8).await?;
         self.write_register(Register::RegPayloadLength, 0x00u8).await?;
         self.write_buffer(Register::RegFifo, payload).await?;
@@ -348,13 +594,32 @@ where
             .await
     }
 
-    async fn do_tx(&mut self) -> Result<(), RadioError> {
+    async fn do_tx(&mut self, _timeout_in_ms: u32) -> Result<(), RadioError> {
+        // There is no TX timer, the LoRa TX watchdog covers the timeout
         self.intf.iv.enable_rf_switch_tx().await?;
 
-        self.write_register(Register::RegOpMode, LoRaMode::Tx.value()).await
+        self.set_mode(LoRaMode::Tx, FskMode::Tx).await
     }
 
     async fn do_rx(&mut self, rx_mode: RxMode) -> Result<(), RadioError> {
+        if self.fsk {
+            // Preamble timeout in units of 16 bits, 0 for none
+            let timeout = match rx_mode {
+                RxMode::DutyCycle(_) => return Err(RadioError::DutyCycleUnsupported),
+                RxMode::Single(bits) => bits.div_ceil(16).clamp(1, 255) as u8,
+                RxMode::Continuous => 0,
+            };
+            self.intf.iv.enable_rf_switch_rx().await?;
+            self.write_fsk_register(FskRegister::RegRxTimeout2, timeout).await?;
+            let lna_gain = if self.config.rx_boost {
+                LnaGain::G1.boosted_value()
+            } else {
+                LnaGain::G1.value()
+            };
+            self.write_register(Register::RegLna, lna_gain).await?;
+            return self.write_op_mode(FskMode::Rx.value()).await;
+        }
+
         let (num_symbols, mode) = match rx_mode {
             RxMode::DutyCycle(_) => Err(RadioError::DutyCycleUnsupported),
             RxMode::Single(ns) => Ok((ns.max(SX127X_MIN_LORA_SYMB_NUM_TIMEOUT), LoRaMode::RxSingle)),
@@ -374,7 +639,7 @@ where
 
         self.write_register(Register::RegFifoAddrPtr, 0x00u8).await?;
 
-        self.write_register(Register::RegOpMode, mode.value()).await
+        self.write_op_mode(mode.value()).await
     }
 
     async fn get_rx_payload(
@@ -402,7 +667,39 @@ where
         Ok(payload_length)
     }
 
+    async fn get_fsk_rx_payload(
+        &mut self,
+        rx_pkt_params: &FskPacketParams,
+        receiving_buffer: &mut [u8],
+    ) -> Result<u8, RadioError> {
+        let payload_length = if rx_pkt_params.variable_length {
+            // The length byte is the first byte of the FIFO
+            self.read_register(Register::RegFifo).await?
+        } else {
+            rx_pkt_params.payload_length
+        };
+        if (payload_length as usize) > receiving_buffer.len() {
+            return Err(RadioError::PayloadSizeMismatch(
+                payload_length as usize,
+                receiving_buffer.len(),
+            ));
+        }
+        self.read_buffer(Register::RegFifo, &mut receiving_buffer[0..payload_length as usize])
+            .await?;
+        Ok(payload_length)
+    }
+
     async fn get_rx_packet_status(&mut self) -> Result<PacketStatus, RadioError> {
+        if self.fsk {
+            // There is no SNR for FSK
+            let rssi = self.get_rssi().await?;
+            return Ok(PacketStatus {
+                rssi,
+                snr: 0,
+                signal_rssi: rssi,
+                frequency_error: None,
+            });
+        }
         let snr = {
             let packet_snr = self.read_register(Register::RegPktSnrValue).await?;
             packet_snr as i8 as i16 / 4
@@ -420,10 +717,43 @@ where
             }
         };
 
-        Ok(PacketStatus { rssi, snr })
+        let freq_error = [
+            self.read_register(Register::RegFreqErrorMsb).await?,
+            self.read_register(Register::RegFreqErrorMid).await?,
+            self.read_register(Register::RegFreqErrorLsb).await?,
+        ];
+        let frequency_error = frequency_error_value(freq_error, self.bandwidth_in_hz);
+
+        Ok(PacketStatus {
+            rssi,
+            snr,
+            signal_rssi: rssi,
+            frequency_error: Some(frequency_error),
+        })
+    }
+
+    async fn get_packet_stats(&mut self) -> Result<PacketStats, RadioError> {
+        Ok(self.packet_stats)
+    }
+
+    async fn reset_packet_stats(&mut self) -> Result<(), RadioError> {
+        self.packet_stats = PacketStats::default();
+        Ok(())
+    }
+
+    async fn get_device_errors(&mut self) -> Result<DeviceErrors, RadioError> {
+        Ok(DeviceErrors::default())
+    }
+
+    async fn clear_device_errors(&mut self) -> Result<(), RadioError> {
+        Ok(())
     }
 
     async fn get_rssi(&mut self) -> Result<i16, RadioError> {
+        if self.fsk {
+            let rssi_value = self.read_fsk_register(FskRegister::RegRssiValue).await?;
+            return Ok(-(rssi_value as i16) / 2);
+        }
         let rssi_value = self.read_register(Register::RegRssiValue).await?;
         let rssi_offset = C::rssi_offset(self).await?;
         Ok(rssi_offset + rssi_value as i16)
@@ -438,13 +768,20 @@ where
         }
         self.write_register(Register::RegLna, lna_gain_final).await?;
 
-        self.write_register(Register::RegOpMode, LoRaMode::Cad.value()).await
+        self.write_op_mode(LoRaMode::Cad.value()).await
     }
 
     // Set the IRQ mask to disable unwanted interrupts,
     // enable interrupts on DIO pins (sx127x has multiple),
     // and allow interrupts.
     async fn set_irq_params(&mut self, radio_mode: Option<RadioMode>) -> Result<(), RadioError> {
+        if self.fsk {
+            // DIO0 signals PacketSent when transmitting and PayloadReady when receiving
+            let dio_mapping_1 = self.read_register(Register::RegDioMapping1).await?;
+            self.write_register(Register::RegDioMapping1, dio_mapping_1 & DioMapping1Dio0::Mask.value())
+                .await?;
+            return self.clear_irq_status().await;
+        }
         match radio_mode {
             Some(RadioMode::Transmit) => {
                 self.write_register(
@@ -517,7 +854,11 @@ where
         radio_mode: RadioMode,
         cad_activity_detected: Option<&mut bool>,
     ) -> Result<Option<IrqState>, RadioError> {
+        if self.fsk {
+            return self.get_fsk_irq_state(radio_mode).await;
+        }
         let irq_flags = self.read_register(Register::RegIrqFlags).await?;
+        self.irq_flags = irq_flags;
         match radio_mode {
             RadioMode::Transmit => {
                 if (irq_flags & IrqMask::TxDone.value()) == IrqMask::TxDone.value() {
@@ -564,6 +905,17 @@ where
     }
 
     async fn clear_irq_status(&mut self) -> Result<(), RadioError> {
+        if self.fsk {
+            // Only the flags which are not cleared by the packet engine itself are writable
+            self.write_fsk_register(FskRegister::RegIrqFlags1, 0xffu8).await?;
+            return self.write_fsk_register(FskRegister::RegIrqFlags2, 0xffu8).await;
+        }
+        self.packet_stats.count(
+            IrqMask::RxDone.is_set_in(self.irq_flags),
+            IrqMask::CRCError.is_set_in(self.irq_flags),
+            false, // the LoRa modem does not flag header errors
+        );
+        self.irq_flags = 0;
         self.write_register(Register::RegIrqFlags, 0xffu8).await // clear all interrupts
     }
 
@@ -594,6 +946,8 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::blocking::block_on;
+    use crate::mock::{write, MockIv, MockSpi, Transaction};
 
     // FXOSC[32 MHz] * 1000000 (Hz/MHz) / 524288 (2^19)
     const FREQUENCY_SYNTHESIZER_STEP: f64 = 61.03515625;
@@ -624,4 +978,75 @@ mod tests {
             }
         }
     }
+
+    fn config<C: Sx127xVariant>(chip: C) -> Config<C> {
+        Config {
+            chip,
+            tcxo_used: false,
+            tx_boost: false,
+            rx_boost: false,
+        }
+    }
+
+    #[test]
+    fn variant_limits() {
+        let mut spi = MockSpi::new(&[]);
+        {
+            let radio = Sx127x::new(&mut spi, MockIv, config(Sx1278));
+            let create = |sf, freq| radio.create_modulation_params(sf, Bandwidth::_125KHz, CodingRate::_4_5, freq);
+            assert!(create(SpreadingFactor::_12, 433_175_000).is_ok());
+            assert!(create(SpreadingFactor::_12, 868_100_000).is_err());
+        }
+        {
+            let radio = Sx127x::new(&mut spi, MockIv, config(Sx1277));
+            let create = |sf| radio.create_modulation_params(sf, Bandwidth::_125KHz, CodingRate::_4_5, 868_100_000);
+            assert!(create(SpreadingFactor::_9).is_ok());
+            assert_eq!(
+                create(SpreadingFactor::_10).err(),
+                Some(RadioError::UnavailableSpreadingFactor)
+            );
+        }
+        assert!(Sx1279::frequency_range().contains(&915_000_000));
+        assert!(!Sx1279::frequency_range().contains(&1_000_000_000));
+        assert!(!Sx1272::frequency_range().contains(&433_175_000));
+        assert!(!Rfm96::frequency_range().contains(&868_100_000));
+        assert_eq!(Rfm97::max_spreading_factor(), SpreadingFactor::_9);
+        spi.assert_done();
+    }
+
+    #[test]
+    fn low_frequency_mode() {
+        const TRANSACTIONS: &[Transaction] = &[
+            // RegFrf (433.175 MHz)
+            write(&[0x86, 0x6C]),
+            write(&[0x87, 0x4B]),
+            write(&[0x88, 0x00]),
+            // RegOpMode: LoRa standby with LowFrequencyModeOn
+            write(&[0x81, 0x89]),
+            // RegFrf (868.1 MHz)
+            write(&[0x86, 0xD9]),
+            write(&[0x87, 0x06]),
+            write(&[0x88, 0x00]),
+            // RegOpMode: LoRa standby
+            write(&[0x81, 0x81]),
+        ];
+        let mut spi = MockSpi::new(TRANSACTIONS);
+        let mut radio = Sx127x::new(&mut spi, MockIv, config(Sx1276));
+        block_on(async {
+            radio.set_channel(433_175_000).await?;
+            radio.set_standby().await?;
+            radio.set_channel(868_100_000).await?;
+            radio.set_standby().await
+        })
+        .unwrap();
+        spi.assert_done();
+    }
+
+    #[test]
+    fn frequency_error() {
+        assert_eq!(frequency_error_value([0x00, 0x10, 0x00], 125_000), 536);
+        assert_eq!(frequency_error_value([0x0F, 0xF0, 0x00], 125_000), -536);
+        // Bits 7:4 of RegFreqErrorMsb are not part of the value
+        assert_eq!(frequency_error_value([0xF0, 0x10, 0x00], 125_000), 536);
+    }
 }
@@ -1,3 +1,5 @@
+use core::ops::RangeInclusive;
+
 use crate::mod_params::*;
 use crate::mod_traits::InterfaceVariant;
 use crate::sx127x::Sx127x;
@@ -48,6 +50,20 @@ pub trait Sx127xVariant {
     ) -> Result<(), RadioError>
     where
         Self: Sized;
+
+    /// Frequencies covered by the chip
+    fn frequency_range() -> RangeInclusive<u32> {
+        137_000_000..=1_020_000_000
+    }
+    /// Largest spreading factor supported by the chip
+    fn max_spreading_factor() -> SpreadingFactor {
+        SpreadingFactor::_12
+    }
+    /// Whether the frequency belongs to the low frequency band, whose registers are selected by
+    /// the LowFrequencyModeOn bit of RegOpMode
+    fn low_frequency_mode(_frequency_in_hz: u32) -> bool {
+        false
+    }
 }
 
 /// Internal sx127x LoRa modes (signified by most significant bit flag)
@@ -206,6 +222,132 @@ impl Register {
     }
 }
 
+/// Registers of the FSK/OOK mode, some of which share their address with a LoRa register
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+pub enum FskRegister {
+    RegBitrateMsb = 0x02,
+    RegBitrateLsb = 0x03,
+    RegFdevMsb = 0x04,
+    RegFdevLsb = 0x05,
+    RegRxConfig = 0x0d,
+    RegRssiValue = 0x11,
+    RegRxBw = 0x12,
+    RegAfcBw = 0x13,
+    RegPreambleDetect = 0x1f,
+    RegRxTimeout1 = 0x20,
+    RegRxTimeout2 = 0x21,
+    RegRxTimeout3 = 0x22,
+    RegPreambleMsb = 0x25,
+    RegPreambleLsb = 0x26,
+    RegSyncConfig = 0x27,
+    RegSyncValue1 = 0x28,
+    RegPacketConfig1 = 0x30,
+    RegPacketConfig2 = 0x31,
+    RegPayloadLength = 0x32,
+    RegFifoThresh = 0x35,
+    RegIrqFlags1 = 0x3e,
+    RegIrqFlags2 = 0x3f,
+}
+
+impl FskRegister {
+    pub fn read_addr(self) -> u8 {
+        (self as u8) & 0x7f
+    }
+    pub fn write_addr(self) -> u8 {
+        (self as u8) | 0x80
+    }
+}
+
+/// Internal sx127x FSK/OOK modes
+#[derive(Clone, Copy)]
+pub enum FskMode {
+    Sleep = 0x00,
+    Standby = 0x01,
+    Tx = 0x03,
+    Rx = 0x05,
+}
+
+impl FskMode {
+    /// Mode value, with FSK modulation
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+}
+
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+pub enum FskIrqFlags1 {
+    SyncAddressMatch = 0x01,
+    PreambleDetect = 0x02,
+    Timeout = 0x04,
+}
+
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+pub enum FskIrqFlags2 {
+    CrcOk = 0x02,
+    PayloadReady = 0x04,
+    PacketSent = 0x08,
+}
+
+impl FskIrqFlags1 {
+    pub fn is_set_in(self, flags: u8) -> bool {
+        (self as u8) & flags == self as u8
+    }
+}
+
+impl FskIrqFlags2 {
+    pub fn is_set_in(self, flags: u8) -> bool {
+        (self as u8) & flags == self as u8
+    }
+}
+
+pub fn pulse_shape_value(pulse_shape: PulseShape) -> Result<u8, RadioError> {
+    // ModulationShaping of RegPaRamp
+    match pulse_shape {
+        PulseShape::None => Ok(0b00 << 5),
+        PulseShape::Bt1_0 => Ok(0b01 << 5),
+        PulseShape::Bt0_5 => Ok(0b10 << 5),
+        PulseShape::Bt0_3 => Ok(0b11 << 5),
+        PulseShape::Bt0_7 => Err(RadioError::InvalidConfiguration),
+    }
+}
+
+// FSK receiver bandwidths (single-sideband, Hz) in ascending order, with their RxBwMant and RxBwExp
+const FSK_BANDWIDTHS: [(u32, u8); 21] = [
+    (2_604, 0x17),
+    (3_125, 0x0F),
+    (3_906, 0x07),
+    (5_208, 0x16),
+    (6_250, 0x0E),
+    (7_812, 0x06),
+    (10_417, 0x15),
+    (12_500, 0x0D),
+    (15_625, 0x05),
+    (20_833, 0x14),
+    (25_000, 0x0C),
+    (31_250, 0x04),
+    (41_667, 0x13),
+    (50_000, 0x0B),
+    (62_500, 0x03),
+    (83_333, 0x12),
+    (100_000, 0x0A),
+    (125_000, 0x02),
+    (166_667, 0x11),
+    (200_000, 0x09),
+    (250_000, 0x01),
+];
+
+/// Smallest FSK receiver bandwidth covering the given single-sideband bandwidth
+pub fn fsk_bandwidth_value(rx_bandwidth: u32) -> Result<u8, RadioError> {
+    FSK_BANDWIDTHS
+        .iter()
+        .find(|(bw, _)| *bw >= rx_bandwidth)
+        .map(|(_, val)| *val)
+        .ok_or(RadioError::UnavailableBandwidth)
+}
+
 #[derive(Clone, Copy)]
 #[allow(dead_code)]
 pub enum RampTime {
@@ -352,3 +494,10 @@ pub fn coding_rate_denominator_value(coding_rate: CodingRate) -> Result<u8, Radi
         CodingRate::_4_8 => Ok(0x08),
     }
 }
+
+// Frequency error (Hz) of the 20-bit two's complement RegFreqError value:
+// FreqError * 2^24 / Fxtal * BW / 500 kHz
+pub fn frequency_error_value(freq_error: [u8; 3], bandwidth_in_hz: u32) -> i32 {
+    let raw = ((u32::from_be_bytes([0, freq_error[0], freq_error[1], freq_error[2]]) << 12) as i32) >> 12;
+    ((raw as i64 * (1 << 24) * bandwidth_in_hz as i64) / (32_000_000 * 500_000)) as i32
+}
@@ -2,6 +2,7 @@ use crate::mod_params::{ModulationParams, PacketParams, RadioError};
 use crate::mod_traits::InterfaceVariant;
 use crate::sx127x::radio_kind_params::{coding_rate_value, spreading_factor_value, RampTime, Register, Sx127xVariant};
 use crate::sx127x::{Sx127x, SX1272_RSSI_OFFSET};
+use core::ops::RangeInclusive;
 use embedded_hal_async::spi::SpiDevice;
 use lora_modulation::Bandwidth;
 
@@ -128,4 +129,8 @@ impl Sx127xVariant for Sx1272 {
     ) -> Result<(), RadioError> {
         todo!()
     }
+
+    fn frequency_range() -> RangeInclusive<u32> {
+        860_000_000..=1_020_000_000
+    }
 }
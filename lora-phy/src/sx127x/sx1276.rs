@@ -6,19 +6,90 @@ use crate::sx127x::radio_kind_params::{
 use crate::sx127x::{
     pll_step_to_freq, Sx127x, SX1276_RF_MID_BAND_THRESH, SX1276_RSSI_OFFSET_HF, SX1276_RSSI_OFFSET_LF,
 };
+use core::ops::RangeInclusive;
 use embedded_hal_async::spi::SpiDevice;
-use lora_modulation::Bandwidth;
+use lora_modulation::{Bandwidth, SpreadingFactor};
 
-/// Sx1276 implements the Sx127xVariant trait
+/// Chips sharing the Sx1276 register map, which only differ by their frequency bands and
+/// spreading factors. Implementing it provides the Sx127xVariant trait.
+pub trait Sx1276Family {
+    /// Frequencies covered by the chip
+    const FREQUENCIES: RangeInclusive<u32>;
+    /// Largest spreading factor supported by the chip
+    const MAX_SPREADING_FACTOR: SpreadingFactor;
+}
+
+/// Sx1276 covers both the low (137 - 525 MHz) and high (862 - 1020 MHz) frequency bands
 pub struct Sx1276;
 
+impl Sx1276Family for Sx1276 {
+    const FREQUENCIES: RangeInclusive<u32> = 137_000_000..=1_020_000_000;
+    const MAX_SPREADING_FACTOR: SpreadingFactor = SpreadingFactor::_12;
+}
+
+/// Sx1277 is an Sx1276 limited to spreading factors 6 to 9
+pub struct Sx1277;
+
+impl Sx1276Family for Sx1277 {
+    const FREQUENCIES: RangeInclusive<u32> = 137_000_000..=1_020_000_000;
+    const MAX_SPREADING_FACTOR: SpreadingFactor = SpreadingFactor::_9;
+}
+
+/// Sx1278 only covers the low frequency band (137 - 525 MHz)
+pub struct Sx1278;
+
+impl Sx1276Family for Sx1278 {
+    const FREQUENCIES: RangeInclusive<u32> = 137_000_000..=525_000_000;
+    const MAX_SPREADING_FACTOR: SpreadingFactor = SpreadingFactor::_12;
+}
+
+/// Sx1279 covers 137 to 960 MHz
+pub struct Sx1279;
+
+impl Sx1276Family for Sx1279 {
+    const FREQUENCIES: RangeInclusive<u32> = 137_000_000..=960_000_000;
+    const MAX_SPREADING_FACTOR: SpreadingFactor = SpreadingFactor::_12;
+}
+
+/// HopeRF RFM95 module, an Sx1276 matched for the 868/915 MHz band
+pub struct Rfm95;
+
+impl Sx1276Family for Rfm95 {
+    const FREQUENCIES: RangeInclusive<u32> = 862_000_000..=1_020_000_000;
+    const MAX_SPREADING_FACTOR: SpreadingFactor = SpreadingFactor::_12;
+}
+
+/// HopeRF RFM96 module, an Sx1276 matched for the 433/470 MHz band
+pub struct Rfm96;
+
+impl Sx1276Family for Rfm96 {
+    const FREQUENCIES: RangeInclusive<u32> = 410_000_000..=525_000_000;
+    const MAX_SPREADING_FACTOR: SpreadingFactor = SpreadingFactor::_12;
+}
+
+/// HopeRF RFM97 module, an Sx1277 matched for the 868/915 MHz band
+pub struct Rfm97;
+
+impl Sx1276Family for Rfm97 {
+    const FREQUENCIES: RangeInclusive<u32> = 862_000_000..=1_020_000_000;
+    const MAX_SPREADING_FACTOR: SpreadingFactor = SpreadingFactor::_9;
+}
+
+/// HopeRF RFM98 module, an Sx1278 matched for the 433/470 MHz band
+pub struct Rfm98;
+
+impl Sx1276Family for Rfm98 {
+    const FREQUENCIES: RangeInclusive<u32> = 410_000_000..=525_000_000;
+    const MAX_SPREADING_FACTOR: SpreadingFactor = SpreadingFactor::_12;
+}
+
 #[derive(Default)]
 pub struct Sx1276Data {
     /// flag to indicate the Errata 2.1: Sensitivity optimization with 500 kHz bandwidth is required
     sensitivity_quirk: bool,
 }
 
-impl Sx127xVariant for Sx1276 {
+impl<T: Sx1276Family> Sx127xVariant for T {
     type Data = Sx1276Data;
 
     async fn init_lora<SPI: SpiDevice<u8>, IV: InterfaceVariant>(
@@ -46,6 +117,9 @@ impl Sx127xVariant for Sx1276 {
             Bandwidth::_125KHz => Ok(0x07),
             Bandwidth::_250KHz => Ok(0x08),
             Bandwidth::_500KHz => Ok(0x09),
+            Bandwidth::_203KHz | Bandwidth::_406KHz | Bandwidth::_812KHz | Bandwidth::_1625KHz => {
+                Err(RadioError::UnavailableBandwidth)
+            }
         }
     }
 
@@ -137,12 +211,12 @@ impl Sx127xVariant for Sx1276 {
             if mdltn_params.bandwidth == Bandwidth::_500KHz {
                 let val_1 = 0x02;
                 match mdltn_params.frequency_in_hz {
-                    862_000..=1_020_000 => {
+                    862_000_000..=1_020_000_000 => {
                         radio.write_register(Register::RegHighBwOptimize1, val_1).await?;
                         radio.write_register(Register::RegHighBwOptimize2, 0x64).await?;
                         return Ok(());
                     }
-                    410_000..=525_000 => {
+                    410_000_000..=525_000_000 => {
                         radio.write_register(Register::RegHighBwOptimize1, val_1).await?;
                         radio.write_register(Register::RegHighBwOptimize2, 0x7f).await?;
                         return Ok(());
@@ -194,10 +268,10 @@ impl Sx127xVariant for Sx1276 {
             pll_step_to_freq((msb << 16) + (mid << 8) + lsb)
         };
 
-        if frequency_in_hz > SX1276_RF_MID_BAND_THRESH {
-            Ok(SX1276_RSSI_OFFSET_HF)
-        } else {
+        if Self::low_frequency_mode(frequency_in_hz) {
             Ok(SX1276_RSSI_OFFSET_LF)
+        } else {
+            Ok(SX1276_RSSI_OFFSET_HF)
         }
     }
 
@@ -208,7 +282,7 @@ impl Sx127xVariant for Sx1276 {
         let pa_config = radio.read_register(Register::RegPaConfig).await?;
         let new_pa_config = pa_config | 0b1000_0000;
         radio.write_register(Register::RegPaConfig, new_pa_config).await?;
-        radio.write_register(Register::RegOpMode, 0b1100_0011).await?;
+        radio.write_op_mode(0b1100_0011).await?;
         let modem_config = radio.read_register(Register::RegModemConfig2).await?;
         let new_modem_config = modem_config | 0b0000_1000;
         radio
@@ -216,4 +290,16 @@ impl Sx127xVariant for Sx1276 {
             .await?;
         Ok(())
     }
+
+    fn frequency_range() -> RangeInclusive<u32> {
+        Self::FREQUENCIES
+    }
+
+    fn max_spreading_factor() -> SpreadingFactor {
+        Self::MAX_SPREADING_FACTOR
+    }
+
+    fn low_frequency_mode(frequency_in_hz: u32) -> bool {
+        frequency_in_hz <= SX1276_RF_MID_BAND_THRESH
+    }
 }
@@ -0,0 +1,954 @@
+mod radio_kind_params;
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::spi::*;
+use radio_kind_params::*;
+
+use crate::mod_params::*;
+use crate::mod_traits::IrqState;
+use crate::{InterfaceVariant, RadioKind, SpiInterface};
+
+// Internal frequency of the radio
+const SX128X_XTAL_FREQ: u64 = 52_000_000;
+
+// Number of PLL steps per crystal period (the PLL step is XTAL_FREQ / 2^18)
+const SX128X_PLL_STEP_SHIFT_AMOUNT: u32 = 18;
+
+// Frequencies covered by the radio
+const ISM_2G4_FREQUENCIES: core::ops::RangeInclusive<u32> = 2_400_000_000..=2_500_000_000;
+
+// SetRx timeout argument for enabling continuous mode
+const RX_CONTINUOUS_TIMEOUT: u16 = 0xffff;
+
+/// Configuration for SX128x-based boards (SX1280 and SX1281)
+///
+/// Only the LoRa packet engine is supported: the GFSK modem of these chips is limited to
+/// bitrates of 125 kbps and above, which no [`FskModulationParams`] user relies on.
+pub struct Config {
+    /// Whether board is using optional DCDC in addition to LDO
+    pub use_dcdc: bool,
+    /// Whether to boost receive (high sensitivity mode)
+    pub rx_boost: bool,
+}
+
+/// Base for the RadioKind implementation for the LoRa chip kind and board type
+pub struct Sx128x<SPI, IV> {
+    intf: SpiInterface<SPI, IV>,
+    config: Config,
+    // Duration of a LoRa symbol with the current modulation parameters, used by single receptions
+    symbol_time_ns: u32,
+    // LoRa bandwidth of the current modulation parameters, scaling the frequency error
+    bandwidth_in_hz: u32,
+    // IRQ flags read last, counted into the packet statistics once cleared
+    irq_flags: u16,
+    packet_stats: PacketStats,
+}
+
+impl<SPI, IV> Sx128x<SPI, IV>
+where
+    SPI: SpiDevice<u8>,
+    IV: InterfaceVariant,
+{
+    /// Create an instance of the RadioKind implementation for the LoRa chip kind and board type
+    pub fn new(spi: SPI, iv: IV, config: Config) -> Self {
+        let intf = SpiInterface::new(spi, iv);
+        Self {
+            intf,
+            config,
+            symbol_time_ns: 0,
+            bandwidth_in_hz: 0,
+            irq_flags: 0,
+            packet_stats: PacketStats::default(),
+        }
+    }
+
+    // Utility functions
+    async fn read_register(&mut self, register: Register) -> Result<u8, RadioError> {
+        let mut value = [0x00u8];
+        self.intf
+            .read(
+                &[OpCode::ReadRegister.value(), register.addr1(), register.addr2(), 0x00u8],
+                &mut value,
+            )
+            .await?;
+        Ok(value[0])
+    }
+
+    async fn write_register(&mut self, register: Register, value: u8) -> Result<(), RadioError> {
+        let register_and_value = [OpCode::WriteRegister.value(), register.addr1(), register.addr2(), value];
+        self.intf.write(&register_and_value, false).await
+    }
+
+    // Select the LNA regime: high sensitivity when rx boosted, low power otherwise
+    async fn set_rx_gain(&mut self) -> Result<(), RadioError> {
+        let rx_gain = self.read_register(Register::RxGain).await?;
+        let rx_gain = match self.config.rx_boost {
+            true => rx_gain | 0xC0,
+            false => rx_gain & 0x3F,
+        };
+        self.write_register(Register::RxGain, rx_gain).await
+    }
+
+    // Convert a duration into a SetRx/SetTx period base and count, preferring the finest base
+    fn timeout_period(timeout_ns: u64) -> (PeriodBase, u16) {
+        let steps = (timeout_ns * 64).div_ceil(1_000_000);
+        if steps < RX_CONTINUOUS_TIMEOUT as u64 {
+            (PeriodBase::_15_625Us, steps as u16)
+        } else {
+            let steps = timeout_ns.div_ceil(4_000_000).min(RX_CONTINUOUS_TIMEOUT as u64 - 1);
+            (PeriodBase::_4Ms, steps as u16)
+        }
+    }
+
+    fn convert_freq_in_hz_to_pll_step(freq_in_hz: u32) -> u32 {
+        let steps = ((freq_in_hz as u64) << SX128X_PLL_STEP_SHIFT_AMOUNT) + (SX128X_XTAL_FREQ >> 1);
+        (steps / SX128X_XTAL_FREQ) as u32
+    }
+
+    // Encode a preamble length as mantissa * 2^exponent, rounding up
+    fn preamble_length_value(preamble_length: u16) -> u8 {
+        let mut mant = preamble_length.max(1);
+        let mut exp = 0u8;
+        while mant > 15 {
+            mant = mant.div_ceil(2);
+            exp += 1;
+        }
+        (exp.min(15) << 4) | (mant as u8)
+    }
+}
+
+// Convert u8 sync word to two byte value expected by sx128x
+fn convert_sync_word(sync_word: u8) -> [u8; 2] {
+    [(sync_word & 0xF0) | 0x04, ((sync_word & 0x0F) << 4) | 0x04]
+}
+
+impl<SPI, IV> RadioKind for Sx128x<SPI, IV>
+where
+    SPI: SpiDevice<u8>,
+    IV: InterfaceVariant,
+{
+    async fn init_lora(&mut self, sync_word: u8) -> Result<(), RadioError> {
+        // DC-DC regulator setup (default is LDO)
+        if self.config.use_dcdc {
+            let reg_data = [OpCode::SetRegulatorMode.value(), RegulatorMode::UseDCDC.value()];
+            self.intf.write(&reg_data, false).await?;
+        }
+
+        // Enable LoRa packet engine...
+        self.intf
+            .write(&[OpCode::SetPacketType.value(), PacketType::LoRa.value()], false)
+            .await?;
+        // ...and network syncword
+        let word = convert_sync_word(sync_word);
+        let lora_syncword_set = [
+            OpCode::WriteRegister.value(),
+            Register::LoRaSyncword.addr1(),
+            Register::LoRaSyncword.addr2(),
+            word[0],
+            word[1],
+        ];
+        self.intf.write(&lora_syncword_set, false).await?;
+
+        self.set_tx_rx_buffer_base_address(0, 0).await
+    }
+
+    async fn init_fsk(&mut self) -> Result<(), RadioError> {
+        Err(RadioError::InvalidConfiguration)
+    }
+
+    fn create_modulation_params(
+        &self,
+        spreading_factor: SpreadingFactor,
+        bandwidth: Bandwidth,
+        coding_rate: CodingRate,
+        frequency_in_hz: u32,
+    ) -> Result<ModulationParams, RadioError> {
+        // Parameter validation
+        spreading_factor_value(spreading_factor)?;
+        bandwidth_value(bandwidth)?;
+        coding_rate_value(coding_rate)?;
+        if !ISM_2G4_FREQUENCIES.contains(&frequency_in_hz) {
+            return Err(RadioError::InvalidBandwidthForFrequency);
+        }
+
+        // Not configurable: the chip always applies it with SF11 and SF12
+        let low_data_rate_optimize =
+            lora_modulation::BaseBandModulationParams::new(spreading_factor, bandwidth, coding_rate).ldro as u8;
+        Ok(ModulationParams {
+            spreading_factor,
+            bandwidth,
+            coding_rate,
+            low_data_rate_optimize,
+            frequency_in_hz,
+        })
+    }
+
+    fn create_packet_params(
+        &self,
+        mut preamble_length: u16,
+        implicit_header: bool,
+        payload_length: u8,
+        crc_on: bool,
+        iq_inverted: bool,
+        modulation_params: &ModulationParams,
+    ) -> Result<PacketParams, RadioError> {
+        if ((modulation_params.spreading_factor == SpreadingFactor::_5)
+            || (modulation_params.spreading_factor == SpreadingFactor::_6))
+            && (preamble_length < 12)
+        {
+            preamble_length = 12;
+        }
+
+        Ok(PacketParams {
+            preamble_length,
+            implicit_header,
+            payload_length,
+            crc_on,
+            iq_inverted,
+        })
+    }
+
+    fn create_fsk_modulation_params(
+        &self,
+        _bitrate: u32,
+        _fdev: u32,
+        _pulse_shape: PulseShape,
+        _rx_bandwidth: u32,
+        _frequency_in_hz: u32,
+    ) -> Result<FskModulationParams, RadioError> {
+        Err(RadioError::InvalidConfiguration)
+    }
+
+    fn create_fsk_packet_params(
+        &self,
+        _preamble_length: u16,
+        _sync_word: &[u8],
+        _variable_length: bool,
+        _payload_length: u8,
+        _crc_on: bool,
+        _whitening: bool,
+    ) -> Result<FskPacketParams, RadioError> {
+        Err(RadioError::InvalidConfiguration)
+    }
+
+    async fn reset(&mut self, delay: &mut impl DelayNs) -> Result<(), RadioError> {
+        self.intf.iv.reset(delay).await
+    }
+
+    // Wakeup the radio if it is in Sleep or ReceiveDutyCycle mode; otherwise, ensure it is not busy.
+    async fn ensure_ready(&mut self, mode: RadioMode) -> Result<(), RadioError> {
+        match mode {
+            RadioMode::Sleep | RadioMode::Receive(RxMode::DutyCycle(_)) => {
+                let op_code_and_null = [OpCode::GetStatus.value(), 0x00u8];
+                self.intf.write(&op_code_and_null, false).await?;
+            }
+            _ => self.intf.iv.wait_on_busy().await?,
+        }
+        Ok(())
+    }
+
+    // Use standby mode RC (not XOSC).
+    async fn set_standby(&mut self) -> Result<(), RadioError> {
+        let op_code_and_standby_mode = [OpCode::SetStandby.value(), StandbyMode::RC.value()];
+        self.intf.write(&op_code_and_standby_mode, false).await?;
+        self.intf.iv.disable_rf_switch().await
+    }
+
+    async fn set_sleep(&mut self, warm_start_if_possible: bool, delay: &mut impl DelayNs) -> Result<(), RadioError> {
+        self.intf.iv.disable_rf_switch().await?;
+        let sleep_params = SleepParams {
+            data_ram_retention: warm_start_if_possible,
+            data_buffer_retention: false,
+        };
+        let op_code_and_sleep_params = [OpCode::SetSleep.value(), sleep_params.value()];
+        self.intf.write(&op_code_and_sleep_params, true).await?;
+        delay.delay_ms(2).await;
+
+        Ok(())
+    }
+
+    async fn set_tx_rx_buffer_base_address(
+        &mut self,
+        tx_base_addr: usize,
+        rx_base_addr: usize,
+    ) -> Result<(), RadioError> {
+        if tx_base_addr > 255 || rx_base_addr > 255 {
+            return Err(RadioError::InvalidBaseAddress(tx_base_addr, rx_base_addr));
+        }
+        let op_code_and_base_addrs = [
+            OpCode::SetBufferBaseAddress.value(),
+            tx_base_addr as u8,
+            rx_base_addr as u8,
+        ];
+        self.intf.write(&op_code_and_base_addrs, false).await
+    }
+
+    // Set parameters associated with power for a send operation.
+    //   output_power            desired RF output power (dBm)
+    //   mdltn_params            unused, there is a single power amplifier for the whole band
+    //   is_tx_prep              indicates which ramp up time to use
+    async fn set_tx_power_and_ramp_time(
+        &mut self,
+        output_power: i32,
+        _mdltn_params: Option<&ModulationParams>,
+        is_tx_prep: bool,
+    ) -> Result<(), RadioError> {
+        const POWER_MIN: i32 = -18;
+        const POWER_MAX: i32 = 13;
+        let ramp_time = match is_tx_prep {
+            true => RampTime::Ramp10Us,  // for instance, prior to TX or CAD
+            false => RampTime::Ramp20Us, // for instance, on initialization
+        };
+
+        // Clamp power between [-18, 13] dBm, the register value starts at 0 for -18 dBm
+        let tx_params_power = (output_power.clamp(POWER_MIN, POWER_MAX) - POWER_MIN) as u8;
+        let op_code_and_tx_params = [OpCode::SetTxParams.value(), tx_params_power, ramp_time.value()];
+        self.intf.write(&op_code_and_tx_params, false).await
+    }
+
+    async fn set_modulation_params(&mut self, mdltn_params: &ModulationParams) -> Result<(), RadioError> {
+        let spreading_factor_val = spreading_factor_value(mdltn_params.spreading_factor)?;
+        let bandwidth_val = bandwidth_value(mdltn_params.bandwidth)?;
+        let coding_rate_val = coding_rate_value(mdltn_params.coding_rate)?;
+        debug!(
+            "sf = {}, bw = {}, cr = {}",
+            spreading_factor_val, bandwidth_val, coding_rate_val
+        );
+        self.bandwidth_in_hz = u32::from(mdltn_params.bandwidth);
+        let op_code_and_mod_params = [
+            OpCode::SetModulationParams.value(),
+            spreading_factor_val,
+            bandwidth_val,
+            coding_rate_val,
+        ];
+        self.intf.write(&op_code_and_mod_params, false).await?;
+
+        // Registers to update after SetModulationParams (see DS_SX1280-1_V3.3 datasheet chapter 14.4.1)
+        self.write_register(
+            Register::SfAdditionalConfig,
+            spreading_factor_config_value(mdltn_params.spreading_factor),
+        )
+        .await?;
+        let freq_error_correction = self.read_register(Register::FrequencyErrorCorrection).await?;
+        self.write_register(
+            Register::FrequencyErrorCorrection,
+            (freq_error_correction & 0xF8) | 0x01,
+        )
+        .await?;
+
+        let symbol_time_ns =
+            (1_000_000_000u64 << mdltn_params.spreading_factor.factor()) / mdltn_params.bandwidth.hz() as u64;
+        self.symbol_time_ns = symbol_time_ns as u32;
+        Ok(())
+    }
+
+    async fn set_packet_params(&mut self, pkt_params: &PacketParams) -> Result<(), RadioError> {
+        let op_code_and_pkt_params = [
+            OpCode::SetPacketParams.value(),
+            Self::preamble_length_value(pkt_params.preamble_length),
+            if pkt_params.implicit_header { 0x80 } else { 0x00 },
+            pkt_params.payload_length,
+            if pkt_params.crc_on { 0x20 } else { 0x00 },
+            if pkt_params.iq_inverted { 0x00 } else { 0x40 },
+            0x00u8, // unused
+            0x00u8, // unused
+        ];
+        self.intf.write(&op_code_and_pkt_params, false).await
+    }
+
+    async fn set_fsk_modulation_params(&mut self, _mdltn_params: &FskModulationParams) -> Result<(), RadioError> {
+        Err(RadioError::InvalidConfiguration)
+    }
+
+    async fn set_fsk_packet_params(&mut self, _pkt_params: &FskPacketParams) -> Result<(), RadioError> {
+        Err(RadioError::InvalidConfiguration)
+    }
+
+    // There is no image calibration on 2.4 GHz chips
+    async fn calibrate_image(&mut self, _frequency_in_hz: u32) -> Result<(), RadioError> {
+        Ok(())
+    }
+
+    fn image_calibration_band(&self, _frequency_in_hz: u32) -> u32 {
+        0
+    }
+
+    async fn set_channel(&mut self, frequency_in_hz: u32) -> Result<(), RadioError> {
+        debug!("channel = {}", frequency_in_hz);
+        let freq_in_pll_steps = Self::convert_freq_in_hz_to_pll_step(frequency_in_hz);
+        let op_code_and_pll_steps = [
+            OpCode::SetRfFrequency.value(),
+            ((freq_in_pll_steps >> 16) & 0xFF) as u8,
+            ((freq_in_pll_steps >> 8) & 0xFF) as u8,
+            (freq_in_pll_steps & 0xFF) as u8,
+        ];
+        self.intf.write(&op_code_and_pll_steps, false).await
+    }
+
+    async fn set_payload(&mut self, payload: &[u8]) -> Result<(), RadioError> {
+        let op_code_and_offset = [OpCode::WriteBuffer.value(), 0x00u8];
+        self.intf.write_with_payload(&op_code_and_offset, payload, false).await
+    }
+
+    async fn do_tx(&mut self, timeout_in_ms: u32) -> Result<(), RadioError> {
+        self.intf.iv.enable_rf_switch_tx().await?;
+
+        // 0 disables the timeout
+        let (period_base, count) = if timeout_in_ms <= 0xFFFF {
+            (PeriodBase::_1Ms, timeout_in_ms)
+        } else {
+            (PeriodBase::_4Ms, timeout_in_ms.div_ceil(4).min(0xFFFF))
+        };
+        let cmd = [
+            OpCode::SetTx.value(),
+            period_base.value(),
+            (count >> 8) as u8,
+            count as u8,
+        ];
+        self.intf.write(&cmd, false).await
+    }
+
+    async fn do_rx(&mut self, rx_mode: RxMode) -> Result<(), RadioError> {
+        self.intf.iv.enable_rf_switch_rx().await?;
+        self.set_rx_gain().await?;
+
+        match rx_mode {
+            RxMode::DutyCycle(args) => {
+                // Both periods are given in 15.625 us steps, use 4 ms steps when they do not fit
+                let (period_base, rx_time, sleep_time) = if args.rx_time.max(args.sleep_time) <= u16::MAX as u32 {
+                    (PeriodBase::_15_625Us, args.rx_time, args.sleep_time)
+                } else {
+                    (
+                        PeriodBase::_4Ms,
+                        args.rx_time.div_ceil(256),
+                        args.sleep_time.div_ceil(256),
+                    )
+                };
+                let rx_time = rx_time.min(u16::MAX as u32) as u16;
+                let sleep_time = sleep_time.min(u16::MAX as u32) as u16;
+                let op = [
+                    OpCode::SetRxDutyCycle.value(),
+                    period_base.value(),
+                    (rx_time >> 8) as u8,
+                    rx_time as u8,
+                    (sleep_time >> 8) as u8,
+                    sleep_time as u8,
+                ];
+                self.intf.write(&op, false).await
+            }
+            RxMode::Single(num_symbols) => {
+                // There is no symbol timeout, wait for the duration of the symbols instead
+                let (period_base, timeout) = match num_symbols {
+                    0 => (PeriodBase::_15_625Us, 0),
+                    n => Self::timeout_period(n as u64 * self.symbol_time_ns as u64),
+                };
+                let op = [
+                    OpCode::SetRx.value(),
+                    period_base.value(),
+                    (timeout >> 8) as u8,
+                    timeout as u8,
+                ];
+                self.intf.write(&op, false).await
+            }
+            RxMode::Continuous => {
+                let op = [
+                    OpCode::SetRx.value(),
+                    PeriodBase::_15_625Us.value(),
+                    (RX_CONTINUOUS_TIMEOUT >> 8) as u8,
+                    RX_CONTINUOUS_TIMEOUT as u8,
+                ];
+                self.intf.write(&op, false).await
+            }
+        }
+    }
+
+    async fn get_rx_payload(
+        &mut self,
+        rx_pkt_params: &PacketParams,
+        receiving_buffer: &mut [u8],
+    ) -> Result<u8, RadioError> {
+        let op_code = [OpCode::GetRxBufferStatus.value()];
+        let mut rx_buffer_status = [0x00u8; 2];
+        let read_status = self.intf.read_with_status(&op_code, &mut rx_buffer_status).await?;
+        if OpStatusErrorMask::is_error(read_status) {
+            return Err(RadioError::OpError(read_status));
+        }
+
+        // The buffer status does not report the length in implicit header mode
+        let payload_length = if rx_pkt_params.implicit_header {
+            self.read_register(Register::LoRaPayloadLength).await?
+        } else {
+            rx_buffer_status[0]
+        };
+        let offset = rx_buffer_status[1];
+
+        if (payload_length as usize) > receiving_buffer.len() {
+            Err(RadioError::PayloadSizeMismatch(
+                payload_length as usize,
+                receiving_buffer.len(),
+            ))
+        } else {
+            self.intf
+                .read(
+                    &[OpCode::ReadBuffer.value(), offset, 0x00u8],
+                    &mut receiving_buffer[..payload_length as usize],
+                )
+                .await?;
+            Ok(payload_length)
+        }
+    }
+
+    async fn get_fsk_rx_payload(
+        &mut self,
+        _rx_pkt_params: &FskPacketParams,
+        _receiving_buffer: &mut [u8],
+    ) -> Result<u8, RadioError> {
+        Err(RadioError::InvalidConfiguration)
+    }
+
+    async fn get_rx_packet_status(&mut self) -> Result<PacketStatus, RadioError> {
+        let op_code = [OpCode::GetPacketStatus.value()];
+        let mut pkt_status = [0x00u8; 5];
+        let read_status = self.intf.read_with_status(&op_code, &mut pkt_status).await?;
+        if OpStatusErrorMask::is_error(read_status) {
+            return Err(RadioError::OpError(read_status));
+        }
+        // RssiSync and Snr, the remaining bytes are unused by the LoRa packet engine
+        let rssi = ((-(pkt_status[0] as i32)) >> 1) as i16;
+        let snr = (((pkt_status[1] as i8) + 2) >> 2) as i16;
+
+        let register = Register::FrequencyErrorIndicator;
+        let mut freq_error = [0x00u8; 3];
+        self.intf
+            .read(
+                &[OpCode::ReadRegister.value(), register.addr1(), register.addr2(), 0x00u8],
+                &mut freq_error,
+            )
+            .await?;
+        let frequency_error = frequency_error_value(freq_error, self.bandwidth_in_hz);
+
+        Ok(PacketStatus {
+            rssi,
+            snr,
+            signal_rssi: rssi,
+            frequency_error: Some(frequency_error),
+        })
+    }
+
+    async fn get_packet_stats(&mut self) -> Result<PacketStats, RadioError> {
+        Ok(self.packet_stats)
+    }
+
+    async fn reset_packet_stats(&mut self) -> Result<(), RadioError> {
+        self.packet_stats = PacketStats::default();
+        Ok(())
+    }
+
+    async fn get_device_errors(&mut self) -> Result<DeviceErrors, RadioError> {
+        Ok(DeviceErrors::default())
+    }
+
+    async fn clear_device_errors(&mut self) -> Result<(), RadioError> {
+        Ok(())
+    }
+
+    async fn get_rssi(&mut self) -> Result<i16, RadioError> {
+        let op_code = [OpCode::GetRSSIInst.value()];
+        let mut response = [0x00u8; 1];
+        let read_status = self.intf.read_with_status(&op_code, &mut response).await?;
+        if OpStatusErrorMask::is_error(read_status) {
+            return Err(RadioError::OpError(read_status));
+        }
+        let rssi = ((-(response[0] as i32)) >> 1) as i16;
+        Ok(rssi)
+    }
+
+    async fn do_cad(&mut self, _mdltn_params: &ModulationParams) -> Result<(), RadioError> {
+        self.intf.iv.enable_rf_switch_rx().await?;
+        self.set_rx_gain().await?;
+
+        let op_code_and_cad_params = [OpCode::SetCADParams.value(), CADSymbols::_8.value()];
+        self.intf.write(&op_code_and_cad_params, false).await?;
+
+        let op_code_for_set_cad = [OpCode::SetCAD.value()];
+        self.intf.write(&op_code_for_set_cad, false).await
+    }
+
+    // Set the IRQ mask and DIO masks
+    async fn set_irq_params(&mut self, radio_mode: Option<RadioMode>) -> Result<(), RadioError> {
+        let mut irq_mask: u16 = IrqMask::None.value();
+        let mut dio1_mask: u16 = IrqMask::None.value();
+        let dio2_mask: u16 = IrqMask::None.value();
+        let dio3_mask: u16 = IrqMask::None.value();
+
+        match radio_mode {
+            Some(RadioMode::Standby) => {
+                irq_mask = IrqMask::All.value();
+                dio1_mask = IrqMask::All.value();
+            }
+            Some(RadioMode::Transmit) => {
+                irq_mask = IrqMask::TxDone.value() | IrqMask::RxTxTimeout.value();
+                dio1_mask = IrqMask::TxDone.value() | IrqMask::RxTxTimeout.value();
+            }
+            Some(RadioMode::Receive(_)) => {
+                irq_mask = IrqMask::All.value();
+                dio1_mask = IrqMask::All.value();
+            }
+            Some(RadioMode::ChannelActivityDetection) => {
+                irq_mask = IrqMask::CADDone.value() | IrqMask::CADActivityDetected.value();
+                dio1_mask = IrqMask::CADDone.value() | IrqMask::CADActivityDetected.value();
+            }
+            _ => {}
+        }
+
+        let op_code_and_masks = [
+            OpCode::CfgDIOIrq.value(),
+            ((irq_mask >> 8) & 0x00FF) as u8,
+            (irq_mask & 0x00FF) as u8,
+            ((dio1_mask >> 8) & 0x00FF) as u8,
+            (dio1_mask & 0x00FF) as u8,
+            ((dio2_mask >> 8) & 0x00FF) as u8,
+            (dio2_mask & 0x00FF) as u8,
+            ((dio3_mask >> 8) & 0x00FF) as u8,
+            (dio3_mask & 0x00FF) as u8,
+        ];
+        self.intf.write(&op_code_and_masks, false).await
+    }
+
+    async fn set_tx_continuous_wave_mode(&mut self) -> Result<(), RadioError> {
+        self.intf.iv.enable_rf_switch_tx().await?;
+
+        let op_code = [OpCode::SetTxContinuousWave.value()];
+        self.intf.write(&op_code, false).await
+    }
+
+    async fn await_irq(&mut self) -> Result<(), RadioError> {
+        self.intf.iv.await_irq().await
+    }
+
+    async fn get_irq_state(
+        &mut self,
+        radio_mode: RadioMode,
+        cad_activity_detected: Option<&mut bool>,
+    ) -> Result<Option<IrqState>, RadioError> {
+        let op_code = [OpCode::GetIrqStatus.value()];
+        let mut irq_status = [0x00u8, 0x00u8];
+        let read_status = self.intf.read_with_status(&op_code, &mut irq_status).await?;
+        let irq_flags = ((irq_status[0] as u16) << 8) | (irq_status[1] as u16);
+        self.irq_flags = irq_flags;
+
+        if OpStatusErrorMask::is_error(read_status) {
+            debug!(
+                "process_irq read status error = 0x{:x} in radio mode {}",
+                read_status, radio_mode
+            );
+        }
+
+        debug!(
+            "process_irq satisfied: irq_flags = 0x{:x} in radio mode {}",
+            irq_flags, radio_mode
+        );
+
+        match radio_mode {
+            RadioMode::Transmit => {
+                if IrqMask::TxDone.is_set(irq_flags) {
+                    return Ok(Some(IrqState::Done));
+                }
+                if IrqMask::RxTxTimeout.is_set(irq_flags) {
+                    return Err(RadioError::TransmitTimeout);
+                }
+            }
+            RadioMode::Receive(_) => {
+                if IrqMask::HeaderError.is_set(irq_flags) {
+                    debug!("HeaderError in radio mode {}", radio_mode);
+                }
+                if IrqMask::CRCError.is_set(irq_flags) {
+                    debug!("CRCError in radio mode {}", radio_mode);
+                }
+                if IrqMask::RxDone.is_set(irq_flags) {
+                    debug!("RxDone in radio mode {}", radio_mode);
+                    return Ok(Some(IrqState::Done));
+                }
+                if IrqMask::RxTxTimeout.is_set(irq_flags) {
+                    return Err(RadioError::ReceiveTimeout);
+                }
+                if IrqMask::PreambleDetected.is_set(irq_flags) || IrqMask::HeaderValid.is_set(irq_flags) {
+                    return Ok(Some(IrqState::PreambleReceived));
+                }
+            }
+            RadioMode::ChannelActivityDetection => {
+                if IrqMask::CADDone.is_set(irq_flags) {
+                    if let Some(detected) = cad_activity_detected {
+                        *detected = IrqMask::CADActivityDetected.is_set(irq_flags);
+                    }
+                    return Ok(Some(IrqState::Done));
+                }
+            }
+            RadioMode::Sleep | RadioMode::Standby | RadioMode::Listen => {
+                warn!("IRQ during sleep/standby/listen?");
+            }
+            RadioMode::FrequencySynthesis => {
+                warn!("IRQ during frequency synthesis?");
+            }
+        }
+
+        // If none of the specific conditions are met, return None to indicate no IRQ state change.
+        Ok(None)
+    }
+
+    async fn clear_irq_status(&mut self) -> Result<(), RadioError> {
+        self.packet_stats.count(
+            IrqMask::RxDone.is_set(self.irq_flags),
+            IrqMask::CRCError.is_set(self.irq_flags),
+            IrqMask::HeaderError.is_set(self.irq_flags),
+        );
+        self.irq_flags = 0;
+        let op_code_and_irq_status = [OpCode::ClrIrqStatus.value(), 0xffu8, 0xffu8]; // clear all interrupts
+        self.intf.write(&op_code_and_irq_status, false).await
+    }
+
+    /// Process the radio IRQ. Log unexpected interrupts. Packets from other
+    /// devices can cause unexpected interrupts.
+    ///
+    /// NB! Do not await this future in a select branch as interrupting it
+    /// mid-flow could cause radio lock up.
+    async fn process_irq_event(
+        &mut self,
+        radio_mode: RadioMode,
+        cad_activity_detected: Option<&mut bool>,
+        clear_interrupts: bool,
+    ) -> Result<Option<IrqState>, RadioError> {
+        let irq_state = self.get_irq_state(radio_mode, cad_activity_detected).await;
+
+        if clear_interrupts {
+            self.clear_irq_status().await?;
+        }
+
+        irq_state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocking::block_on;
+    use crate::mock::{write, MockIv, MockSpi, Transaction};
+
+    fn sx1280(spi: &mut MockSpi, rx_boost: bool) -> Sx128x<&mut MockSpi, MockIv> {
+        let config = Config {
+            use_dcdc: true,
+            rx_boost,
+        };
+        Sx128x::new(spi, MockIv, config)
+    }
+
+    #[test]
+    fn init() {
+        const TRANSACTIONS: &[Transaction] = &[
+            // SetRegulatorMode (DC-DC), SetPacketType (LoRa) and LoRa syncword
+            write(&[0x96, 0x01]),
+            write(&[0x8A, 0x01]),
+            write(&[0x18, 0x09, 0x44, 0x34, 0x44]),
+            // SetBufferBaseAddress
+            write(&[0x8F, 0x00, 0x00]),
+        ];
+        let mut spi = MockSpi::new(TRANSACTIONS);
+        let mut radio = sx1280(&mut spi, false);
+        block_on(radio.init_lora(0x34)).unwrap();
+        spi.assert_done();
+    }
+
+    #[test]
+    fn modulation_params_validation() {
+        let mut spi = MockSpi::new(&[]);
+        let radio = sx1280(&mut spi, false);
+        let create = |bw, freq| radio.create_modulation_params(SpreadingFactor::_12, bw, CodingRate::_4_5, freq);
+        assert!(create(Bandwidth::_125KHz, 2_450_000_000).is_err());
+        assert!(create(Bandwidth::_812KHz, 868_100_000).is_err());
+        let mdltn_params = create(Bandwidth::_812KHz, 2_450_000_000).unwrap();
+        assert_eq!(mdltn_params.low_data_rate_optimize, 1);
+        assert!(radio
+            .create_fsk_modulation_params(50_000, 25_000, PulseShape::Bt0_5, 58_600, 2_450_000_000)
+            .is_err());
+    }
+
+    #[test]
+    fn lora_tx() {
+        const TRANSACTIONS: &[Transaction] = &[
+            // SetModulationParams (SF7, BW812, CR4/5) and the registers depending on it
+            write(&[0x8B, 0x70, 0x18, 0x01]),
+            write(&[0x18, 0x09, 0x25, 0x37]),
+            Transaction {
+                write: &[0x19, 0x09, 0x3C, 0x00],
+                read: &[0x02],
+            },
+            write(&[0x18, 0x09, 0x3C, 0x01]),
+            // SetTxParams (10 dBm, 10 us)
+            write(&[0x8E, 0x1C, 0x80]),
+            // SetPacketParams (12 symbols, explicit header, 3 bytes, CRC, standard IQ)
+            write(&[0x8C, 0x0C, 0x00, 0x03, 0x20, 0x40, 0x00, 0x00]),
+            // SetRfFrequency (2450 MHz)
+            write(&[0x86, 0xBC, 0x76, 0x27]),
+            // WriteBuffer
+            write(&[0x1A, 0x00, 0x01, 0x02, 0x03]),
+            // SetTx with a 250 ms timeout in 1 ms steps
+            write(&[0x83, 0x02, 0x00, 0xFA]),
+            // GetIrqStatus: TxDone
+            Transaction {
+                write: &[0x15],
+                read: &[0xC4, 0x00, 0x01],
+            },
+            // ClrIrqStatus
+            write(&[0x97, 0xFF, 0xFF]),
+        ];
+        let mut spi = MockSpi::new(TRANSACTIONS);
+        let mut radio = sx1280(&mut spi, false);
+        let mdltn_params = radio
+            .create_modulation_params(SpreadingFactor::_7, Bandwidth::_812KHz, CodingRate::_4_5, 2_450_000_000)
+            .unwrap();
+        let mut pkt_params = radio
+            .create_packet_params(12, false, 0, true, false, &mdltn_params)
+            .unwrap();
+        pkt_params.set_payload_length(3).unwrap();
+        block_on(async {
+            radio.set_modulation_params(&mdltn_params).await?;
+            radio.set_tx_power_and_ramp_time(10, Some(&mdltn_params), true).await?;
+            radio.set_packet_params(&pkt_params).await?;
+            radio.set_channel(2_450_000_000).await?;
+            radio.set_payload(&[1, 2, 3]).await?;
+            radio.do_tx(250).await?;
+            let state = radio.process_irq_event(RadioMode::Transmit, None, true).await?;
+            assert!(state == Some(IrqState::Done));
+            Ok::<(), RadioError>(())
+        })
+        .unwrap();
+        spi.assert_done();
+    }
+
+    #[test]
+    fn lora_rx() {
+        const TRANSACTIONS: &[Transaction] = &[
+            // SetModulationParams (SF7, BW812, CR4/5) and the registers depending on it
+            write(&[0x8B, 0x70, 0x18, 0x01]),
+            write(&[0x18, 0x09, 0x25, 0x37]),
+            Transaction {
+                write: &[0x19, 0x09, 0x3C, 0x00],
+                read: &[0x00],
+            },
+            write(&[0x18, 0x09, 0x3C, 0x01]),
+            // Rx gain (low power)
+            Transaction {
+                write: &[0x19, 0x08, 0x91, 0x00],
+                read: &[0xE5],
+            },
+            write(&[0x18, 0x08, 0x91, 0x25]),
+            // SetRx with a timeout of 16 symbols (162 * 15.625 us)
+            write(&[0x82, 0x00, 0x00, 0xA2]),
+            // GetIrqStatus: RxDone
+            Transaction {
+                write: &[0x15],
+                read: &[0xA4, 0x00, 0x02],
+            },
+            // GetRxBufferStatus: 3 bytes at offset 0x10
+            Transaction {
+                write: &[0x17],
+                read: &[0x84, 0x03, 0x10],
+            },
+            // ReadBuffer
+            Transaction {
+                write: &[0x1B, 0x10, 0x00],
+                read: &[0xAA, 0xBB, 0xCC],
+            },
+            // GetPacketStatus: -40 dBm, 7 dB SNR
+            Transaction {
+                write: &[0x1D],
+                read: &[0x84, 0x50, 0x1C, 0x00, 0x00, 0x00],
+            },
+            // Frequency error indicator: 1024 steps
+            Transaction {
+                write: &[0x19, 0x09, 0x54, 0x00],
+                read: &[0x00, 0x04, 0x00],
+            },
+            // ClrIrqStatus
+            write(&[0x97, 0xFF, 0xFF]),
+        ];
+        let mut spi = MockSpi::new(TRANSACTIONS);
+        let mut radio = sx1280(&mut spi, false);
+        let mdltn_params = radio
+            .create_modulation_params(SpreadingFactor::_7, Bandwidth::_812KHz, CodingRate::_4_5, 2_450_000_000)
+            .unwrap();
+        let pkt_params = radio
+            .create_packet_params(12, false, 255, true, true, &mdltn_params)
+            .unwrap();
+        let mut buf = [0u8; 255];
+        block_on(async {
+            radio.set_modulation_params(&mdltn_params).await?;
+            let rx_mode = RadioMode::Receive(RxMode::Single(16));
+            radio.do_rx(RxMode::Single(16)).await?;
+            assert!(radio.get_irq_state(rx_mode, None).await? == Some(IrqState::Done));
+            assert_eq!(radio.get_rx_payload(&pkt_params, &mut buf).await?, 3);
+            let status = radio.get_rx_packet_status().await?;
+            assert_eq!((status.rssi, status.snr), (-40, 7));
+            assert_eq!(status.frequency_error, Some(806));
+            // Packet statistics are counted in software once the IRQ is cleared
+            radio.clear_irq_status().await?;
+            let stats = radio.get_packet_stats().await?;
+            assert_eq!((stats.received, stats.crc_errors, stats.header_errors), (1, 0, 0));
+            Ok::<(), RadioError>(())
+        })
+        .unwrap();
+        assert_eq!(buf[..3], [0xAA, 0xBB, 0xCC]);
+        spi.assert_done();
+    }
+
+    #[test]
+    fn cad_and_rssi() {
+        const TRANSACTIONS: &[Transaction] = &[
+            // Rx gain (high sensitivity)
+            Transaction {
+                write: &[0x19, 0x08, 0x91, 0x00],
+                read: &[0x25],
+            },
+            write(&[0x18, 0x08, 0x91, 0xE5]),
+            // SetCadParams (8 symbols) and SetCad
+            write(&[0x88, 0x60]),
+            write(&[0xC5]),
+            // GetIrqStatus: CadDone and CadDetected
+            Transaction {
+                write: &[0x15],
+                read: &[0xA4, 0x30, 0x00],
+            },
+            // GetRssiInst: -40 dBm
+            Transaction {
+                write: &[0x1F],
+                read: &[0xA4, 0x50],
+            },
+        ];
+        let mut spi = MockSpi::new(TRANSACTIONS);
+        let mut radio = sx1280(&mut spi, true);
+        let mdltn_params = radio
+            .create_modulation_params(SpreadingFactor::_9, Bandwidth::_406KHz, CodingRate::_4_5, 2_402_000_000)
+            .unwrap();
+        let mut detected = false;
+        block_on(async {
+            radio.do_cad(&mdltn_params).await?;
+            let state = radio
+                .get_irq_state(RadioMode::ChannelActivityDetection, Some(&mut detected))
+                .await?;
+            assert!(state == Some(IrqState::Done));
+            assert_eq!(radio.get_rssi().await?, -40);
+            Ok::<(), RadioError>(())
+        })
+        .unwrap();
+        assert!(detected);
+        spi.assert_done();
+    }
+
+    #[test]
+    fn preamble_length_encoding() {
+        // mantissa * 2^exponent, rounded up
+        assert_eq!(Sx128x::<&mut MockSpi, MockIv>::preamble_length_value(8), 0x08);
+        assert_eq!(Sx128x::<&mut MockSpi, MockIv>::preamble_length_value(16), 0x18);
+        assert_eq!(Sx128x::<&mut MockSpi, MockIv>::preamble_length_value(33), 0x29);
+    }
+}
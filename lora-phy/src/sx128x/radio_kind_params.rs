@@ -0,0 +1,267 @@
+use crate::mod_params::*;
+
+#[derive(Clone, Copy, PartialEq)]
+#[allow(dead_code)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum PacketType {
+    GFSK = 0x00,
+    LoRa = 0x01,
+    Ranging = 0x02,
+    FLRC = 0x03,
+    BLE = 0x04,
+}
+
+impl PacketType {
+    pub const fn value(self) -> u8 {
+        self as u8
+    }
+}
+
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+pub enum IrqMask {
+    None = 0x0000,
+    TxDone = 0x0001,
+    RxDone = 0x0002,
+    SyncwordValid = 0x0004,
+    SyncwordError = 0x0008,
+    HeaderValid = 0x0010,
+    HeaderError = 0x0020,
+    CRCError = 0x0040,
+    CADDone = 0x1000,
+    CADActivityDetected = 0x2000,
+    RxTxTimeout = 0x4000,
+    PreambleDetected = 0x8000,
+    All = 0xFFFF,
+}
+
+impl IrqMask {
+    pub fn value(self) -> u16 {
+        self as u16
+    }
+
+    pub fn is_set(self, mask: u16) -> bool {
+        self.value() & mask == self.value()
+    }
+}
+
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+pub enum Register {
+    LoRaPayloadLength = 0x0901,        // payload size received in implicit header mode
+    RxGain = 0x0891,                   // LNA regime (0xC0 in bits 7:6 for high sensitivity)
+    SfAdditionalConfig = 0x0925,       // must be set according to the spreading factor after SetModulationParams
+    FrequencyErrorCorrection = 0x093C, // must be set to 0x1 after SetModulationParams
+    LoRaSyncword = 0x0944,             // LoRa Syncword value
+    FrequencyErrorIndicator = 0x0954,  // LoRa frequency error estimation (20 bits), valid after a reception
+}
+
+impl Register {
+    pub fn addr1(self) -> u8 {
+        ((self as u16 & 0xFF00) >> 8) as u8
+    }
+    pub fn addr2(self) -> u8 {
+        (self as u16 & 0x00FF) as u8
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[allow(dead_code)]
+pub enum OpCode {
+    GetStatus = 0xC0,
+    WriteRegister = 0x18,
+    ReadRegister = 0x19,
+    WriteBuffer = 0x1A,
+    ReadBuffer = 0x1B,
+    SetSleep = 0x84,
+    SetStandby = 0x80,
+    SetFs = 0xC1,
+    SetTx = 0x83,
+    SetRx = 0x82,
+    SetRxDutyCycle = 0x94,
+    SetCAD = 0xC5,
+    SetTxContinuousWave = 0xD1,
+    SetTxContinuousPreamble = 0xD2,
+    SetPacketType = 0x8A,
+    GetPacketType = 0x03,
+    SetRfFrequency = 0x86,
+    SetTxParams = 0x8E,
+    SetCADParams = 0x88,
+    SetBufferBaseAddress = 0x8F,
+    SetModulationParams = 0x8B,
+    SetPacketParams = 0x8C,
+    GetRxBufferStatus = 0x17,
+    GetPacketStatus = 0x1D,
+    GetRSSIInst = 0x1F,
+    CfgDIOIrq = 0x8D,
+    GetIrqStatus = 0x15,
+    ClrIrqStatus = 0x97,
+    Calibrate = 0x89,
+    SetRegulatorMode = 0x96,
+    SetSaveContext = 0xD5,
+    SetAutoFs = 0x9E,
+    SetLongPreamble = 0x9B,
+}
+
+impl OpCode {
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Command status reported in bits 4:2 of the status byte
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+pub enum OpStatusErrorMask {
+    Timeout = (0x03 << 2),
+    ProcessingError = (0x04 << 2),
+    ExecutionError = (0x05 << 2),
+}
+
+impl OpStatusErrorMask {
+    pub fn is_error(status: u8) -> bool {
+        let error_flags = status & 0x1c;
+        OpStatusErrorMask::Timeout as u8 == error_flags
+            || OpStatusErrorMask::ProcessingError as u8 == error_flags
+            || OpStatusErrorMask::ExecutionError as u8 == error_flags
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct SleepParams {
+    pub data_ram_retention: bool, // retain the configuration for a warm start
+    pub data_buffer_retention: bool,
+}
+
+impl SleepParams {
+    pub fn value(self) -> u8 {
+        ((self.data_buffer_retention as u8) << 1) | (self.data_ram_retention as u8)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[allow(dead_code)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum StandbyMode {
+    RC = 0x00,
+    XOSC = 0x01,
+}
+
+impl StandbyMode {
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+}
+
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+pub enum RegulatorMode {
+    UseLDO = 0x00,
+    UseDCDC = 0x01,
+}
+
+impl RegulatorMode {
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Time base of the SetTx, SetRx and SetRxDutyCycle timeouts
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+pub enum PeriodBase {
+    _15_625Us = 0x00,
+    _62_5Us = 0x01,
+    _1Ms = 0x02,
+    _4Ms = 0x03,
+}
+
+impl PeriodBase {
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+}
+
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+#[allow(clippy::enum_variant_names)]
+pub enum RampTime {
+    Ramp2Us = 0x00,
+    Ramp4Us = 0x20,
+    Ramp6Us = 0x40,
+    Ramp8Us = 0x60,
+    Ramp10Us = 0x80,
+    Ramp12Us = 0xA0,
+    Ramp16Us = 0xC0,
+    Ramp20Us = 0xE0,
+}
+
+impl RampTime {
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+}
+
+pub fn spreading_factor_value(spreading_factor: SpreadingFactor) -> Result<u8, RadioError> {
+    match spreading_factor {
+        SpreadingFactor::_5 => Ok(0x50),
+        SpreadingFactor::_6 => Ok(0x60),
+        SpreadingFactor::_7 => Ok(0x70),
+        SpreadingFactor::_8 => Ok(0x80),
+        SpreadingFactor::_9 => Ok(0x90),
+        SpreadingFactor::_10 => Ok(0xA0),
+        SpreadingFactor::_11 => Ok(0xB0),
+        SpreadingFactor::_12 => Ok(0xC0),
+    }
+}
+
+// Value of the SF additional configuration register, depending on the spreading factor
+pub fn spreading_factor_config_value(spreading_factor: SpreadingFactor) -> u8 {
+    match spreading_factor {
+        SpreadingFactor::_5 | SpreadingFactor::_6 => 0x1E,
+        SpreadingFactor::_7 | SpreadingFactor::_8 => 0x37,
+        _ => 0x32,
+    }
+}
+
+pub fn bandwidth_value(bandwidth: Bandwidth) -> Result<u8, RadioError> {
+    match bandwidth {
+        Bandwidth::_203KHz => Ok(0x34),
+        Bandwidth::_406KHz => Ok(0x26),
+        Bandwidth::_812KHz => Ok(0x18),
+        Bandwidth::_1625KHz => Ok(0x0A),
+        _ => Err(RadioError::UnavailableBandwidth),
+    }
+}
+
+// Frequency error (Hz) of the 20-bit two's complement frequency error indicator
+pub fn frequency_error_value(freq_error: [u8; 3], bandwidth_in_hz: u32) -> i32 {
+    let raw = ((u32::from_be_bytes([0, freq_error[0], freq_error[1], freq_error[2]]) << 12) as i32) >> 12;
+    // 1.55 Hz per step at a 1.6 MHz bandwidth
+    (raw as i64 * bandwidth_in_hz as i64 * 31 / 32_000_000) as i32
+}
+
+pub fn coding_rate_value(coding_rate: CodingRate) -> Result<u8, RadioError> {
+    match coding_rate {
+        CodingRate::_4_5 => Ok(0x01),
+        CodingRate::_4_6 => Ok(0x02),
+        CodingRate::_4_7 => Ok(0x03),
+        CodingRate::_4_8 => Ok(0x04),
+    }
+}
+
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+pub enum CADSymbols {
+    _1 = 0x00,
+    _2 = 0x20,
+    _4 = 0x40,
+    _8 = 0x60,
+    _16 = 0x80,
+}
+
+impl CADSymbols {
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+}
@@ -2,7 +2,9 @@
 //! allowing for asynchronous radio implementations. Requires the `async` feature.
 use super::mac::{self, FcntDown, Frame, Mac, Window};
 pub use super::{
-    mac::{NetworkCredentials, SendData, Session},
+    mac::{
+        NetworkCredentials, RejoinPolicy, RejoinReason, RetransmissionPolicy, SendData, Session,
+    },
     region::{self, Region},
     Downlink, JoinMode,
 };
@@ -16,6 +18,9 @@ use crate::{
 };
 
 pub mod radio;
+#[cfg(feature = "certification")]
+pub use crate::mac::DeviceEvent;
+#[cfg(feature = "multicast")]
 use lorawan::default_crypto::DefaultFactory;
 
 #[cfg(feature = "embassy-time")]
@@ -26,6 +31,8 @@ pub use embassy_time::EmbassyTimer;
 #[cfg(feature = "multicast")]
 use crate::mac::multicast;
 #[cfg(feature = "multicast")]
+pub use crate::mac::multicast::SessionWindow as McSessionWindow;
+#[cfg(feature = "multicast")]
 pub use lorawan::{
     keys::{AppKey, AppSKey, GenAppKey, McAppSKey, McNetSKey, McRootKey},
     parser::McAddr,
@@ -41,6 +48,18 @@ pub enum McGroup {
     _3,
 }
 
+#[cfg(feature = "multicast")]
+impl McGroup {
+    pub(crate) fn index(self) -> usize {
+        match self {
+            McGroup::_0 => 0,
+            McGroup::_1 => 1,
+            McGroup::_2 => 2,
+            McGroup::_3 => 3,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test;
 
@@ -75,6 +94,9 @@ where
     downlink: Vec<Downlink, D>,
     #[cfg(feature = "class-c")]
     class_c: bool,
+    /// Credentials of the most recent OTAA join, used for automatic rejoins.
+    join_mode: Option<JoinMode>,
+    rejoin: mac::Rejoin,
 }
 
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
@@ -89,8 +111,17 @@ pub enum Error<R> {
 pub enum SendResponse {
     DownlinkReceived(FcntDown),
     SessionExpired,
-    NoAck,
+    /// Confirmed uplink which was not acknowledged after the given amount of transmissions.
+    NoAck {
+        attempts: u8,
+    },
     RxComplete,
+    /// MAC commands which did not fit into FOpts of the uplink are to be sent using
+    /// [`Device::flush_mac_commands`].
+    MacUplinkPending,
+    /// Certification request which has to be carried out by the application.
+    #[cfg(feature = "certification")]
+    DeviceEvent(DeviceEvent),
     #[cfg(feature = "multicast")]
     Multicast(MulticastResponse),
 }
@@ -107,6 +138,9 @@ pub enum JoinResponse {
 pub enum ListenResponse {
     SessionExpired,
     DownlinkReceived(FcntDown),
+    /// Certification request which has to be carried out by the application.
+    #[cfg(feature = "certification")]
+    DeviceEvent(DeviceEvent),
     #[cfg(feature = "multicast")]
     Multicast(MulticastResponse),
 }
@@ -201,6 +235,8 @@ where
             downlink: Vec::new(),
             #[cfg(feature = "class-c")]
             class_c: false,
+            join_mode: None,
+            rejoin: mac::Rejoin::default(),
         }
     }
 
@@ -258,13 +294,34 @@ where
     /// Sets a multicast session for this device for a specific group.
     #[cfg(feature = "multicast")]
     pub fn set_multicast_session(&mut self, group: McGroup, session: multicast::Session) {
-        let index = match group {
-            McGroup::_0 => 0,
-            McGroup::_1 => 1,
-            McGroup::_2 => 2,
-            McGroup::_3 => 3,
-        };
-        self.mac.multicast.sessions[index] = Some(session);
+        self.mac.multicast.sessions[group.index()] = Some(session);
+    }
+
+    /// Multicast session of a group, including its frame counter state. Sessions may be persisted
+    /// (e.g. with the `serde` feature) and restored with `set_multicast_session`.
+    #[cfg(feature = "multicast")]
+    pub fn get_multicast_session(&self, group: McGroup) -> Option<&multicast::Session> {
+        self.mac.multicast.sessions[group.index()].as_ref()
+    }
+
+    /// Window of the Class C multicast session of a group, as set up by McClassCSessionReq.
+    #[cfg(feature = "multicast")]
+    pub fn get_multicast_session_window(&self, group: McGroup) -> Option<McSessionWindow> {
+        self.mac.multicast.windows[group.index()]
+    }
+
+    /// Sets the current GPS time in seconds since the GPS epoch (00:00:00, 6 January 1980), eg:
+    /// obtained by DeviceTimeReq or by the clock synchronization package. It is used to schedule
+    /// multicast sessions at their requested start time, otherwise sessions start as soon as they
+    /// are set up. Class C multicast sessions require a timer providing
+    /// [`now_ms`](radio::Timer::now_ms) to follow their window, without it McClassCSessionReq is
+    /// answered with McGroupUndefined. McClassBSessionReq is always answered this way, since
+    /// Class B is not supported.
+    #[cfg(feature = "multicast")]
+    pub fn set_gps_time(&mut self, gps_seconds: u32) {
+        if let Some(now_ms) = self.timer.now_ms() {
+            self.mac.multicast.set_gps_time(gps_seconds, now_ms);
+        }
     }
 
     /// Disables Class C behavior. Note that an uplink must be set for the radio to disable
@@ -278,6 +335,21 @@ where
         self.mac.get_session()
     }
 
+    /// Metadata of the most recent uplink transmitted by [`send`](Device::send) or
+    /// [`join`](Device::join), including RX window and quality of a downlink received in
+    /// response. It is replaced by the next uplink, so it is to be read once `send` or `join`
+    /// returns.
+    pub fn get_uplink_metadata(&self) -> Option<mac::UplinkMetadata> {
+        self.mac.uplink_metadata
+    }
+
+    /// Take the oldest buffered MAC event, such as applied MAC commands or dropped downlinks. Up
+    /// to [`mac::EVENT_QUEUE_LEN`] events are buffered, after which the oldest are discarded.
+    #[cfg(feature = "mac-events")]
+    pub fn take_mac_event(&mut self) -> Option<mac::Event> {
+        self.mac.events.pop()
+    }
+
     pub fn get_region(&mut self) -> &region::Configuration {
         &self.mac.region
     }
@@ -290,6 +362,31 @@ where
         &mut self.radio
     }
 
+    /// Configure automatic rejoins for OTAA devices, or disable them by providing `None`.
+    ///
+    /// Once any of the policy conditions holds, the next call to [`send`](Device::send) first
+    /// attempts to rejoin using the credentials of the most recent OTAA [`join`](Device::join).
+    /// Rejoin attempts honor the JoinRequest duty-cycle limits and the current session is kept
+    /// until a JoinAccept has been received.
+    pub fn set_rejoin_policy(&mut self, policy: Option<RejoinPolicy>) {
+        self.rejoin.set_policy(policy);
+    }
+
+    /// Configure retransmissions of confirmed uplinks, or disable them by providing `None`.
+    ///
+    /// When enabled, [`send`](Device::send) retransmits an unacknowledged confirmed uplink with the
+    /// same FCntUp after a random ACK timeout until it is acknowledged or the maximum amount of
+    /// attempts has been reached, in which case `SendResponse::NoAck` is returned.
+    pub fn set_retransmission_policy(&mut self, policy: Option<RetransmissionPolicy>) {
+        self.mac.retransmission.set_policy(policy);
+    }
+
+    /// Amount of transmissions of the most recent confirmed uplink, if retransmissions are
+    /// enabled.
+    pub fn get_retransmission_attempts(&self) -> u8 {
+        self.mac.retransmission.attempts()
+    }
+
     /// Retrieve the current data rate being used by this device.
     pub fn get_datarate(&mut self) -> DR {
         self.mac.configuration.data_rate
@@ -300,6 +397,12 @@ where
         self.mac.configuration.data_rate = datarate;
     }
 
+    /// Maximum application payload size in bytes for the current data rate. Larger payloads are
+    /// rejected by [`send`](Self::send) with [`mac::Error::PayloadTooLarge`].
+    pub fn get_max_payload_len(&self) -> u8 {
+        self.mac.max_payload_len()
+    }
+
     /// Join the LoRaWAN network asynchronously. The returned future completes when
     /// the LoRaWAN network has been joined successfully, or an error has occurred.
     ///
@@ -308,13 +411,22 @@ where
     /// Note that for a Class C enabled device, you must repeatedly send *confirmed* uplink until
     /// LoRaWAN Network Server (LNS) confirmation after joining.
     pub async fn join(&mut self, join_mode: &JoinMode) -> Result<JoinResponse, Error<R::PhyError>> {
-        match join_mode {
+        let response = match join_mode {
             JoinMode::OTAA { deveui, appeui, appkey } => {
+                self.join_mode = Some(*join_mode);
                 let (tx_config, _) = self.mac.join_otaa::<G, N>(
                     &mut self.rng,
                     NetworkCredentials::new(*appeui, *deveui, *appkey),
                     &mut self.radio_buffer,
                 );
+                let airtime_us = tx_config.rf.time_on_air_us(
+                    Some(8),
+                    true,
+                    self.radio_buffer.as_ref_for_read().len() as u8,
+                );
+                self.rejoin
+                    .duty_cycle()
+                    .join_sent(self.timer.now_ms(), airtime_us.div_ceil(1000).into());
 
                 // Transmit the join payload
                 let ms = self
@@ -325,13 +437,52 @@ where
 
                 // Receive join response within RX window
                 self.timer.reset();
-                Ok(self.rx_downlink(&Frame::Join, ms).await?.into())
+                self.rx_downlink(&Frame::Join, ms).await?.into()
             }
             JoinMode::ABP { nwkskey, appskey, devaddr } => {
                 self.mac.join_abp(*nwkskey, *appskey, *devaddr);
-                Ok(JoinResponse::JoinSuccess)
+                JoinResponse::JoinSuccess
             }
+        };
+        if let JoinResponse::JoinSuccess = response {
+            self.rejoin.session_started(self.timer.now_ms());
+        }
+        Ok(response)
+    }
+
+    /// Rejoin the network if the configured [`RejoinPolicy`] requires it.
+    ///
+    /// This is called by [`send`](Device::send) before each uplink, but may also be called by the
+    /// application while idle. Returns `None` if no rejoin was attempted, either because none is
+    /// due or because the JoinRequest duty-cycle does not allow another attempt yet. Unless the
+    /// rejoin succeeds, including when it fails with an error, the previous session remains active.
+    ///
+    /// Without [`Timer::now_ms`](radio::Timer::now_ms), the time passed since the last
+    /// JoinRequest is only accounted by the uplinks sent in the meantime, so a rejoin may be
+    /// deferred for longer than the duty-cycle requires.
+    pub async fn rejoin_if_due(
+        &mut self,
+    ) -> Result<Option<(RejoinReason, JoinResponse)>, Error<R::PhyError>> {
+        let (Some(join_mode), Some(session)) = (self.join_mode, self.mac.get_session().cloned())
+        else {
+            return Ok(None);
+        };
+        let now_ms = self.timer.now_ms();
+        let Some(reason) = self.rejoin.due(session.fcnt_up, now_ms) else {
+            return Ok(None);
+        };
+        let backoff = self.rejoin.duty_cycle().backoff_ms(now_ms);
+        if backoff > 0 {
+            debug!("Rejoin ({}) deferred for {} ms due to duty-cycle.", reason, backoff);
+            return Ok(None);
         }
+        debug!("Rejoining: {}", reason);
+        let response = self.join(&join_mode).await;
+        if !matches!(response, Ok(JoinResponse::JoinSuccess)) {
+            // Keep the previous session until a new one is confirmed
+            self.mac.set_session(session);
+        }
+        Ok(Some((reason, response?)))
     }
 
     /// Send data on a given port with the expected confirmation. If downlink data is provided, the
@@ -344,12 +495,18 @@ where
     /// In Class C mode, it is possible to get one or more downlinks and `Reponse::DownlinkReceived`
     /// maybe not even be indicated. It is recommended to call `take_downlink` after `send` until
     /// it returns `None`.
+    ///
+    /// Pending MAC commands which do not fit into FOpts alongside the application data are left
+    /// for the next uplink, `SendResponse::MacUplinkPending` indicates that they are to be sent
+    /// using [`flush_mac_commands`](Self::flush_mac_commands). If a downlink of a Class C device
+    /// modifies the RX2 parameters, an additional uplink confirming them is sent before returning.
     pub async fn send(
         &mut self,
         data: &[u8],
         fport: u8,
         confirmed: bool,
     ) -> Result<SendResponse, Error<R::PhyError>> {
+        self.rejoin_if_due().await?;
         // Prepare transmission buffer
         let (tx_config, _fcnt_up) = self.mac.send::<G, N>(
             &mut self.rng,
@@ -365,7 +522,107 @@ where
 
         // Wait for received data within window
         self.timer.reset();
-        Ok(self.rx_downlink(&Frame::Data, ms).await?.into())
+        let mut response = self.rx_downlink(&Frame::Data, ms).await?;
+        while let mac::Response::Retransmit = response {
+            let ack_timeout = mac::Retransmission::ack_timeout_ms(&mut self.rng);
+            debug!("No ACK received, retransmitting in {} ms.", ack_timeout);
+            // sleep or RXC
+            self.timer.reset();
+            let _ = self.between_windows(ack_timeout).await?;
+
+            let (tx_config, _fcnt_up) =
+                self.mac.retransmit::<G, N>(&mut self.rng, &mut self.radio_buffer)?;
+            let ms = self
+                .radio
+                .tx(tx_config, self.radio_buffer.as_ref_for_read())
+                .await
+                .map_err(Error::Radio)?;
+            self.timer.reset();
+            response = self.rx_downlink(&Frame::Data, ms).await?;
+        }
+        self.rejoin.uplink_complete(
+            matches!(response, mac::Response::DownlinkReceived(_)),
+            matches!(response, mac::Response::SessionExpired),
+        );
+        let attempts = self.mac.retransmission.attempts().max(1);
+
+        // Class C devices confirm modified RX2 parameters (used also by RXC) with an uplink
+        // carrying RXParamSetupAns as soon as possible
+        #[cfg(feature = "class-c")]
+        if self.mac.rx2_changed() && self.class_c {
+            debug!("RX2 parameters modified, sending uplink and restarting RXC.");
+            let mac_response = self.send_mac_commands().await?;
+            if matches!(response, mac::Response::RxComplete)
+                || matches!(mac_response, mac::Response::SessionExpired)
+            {
+                response = mac_response;
+            }
+        }
+        #[cfg(feature = "certification")]
+        self.handle_device_event(&response);
+        match response {
+            mac::Response::NoAck => Ok(SendResponse::NoAck { attempts }),
+            // MAC commands which did not fit into FOpts are to be sent in a dedicated uplink
+            mac::Response::RxComplete if self.mac.mac_only_uplink_pending() => {
+                Ok(SendResponse::MacUplinkPending)
+            }
+            response => Ok(response.into()),
+        }
+    }
+
+    /// Apply certification requests which concern the device itself before passing them on to
+    /// the application.
+    #[cfg(feature = "certification")]
+    #[allow(unused_variables)]
+    fn handle_device_event(&mut self, response: &mac::Response) {
+        #[cfg(feature = "class-c")]
+        if let mac::Response::DeviceHandler(mac::DeviceEvent::SwitchClass { class }) = response {
+            match class {
+                lorawan::certification::DeviceClass::C => self.enable_class_c(),
+                _ => self.disable_class_c(),
+            }
+        }
+    }
+
+    /// Send pending MAC commands in an unconfirmed FPort 0 uplink.
+    #[cfg(feature = "class-c")]
+    async fn send_mac_commands(&mut self) -> Result<mac::Response, Error<R::PhyError>> {
+        let (tx_config, _fcnt_up) =
+            self.mac.send_mac_commands::<G, N>(&mut self.rng, &mut self.radio_buffer)?;
+        let ms = self
+            .radio
+            .tx(tx_config, self.radio_buffer.as_ref_for_read())
+            .await
+            .map_err(Error::Radio)?;
+        self.timer.reset();
+        let response = self.rx_downlink(&Frame::Data, ms).await?;
+        self.rejoin.uplink_complete(
+            matches!(response, mac::Response::DownlinkReceived(_)),
+            matches!(response, mac::Response::SessionExpired),
+        );
+        Ok(response)
+    }
+
+    /// Send pending MAC commands in an unconfirmed FPort 0 uplink, without any application data.
+    ///
+    /// Pending MAC commands which do not fit into FOpts alongside the application data of an
+    /// uplink are left for the next uplink, this allows sending them right away. This is
+    /// announced by `SendResponse::MacUplinkPending` or [`mac_uplink_due`](Self::mac_uplink_due).
+    /// If no MAC commands are pending, no uplink is sent and `SendResponse::RxComplete` is
+    /// returned.
+    pub async fn flush_mac_commands(&mut self) -> Result<SendResponse, Error<R::PhyError>> {
+        if self.mac.get_session().is_some_and(|session| session.uplink.mac_commands().is_empty()) {
+            return Ok(SendResponse::RxComplete);
+        }
+        self.send(&[], 0, false).await
+    }
+
+    /// Whether MAC commands did not fit into FOpts of the previous uplink and are to be sent
+    /// using [`flush_mac_commands`](Self::flush_mac_commands). Once the RX windows are complete
+    /// without a downlink, this is announced by `SendResponse::MacUplinkPending` instead of
+    /// `SendResponse::RxComplete`.
+    pub fn mac_uplink_due(&self) -> bool {
+        self.mac.mac_only_uplink_pending()
     }
 
     /// Take the downlink data from the device. This is typically called after a
@@ -375,10 +632,35 @@ where
         self.downlink.pop()
     }
 
+    /// Whether the device listens for RXC frames, either as Class C device or during the window of
+    /// a Class C multicast session.
+    #[cfg(feature = "class-c")]
+    fn rxc_enabled(&self) -> bool {
+        #[cfg(feature = "multicast")]
+        if let Some(now_ms) = self.timer.now_ms() {
+            if self.mac.multicast.active_class_c_window(now_ms).is_some() {
+                return true;
+            }
+        }
+        self.class_c
+    }
+
+    /// RXC configuration, using the parameters of a Class C multicast session during its window.
+    #[cfg(feature = "class-c")]
+    fn rxc_config(&self) -> RxConfig {
+        #[cfg(feature = "multicast")]
+        if let Some(now_ms) = self.timer.now_ms() {
+            if let Some(config) = self.mac.get_multicast_rxc_config(now_ms) {
+                return config;
+            }
+        }
+        self.mac.get_rxc_config()
+    }
+
     async fn window_complete(&mut self) -> Result<(), Error<R::PhyError>> {
         #[cfg(feature = "class-c")]
-        if self.class_c {
-            let rf_config = self.mac.get_rxc_config();
+        if self.rxc_enabled() {
+            let rf_config = self.rxc_config();
             return self.radio.setup_rx(rf_config).await.map_err(Error::Radio);
         }
 
@@ -403,7 +685,7 @@ where
         use self::radio::RxQuality;
         use futures::{future::select, future::Either, pin_mut};
 
-        if !self.class_c {
+        if !self.rxc_enabled() {
             self.radio.low_power().await.map_err(Error::Radio)?;
             self.timer.at(duration.into()).await;
             return Ok(None);
@@ -445,10 +727,13 @@ where
         }
 
         // Class C listen while waiting for the window
-        let rx_config = self.mac.get_rxc_config();
+        let rx_config = self.rxc_config();
         debug!("Configuring RXC window with config {}.", rx_config);
         self.radio.setup_rx(rx_config).await.map_err(Error::Radio)?;
         let mut response = None;
+        // The timer is busy while listening, frames are handled with the time the window opened
+        #[cfg(feature = "multicast")]
+        self.mac.multicast.set_now_ms(self.timer.now_ms());
         let timeout_fut = self.timer.at(duration.into());
         pin_mut!(timeout_fut);
         let mut maybe_timeout_fut = Some(timeout_fut);
@@ -513,6 +798,13 @@ where
         frame: &Frame,
         window_delay: u32,
     ) -> Result<mac::Response, Error<R::PhyError>> {
+        self.mac.uplink_transmitted(self.timer.now_ms());
+        if let (Frame::Data, Some(metadata)) = (frame, self.mac.uplink_metadata) {
+            // Time on air and RX1 delay pass at least, which accounts time without a clock
+            let rx1_delay = self.mac.get_rx_delay(frame, &Window::_1);
+            let ms = metadata.time_on_air_us.div_ceil(1000) + rx1_delay;
+            self.rejoin.duty_cycle().elapsed(ms.into());
+        }
         self.radio_buffer.clear();
 
         let rx1_start_delay = self.mac.get_rx_delay(frame, &Window::_1) + window_delay
@@ -528,7 +820,8 @@ where
         debug!("Configuring RX1 window with config {}.", rx_config);
         self.radio.setup_rx(rx_config).await.map_err(Error::Radio)?;
 
-        if let Some(response) = self.rx_listen(&rx_config.rf).await? {
+        let response = self.rx_listen(Window::_1, &rx_config.rf).await?;
+        if let Some(response) = response {
             debug!("RX1 received {}", response);
             return Ok(response);
         }
@@ -545,7 +838,8 @@ where
         debug!("Configuring RX2 window with config {}.", rx_config);
         self.radio.setup_rx(rx_config).await.map_err(Error::Radio)?;
 
-        if let Some(response) = self.rx_listen(&rx_config.rf).await? {
+        let response = self.rx_listen(Window::_2, &rx_config.rf).await?;
+        if let Some(response) = response {
             debug!("RX2 received {}", response);
             return Ok(response);
         }
@@ -578,6 +872,13 @@ where
                 radio.tx(tx_config, radio_buffer.as_ref_for_read()).await.map_err(Error::Radio)?;
                 Ok(Some(mac.rx2_complete()))
             }
+            #[cfg(feature = "certification")]
+            mac::Response::TxContinuousWave { frequency, tx_power, timeout_ms } => {
+                let tx_config = mac.certification_tx_cw_config(rng, frequency, tx_power);
+                radio.tx_continuous_wave(tx_config, timeout_ms).await.map_err(Error::Radio)?;
+                radio.low_power().await.map_err(Error::Radio)?;
+                Ok(Some(mac::Response::RxComplete))
+            }
             #[cfg(feature = "multicast")]
             mac::Response::Multicast(mut response) => {
                 if response.is_transmit_request() {
@@ -607,18 +908,24 @@ where
 
     async fn rx_listen(
         &mut self,
+        window: Window,
         rf_config: &RfConfig,
     ) -> Result<Option<mac::Response>, Error<R::PhyError>> {
         let response =
             match self.radio.rx_single(self.radio_buffer.as_mut()).await.map_err(Error::Radio)? {
                 RxStatus::Rx(s, q) => {
                     self.radio_buffer.set_pos(s);
+                    #[cfg(feature = "multicast")]
+                    self.mac.multicast.set_now_ms(self.timer.now_ms());
                     let mac_response = self.mac.handle_rx::<N, D>(
                         &mut self.radio_buffer,
                         &mut self.downlink,
                         q.snr(),
                         rf_config,
                     );
+                    if !matches!(mac_response, mac::Response::NoUpdate) {
+                        self.mac.downlink_received(window, q);
+                    }
                     Self::handle_mac_response(
                         &mut self.radio_buffer,
                         &mut self.mac,
@@ -637,13 +944,54 @@ where
 
     /// When not involved in sending and RX1/RX2 windows, a class C configured device will be
     /// listening to RXC frames. The caller is expected to be awaiting this message at all times.
+    ///
+    /// While a Class C multicast session window is active, the multicast frequency and data rate
+    /// are used instead of the RXC parameters. `ListenResponse::Multicast` with
+    /// `MulticastResponse::SessionExpired` is returned once a session window has ended.
     #[cfg(feature = "class-c")]
     pub async fn rxc_listen(&mut self) -> Result<ListenResponse, Error<R::PhyError>> {
-        let rx_config = self.mac.get_rxc_config();
+        use futures::{future::select, future::Either, pin_mut};
         loop {
-            let (sz, q) =
-                self.radio.rx_continuous(self.radio_buffer.as_mut()).await.map_err(Error::Radio)?;
+            let rx_config = self.rxc_config();
+            // Time until the next start or end of a multicast session window
+            #[cfg(feature = "multicast")]
+            let event_ms = match self.timer.now_ms() {
+                Some(now_ms) => {
+                    if let Some(group_id) = self.mac.multicast.take_expired_window(now_ms) {
+                        self.window_complete().await?;
+                        return Ok(ListenResponse::Multicast(MulticastResponse::SessionExpired {
+                            group_id,
+                        }));
+                    }
+                    self.mac.multicast.next_window_event_ms(now_ms).map(|ms| ms - now_ms)
+                }
+                None => None,
+            };
+            #[cfg(not(feature = "multicast"))]
+            let event_ms: Option<u64> = None;
+
+            let received = match event_ms {
+                Some(event_ms) => {
+                    self.radio.setup_rx(rx_config).await.map_err(Error::Radio)?;
+                    self.timer.reset();
+                    let timeout_fut = self.timer.at(event_ms);
+                    let rx_fut = self.radio.rx_continuous(self.radio_buffer.as_mut());
+                    pin_mut!(timeout_fut, rx_fut);
+                    match select(rx_fut, timeout_fut).await {
+                        Either::Left((r, _)) => Some(r),
+                        Either::Right(_) => None,
+                    }
+                }
+                None => Some(self.radio.rx_continuous(self.radio_buffer.as_mut()).await),
+            };
+            // A session window has started or ended
+            let Some(received) = received else {
+                continue;
+            };
+            let (sz, q) = received.map_err(Error::Radio)?;
             self.radio_buffer.set_pos(sz);
+            #[cfg(feature = "multicast")]
+            self.mac.multicast.set_now_ms(self.timer.now_ms());
             let mac_response = self.mac.handle_rxc::<N, D>(
                 &mut self.radio_buffer,
                 &mut self.downlink,
@@ -660,6 +1008,8 @@ where
             )
             .await?
             {
+                #[cfg(feature = "certification")]
+                self.handle_device_event(&response);
                 return Ok(response.into());
             }
         }
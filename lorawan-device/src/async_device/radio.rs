@@ -1,4 +1,4 @@
-pub use crate::radio::{RfConfig, RxConfig, RxMode, RxQuality, TxConfig};
+pub use crate::radio::{FskModulation, RfConfig, RxConfig, RxMode, RxQuality, TxConfig};
 
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 pub struct Error<E>(pub E);
@@ -25,6 +25,12 @@ pub trait Timer {
 
     /// Delay for millis milliseconds
     async fn delay_ms(&mut self, millis: u64);
+
+    /// Milliseconds elapsed since an arbitrary, fixed point in time. Providing a monotonic clock
+    /// enables time-based rejoin policies and precise JoinRequest duty-cycle accounting.
+    fn now_ms(&self) -> Option<u64> {
+        None
+    }
 }
 
 /// An asynchronous radio implementation that can transmit and receive data.
@@ -62,4 +68,14 @@ pub trait PhyRxTx: Sized {
     async fn low_power(&mut self) -> Result<(), Self::PhyError> {
         Ok(())
     }
+
+    /// Transmit an unmodulated carrier with the given transceiver configuration for `timeout_ms`,
+    /// as requested by the TxCwReq certification command. The default implementation does nothing.
+    async fn tx_continuous_wave(
+        &mut self,
+        _config: TxConfig,
+        _timeout_ms: u32,
+    ) -> Result<(), Self::PhyError> {
+        Ok(())
+    }
 }
@@ -17,6 +17,7 @@ mod dlchannelreq_eu868;
 mod newchannelreq_eu868;
 mod oversized_payload_eu868;
 mod rxparamsetup_eu868;
+mod ts009;
 
 /// Decrypts the payload allowing access to payload contents
 fn decrypt<T>(data: EncryptedDataPayload<T>, fcnt: u32) -> DecryptedDataPayload<T>
@@ -0,0 +1,225 @@
+//! LoRaWAN Certification Protocol (TS009) commands handled by the device
+//!
+//! Each test sends an uplink, answers it with a certification request on `fport = 224` in RX1 and
+//! checks the reaction of the device under test.
+use super::util;
+use crate::async_device::{DeviceEvent, SendResponse};
+use crate::radio::RfConfig;
+use crate::test_util::{RxTxHandler, Uplink};
+use lorawan::certification::DeviceClass;
+use lorawan::parser::{DataHeader, DataPayload, FRMPayload, PhyPayload};
+
+use super::super::radio::RadioChannel;
+use super::super::timer::TimerChannel;
+use super::super::Device;
+use super::{build_packet, decrypt};
+
+type Response = Result<SendResponse, crate::async_device::Error<&'static str>>;
+
+/// Send an uplink and answer it in RX1 using `handler`.
+async fn exchange(
+    mut device: Device,
+    radio: &RadioChannel,
+    timer: &TimerChannel,
+    handler: RxTxHandler,
+) -> (Device, Response) {
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 1, false).await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(handler).await;
+    task.await.unwrap()
+}
+
+/// FPort and decrypted FRMPayload of the last uplink.
+async fn last_uplink(radio: &RadioChannel) -> (Option<u8>, Vec<u8>) {
+    let mut uplink = radio.get_last_uplink().await;
+    match uplink.get_payload() {
+        PhyPayload::Data(DataPayload::Encrypted(data)) => {
+            let fport = data.f_port();
+            let fcnt = data.fhdr().fcnt() as u32;
+            match decrypt(data, fcnt).frm_payload() {
+                FRMPayload::Data(data) => (fport, data.to_vec()),
+                _ => panic!(),
+            }
+        }
+        _ => panic!(),
+    }
+}
+
+#[tokio::test]
+async fn package_version_req() {
+    fn package_version_req(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        build_packet(buf, "00", 1)
+    }
+    let (radio, timer, device) =
+        util::session_with_region(crate::region::EU868::new_eu868().into());
+    let (_device, response) = exchange(device, &radio, &timer, package_version_req).await;
+    assert!(response.is_ok());
+    // PackageVersionAns: PackageIdentifier = 6, PackageVersion = 1
+    assert_eq!(last_uplink(&radio).await, (Some(224), vec![0x00, 6, 1]));
+}
+
+#[tokio::test]
+async fn dut_version_req() {
+    fn dut_version_req(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        build_packet(buf, "7f", 1)
+    }
+    let (radio, timer, device) =
+        util::session_with_region(crate::region::EU868::new_eu868().into());
+    let (_device, response) = exchange(device, &radio, &timer, dut_version_req).await;
+    assert!(response.is_ok());
+    let (fport, data) = last_uplink(&radio).await;
+    assert_eq!(fport, Some(224));
+    assert_eq!(data.len(), 13);
+    assert_eq!(data[0], 0x7f);
+    // LoRaWAN 1.0.4 and RP002-1.0.4
+    assert_eq!(&data[5..], &[1, 0, 4, 0, 2, 1, 0, 4]);
+}
+
+#[tokio::test]
+async fn rx_app_cnt_reset_req() {
+    fn rx_app_cnt_reset_req(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        build_packet(buf, "0a", 1)
+    }
+    fn rx_app_cnt_req(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        build_packet(buf, "09", 2)
+    }
+    let (radio, timer, device) =
+        util::session_with_region(crate::region::EU868::new_eu868().into());
+    let (device, response) = exchange(device, &radio, &timer, rx_app_cnt_reset_req).await;
+    assert!(response.is_ok());
+    assert_eq!(device.mac.get_session().unwrap().rx_app_cnt, 0);
+
+    // The counter restarts with the RxAppCntReq itself
+    let (_device, response) = exchange(device, &radio, &timer, rx_app_cnt_req).await;
+    assert!(response.is_ok());
+    assert_eq!(last_uplink(&radio).await, (Some(224), vec![0x09, 1, 0]));
+}
+
+#[tokio::test]
+async fn tx_cw_req() {
+    fn tx_cw_req(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        // 60 s at 868.1 MHz with 14 dBm
+        build_packet(buf, "7d3c002876840e", 1)
+    }
+    let (radio, timer, device) =
+        util::session_with_region(crate::region::EU868::new_eu868().into());
+    let (_device, response) = exchange(device, &radio, &timer, tx_cw_req).await;
+    assert!(matches!(response, Ok(SendResponse::RxComplete)));
+    let (tx_config, timeout_ms) = radio.get_last_cw().await.unwrap();
+    assert_eq!(tx_config.rf.frequency, 868_100_000);
+    assert_eq!(tx_config.pw, 14);
+    assert_eq!(timeout_ms, 60_000);
+}
+
+#[tokio::test]
+async fn switch_class_req() {
+    fn switch_class_req(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        build_packet(buf, "0302", 1)
+    }
+    let (radio, timer, device) =
+        util::session_with_region(crate::region::EU868::new_eu868().into());
+    let (_device, response) = exchange(device, &radio, &timer, switch_class_req).await;
+    assert!(matches!(
+        response,
+        Ok(SendResponse::DeviceEvent(DeviceEvent::SwitchClass { class: DeviceClass::C }))
+    ));
+    #[cfg(feature = "class-c")]
+    assert!(_device.class_c);
+}
+
+#[tokio::test]
+async fn dut_fport_224_disable_req() {
+    fn dut_fport_224_disable_req(
+        _uplink: Option<Uplink>,
+        _config: RfConfig,
+        buf: &mut [u8],
+    ) -> usize {
+        build_packet(buf, "7e", 1)
+    }
+    fn application_downlink(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        build_packet(buf, "00", 2)
+    }
+    let (radio, timer, mut device) =
+        util::session_with_region(crate::region::EU868::new_eu868().into());
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 1, false).await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(dut_fport_224_disable_req).await;
+    // Nothing to be answered, RX2 is still opened once the frame has been handled
+    tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::RxComplete)));
+    assert!(device.take_downlink().is_none());
+
+    // FPort 224 is now an ordinary application port
+    let (mut device, response) = exchange(device, &radio, &timer, application_downlink).await;
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(2))));
+    let downlink = device.take_downlink().unwrap();
+    assert_eq!((downlink.fport, downlink.data.as_slice()), (224, &[0x00][..]));
+}
+
+/// Request and FOpts of the preceding uplink for the current step of `scripted_sequence`.
+static SCRIPT_STEP: std::sync::Mutex<(&str, u16, Vec<u8>)> =
+    std::sync::Mutex::new(("", 0, Vec::new()));
+
+fn scripted_request(uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+    let mut step = SCRIPT_STEP.lock().unwrap();
+    if let PhyPayload::Data(DataPayload::Encrypted(data)) = uplink.unwrap().get_payload() {
+        step.2 = data.fhdr().data().to_vec();
+    }
+    build_packet(buf, step.0, step.1)
+}
+
+/// Certification request, expected FOpts of the uplink it answers and expected FPort 224 answer.
+type ScriptStep = (&'static str, &'static [u8], Option<&'static [u8]>);
+
+/// Sequence of certification requests in the style of the LoRaWAN Certification Test Tool: every
+/// uplink is answered by the next request, checking both the FOpts of the uplink and the FPort
+/// 224 answer of the device.
+#[tokio::test]
+async fn scripted_sequence() {
+    #[rustfmt::skip]
+    let script: [ScriptStep; 9] = [
+        // PackageVersionReq
+        ("00", &[], Some(&[0x00, 6, 1])),
+        // RxAppCntResetReq
+        ("0a", &[], None),
+        // EchoIncPayloadReq
+        ("08010203", &[], Some(&[0x08, 2, 3, 4])),
+        // RxAppCntReq: counts the EchoIncPayloadReq and itself
+        ("09", &[], Some(&[0x09, 2, 0])),
+        // DeviceTimeReq
+        ("21", &[], None),
+        // PingSlotInfoReq, while the uplink carries MAC DeviceTimeReq
+        ("2205", &[0x0d], None),
+        // TxFramesCtrlReq: confirmed uplinks, while the uplink carries MAC PingSlotInfoReq
+        ("0702", &[0x10, 5], None),
+        // BeaconCntReq
+        ("41", &[], Some(&[0x41, 0, 0])),
+        // TxFramesCtrlReq: unconfirmed uplinks
+        ("0701", &[], None),
+    ];
+    let (radio, timer, mut device) =
+        util::session_with_region(crate::region::EU868::new_eu868().into());
+    for (fcnt, (request, fopts, answer)) in script.into_iter().enumerate() {
+        *SCRIPT_STEP.lock().unwrap() = (request, fcnt as u16 + 1, Vec::new());
+        let response;
+        (device, response) = exchange(device, &radio, &timer, scripted_request).await;
+        assert!(response.is_ok(), "step {fcnt}: {response:?}");
+        assert_eq!(SCRIPT_STEP.lock().unwrap().2, fopts, "step {fcnt}");
+        let (fport, data) = last_uplink(&radio).await;
+        match answer {
+            Some(answer) => assert_eq!((fport, data.as_slice()), (Some(224), answer)),
+            None => assert_eq!((fport, data.as_slice()), (Some(1), &[1, 2, 3][..])),
+        }
+    }
+    let session = device.mac.get_session().unwrap();
+    assert_eq!(session.override_confirmed, Some(false));
+}
@@ -1,25 +1,10 @@
 use super::util;
 use crate::async_device::{ListenResponse, SendResponse};
 use crate::radio::RfConfig;
-use crate::test_util::{get_key, Uplink};
+use crate::test_util::{class_c_downlink, get_key, Uplink};
 use lorawan::creator::DataPayloadCreator;
 use lorawan::default_crypto::DefaultFactory;
 
-pub fn class_c_downlink<const FCNT_DOWN: u32>(
-    _uplink: Option<Uplink>,
-    _config: RfConfig,
-    rx_buffer: &mut [u8],
-) -> usize {
-    let mut phy = DataPayloadCreator::new(rx_buffer).unwrap();
-    phy.set_f_port(3);
-    phy.set_dev_addr(&[0; 4]);
-    phy.set_uplink(false);
-    phy.set_fcnt(FCNT_DOWN);
-
-    let finished =
-        phy.build(&[1, 2, 3], [], &get_key().into(), &get_key().into(), &DefaultFactory).unwrap();
-    finished.len()
-}
 #[tokio::test]
 async fn test_class_c_data_before_rx1() {
     let (radio, timer, mut async_device) = util::setup_with_session_class_c().await;
@@ -98,3 +83,64 @@ async fn test_class_c_async_down() {
     }
     let _ = device.take_downlink().unwrap();
 }
+
+fn rx_param_setup_req(uplink: Option<Uplink>, _config: RfConfig, rx_buffer: &mut [u8]) -> usize {
+    let _ = uplink.unwrap();
+    let mut phy = DataPayloadCreator::new(rx_buffer).unwrap();
+    phy.set_f_port(0);
+    phy.set_dev_addr(&[0; 4]);
+    phy.set_uplink(false);
+    phy.set_fcnt(1);
+    // RXParamSetupReq: RX1DROffset 0, RX2 DR8, 923.9 MHz
+    let finished = phy
+        .build(
+            &[],
+            [0x05, 0x08, 0xd8, 0xf9, 0x8c],
+            &get_key().into(),
+            &get_key().into(),
+            &DefaultFactory,
+        )
+        .unwrap();
+    finished.len()
+}
+
+#[tokio::test]
+async fn test_class_c_rx_param_setup_uplink() {
+    use crate::radio::RxMode;
+    use lorawan::parser::{DataHeader, DataPayload, FRMPayload, PhyPayload};
+
+    let (radio, timer, mut async_device) = util::setup_with_session_class_c().await;
+    let task = tokio::spawn(async move {
+        let response = async_device.send(&[1, 2, 3], 3, false).await;
+        (async_device, response)
+    });
+    // Trigger beginning of RX1
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(rx_param_setup_req).await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(15)).await;
+
+    // An uplink carrying RXParamSetupAns follows right away
+    timer.fire_most_recent().await;
+    let mut uplink = radio.get_last_uplink().await;
+    let PhyPayload::Data(DataPayload::Encrypted(data)) = uplink.get_payload() else {
+        panic!("Did not decode PhyPayload::Data!");
+    };
+    assert_eq!(data.fhdr().fcnt(), 2);
+    let data = data.decrypt(Some(&get_key().into()), None, 2, &DefaultFactory).unwrap();
+    assert_eq!(data.f_port(), Some(0));
+    let FRMPayload::MACCommands(cmds) = data.frm_payload() else {
+        panic!("No MAC commands in FRMPayload");
+    };
+    assert_eq!(cmds.data(), [0x05, 0x07]);
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+
+    let (_device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(1))));
+
+    // RXC window is restarted with the new RX2 parameters
+    let rx_config = radio.get_rxconfig().await.unwrap();
+    assert!(matches!(rx_config.mode, RxMode::Continuous));
+    assert_eq!(rx_config.rf.frequency, 923_900_000);
+}
@@ -388,6 +388,7 @@ fn newchannelreq_invalid_eu868(
     build_frm_payload(buf, "0700184f84500701184f84500702184f8450", 2)
 }
 
+#[cfg(feature = "region-eu868")]
 fn newchannelreq_invalid_eu868_dr(
     _uplink: Option<Uplink>,
     _config: RfConfig,
@@ -564,3 +565,224 @@ async fn maccommands_in_frmpayload() {
         panic!("Session not joined?");
     }
 }
+
+/// Decrypt an uplink and provide its FPort, FOpts and FRMPayload.
+#[cfg(feature = "region-eu868")]
+fn decrypt_uplink(mut uplink: Uplink) -> (Option<u8>, std::vec::Vec<u8>, std::vec::Vec<u8>) {
+    use lorawan::parser::{DataHeader, DataPayload, FRMPayload, PhyPayload};
+    let PhyPayload::Data(DataPayload::Encrypted(data)) = uplink.get_payload() else {
+        panic!("Did not decode PhyPayload::Data!");
+    };
+    let fcnt = data.fhdr().fcnt() as u32;
+    let data = data
+        .decrypt(Some(&get_key().into()), Some(&get_key().into()), fcnt, &DefaultFactory)
+        .unwrap();
+    let frm_payload = match data.frm_payload() {
+        FRMPayload::Data(d) => d.to_vec(),
+        FRMPayload::MACCommands(cmds) => cmds.data().to_vec(),
+        FRMPayload::None => std::vec::Vec::new(),
+    };
+    (data.f_port(), data.fhdr().data().to_vec(), frm_payload)
+}
+
+#[tokio::test]
+#[cfg(feature = "region-eu868")]
+async fn mac_commands_exceeding_fopts_space() {
+    let (radio, timer, mut device) =
+        util::session_with_region(crate::region::EU868::new_eu868().into());
+
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+
+    fn addreq_chain(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        build_frm_payload(buf, "034401000003310000610350000001", 1)
+    }
+
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(addreq_chain).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(_))));
+    assert_eq!(device.mac.get_session().unwrap().uplink.mac_commands(), [3, 6, 3, 6, 3, 6]);
+
+    // Payload of maximum size is rejected when exceeding the limit by a single byte
+    let max_len = device.get_max_payload_len();
+    assert_eq!(max_len, 51);
+    let payload = [0xaa; 52];
+    let response = device.send(&payload, 3, false).await;
+    assert!(matches!(
+        response,
+        Err(crate::async_device::Error::Mac(crate::mac::Error::PayloadTooLarge { max_len: 51 }))
+    ));
+
+    let task = tokio::spawn(async move {
+        let response = device.send(&payload[..51], 3, false).await;
+        (device, response)
+    });
+    // Application uplink without FOpts
+    timer.fire_most_recent().await;
+    let (fport, fopts, frm_payload) = decrypt_uplink(radio.get_last_uplink().await);
+    assert_eq!(fport, Some(3));
+    assert!(fopts.is_empty());
+    assert_eq!(frm_payload, [0xaa; 51]);
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::MacUplinkPending)));
+    assert!(device.mac_uplink_due());
+
+    let task = tokio::spawn(async move {
+        let response = device.flush_mac_commands().await;
+        (device, response)
+    });
+    // MAC commands follow in a dedicated FPort 0 uplink
+    timer.fire_most_recent().await;
+    let (fport, fopts, frm_payload) = decrypt_uplink(radio.get_last_uplink().await);
+    assert_eq!(fport, Some(0));
+    assert!(fopts.is_empty());
+    assert_eq!(frm_payload, [3, 6, 3, 6, 3, 6]);
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::RxComplete)));
+    assert!(!device.mac_uplink_due());
+    let session = device.get_session().unwrap();
+    assert_eq!(session.fcnt_up, 3);
+    assert!(session.uplink.mac_commands().is_empty());
+}
+
+#[tokio::test]
+#[cfg(feature = "region-eu868")]
+async fn mac_commands_exceeding_fopts_length() {
+    let (radio, timer, mut device) =
+        util::session_with_region(crate::region::EU868::new_eu868().into());
+
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+
+    fn many_commands(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        // 3x LinkADRReq, 3x NewChannelReq, RXTimingSetupReq, DevStatusReq
+        build_frm_payload(
+            buf,
+            "0344010000033100006103500000010700184f84500701184f84500702184f8450080106",
+            1,
+        )
+    }
+
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(many_commands).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(_))));
+    assert_eq!(device.mac.get_session().unwrap().uplink.mac_commands().len(), 16);
+
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    // FOpts carry as many answers as fit
+    timer.fire_most_recent().await;
+    let (fport, fopts, _) = decrypt_uplink(radio.get_last_uplink().await);
+    assert_eq!(fport, Some(3));
+    assert_eq!(fopts, [3, 6, 3, 6, 3, 6, 7, 0, 7, 0, 7, 0, 8]);
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::MacUplinkPending)));
+
+    let task = tokio::spawn(async move {
+        let response = device.flush_mac_commands().await;
+        (device, response)
+    });
+    // Remaining DevStatusAns follows in FPort 0 uplink along with the sticky RXTimingSetupAns
+    timer.fire_most_recent().await;
+    let (fport, fopts, frm_payload) = decrypt_uplink(radio.get_last_uplink().await);
+    assert_eq!(fport, Some(0));
+    assert!(fopts.is_empty());
+    assert_eq!(frm_payload[..3], [8, 6, 255]);
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::RxComplete)));
+    // RXTimingSetupAns is retained until a downlink is received
+    assert_eq!(device.mac.get_session().unwrap().uplink.mac_commands(), [8]);
+
+    let task = tokio::spawn(async move {
+        let response = device.flush_mac_commands().await;
+        (device, response)
+    });
+
+    fn empty_downlink(uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        let (fport, fopts, frm_payload) = decrypt_uplink(uplink.unwrap());
+        assert_eq!(fport, Some(0));
+        assert!(fopts.is_empty());
+        assert_eq!(frm_payload, [8]);
+        build_frm_payload(buf, "", 2)
+    }
+
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(empty_downlink).await;
+    let (device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(2))));
+    assert!(device.mac.get_session().unwrap().uplink.mac_commands().is_empty());
+}
+
+#[tokio::test]
+async fn flush_without_mac_commands() {
+    let (_radio, _timer, mut device) = util::setup_with_session();
+    assert!(device.mac.get_session().unwrap().uplink.mac_commands().is_empty());
+    // No uplink is sent, which would otherwise block waiting for the timer
+    let response = device.flush_mac_commands().await;
+    assert!(matches!(response, Ok(SendResponse::RxComplete)));
+    assert_eq!(device.get_session().unwrap().fcnt_up, 0);
+}
+
+#[tokio::test]
+#[cfg(all(feature = "region-au915", feature = "region-us915"))]
+async fn txparamsetupreq_uplink_dwell_time() {
+    fn txparamsetupreq(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        // TXParamSetupReq - uplink dwell time, MaxEIRP 8 dBm
+        build_frm_payload(buf, "0910", 1)
+    }
+
+    // AU915 limits DR2 from 51 to 11 bytes of application payload with the uplink dwell time
+    let (radio, timer, mut device) =
+        util::session_with_region(crate::region::AU915::default().into());
+    device.set_datarate(crate::region::DR::_2);
+    assert_eq!(device.get_max_payload_len(), 51);
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(txparamsetupreq).await;
+    let (device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(_))));
+    assert_eq!(device.mac.get_session().unwrap().uplink.mac_commands(), [9]);
+    assert_eq!(device.get_max_payload_len(), 11);
+
+    // US915 ignores the command
+    let (radio, timer, mut device) =
+        util::session_with_region(crate::region::US915::default().into());
+    device.set_datarate(crate::region::DR::_2);
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(txparamsetupreq).await;
+    let (device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(_))));
+    assert!(device.mac.get_session().unwrap().uplink.mac_commands().is_empty());
+    assert_eq!(device.get_max_payload_len(), 125);
+}
@@ -17,15 +17,20 @@ use radio::TestRadio;
 mod util;
 pub(crate) use util::{setup, setup_with_session};
 
-#[cfg(feature = "certification")]
+#[cfg(all(feature = "certification", feature = "region-eu868"))]
 mod certification;
 
 mod maccommands;
 
+mod rejoin;
+
+#[cfg(feature = "region-eu868")]
+mod simulator;
+
 #[cfg(feature = "class-c")]
 mod class_c;
 
-#[cfg(feature = "multicast")]
+#[cfg(all(feature = "multicast", feature = "class-c"))]
 mod multicast;
 
 type Device = crate::async_device::Device<TestRadio, TestTimer, rand_core::OsRng, 512, 4>;
@@ -74,6 +79,33 @@ async fn test_join_rx2() {
     }
 }
 
+#[tokio::test]
+async fn test_join_uplink_metadata() {
+    let (radio, timer, mut async_device) = setup();
+    let task = tokio::spawn(async move {
+        let response = async_device.join(&get_otaa_credentials()).await;
+        (async_device, response)
+    });
+    // Trigger beginning of RX1
+    timer.fire_most_recent().await;
+    // Trigger end of RX1
+    radio.handle_timeout().await;
+    // Trigger start of RX2
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(handle_join_request::<7>).await;
+
+    let (device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(JoinResponse::JoinSuccess)));
+    let metadata = device.get_uplink_metadata().unwrap();
+    assert_eq!(metadata.fcnt_up, None);
+    assert_eq!(metadata.data_rate, Some(region::DR::_0));
+    assert!(metadata.time_on_air_us > 0);
+    // TestTimer provides no clock
+    assert_eq!(metadata.tx_done_ms, None);
+    assert_eq!(metadata.rx_window, Some(mac::Window::_2));
+    assert!(metadata.rx_quality.is_some());
+}
+
 #[tokio::test]
 async fn test_no_join_accept() {
     let (radio, timer, mut async_device) = setup();
@@ -157,7 +189,7 @@ async fn test_confirmed_uplink_no_ack() {
     radio.handle_timeout().await;
 
     match async_device.await.unwrap() {
-        Ok(SendResponse::NoAck) => (),
+        Ok(SendResponse::NoAck { attempts: 1 }) => (),
         _ => panic!(),
     }
     assert!(*send_await_complete.lock().await);
@@ -225,6 +257,75 @@ async fn test_confirmed_uplink_with_ack_rx2() {
     }
 }
 
+#[tokio::test]
+async fn test_confirmed_uplink_retransmission() {
+    let (radio, timer, mut async_device) = setup_with_session();
+    async_device.set_retransmission_policy(Some(RetransmissionPolicy {
+        max_attempts: 2,
+        data_rate_step_down: false,
+    }));
+
+    // Run the device
+    let async_device = tokio::spawn(async move {
+        let response = async_device.send(&[1, 2, 3], 3, true).await;
+        (async_device, response)
+    });
+    // Trigger beginning of RX1
+    timer.fire_most_recent().await;
+    // Trigger end of RX1
+    radio.handle_timeout().await;
+    // Trigger start of RX2
+    timer.fire_most_recent().await;
+    // Trigger end of RX2
+    radio.handle_timeout().await;
+    // Trigger end of ACK timeout
+    timer.fire_most_recent().await;
+    // Trigger beginning of RX1
+    timer.fire_most_recent().await;
+    // Retransmission carries the same FCntUp
+    radio.handle_rxtx(handle_data_uplink_with_link_adr_req::<0, 0>).await;
+
+    let (mut device, response) = async_device.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(0))));
+    assert_eq!(device.get_retransmission_attempts(), 2);
+    assert_eq!(device.get_session().unwrap().fcnt_up, 1);
+    assert_eq!(timer.get_armed_count().await, 4);
+}
+
+#[tokio::test]
+async fn test_confirmed_uplink_retransmission_step_down() {
+    let (radio, timer, mut async_device) = setup_with_session();
+    async_device.set_datarate(DR::_3);
+    async_device.set_retransmission_policy(Some(RetransmissionPolicy {
+        max_attempts: 3,
+        data_rate_step_down: true,
+    }));
+
+    // Run the device
+    let async_device = tokio::spawn(async move {
+        let response = async_device.send(&[1, 2, 3], 3, true).await;
+        (async_device, response)
+    });
+    for attempt in 0..3 {
+        if attempt > 0 {
+            // Trigger end of ACK timeout
+            timer.fire_most_recent().await;
+        }
+        // RX1 and RX2 without downlink
+        timer.fire_most_recent().await;
+        radio.handle_timeout().await;
+        timer.fire_most_recent().await;
+        radio.handle_timeout().await;
+    }
+
+    let (mut device, response) = async_device.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::NoAck { attempts: 3 })));
+    // Only the third transmission uses DR2 (SF8), the configured data rate is kept
+    let uplink = radio.get_last_uplink().await;
+    assert_eq!(uplink.tx_config.rf.bb.sf, lora_modulation::SpreadingFactor::_8);
+    assert_eq!(device.get_datarate(), DR::_3);
+}
+
 #[tokio::test]
 async fn test_link_adr_ans() {
     let (radio, timer, mut async_device) = setup_with_session();
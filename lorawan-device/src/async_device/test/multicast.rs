@@ -1,85 +1,7 @@
 use super::*;
 use crate::async_device::McAddr;
-use lorawan::creator::DataPayloadCreator;
-use lorawan::keys::{McKEKey, McKey};
-use lorawan::multicast::{
-    parse_uplink_multicast_messages, McGroupDeleteReqCreator, McGroupSetupReqCreator,
-    UplinkRemoteSetup,
-};
-use lorawan::parser::{DataHeader, DataPayload, FRMPayload, PhyPayload};
-
-fn handle_multicast_setup_req(
-    _uplink: Option<Uplink>,
-    _config: RfConfig,
-    rx_buffer: &mut [u8],
-) -> usize {
-    let mut req = McGroupSetupReqCreator::new();
-    let mc_addr = McAddr::from([52, 110, 29, 60]);
-    let mc_key = McKey::from([0x44; 16]);
-    let mcke_key = McKEKey::from([0x66; 16]);
-
-    req.mc_group_id_header(0x01);
-    req.mc_addr(&mc_addr);
-    req.mc_key(&DefaultFactory, &mc_key, &mcke_key);
-    req.min_mc_fcount(0x12345678);
-    req.max_mc_fcount(0x87654321);
-    let setup_req = req.build();
-
-    // Create a downlink frame containing the McGroupSetupReq
-    let mut phy = DataPayloadCreator::new(rx_buffer).unwrap();
-    phy.set_f_port(200); // Remote multicast setup port
-    phy.set_dev_addr(&[0; 4]);
-    phy.set_uplink(false);
-    phy.set_fcnt(0);
-
-    let finished =
-        phy.build(setup_req, [], &get_key().into(), &get_key().into(), &DefaultFactory).unwrap();
-    finished.len()
-}
-
-fn verify_multicast_message(
-    uplink: Option<Uplink>,
-    expected_port: u8,
-    verify_payload: impl FnOnce(&[u8]) -> bool,
-) -> usize {
-    let mut uplink = uplink.unwrap();
-    let payload = uplink.get_payload();
-    if let PhyPayload::Data(DataPayload::Encrypted(data)) = payload {
-        let fcnt = data.fhdr().fcnt() as u32;
-        assert!(data.validate_mic(&get_key().into(), fcnt, &DefaultFactory));
-        let uplink = data
-            .decrypt(Some(&get_key().into()), Some(&get_key().into()), fcnt, &DefaultFactory)
-            .unwrap();
-        assert_eq!(uplink.f_port().unwrap(), expected_port);
-
-        if let FRMPayload::Data(ans_data) = uplink.frm_payload() {
-            assert!(verify_payload(ans_data));
-        } else {
-            panic!("Expected data payload");
-        }
-        0
-    } else {
-        panic!("Expected encrypted data payload");
-    }
-}
-
-fn verify_multicast_setup_ans(
-    uplink: Option<Uplink>,
-    _config: RfConfig,
-    _rx_buffer: &mut [u8],
-) -> usize {
-    verify_multicast_message(uplink, 200, |ans_data| {
-        let mut msgs = parse_uplink_multicast_messages(ans_data);
-        let msg = msgs.next().unwrap();
-        if let UplinkRemoteSetup::McGroupSetupAns(ans) = msg {
-            assert_eq!(ans.mc_group_id_header(), 0x01);
-        } else {
-            panic!("Expected McGroupSetupAns");
-        }
-        assert!(msgs.next().is_none());
-        true
-    })
-}
+use crate::test_util::multicast::*;
+use lorawan::keys::McKEKey;
 
 #[tokio::test]
 async fn test_multicast_remote_setup() {
@@ -121,62 +43,6 @@ async fn test_multicast_remote_setup() {
     }
 }
 
-fn handle_mc_group_delete_req<const GROUP_ID: u8>(
-    _uplink: Option<Uplink>,
-    _config: RfConfig,
-    rx_buffer: &mut [u8],
-) -> usize {
-    let mut req = McGroupDeleteReqCreator::new();
-    req.mc_group_id_header(GROUP_ID);
-    let setup_req = req.build();
-
-    // Create a downlink frame containing the McGroupDeleteReq
-    let mut phy = DataPayloadCreator::new(rx_buffer).unwrap();
-    phy.set_f_port(200); // Remote multicast setup port
-    phy.set_dev_addr(&[0; 4]);
-    phy.set_uplink(false);
-    phy.set_fcnt(1);
-
-    let finished =
-        phy.build(setup_req, [], &get_key().into(), &get_key().into(), &DefaultFactory).unwrap();
-    finished.len()
-}
-
-fn verify_mc_group_delete_ans(
-    uplink: Option<Uplink>,
-    _config: RfConfig,
-    _rx_buffer: &mut [u8],
-) -> usize {
-    verify_multicast_message(uplink, 200, |ans_data| {
-        let mut msgs = parse_uplink_multicast_messages(ans_data);
-        let msg = msgs.next().unwrap();
-        if let UplinkRemoteSetup::McGroupDeleteAns(ans) = msg {
-            assert_eq!(ans.mc_group_id_header(), 0x01);
-            assert!(!ans.mc_group_undefined());
-        } else {
-            panic!("Expected McGroupDeleteAns");
-        }
-        assert!(msgs.next().is_none());
-        true
-    })
-}
-
-fn handle_regular_downlink_msg<const FCNT: u32>(
-    _uplink: Option<Uplink>,
-    _config: RfConfig,
-    rx_buffer: &mut [u8],
-) -> usize {
-    let mut phy = DataPayloadCreator::new(rx_buffer).unwrap();
-    phy.set_f_port(1); // a random fport that's not the multicast port
-    phy.set_dev_addr(&[0; 4]);
-    phy.set_uplink(false);
-    phy.set_fcnt(FCNT);
-
-    let finished =
-        phy.build(&[1, 2, 3], [], &get_key().into(), &get_key().into(), &DefaultFactory).unwrap();
-    finished.len()
-}
-
 #[tokio::test]
 async fn test_multicast_group_delete() {
     let (radio, _timer, mut async_device) = util::setup_with_session_class_c().await;
@@ -206,25 +72,6 @@ async fn test_multicast_group_delete() {
     let _ = task.await.unwrap();
 }
 
-fn verify_mc_group_delete_ans_undefined(
-    uplink: Option<Uplink>,
-    _config: RfConfig,
-    _rx_buffer: &mut [u8],
-) -> usize {
-    verify_multicast_message(uplink, 200, |ans_data| {
-        let mut msgs = parse_uplink_multicast_messages(ans_data);
-        let msg = msgs.next().unwrap();
-        if let UplinkRemoteSetup::McGroupDeleteAns(ans) = msg {
-            assert_eq!(ans.mc_group_id_header(), 0x00);
-            assert!(ans.mc_group_undefined());
-        } else {
-            panic!("Expected McGroupDeleteAns");
-        }
-        assert!(msgs.next().is_none());
-        true
-    })
-}
-
 #[tokio::test]
 async fn test_multicast_invalid_group_delete() {
     let (radio, _timer, mut async_device) = util::setup_with_session_class_c().await;
@@ -253,3 +100,204 @@ async fn test_multicast_invalid_group_delete() {
     radio.handle_rxtx(handle_regular_downlink_msg::<2>).await;
     let _ = task.await.unwrap();
 }
+
+#[tokio::test]
+async fn test_multicast_session_req_without_clock() {
+    let (radio, _timer, mut async_device) = util::setup_with_session_class_c().await;
+    let mcke_key = McKEKey::from([0x66; 16]);
+    async_device.mac.multicast.mc_k_e_key = Some(mcke_key);
+
+    let task = tokio::spawn(async move {
+        let response = async_device.rxc_listen().await;
+        (async_device, response)
+    });
+    radio.handle_rxtx(handle_multicast_setup_req).await;
+    radio.handle_rxtx(verify_multicast_setup_ans).await;
+    let (mut device, _) = task.await.unwrap();
+
+    let task = tokio::spawn(async move {
+        let response = device.rxc_listen().await;
+        (device, response)
+    });
+    // The test timer provides no time, hence the window of the session cannot be followed
+    radio.handle_rxtx(handle_mc_class_c_session_req::<0x01, 1>).await;
+    radio.handle_rxtx(verify_mc_class_c_session_ans::<0x11>).await;
+    // Group 3 has not been set up
+    radio.handle_rxtx(handle_mc_class_c_session_req::<0x03, 2>).await;
+    radio.handle_rxtx(verify_mc_class_c_session_ans::<0x13>).await;
+    // Class B is not supported
+    radio.handle_rxtx(handle_mc_class_b_session_req::<0x01, 3>).await;
+    radio.handle_rxtx(verify_mc_class_b_session_ans::<0x11>).await;
+    radio.handle_rxtx(handle_regular_downlink_msg::<4>).await;
+    let (device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(ListenResponse::DownlinkReceived(4))));
+    assert!(device.get_multicast_session_window(McGroup::_1).is_none());
+}
+
+#[tokio::test]
+#[cfg(feature = "region-eu868")]
+async fn test_multicast_session_window() {
+    use crate::async_device::McSessionWindow;
+    use crate::test_utils::{NetworkServer, Simulator};
+    use lorawan::keys::{McAppSKey, McNetSKey};
+    use lorawan::multicast::{McClassCSessionReqCreator, Session};
+
+    let region = region::Configuration::new(region::Region::EU868);
+    let sim = Simulator::new(NetworkServer::new(region.clone(), 0));
+    let mut device: crate::async_device::Device<_, _, _> =
+        crate::async_device::Device::new_with_seed(region, sim.radio(), sim.timer(), 0);
+    let session = Session::new(
+        McAddr::from([1, 2, 3, 4]),
+        McNetSKey::from([0x11; 16]),
+        McAppSKey::from([0x22; 16]),
+        0,
+        u32::MAX,
+    );
+    device.mac.multicast.mc_k_e_key = Some(McKEKey::from([0x66; 16]));
+    device.set_multicast_session(McGroup::_0, session);
+    device.set_gps_time(1_000_000);
+
+    // Session starting in 10 s for 2^4 s
+    let mut req = McClassCSessionReqCreator::new();
+    req.mc_group_id_header(0)
+        .session_time(1_000_010)
+        .session_timeout(4)
+        .dl_frequency(868_500_000)
+        .data_rate(DR::_3);
+    device.mac.multicast.set_now_ms(Some(sim.now_ms()));
+    let response = device.mac.multicast.handle_setup_message(&device.mac.region, req.build());
+    assert!(response.is_transmit_request());
+    let expected = McSessionWindow {
+        start_ms: 10_000,
+        duration_ms: 16_000,
+        frequency: 868_500_000,
+        data_rate: DR::_3,
+    };
+    assert_eq!(device.get_multicast_session_window(McGroup::_0), Some(expected));
+    assert_ne!(device.rxc_config().rf.frequency, 868_500_000);
+
+    // RXC uses the multicast parameters during the window
+    sim.advance_ms(10_000);
+    assert_eq!(device.rxc_config().rf.frequency, 868_500_000);
+
+    let response = device.rxc_listen().await.unwrap();
+    assert!(matches!(
+        response,
+        ListenResponse::Multicast(MulticastResponse::SessionExpired { group_id: 0 })
+    ));
+    assert_eq!(sim.now_ms(), 26_000);
+    assert!(device.get_multicast_session_window(McGroup::_0).is_none());
+    assert_ne!(device.rxc_config().rf.frequency, 868_500_000);
+}
+
+#[tokio::test]
+async fn test_multicast_fcnt_replay() {
+    let (radio, _timer, mut device) = util::setup_with_session_class_c().await;
+    device.set_multicast_session(McGroup::_0, multicast_session(10, 100));
+
+    let task = tokio::spawn(async move {
+        let response = device.rxc_listen().await;
+        (device, response)
+    });
+    radio.handle_rxtx(handle_multicast_downlink::<10>).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(
+        response,
+        Ok(ListenResponse::Multicast(MulticastResponse::DownlinkReceived {
+            group_id: 0,
+            fcnt: 10
+        }))
+    ));
+    assert_eq!(device.take_downlink().unwrap().data, [10]);
+
+    let task = tokio::spawn(async move {
+        let response = device.rxc_listen().await;
+        (device, response)
+    });
+    // Replayed frame, frames below minMcFCount and FCnt 0 are all dropped
+    radio.handle_rxtx(handle_multicast_downlink::<10>).await;
+    radio.handle_rxtx(handle_multicast_downlink::<5>).await;
+    radio.handle_rxtx(handle_multicast_downlink::<0>).await;
+    radio.handle_rxtx(handle_multicast_downlink::<11>).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(
+        response,
+        Ok(ListenResponse::Multicast(MulticastResponse::DownlinkReceived {
+            group_id: 0,
+            fcnt: 11
+        }))
+    ));
+    assert_eq!(device.take_downlink().unwrap().data, [11]);
+    assert!(device.take_downlink().is_none());
+    assert_eq!(device.get_multicast_session(McGroup::_0).unwrap().fcnt_down, 12);
+}
+
+#[tokio::test]
+async fn test_multicast_fcnt_expiry() {
+    let (radio, _timer, mut device) = util::setup_with_session_class_c().await;
+    device.set_multicast_session(McGroup::_0, multicast_session(0, 3));
+
+    let task = tokio::spawn(async move {
+        let response = device.rxc_listen().await;
+        (device, response)
+    });
+    // FCnt 1 skips 0, which may not be received afterwards
+    radio.handle_rxtx(handle_multicast_downlink::<1>).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(
+        response,
+        Ok(ListenResponse::Multicast(MulticastResponse::DownlinkReceived { group_id: 0, fcnt: 1 }))
+    ));
+    assert_eq!(device.take_downlink().unwrap().data, [1]);
+
+    let task = tokio::spawn(async move {
+        let response = device.rxc_listen().await;
+        (device, response)
+    });
+    // The last FCnt below maxMcFCount is delivered and expires the group
+    radio.handle_rxtx(handle_multicast_downlink::<0>).await;
+    radio.handle_rxtx(handle_multicast_downlink::<2>).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(
+        response,
+        Ok(ListenResponse::Multicast(MulticastResponse::SessionExpired { group_id: 0 }))
+    ));
+    assert_eq!(device.take_downlink().unwrap().data, [2]);
+    assert!(device.get_multicast_session(McGroup::_0).unwrap().is_expired());
+
+    let task = tokio::spawn(async move {
+        let response = device.rxc_listen().await;
+        (device, response)
+    });
+    // Nothing is accepted for the group anymore
+    radio.handle_rxtx(handle_multicast_downlink::<3>).await;
+    radio.handle_rxtx(handle_multicast_downlink::<4>).await;
+    radio.handle_rxtx(handle_regular_downlink_msg::<2>).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(ListenResponse::DownlinkReceived(2))));
+    assert_eq!(device.take_downlink().unwrap().fport, 1);
+    assert!(device.take_downlink().is_none());
+}
+
+#[cfg(feature = "serde")]
+#[tokio::test]
+async fn test_multicast_session_serde() {
+    let (radio, _timer, mut device) = util::setup_with_session_class_c().await;
+    device.set_multicast_session(McGroup::_2, multicast_session(10, 100));
+
+    let task = tokio::spawn(async move {
+        let response = device.rxc_listen().await;
+        (device, response)
+    });
+    radio.handle_rxtx(handle_multicast_downlink::<20>).await;
+    let (device, _) = task.await.unwrap();
+
+    // The frame counter state survives persisting the session
+    let session = device.get_multicast_session(McGroup::_2).unwrap();
+    let json = serde_json::to_string(session).unwrap();
+    let restored: lorawan::multicast::Session = serde_json::from_str(&json).unwrap();
+    assert_eq!(&restored, session);
+    assert_eq!(restored.fcnt_down, 21);
+    assert_eq!(restored.validate_fcnt(20), None);
+    assert_eq!(restored.validate_fcnt(21), Some(21));
+}
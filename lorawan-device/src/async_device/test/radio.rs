@@ -10,21 +10,27 @@ impl TestRadio {
         let (tx, rx) = mpsc::channel(2);
         let last_rxconfig = Arc::new(Mutex::new(None));
         let last_uplink = Arc::new(Mutex::new(None));
+        let last_cw = Arc::new(Mutex::new(None));
+        let fail_tx = Arc::new(Mutex::new(false));
         (
             RadioChannel {
                 tx,
                 last_uplink: last_uplink.clone(),
                 last_rxconfig: last_rxconfig.clone(),
+                last_cw: last_cw.clone(),
+                fail_tx: fail_tx.clone(),
             },
-            Self { rx, last_rxconfig, last_uplink, current_config: None, snr: 0 },
+            Self { rx, last_rxconfig, last_uplink, last_cw, fail_tx, current_config: None, snr: 0 },
         )
     }
 
+    #[allow(unused)]
     pub fn set_snr(&mut self, snr: i8) {
         self.snr = snr
     }
 
     /// Return snr in a 6-bit scaled format as in DevStatusAns
+    #[allow(unused)]
     pub fn snr_scaled(&self) -> u8 {
         ((self.snr << 2) as u8) >> 2
     }
@@ -40,6 +46,8 @@ pub struct TestRadio {
     current_config: Option<RxConfig>,
     last_rxconfig: Arc<Mutex<Option<RxConfig>>>,
     last_uplink: Arc<Mutex<Option<Uplink>>>,
+    last_cw: Arc<Mutex<Option<(TxConfig, u32)>>>,
+    fail_tx: Arc<Mutex<bool>>,
     rx: mpsc::Receiver<Msg>,
     snr: i8,
 }
@@ -53,6 +61,9 @@ impl PhyRxTx for TestRadio {
 
     async fn tx(&mut self, config: TxConfig, buffer: &[u8]) -> Result<u32, Self::PhyError> {
         let length = buffer.len();
+        if core::mem::take(&mut *self.fail_tx.lock().await) {
+            return Err("TX failed");
+        }
         // stash the uplink, to be consumed by channel or by rx handler
         let mut last_uplink = self.last_uplink.lock().await;
         *last_uplink = Some(Uplink::new(buffer, config).map_err(|_| "Parse error")?);
@@ -104,6 +115,15 @@ impl PhyRxTx for TestRadio {
             Msg::Timeout => Ok(RxStatus::RxTimeout),
         }
     }
+
+    async fn tx_continuous_wave(
+        &mut self,
+        config: TxConfig,
+        timeout_ms: u32,
+    ) -> Result<(), Self::PhyError> {
+        *self.last_cw.lock().await = Some((config, timeout_ms));
+        Ok(())
+    }
 }
 
 impl Timings for TestRadio {
@@ -116,7 +136,11 @@ impl Timings for TestRadio {
 pub struct RadioChannel {
     #[allow(unused)]
     last_rxconfig: Arc<Mutex<Option<RxConfig>>>,
+    #[allow(unused)]
     last_uplink: Arc<Mutex<Option<Uplink>>>,
+    #[allow(unused)]
+    last_cw: Arc<Mutex<Option<(TxConfig, u32)>>>,
+    fail_tx: Arc<Mutex<bool>>,
     tx: mpsc::Sender<Msg>,
 }
 
@@ -130,13 +154,26 @@ impl RadioChannel {
         self.tx.send(Msg::Timeout).await.unwrap();
     }
 
+    #[allow(unused)]
     pub async fn get_rxconfig(&self) -> Option<RxConfig> {
         let rxconf = self.last_rxconfig.lock().await;
         *rxconf
     }
 
+    #[allow(unused)]
     pub async fn get_last_uplink(&self) -> Uplink {
         let uplink = self.last_uplink.lock().await;
         uplink.clone().unwrap()
     }
+
+    /// Make the next transmission fail with a radio error.
+    pub async fn fail_next_tx(&self) {
+        *self.fail_tx.lock().await = true;
+    }
+
+    /// Configuration and duration of the last continuous wave transmission.
+    #[allow(unused)]
+    pub async fn get_last_cw(&self) -> Option<(TxConfig, u32)> {
+        *self.last_cw.lock().await
+    }
 }
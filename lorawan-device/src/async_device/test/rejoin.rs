@@ -0,0 +1,148 @@
+use super::*;
+use super::{radio::RadioChannel, timer::TimerChannel};
+
+async fn join(radio: &RadioChannel, timer: &TimerChannel, mut device: Device) -> Device {
+    let task = tokio::spawn(async move {
+        let response = device.join(&get_otaa_credentials()).await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(handle_join_request::<5>).await;
+    let (device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(JoinResponse::JoinSuccess)));
+    device
+}
+
+/// TestTimer provides no clock, let the JoinRequest duty-cycle back-off of the join pass.
+fn pass_join_backoff(device: &mut Device) {
+    device.rejoin.duty_cycle().elapsed(3_600_000);
+}
+
+#[tokio::test]
+async fn rejoin_after_max_uplinks() {
+    let (radio, timer, device) = util::setup();
+    let mut device = join(&radio, &timer, device).await;
+    let nwkskey = *device.get_session().unwrap().nwkskey();
+    pass_join_backoff(&mut device);
+    device.set_rejoin_policy(Some(RejoinPolicy { max_uplinks: Some(1), ..Default::default() }));
+
+    let task = tokio::spawn(async move {
+        let first = device.send(&[1, 2, 3], 3, false).await;
+        let second = device.send(&[1, 2, 3], 3, false).await;
+        (device, first, second)
+    });
+    // First uplink is sent with the original session
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+
+    // Second uplink triggers a rejoin
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(handle_join_request::<6>).await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(15)).await;
+
+    // Uplink is sent within the new session
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+
+    let (mut device, first, second) = task.await.unwrap();
+    assert!(matches!(first, Ok(SendResponse::RxComplete)));
+    assert!(matches!(second, Ok(SendResponse::RxComplete)));
+    let session = device.get_session().unwrap();
+    assert_eq!(session.fcnt_up, 1);
+    assert_ne!(*session.nwkskey(), nwkskey);
+    assert_eq!(timer.get_armed_count().await, 6);
+}
+
+#[tokio::test]
+async fn failed_rejoin_keeps_session() {
+    let (radio, timer, device) = util::setup();
+    let mut device = join(&radio, &timer, device).await;
+    let nwkskey = *device.get_session().unwrap().nwkskey();
+    pass_join_backoff(&mut device);
+    device.set_rejoin_policy(Some(RejoinPolicy { max_uplinks: Some(0), ..Default::default() }));
+
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    // Rejoin attempt without any JoinAccept
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+
+    // Uplink is sent within the previous session
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::RxComplete)));
+    let session = device.get_session().unwrap();
+    assert_eq!(*session.nwkskey(), nwkskey);
+    assert_eq!(session.fcnt_up, 1);
+}
+
+#[tokio::test]
+async fn rejoin_radio_error_keeps_session() {
+    let (radio, timer, device) = util::setup();
+    let mut device = join(&radio, &timer, device).await;
+    let nwkskey = *device.get_session().unwrap().nwkskey();
+    pass_join_backoff(&mut device);
+    device.set_rejoin_policy(Some(RejoinPolicy { max_uplinks: Some(0), ..Default::default() }));
+    radio.fail_next_tx().await;
+
+    // The JoinRequest of the rejoin fails to be sent
+    let response = device.send(&[1, 2, 3], 3, false).await;
+    assert!(matches!(response, Err(Error::Radio("TX failed"))));
+    let session = device.get_session().unwrap();
+    assert_eq!(*session.nwkskey(), nwkskey);
+    assert_eq!(session.fcnt_up, 0);
+
+    // Uplink is sent within the previous session
+    device.set_rejoin_policy(None);
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::RxComplete)));
+    assert_eq!(device.get_session().unwrap().fcnt_up, 1);
+}
+
+#[tokio::test]
+async fn rejoin_deferred_without_clock() {
+    let (radio, timer, device) = util::setup();
+    let mut device = join(&radio, &timer, device).await;
+    let nwkskey = *device.get_session().unwrap().nwkskey();
+    device.set_rejoin_policy(Some(RejoinPolicy { max_uplinks: Some(0), ..Default::default() }));
+    assert!(matches!(device.rejoin_if_due().await, Ok(None)));
+
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    // The rejoin is deferred without waiting, the uplink is sent within the previous session
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::RxComplete)));
+    let session = device.get_session().unwrap();
+    assert_eq!(*session.nwkskey(), nwkskey);
+    assert_eq!(session.fcnt_up, 1);
+    // One RX window for the join, two for the uplink; no back-off delay
+    assert_eq!(timer.get_armed_count().await, 3);
+}
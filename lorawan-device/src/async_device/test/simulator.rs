@@ -0,0 +1,124 @@
+use super::*;
+use crate::mac::Window;
+use crate::test_utils::{Gateway, NetworkServer, SimRadio, SimTimer, Simulator, ADR_HISTORY_LEN};
+use crate::DevEui;
+use lorawan::types::DR;
+
+type SimDevice = crate::async_device::Device<SimRadio, SimTimer, crate::Prng>;
+
+fn simulation(seed: u64, gateway: Gateway) -> (Simulator, SimDevice) {
+    let mut ns = NetworkServer::new(region::Configuration::new(region::Region::EU868), seed);
+    let JoinMode::OTAA { deveui, appeui, appkey } = get_otaa_credentials() else { unreachable!() };
+    ns.add_otaa_device(deveui, appeui, appkey);
+    ns.add_gateway(gateway);
+    let sim = Simulator::new(ns);
+    let device = SimDevice::new_with_seed(
+        region::Configuration::new(region::Region::EU868),
+        sim.radio(),
+        sim.timer(),
+        seed,
+    );
+    (sim, device)
+}
+
+#[tokio::test]
+async fn test_simulator_join_and_downlink() {
+    let (sim, mut device) = simulation(1, Gateway::new(-80, 5));
+    let response = device.join(&get_otaa_credentials()).await.unwrap();
+    assert!(matches!(response, JoinResponse::JoinSuccess));
+    let devaddr = sim.network_server(|ns| ns.get_dev_addr(&DevEui::from([0; 8]))).unwrap();
+    assert_eq!(device.get_session().unwrap().devaddr, devaddr);
+
+    let response = device.send(&[1, 2, 3], 2, true).await.unwrap();
+    assert!(matches!(response, SendResponse::DownlinkReceived(0)));
+    let uplink = sim.network_server(|ns| ns.take_uplink()).unwrap();
+    assert_eq!((uplink.fcnt, uplink.fport, uplink.data.as_slice()), (0, Some(2), &[1, 2, 3][..]));
+    assert!(uplink.confirmed);
+
+    sim.network_server(|ns| ns.queue_downlink(&devaddr, 5, &[4, 5], false));
+    let response = device.send(&[], 2, false).await.unwrap();
+    assert!(matches!(response, SendResponse::DownlinkReceived(1)));
+    let downlink = device.take_downlink().unwrap();
+    assert_eq!((downlink.fport, downlink.data.as_slice()), (5, &[4, 5][..]));
+    assert_eq!(sim.network_server(|ns| ns.get_fcnt_down(&devaddr)), Some(2));
+    assert!(sim.now_ms() > 0);
+}
+
+#[tokio::test]
+async fn test_simulator_rx2() {
+    let (sim, mut device) = simulation(2, Gateway::new(-80, 5));
+    sim.network_server(|ns| ns.set_downlink_window(Window::_2));
+    let response = device.join(&get_otaa_credentials()).await.unwrap();
+    assert!(matches!(response, JoinResponse::JoinSuccess));
+    let metadata = device.get_uplink_metadata().unwrap();
+    assert_eq!(metadata.rx_window, Some(Window::_2));
+    // The simulated clock advances by the time on air of the JoinRequest
+    assert_eq!(metadata.tx_done_ms, Some(metadata.time_on_air_us.div_ceil(1000) as u64));
+    // JoinAccept is received after JOIN_ACCEPT_DELAY2
+    assert!(sim.now_ms() >= 6000);
+}
+
+#[tokio::test]
+async fn test_simulator_adr() {
+    let (sim, mut device) = simulation(3, Gateway::new(-80, 5));
+    sim.network_server(|ns| ns.set_adr(true));
+    device.join(&get_otaa_credentials()).await.unwrap();
+    let initial = device.get_datarate();
+    for _ in 0..ADR_HISTORY_LEN {
+        device.send(&[0], 1, false).await.unwrap();
+    }
+    // 5 dB SNR at SF12 leaves enough margin for the highest 125 kHz data rate
+    assert_ne!(initial, DR::_5);
+    assert_eq!(device.get_datarate(), DR::_5);
+
+    // LinkADRAns is piggybacked on the next uplink
+    device.send(&[0], 1, false).await.unwrap();
+    let uplink = core::iter::from_fn(|| sim.network_server(|ns| ns.take_uplink())).last().unwrap();
+    assert_eq!(uplink.mac_commands, [0x03, 0x07]);
+    assert_eq!(uplink.data_rate, Some(DR::_5));
+}
+
+#[tokio::test]
+async fn test_simulator_adr_high_snr() {
+    // The link margin exceeds the range of the SNR
+    let (sim, mut device) = simulation(3, Gateway::new(-30, i8::MAX));
+    sim.network_server(|ns| ns.set_adr(true));
+    device.join(&get_otaa_credentials()).await.unwrap();
+    for _ in 0..ADR_HISTORY_LEN {
+        device.send(&[0], 1, false).await.unwrap();
+    }
+    assert_eq!(device.get_datarate(), DR::_5);
+}
+
+#[tokio::test]
+async fn test_simulator_coverage() {
+    // Too weak to demodulate any data rate
+    let (sim, mut device) = simulation(4, Gateway::new(-130, -25));
+    let response = device.join(&get_otaa_credentials()).await.unwrap();
+    assert!(matches!(response, JoinResponse::NoJoinAccept));
+    assert_eq!(sim.network_server(|ns| ns.stats()).uplinks_lost, 1);
+
+    sim.network_server(|ns| ns.get_mut_gateway(0).unwrap().snr = -5);
+    let response = device.join(&get_otaa_credentials()).await.unwrap();
+    assert!(matches!(response, JoinResponse::JoinSuccess));
+}
+
+#[tokio::test]
+async fn test_simulator_packet_loss_is_deterministic() {
+    async fn run(seed: u64) -> crate::test_utils::Stats {
+        let (sim, mut device) = simulation(seed, Gateway::new(-80, 5));
+        device.join(&get_otaa_credentials()).await.unwrap();
+        sim.network_server(|ns| {
+            ns.set_uplink_loss(30);
+            ns.set_downlink_loss(30);
+        });
+        for _ in 0..20 {
+            device.send(&[0], 1, true).await.unwrap();
+        }
+        sim.network_server(|ns| ns.stats())
+    }
+    let stats = run(5).await;
+    assert!(stats.uplinks_lost > 0);
+    assert!(stats.downlinks_lost > 0);
+    assert_eq!(stats, run(5).await);
+}
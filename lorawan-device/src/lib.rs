@@ -23,6 +23,13 @@ pub use region::Region;
 #[cfg(test)]
 mod test_util;
 
+#[cfg(feature = "test-utils")]
+extern crate std;
+
+#[cfg(any(test, feature = "test-utils"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-utils")))]
+pub mod test_utils;
+
 pub mod async_device;
 
 pub mod nb_device;
@@ -1,18 +1,50 @@
 use crate::mac;
 use crate::radio::RadioBuffer;
-use lorawan::certification::parse_downlink_certification_messages;
+use lorawan::certification::{parse_downlink_certification_messages, DeviceClass};
+use lorawan::packet_length::phy::mac::fhdr::FOPTS_MAX_LEN;
 
 /// Certification protocol uses `fport = 224`
 pub(crate) const CERTIFICATION_PORT: u8 = 224;
 
+/// LoRaWAN specification implemented by the stack: 1.0.4
+const LORAWAN_VERSION: [u8; 4] = [1, 0, 4, 0];
+
+/// Regional parameters implemented by the stack: RP002-1.0.4 == 2.1.0.4
+const REGIONAL_PARAMETERS_VERSION: [u8; 4] = [2, 1, 0, 4];
+
+/// Version of this crate, reported as firmware version
+const FIRMWARE_VERSION: [u8; 4] = [
+    parse_version(env!("CARGO_PKG_VERSION_MAJOR")),
+    parse_version(env!("CARGO_PKG_VERSION_MINOR")),
+    parse_version(env!("CARGO_PKG_VERSION_PATCH")),
+    0,
+];
+
+const fn parse_version(s: &str) -> u8 {
+    let bytes = s.as_bytes();
+    let mut value: u8 = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        value = value * 10 + (bytes[i] - b'0');
+        i += 1;
+    }
+    value
+}
+
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 pub(crate) enum Response {
     NoUpdate,
     AdrBitChange(bool),
+    DeviceTimeReq,
     DutJoinReq,
     DutResetReq,
+    DutyCycleCtrl(bool),
     LinkCheckReq,
+    PingSlotInfoReq(u8),
+    RxAppCntReset,
+    SwitchClass(DeviceClass),
+    TxCw { timeout: u16, frequency: u32, tx_power: i8 },
     TxFramesCtrlReq(Option<bool>),
     TxPeriodicityChange(Option<u16>),
     UplinkPrepared,
@@ -20,12 +52,22 @@ pub(crate) enum Response {
 
 pub(crate) struct Certification {
     pending_uplink: Option<heapless::Vec<u8, 256>>,
+    /// Cleared by DutFPort224DisableReq, after which `fport = 224` is handled as application port
+    enabled: bool,
 }
 
 impl Certification {
     pub fn new() -> Self {
-        Self { pending_uplink: None }
+        Self { pending_uplink: None, enabled: true }
+    }
+
+    fn prepare_uplink(&mut self, data: &[u8]) -> Response {
+        let mut buf: heapless::Vec<u8, 256> = heapless::Vec::new();
+        buf.extend_from_slice(data).unwrap();
+        self.pending_uplink = Some(buf);
+        Response::UplinkPrepared
     }
+
     pub(crate) fn handle_message(&mut self, data: &[u8], rx_app_cnt: u16) -> Response {
         use lorawan::certification::DownlinkDUTCommand::*;
         let messages = parse_downlink_certification_messages(data);
@@ -34,43 +76,74 @@ impl Certification {
                 // Device layer
                 DutJoinReq(..) => return Response::DutJoinReq,
                 DutResetReq(..) => return Response::DutResetReq,
+                DutFPort224DisableReq(..) => {
+                    self.enabled = false;
+                    return Response::NoUpdate;
+                }
+                SwitchClassReq(payload) => {
+                    if let Ok(class) = payload.class() {
+                        return Response::SwitchClass(class);
+                    }
+                }
+                RegionalDutyCycleCtrlReq(payload) => {
+                    if let Ok(enabled) = payload.duty_cycle_enable() {
+                        return Response::DutyCycleCtrl(enabled);
+                    }
+                }
                 TxPeriodicityChangeReq(payload) => {
                     if let Ok(periodicity) = payload.periodicity() {
                         return Response::TxPeriodicityChange(periodicity);
                     }
                 }
+                TxCwReq(payload) => {
+                    return Response::TxCw {
+                        timeout: payload.timeout(),
+                        frequency: payload.frequency(),
+                        tx_power: payload.tx_power(),
+                    }
+                }
+                RxAppCntResetReq(..) => return Response::RxAppCntReset,
                 // Responses with uplink
                 LinkCheckReq(..) => return Response::LinkCheckReq,
+                DeviceTimeReq(..) => return Response::DeviceTimeReq,
+                PackageVersionReq(..) => {
+                    let mut ans = lorawan::certification::PackageVersionAnsCreator::new();
+                    ans.set_package(
+                        lorawan::certification::PACKAGE_IDENTIFIER,
+                        lorawan::certification::PACKAGE_VERSION,
+                    );
+                    return self.prepare_uplink(ans.build());
+                }
                 DutVersionsReq(..) => {
-                    let mut buf: heapless::Vec<u8, 256> = heapless::Vec::new();
                     let mut ans = lorawan::certification::DutVersionsAnsCreator::new();
-                    ans.set_versions_raw([
-                        // TODO: Pass it via session::configuration?
-                        0, 0, 0, 1, 1, 0, 4, 0, // Lorawan version (1.0.4 \o/)
-                        // region version, RP002-1.0.4 == 2.1.0.4
-                        // TODO: define and import from crate::region::constants::REGION_VERSION?
-                        2, 1, 0, 4,
-                    ]);
-                    buf.extend_from_slice(ans.build()).unwrap();
-                    self.pending_uplink = Some(buf);
-                    return Response::UplinkPrepared;
+                    ans.set_versions(
+                        FIRMWARE_VERSION,
+                        LORAWAN_VERSION,
+                        REGIONAL_PARAMETERS_VERSION,
+                    );
+                    return self.prepare_uplink(ans.build());
                 }
                 EchoIncPayloadReq(payload) => {
-                    let mut buf: heapless::Vec<u8, 256> = heapless::Vec::new();
                     let mut ans = lorawan::certification::EchoIncPayloadAnsCreator::new();
                     ans.payload(payload.payload());
-                    buf.extend_from_slice(ans.build()).unwrap();
-                    self.pending_uplink = Some(buf);
-                    return Response::UplinkPrepared;
+                    return self.prepare_uplink(ans.build());
                 }
                 RxAppCntReq(..) => {
-                    let mut buf: heapless::Vec<u8, 256> = heapless::Vec::new();
                     let mut ans = lorawan::certification::RxAppCntAnsCreator::new();
                     ans.set_rx_app_cnt(rx_app_cnt);
-                    buf.extend_from_slice(ans.build()).unwrap();
-                    self.pending_uplink = Some(buf);
-                    return Response::UplinkPrepared;
+                    return self.prepare_uplink(ans.build());
+                }
+                // Class B is not supported, hence no beacons are ever received
+                BeaconCntReq(..) => {
+                    let mut ans = lorawan::certification::BeaconCntAnsCreator::new();
+                    ans.set_beacon_cnt(0);
+                    return self.prepare_uplink(ans.build());
                 }
+                PingSlotInfoReq(payload) => {
+                    return Response::PingSlotInfoReq(payload.periodicity());
+                }
+                // Without beacons there is no counter to reset
+                BeaconCntRstReq(..) => {}
                 // MAC layer
                 AdrBitChangeReq(payload) => {
                     if let Ok(adr) = payload.adr_enable() {
@@ -88,7 +161,7 @@ impl Certification {
     }
 
     pub(crate) const fn fport(&self, fport: u8) -> bool {
-        CERTIFICATION_PORT == fport
+        self.enabled && CERTIFICATION_PORT == fport
     }
 
     pub(crate) fn setup_send<const N: usize>(
@@ -102,7 +175,9 @@ impl Certification {
             confirmed: false,
         };
         match &mut state {
-            mac::State::Joined(ref mut session) => Ok(session.prepare_buffer::<N>(&send_data, buf)),
+            mac::State::Joined(ref mut session) => {
+                Ok(session.prepare_buffer::<N>(&send_data, buf, FOPTS_MAX_LEN))
+            }
             mac::State::Otaa(_) => Err(mac::Error::NotJoined),
             mac::State::Unjoined => Err(mac::Error::NotJoined),
         }
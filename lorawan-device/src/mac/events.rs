@@ -0,0 +1,81 @@
+//! Typed events about changes the MAC layer applies internally, eg: due to MAC commands from the
+//! network. With the `mac-events` feature, events are buffered in a bounded queue which the
+//! application may drain for diagnostics using `take_mac_event` on the device. Otherwise they
+//! are discarded right away.
+#[cfg(feature = "mac-events")]
+use heapless::Deque;
+use lorawan::types::DR;
+
+/// Amount of events buffered until the oldest events are discarded.
+#[cfg(feature = "mac-events")]
+pub const EVENT_QUEUE_LEN: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum Event {
+    /// A downlink MAC command with the given CID was applied.
+    MacCommandApplied { cid: u8 },
+    /// A downlink MAC command with the given CID was rejected or is not supported.
+    MacCommandRejected { cid: u8 },
+    /// Data rate or TX power was changed by LinkADRReq. `tx_power` is given in dBm EIRP, or
+    /// `None` for the maximum power of the board.
+    AdrChange { data_rate: DR, tx_power: Option<u8> },
+    /// A channel was added or modified by NewChannelReq.
+    ChannelAdded { index: u8, frequency: u32 },
+    /// A received frame was discarded.
+    DownlinkDropped { reason: DropReason },
+    /// A JoinRequest was prepared for transmission.
+    JoinAttempt { dev_nonce: u16 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum DropReason {
+    /// The frame exceeds the maximum payload size of the data rate.
+    Oversize,
+    /// MIC verification failed, eg: the frame is addressed to another device.
+    InvalidMic,
+    /// FCntDown of the frame was already received.
+    Replay,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Events {
+    #[cfg(feature = "mac-events")]
+    queue: Deque<Event, EVENT_QUEUE_LEN>,
+}
+
+impl Events {
+    /// Enqueue an event, discarding the oldest one if the queue is full.
+    #[allow(unused_variables)]
+    pub(crate) fn push(&mut self, event: Event) {
+        #[cfg(feature = "mac-events")]
+        {
+            if self.queue.is_full() {
+                self.queue.pop_front();
+            }
+            let _ = self.queue.push_back(event);
+        }
+    }
+
+    #[cfg(feature = "mac-events")]
+    pub(crate) fn pop(&mut self) -> Option<Event> {
+        self.queue.pop_front()
+    }
+}
+
+#[cfg(all(test, feature = "mac-events"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn oldest_events_discarded() {
+        let mut events = Events::default();
+        for dev_nonce in 0..EVENT_QUEUE_LEN as u16 + 2 {
+            events.push(Event::JoinAttempt { dev_nonce });
+        }
+        assert_eq!(events.pop(), Some(Event::JoinAttempt { dev_nonce: 2 }));
+        let remaining = core::iter::from_fn(|| events.pop()).count();
+        assert_eq!(remaining, EVENT_QUEUE_LEN - 1);
+    }
+}
@@ -8,7 +8,12 @@ use crate::{
 };
 use heapless::Vec;
 use lora_modulation::BaseBandModulationParams;
+#[cfg(feature = "certification")]
 use lorawan::maccommands::SerializableMacCommand;
+use lorawan::packet_length::phy::mac::{
+    fhdr::{FHDR_MIN_LEN, FOPTS_MAX_LEN},
+    FPORT_LEN,
+};
 use lorawan::parser::DevAddr;
 use lorawan::types::DR;
 
@@ -27,6 +32,20 @@ use crate::nb_device;
 
 pub(crate) mod uplink;
 
+mod rejoin;
+pub(crate) use rejoin::Rejoin;
+pub use rejoin::{RejoinPolicy, RejoinReason};
+
+mod retransmission;
+pub(crate) use retransmission::Retransmission;
+pub use retransmission::RetransmissionPolicy;
+
+mod events;
+pub(crate) use events::Events;
+#[cfg(feature = "mac-events")]
+pub use events::EVENT_QUEUE_LEN;
+pub use events::{DropReason, Event};
+
 #[cfg(feature = "certification")]
 pub(crate) mod certification;
 #[cfg(feature = "multicast")]
@@ -38,13 +57,39 @@ pub(crate) enum Frame {
     Data,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
-pub(crate) enum Window {
+/// Class A receive windows following an uplink.
+pub enum Window {
     _1,
     _2,
 }
 
+/// Metadata of the most recent uplink and of a downlink received in its RX windows, eg: for
+/// correlating with gateway logs.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct UplinkMetadata {
+    /// Frequency in Hz.
+    pub frequency: u32,
+    /// Data rate, or `None` if the modulation does not match a data rate of the region.
+    pub data_rate: Option<DR>,
+    /// Effective TX power in dBm, after accounting for antenna gain and maximum radio power.
+    pub tx_power: i8,
+    /// Computed time on air in microseconds.
+    pub time_on_air_us: u32,
+    /// FCntUp of a data uplink, `None` for a JoinRequest.
+    pub fcnt_up: Option<FcntUp>,
+    /// Timestamp in milliseconds once the transmission completed, taken from `Timer::now_ms` by
+    /// `async_device` and from the TX done event of the radio by `nb_device`. `None` if no clock
+    /// is available.
+    pub tx_done_ms: Option<u64>,
+    /// RX window in which a downlink was received.
+    pub rx_window: Option<Window>,
+    /// RSSI and SNR of the downlink received in `rx_window`.
+    pub rx_quality: Option<radio::RxQuality>,
+}
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 /// LoRaWAN Session and Network Configurations
@@ -58,6 +103,8 @@ pub struct Configuration {
     pub(crate) rx1_dr_offset: u8,
     pub(crate) rx2_data_rate: Option<DR>,
     pub(crate) rx2_frequency: Option<u32>,
+    /// Uplink dwell time limitation set by TXParamSetupReq, which lowers the maximum payload size.
+    pub(crate) uplink_dwell_time: bool,
 }
 
 pub(crate) struct Mac {
@@ -65,6 +112,15 @@ pub(crate) struct Mac {
     pub region: region::Configuration,
     board_eirp: BoardEirp,
     state: State,
+    pub retransmission: Retransmission,
+    /// Pending MAC commands did not fit into FOpts of the last uplink and are to be sent in a
+    /// dedicated FPort 0 uplink.
+    mac_only_uplink_due: bool,
+    /// RX2 parameters have been modified by the network, which also applies to the RXC window.
+    #[cfg(feature = "class-c")]
+    rx2_changed: bool,
+    pub(crate) events: Events,
+    pub(crate) uplink_metadata: Option<UplinkMetadata>,
     #[cfg(feature = "certification")]
     certification: certification::Certification,
     #[cfg(feature = "multicast")]
@@ -87,6 +143,11 @@ pub(crate) enum State {
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 pub enum Error {
     NotJoined,
+    /// Application payload exceeds the maximum size for the current data rate.
+    PayloadTooLarge {
+        /// Maximum application payload size in bytes.
+        max_len: u8,
+    },
     #[cfg(feature = "multicast")]
     Multicast(multicast::Error),
 }
@@ -115,7 +176,14 @@ impl Mac {
                 rx2_data_rate: None,
                 rx2_frequency: None,
                 tx_power: None,
+                uplink_dwell_time: false,
             },
+            retransmission: Retransmission::default(),
+            mac_only_uplink_due: false,
+            #[cfg(feature = "class-c")]
+            rx2_changed: false,
+            events: Events::default(),
+            uplink_metadata: None,
             #[cfg(feature = "certification")]
             certification: certification::Certification::new(),
             #[cfg(feature = "multicast")]
@@ -134,9 +202,11 @@ impl Mac {
         let mut otaa = otaa::Otaa::new(credentials);
         let dev_nonce = otaa.prepare_buffer::<RNG, N>(rng, buf);
         self.state = State::Otaa(otaa);
+        self.events.push(Event::JoinAttempt { dev_nonce });
         let mut tx_config =
             self.region.create_tx_config(rng, self.configuration.data_rate, &Frame::Join);
         tx_config.adjust_power(self.board_eirp.max_power, self.board_eirp.antenna_gain);
+        self.uplink_prepared(&tx_config, None, buf.as_ref_for_read().len());
         (tx_config, dev_nonce)
     }
 
@@ -155,16 +225,51 @@ impl Mac {
         self.state = State::Joined(session);
     }
 
+    /// Maximum application payload size for the current data rate, without any MAC commands in
+    /// FOpts.
+    pub(crate) fn max_payload_len(&self) -> u8 {
+        let max_mac_payload = self.region.get_max_payload_length(
+            self.configuration.data_rate,
+            false,
+            self.configuration.uplink_dwell_time,
+        );
+        max_mac_payload.saturating_sub((FHDR_MIN_LEN + FPORT_LEN) as u8)
+    }
+
     /// Prepare the radio buffer for transmitting a data frame and provide the radio configuration
-    /// for the transmission. Returns an error if the device is not joined.
+    /// for the transmission. Returns an error if the device is not joined or if the payload
+    /// exceeds the maximum payload size of the current data rate.
+    ///
+    /// Pending MAC commands which do not fit into FOpts alongside the payload are held back for a
+    /// dedicated FPort 0 uplink, see [`Mac::mac_only_uplink_pending`].
     pub(crate) fn send<RNG: RngCore, const N: usize>(
         &mut self,
         rng: &mut RNG,
         buf: &mut RadioBuffer<N>,
         send_data: &SendData<'_>,
     ) -> Result<(radio::TxConfig, FcntUp)> {
+        let max_len = self.max_payload_len();
         let fcnt = match &mut self.state {
-            State::Joined(ref mut session) => Ok(session.prepare_buffer::<N>(send_data, buf)),
+            State::Joined(ref mut session) => {
+                if send_data.data.len() > max_len as usize {
+                    return Err(Error::PayloadTooLarge { max_len });
+                }
+                // FPort 0 carries MAC commands in FRMPayload, otherwise they go into FOpts
+                let mac_commands_max_len = if send_data.fport == 0 {
+                    max_len as usize
+                } else {
+                    FOPTS_MAX_LEN.min(max_len as usize - send_data.data.len())
+                };
+                let pending = session.uplink.mac_commands().len();
+                self.mac_only_uplink_due =
+                    session.uplink.mac_commands_len_within(mac_commands_max_len) < pending;
+                if self.mac_only_uplink_due {
+                    debug!("MAC commands exceed available space, deferring to FPort 0 uplink");
+                }
+                let fcnt = session.prepare_buffer::<N>(send_data, buf, mac_commands_max_len);
+                self.retransmission.uplink_sent(buf.as_ref_for_read(), session.confirmed);
+                Ok(fcnt)
+            }
             State::Otaa(_) => Err(Error::NotJoined),
             State::Unjoined => Err(Error::NotJoined),
         }?;
@@ -174,9 +279,92 @@ impl Mac {
             self.configuration.tx_power.unwrap_or(self.board_eirp.max_power),
             self.board_eirp.antenna_gain,
         );
+        self.uplink_prepared(&tx_config, Some(fcnt), buf.as_ref_for_read().len());
         Ok((tx_config, fcnt))
     }
 
+    /// Record the metadata of an uplink which is about to be transmitted.
+    fn uplink_prepared(
+        &mut self,
+        tx_config: &radio::TxConfig,
+        fcnt_up: Option<FcntUp>,
+        len: usize,
+    ) {
+        self.uplink_metadata = Some(UplinkMetadata {
+            frequency: tx_config.rf.frequency,
+            data_rate: self.region.get_datarate_of(&tx_config.rf),
+            tx_power: tx_config.pw,
+            time_on_air_us: tx_config.rf.time_on_air_us(Some(8), true, len as u8),
+            fcnt_up,
+            tx_done_ms: None,
+            rx_window: None,
+            rx_quality: None,
+        });
+    }
+
+    /// Record the timestamp provided by the radio once the uplink has been transmitted.
+    pub(crate) fn uplink_transmitted(&mut self, tx_done_ms: Option<u64>) {
+        if let Some(metadata) = &mut self.uplink_metadata {
+            metadata.tx_done_ms = tx_done_ms;
+        }
+    }
+
+    /// Record the RX window and quality of a downlink received after the uplink.
+    pub(crate) fn downlink_received(&mut self, window: Window, quality: radio::RxQuality) {
+        if let Some(metadata) = &mut self.uplink_metadata {
+            metadata.rx_window = Some(window);
+            metadata.rx_quality = Some(quality);
+        }
+    }
+
+    /// Whether MAC commands were held back from the previous uplink and should be sent in a
+    /// dedicated FPort 0 uplink. The indication is reset by the next uplink which carries them.
+    pub(crate) fn mac_only_uplink_pending(&self) -> bool {
+        self.mac_only_uplink_due
+    }
+
+    /// Whether RX2 parameters were modified by a downlink since the last call. Class C devices
+    /// are to send an uplink as soon as possible to confirm the new RXC window parameters.
+    #[cfg(feature = "class-c")]
+    pub(crate) fn rx2_changed(&mut self) -> bool {
+        core::mem::take(&mut self.rx2_changed)
+    }
+
+    /// Prepare the radio buffer for an unconfirmed FPort 0 uplink carrying the pending MAC
+    /// commands in the encrypted FRMPayload.
+    #[cfg(feature = "class-c")]
+    pub(crate) fn send_mac_commands<RNG: RngCore, const N: usize>(
+        &mut self,
+        rng: &mut RNG,
+        buf: &mut RadioBuffer<N>,
+    ) -> Result<(radio::TxConfig, FcntUp)> {
+        self.send(rng, buf, &SendData { data: &[], fport: 0, confirmed: false })
+    }
+
+    /// Prepare the radio buffer for retransmitting the most recent confirmed uplink without
+    /// incrementing FCntUp. Must only be called when [`Response::Retransmit`] was provided.
+    pub(crate) fn retransmit<RNG: RngCore, const N: usize>(
+        &mut self,
+        rng: &mut RNG,
+        buf: &mut RadioBuffer<N>,
+    ) -> Result<(radio::TxConfig, FcntUp)> {
+        let fcnt = self.get_fcnt_up().ok_or(Error::NotJoined)?;
+        let (region, dwell_time) = (&self.region, self.configuration.uplink_dwell_time);
+        let (frame, dr) = self.retransmission.next_attempt(self.configuration.data_rate, |dr| {
+            region.get_max_payload_length(dr, false, dwell_time)
+        });
+        buf.clear();
+        buf.extend_from_slice(frame).unwrap();
+        let mut tx_config = self.region.create_tx_config(rng, dr, &Frame::Data);
+        tx_config.adjust_power(
+            self.configuration.tx_power.unwrap_or(self.board_eirp.max_power),
+            self.board_eirp.antenna_gain,
+        );
+        self.uplink_prepared(&tx_config, Some(fcnt), buf.as_ref_for_read().len());
+        Ok((tx_config, fcnt))
+    }
+
+    #[cfg(feature = "certification")]
     pub(crate) fn add_uplink<M: SerializableMacCommand>(&mut self, cmd: M) -> Result<()> {
         let _fcnt = match &mut self.state {
             State::Joined(ref mut session) => {
@@ -220,6 +408,22 @@ impl Mac {
         })
     }
 
+    /// Transmit configuration for a continuous wave requested by TxCwReq.
+    #[cfg(feature = "certification")]
+    pub(crate) fn certification_tx_cw_config<RNG: RngCore>(
+        &mut self,
+        rng: &mut RNG,
+        frequency: u32,
+        tx_power: i8,
+    ) -> radio::TxConfig {
+        let mut tx_config =
+            self.region.create_tx_config(rng, self.configuration.data_rate, &Frame::Data);
+        tx_config.rf.frequency = frequency;
+        tx_config.pw = tx_power;
+        tx_config.adjust_power(self.board_eirp.max_power, self.board_eirp.antenna_gain);
+        tx_config
+    }
+
     pub(crate) fn get_rx_delay(&self, frame: &Frame, window: &Window) -> u32 {
         match frame {
             Frame::Join => match window {
@@ -247,19 +451,32 @@ impl Mac {
         rf_config: &RfConfig,
     ) -> Response {
         match &mut self.state {
-            State::Joined(ref mut session) => session.handle_rx::<N, D>(
-                &mut self.region,
-                &mut self.configuration,
-                #[cfg(feature = "certification")]
-                &mut self.certification,
-                #[cfg(feature = "multicast")]
-                &mut self.multicast,
-                buf,
-                dl,
-                rf_config.max_payload_len,
-                snr,
-                false,
-            ),
+            State::Joined(ref mut session) => {
+                #[cfg(feature = "class-c")]
+                let rx2 = (self.configuration.rx2_frequency, self.configuration.rx2_data_rate);
+                let response = session.handle_rx::<N, D>(
+                    &mut self.region,
+                    &mut self.configuration,
+                    #[cfg(feature = "certification")]
+                    &mut self.certification,
+                    #[cfg(feature = "multicast")]
+                    &mut self.multicast,
+                    &mut self.events,
+                    buf,
+                    dl,
+                    rf_config.max_payload_len,
+                    snr,
+                    false,
+                );
+                if !matches!(response, Response::NoUpdate) {
+                    self.retransmission.complete();
+                }
+                #[cfg(feature = "class-c")]
+                if rx2 != (self.configuration.rx2_frequency, self.configuration.rx2_data_rate) {
+                    self.rx2_changed = true;
+                }
+                response
+            }
             State::Otaa(ref mut otaa) => {
                 if let Some(session) =
                     otaa.handle_rx::<N>(&mut self.region, &mut self.configuration, buf)
@@ -293,6 +510,7 @@ impl Mac {
                 &mut self.certification,
                 #[cfg(feature = "multicast")]
                 &mut self.multicast,
+                &mut self.events,
                 buf,
                 dl,
                 rf_config.max_payload_len,
@@ -304,9 +522,17 @@ impl Mac {
         }
     }
 
+    /// Provides [`Response::Retransmit`] instead of concluding the uplink when a confirmed uplink
+    /// is to be retransmitted. In that case FCntUp is not incremented.
     pub(crate) fn rx2_complete(&mut self) -> Response {
         match &mut self.state {
-            State::Joined(session) => session.rx2_complete(),
+            State::Joined(session) => {
+                if self.retransmission.pending() {
+                    return Response::Retransmit;
+                }
+                self.retransmission.complete();
+                session.rx2_complete()
+            }
             State::Otaa(otaa) => otaa.rx2_complete(),
             State::Unjoined => Response::NoUpdate,
         }
@@ -343,14 +569,12 @@ impl Mac {
     /// Build RfConfig for given `Frame` and `Window` and apply
     /// network-specific overrides.
     pub(crate) fn get_rf_config(&self, frame: &Frame, window: &Window) -> RfConfig {
+        // RX1 follows the data rate of the uplink, which is lowered by retransmissions
+        let tx_dr = self.retransmission.data_rate().unwrap_or(self.configuration.data_rate);
         let (frequency, dr) = match window {
             Window::_1 => (
                 self.region.get_rx_frequency(frame, window),
-                self.region.get_rx_datarate(
-                    self.configuration.data_rate,
-                    self.configuration.rx1_dr_offset,
-                    window,
-                ),
+                self.region.get_rx_datarate(tx_dr, self.configuration.rx1_dr_offset, window),
             ),
             Window::_2 => {
                 (
@@ -360,11 +584,7 @@ impl Mac {
                         .unwrap_or_else(|| self.region.get_rx_frequency(frame, window)),
                     // RX2 datarate override
                     self.configuration.rx2_data_rate.unwrap_or_else(|| {
-                        self.region.get_rx_datarate(
-                            self.configuration.data_rate,
-                            self.configuration.rx1_dr_offset,
-                            window,
-                        )
+                        self.region.get_rx_datarate(tx_dr, self.configuration.rx1_dr_offset, window)
                     }),
                 )
             }
@@ -374,13 +594,10 @@ impl Mac {
         let datarate = match self.region.get_datarate(dr as u8) {
             Some(d) => d,
             None => {
-                warn!(
-                    "Unsupported DR: {:?} (TX DR: {:?}, Window: {:?})",
-                    dr, self.configuration.data_rate, window
-                );
+                warn!("Unsupported DR: {:?} (TX DR: {:?}, Window: {:?})", dr, tx_dr, window);
                 self.region
                     .get_datarate(self.region.get_rx_datarate(
-                        self.configuration.data_rate,
+                        tx_dr,
                         self.configuration.rx1_dr_offset,
                         &Window::_2,
                     ) as u8)
@@ -396,6 +613,7 @@ impl Mac {
                 self.region.get_coding_rate(),
             ),
             max_payload_len: datarate.max_mac_payload_size,
+            fsk: datarate.fsk,
         }
     }
 
@@ -407,6 +625,26 @@ impl Mac {
     pub(crate) fn get_rxc_config(&self) -> RxConfig {
         RxConfig { rf: self.get_rf_config(&Frame::Data, &Window::_2), mode: RxMode::Continuous }
     }
+
+    /// RXC configuration of the Class C multicast session window active at `now_ms`.
+    #[cfg(all(feature = "class-c", feature = "multicast"))]
+    pub(crate) fn get_multicast_rxc_config(&self, now_ms: u64) -> Option<RxConfig> {
+        let window = self.multicast.active_class_c_window(now_ms)?;
+        let datarate = self.region.get_datarate(window.data_rate as u8)?;
+        Some(RxConfig {
+            rf: RfConfig {
+                frequency: window.frequency,
+                bb: BaseBandModulationParams::new(
+                    datarate.spreading_factor,
+                    datarate.bandwidth,
+                    self.region.get_coding_rate(),
+                ),
+                max_payload_len: datarate.max_mac_payload_size,
+                fsk: datarate.fsk,
+            },
+            mode: RxMode::Continuous,
+        })
+    }
 }
 
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
@@ -419,22 +657,41 @@ pub(crate) enum Response {
     JoinSuccess,
     NoUpdate,
     RxComplete,
+    /// Confirmed uplink was not acknowledged and is to be retransmitted after the ACK timeout.
+    Retransmit,
+    #[cfg(feature = "certification")]
     LinkCheckReq,
     #[cfg(feature = "certification")]
     UplinkPrepared,
     #[cfg(feature = "certification")]
     DeviceHandler(DeviceEvent),
+    /// Continuous wave transmission requested by TxCwReq.
+    #[cfg(feature = "certification")]
+    TxContinuousWave {
+        frequency: u32,
+        tx_power: i8,
+        timeout_ms: u32,
+    },
     #[cfg(feature = "multicast")]
     Multicast(multicast::Response),
 }
 
+/// Requests of the certification protocol which have to be carried out by the application.
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg(feature = "certification")]
-pub(crate) enum DeviceEvent {
+pub enum DeviceEvent {
+    /// DutResetReq: reset the MCU.
     ResetDevice,
+    /// DutJoinReq: reset the MAC layer and join again.
     ResetMac,
+    /// TxPeriodicityChangeReq: send uplinks with the given periodicity in seconds, or with the
+    /// application default if `None`.
     TxPeriodicityChange { periodicity: Option<u16> },
+    /// SwitchClassReq: Class C is enabled or disabled by the device before this is returned.
+    SwitchClass { class: lorawan::certification::DeviceClass },
+    /// RegionalDutyCycleCtrlReq: enable or disable regional duty-cycle limitations.
+    DutyCycleCtrl { enabled: bool },
 }
 
 impl From<Response> for nb_device::Response {
@@ -447,13 +704,22 @@ impl From<Response> for nb_device::Response {
             Response::JoinSuccess => nb_device::Response::JoinSuccess,
             Response::NoUpdate => nb_device::Response::NoUpdate,
             Response::RxComplete => nb_device::Response::RxComplete,
+            // Only provided by `Mac::rx2_complete`, whose caller schedules the retransmission
+            Response::Retransmit => {
+                unreachable!("Retransmit is handled by the nb_device state machine")
+            }
+            #[cfg(feature = "certification")]
             Response::LinkCheckReq => unimplemented!(),
             #[cfg(feature = "certification")]
             Response::UplinkPrepared => unimplemented!(),
             #[cfg(feature = "certification")]
             Response::DeviceHandler(_) => unimplemented!(),
+            #[cfg(feature = "certification")]
+            Response::TxContinuousWave { frequency, tx_power, timeout_ms } => {
+                nb_device::Response::TxContinuousWave { frequency, tx_power, timeout_ms }
+            }
             #[cfg(feature = "multicast")]
-            Response::Multicast(_) => unimplemented!(),
+            Response::Multicast(r) => r.into(),
         }
     }
 }
@@ -463,8 +729,9 @@ impl From<Response> for async_device::SendResponse {
         match r {
             Response::SessionExpired => async_device::SendResponse::SessionExpired,
             Response::DownlinkReceived(fcnt) => async_device::SendResponse::DownlinkReceived(fcnt),
-            Response::NoAck => async_device::SendResponse::NoAck,
             Response::RxComplete => async_device::SendResponse::RxComplete,
+            #[cfg(feature = "certification")]
+            Response::DeviceHandler(event) => async_device::SendResponse::DeviceEvent(event),
             #[cfg(feature = "multicast")]
             Response::Multicast(mc) => async_device::SendResponse::Multicast(mc.into()),
             r => panic!("Invalid async_device::SendResponse::from {:?}", r),
@@ -489,6 +756,8 @@ impl From<Response> for async_device::ListenResponse {
             Response::DownlinkReceived(fcnt) => {
                 async_device::ListenResponse::DownlinkReceived(fcnt)
             }
+            #[cfg(feature = "certification")]
+            Response::DeviceHandler(event) => async_device::ListenResponse::DeviceEvent(event),
             #[cfg(feature = "multicast")]
             Response::Multicast(mc) => async_device::ListenResponse::Multicast(mc.into()),
             r => panic!("Invalid async_device::ListenResponse::from {:?}", r),
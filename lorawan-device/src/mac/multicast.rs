@@ -1,16 +1,19 @@
 use crate::mac::FcntDown;
 use crate::radio::RadioBuffer;
+use crate::region::{self, DR};
 use crate::Downlink;
-use crate::{async_device, mac};
+use crate::{async_device, mac, nb_device};
 use core::fmt::Debug;
 use core::ops::RangeInclusive;
 use lorawan::default_crypto::DefaultFactory;
 use lorawan::keys::McKEKey;
 pub use lorawan::multicast::{self, Session};
 use lorawan::multicast::{
-    parse_downlink_multicast_messages, DownlinkRemoteSetup, McGroupDeleteAnsCreator,
-    McGroupSetupAnsCreator, McGroupStatusAnsCreator, PackageVersionAnsCreator,
+    parse_downlink_multicast_messages, DownlinkRemoteSetup, McClassBSessionAnsCreator,
+    McClassCSessionAnsCreator, McGroupDeleteAnsCreator, McGroupSetupAnsCreator,
+    McGroupStatusAnsCreator, PackageVersionAnsCreator,
 };
+use lorawan::packet_length::phy::mac::fhdr::FOPTS_MAX_LEN;
 use lorawan::parser::FRMPayload;
 pub use lorawan::parser::McAddr;
 use lorawan::parser::{DataHeader, EncryptedDataPayload};
@@ -37,12 +40,40 @@ const REMOTE_MULTICAST_SETUP_PORT: u8 = 200;
 /// session
 const DEFAULT_MC_PORT_RANGE: RangeInclusive<u8> = 201..=205;
 
+/// Time window and radio parameters of a Class C multicast session, as requested by
+/// McClassCSessionReq.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct SessionWindow {
+    /// Start of the session in milliseconds of [`Timer::now_ms`](crate::async_device::radio::Timer::now_ms).
+    pub start_ms: u64,
+    pub duration_ms: u64,
+    /// Downlink frequency in Hz.
+    pub frequency: u32,
+    pub data_rate: DR,
+}
+
+impl SessionWindow {
+    pub fn end_ms(&self) -> u64 {
+        self.start_ms + self.duration_ms
+    }
+
+    pub fn is_active(&self, now_ms: u64) -> bool {
+        (self.start_ms..self.end_ms()).contains(&now_ms)
+    }
+}
+
 pub struct Multicast {
     pub(crate) mc_k_e_key: Option<McKEKey>,
     pub(crate) sessions: [Option<Session>; multicast::MAX_GROUPS],
+    pub(crate) windows: [Option<SessionWindow>; multicast::MAX_GROUPS],
     range: RangeInclusive<u8>,
     remote_setup_port: u8,
     pending_uplinks: heapless::Vec<u8, 256>,
+    /// GPS time in seconds at the given timestamp in milliseconds.
+    gps_time: Option<(u32, u64)>,
+    /// Timestamp of the downlink being handled.
+    now_ms: Option<u64>,
 }
 
 impl Default for Multicast {
@@ -58,51 +89,133 @@ impl Multicast {
             range: DEFAULT_MC_PORT_RANGE,
             remote_setup_port: REMOTE_MULTICAST_SETUP_PORT,
             sessions: [None, None, None, None],
+            windows: [None; multicast::MAX_GROUPS],
             pending_uplinks: heapless::Vec::new(),
+            gps_time: None,
+            now_ms: None,
         }
     }
 
+    /// Sets the current GPS time (seconds since the GPS epoch) at the timestamp `now_ms`, which
+    /// is required to schedule sessions at the requested time.
+    pub(crate) fn set_gps_time(&mut self, gps_seconds: u32, now_ms: u64) {
+        self.gps_time = Some((gps_seconds, now_ms));
+    }
+
+    /// Sets the timestamp at which the next downlink is received.
+    pub(crate) fn set_now_ms(&mut self, now_ms: Option<u64>) {
+        self.now_ms = now_ms;
+    }
+
+    /// Timestamp of the session start and the seconds until then. Sessions start right away if
+    /// the GPS time is unknown.
+    fn schedule(&self, now_ms: u64, session_time: u32) -> (u64, u32) {
+        let Some((gps_seconds, gps_ms)) = self.gps_time else {
+            return (now_ms, 0);
+        };
+        let now_gps_ms = gps_seconds as u64 * 1000 + now_ms.saturating_sub(gps_ms);
+        // SessionTime is transmitted modulo 2^32
+        let delta_s = session_time.wrapping_sub((now_gps_ms / 1000) as u32) as i32;
+        let offset_ms = delta_s as i64 * 1000 - (now_gps_ms % 1000) as i64;
+        ((now_ms as i64 + offset_ms).max(0) as u64, delta_s.max(0) as u32)
+    }
+
+    /// Validates and stores the window of a session, returning the status flags
+    /// (DR error, frequency error, group undefined) and the seconds until its start.
+    #[allow(clippy::too_many_arguments)]
+    fn setup_window(
+        &mut self,
+        region: &region::Configuration,
+        group_id: u8,
+        session_time: u32,
+        timeout: u8,
+        frequency: u32,
+        data_rate: DR,
+    ) -> ((bool, bool, bool), u32) {
+        let dr_error = region.get_datarate(data_rate as u8).is_none();
+        let frequency_error = !region.frequency_valid(frequency);
+        // Without a clock, the window of the session cannot be followed. There is no status for
+        // this, hence the group is reported as undefined.
+        let undefined = self.sessions[group_id as usize].is_none() || self.now_ms.is_none();
+        let Some(now_ms) = self.now_ms.filter(|_| !(dr_error || frequency_error || undefined))
+        else {
+            return ((dr_error, frequency_error, undefined), 0);
+        };
+        let (start_ms, time_to_start) = self.schedule(now_ms, session_time);
+        self.windows[group_id as usize] =
+            Some(SessionWindow { start_ms, duration_ms: 1000 << timeout, frequency, data_rate });
+        ((false, false, false), time_to_start)
+    }
+
+    /// Class C session window which is active at `now_ms`.
+    #[cfg(feature = "class-c")]
+    pub(crate) fn active_class_c_window(&self, now_ms: u64) -> Option<&SessionWindow> {
+        self.windows.iter().flatten().find(|w| w.is_active(now_ms))
+    }
+
+    /// Timestamp of the next start of a Class C session window or the next end of any window.
+    #[cfg(feature = "class-c")]
+    pub(crate) fn next_window_event_ms(&self, now_ms: u64) -> Option<u64> {
+        self.windows
+            .iter()
+            .flatten()
+            .flat_map(|w| {
+                [(w.start_ms > now_ms).then_some(w.start_ms), Some(w.end_ms().max(now_ms))]
+            })
+            .flatten()
+            .min()
+    }
+
+    /// Removes a session window which has ended at `now_ms` and returns its group.
+    #[cfg(feature = "class-c")]
+    pub(crate) fn take_expired_window(&mut self, now_ms: u64) -> Option<u8> {
+        let group_id = self.windows.iter().position(|w| w.is_some_and(|w| w.end_ms() <= now_ms))?;
+        self.windows[group_id] = None;
+        Some(group_id as u8)
+    }
+
     pub(crate) fn handle_rx<const D: usize>(
         &mut self,
         dl: &mut heapless::Vec<Downlink, D>,
         encrypted_data: EncryptedDataPayload<&mut [u8]>,
     ) -> Response {
         let mc_addr = encrypted_data.fhdr().mc_addr();
-        if let Some((group_id, session)) = self.matching_session(mc_addr) {
-            let fcnt = encrypted_data.fhdr().fcnt() as u32;
-            if encrypted_data.validate_mic(session.mc_net_s_key().inner(), fcnt, &DefaultFactory)
-                && (fcnt > session.fcnt_down || fcnt == 0)
-            {
-                return {
-                    session.fcnt_down = fcnt;
-                    // We can safely unwrap here because we already validated the MIC
-                    let decrypted = encrypted_data
-                        .decrypt(
-                            Some(session.mc_net_s_key().inner()),
-                            Some(session.mc_app_s_key().inner()),
-                            session.fcnt_down,
-                            &DefaultFactory,
-                        )
-                        .unwrap();
-                    if session.fcnt_down == session.max_fcnt_down() {
-                        // if the FCnt is used up, the session has expired
-                        Response::SessionExpired { group_id }
-                    } else {
-                        if let (Some(fport), FRMPayload::Data(data)) =
-                            (decrypted.f_port(), decrypted.frm_payload())
-                        {
-                            // heapless Vec from slice fails only if slice is too large.
-                            // A data FRM payload will never exceed 256 bytes.
-                            let data = heapless::Vec::from_slice(data).unwrap();
-                            // TODO: propagate error when heapless vec is full?
-                            let _ = dl.push(Downlink { data, fport });
-                        }
-                        Response::DownlinkReceived { group_id, fcnt }
-                    }
-                };
-            }
+        let Some((group_id, session)) = self.matching_session(mc_addr) else {
+            return Response::NoUpdate;
+        };
+        // Frames replaying an already received FCnt, below minMcFCount or beyond maxMcFCount
+        // are dropped. So is anything received once the group has expired.
+        let Some(fcnt) = session.validate_fcnt(encrypted_data.fhdr().fcnt()) else {
+            return Response::NoUpdate;
+        };
+        if !encrypted_data.validate_mic(session.mc_net_s_key().inner(), fcnt, &DefaultFactory) {
+            return Response::NoUpdate;
+        }
+        session.accept_fcnt(fcnt);
+        // We can safely unwrap here because we already validated the MIC
+        let decrypted = encrypted_data
+            .decrypt(
+                Some(session.mc_net_s_key().inner()),
+                Some(session.mc_app_s_key().inner()),
+                fcnt,
+                &DefaultFactory,
+            )
+            .unwrap();
+        if let (Some(fport), FRMPayload::Data(data)) = (decrypted.f_port(), decrypted.frm_payload())
+        {
+            // heapless Vec from slice fails only if slice is too large.
+            // A data FRM payload will never exceed 256 bytes.
+            let data = heapless::Vec::from_slice(data).unwrap();
+            // TODO: propagate error when heapless vec is full?
+            let _ = dl.push(Downlink { data, fport });
+        }
+        if session.is_expired() {
+            // The last FCnt of the group has been used: the frame is still delivered, but the
+            // session has expired
+            Response::SessionExpired { group_id }
+        } else {
+            Response::DownlinkReceived { group_id, fcnt }
         }
-        Response::NoUpdate
     }
 
     /// Sets a custom range for the multicast.
@@ -125,11 +238,14 @@ impl Multicast {
         self.remote_setup_port == port
     }
 
-    pub(crate) fn handle_setup_message(&mut self, data: &[u8]) -> Response {
-        if self.mc_k_e_key.is_none() {
+    pub(crate) fn handle_setup_message(
+        &mut self,
+        region: &region::Configuration,
+        data: &[u8],
+    ) -> Response {
+        let Some(mc_k_e_key) = self.mc_k_e_key else {
             return Response::NoUpdate;
-        }
-        let mc_k_e_key = self.mc_k_e_key.as_ref().unwrap();
+        };
         let messages = parse_downlink_multicast_messages(data);
         let mut new_session = None;
         for message in messages {
@@ -137,8 +253,9 @@ impl Multicast {
                 DownlinkRemoteSetup::McGroupSetupReq(mc_group_setup_req) => {
                     let crypto = DefaultFactory;
                     let (group_id, session) =
-                        mc_group_setup_req.derive_session(&crypto, mc_k_e_key);
+                        mc_group_setup_req.derive_session(&crypto, &mc_k_e_key);
                     self.sessions[group_id as usize] = Some(session);
+                    self.windows[group_id as usize] = None;
                     let mut ans = McGroupSetupAnsCreator::new();
                     ans.mc_group_id_header(group_id);
                     self.pending_uplinks.extend_from_slice(ans.build()).unwrap();
@@ -158,6 +275,7 @@ impl Multicast {
                     if self.sessions[group_id as usize].is_some() {
                         ans.mc_group_id_header(group_id);
                         self.sessions[group_id as usize] = None;
+                        self.windows[group_id as usize] = None;
                     } else {
                         ans.mc_group_undefined(true);
                     }
@@ -181,8 +299,39 @@ impl Multicast {
                     ans.nb_total_groups(nb_total_groups);
                     self.pending_uplinks.extend_from_slice(ans.build()).unwrap();
                 }
-                m => {
-                    warn!("Unhandled multicast message: {}", m);
+                DownlinkRemoteSetup::McClassCSessionReq(req) => {
+                    let group_id = req.mc_group_id_header();
+                    let ((dr_error, frequency_error, undefined), time_to_start) = self
+                        .setup_window(
+                            region,
+                            group_id,
+                            req.session_time(),
+                            req.session_timeout(),
+                            req.dl_frequency().value(),
+                            req.data_rate(),
+                        );
+                    let mut ans = McClassCSessionAnsCreator::new();
+                    ans.mc_group_id_header(group_id)
+                        .dr_error(dr_error)
+                        .frequency_error(frequency_error)
+                        .mc_group_undefined(undefined)
+                        .time_to_start(time_to_start);
+                    let ans = if ans.is_error() {
+                        ans.build_status_only()
+                    } else {
+                        ans.build()
+                    };
+                    self.pending_uplinks.extend_from_slice(ans).unwrap();
+                }
+                DownlinkRemoteSetup::McClassBSessionReq(req) => {
+                    // Class B reception is not supported. There is no status for this, hence the
+                    // group is reported as undefined.
+                    let mut ans = McClassBSessionAnsCreator::new();
+                    ans.mc_group_id_header(req.mc_group_id_header())
+                        .dr_error(region.get_datarate(req.data_rate() as u8).is_none())
+                        .frequency_error(!region.frequency_valid(req.dl_frequency().value()))
+                        .mc_group_undefined(true);
+                    self.pending_uplinks.extend_from_slice(ans.build_status_only()).unwrap();
                 }
             }
         }
@@ -197,6 +346,11 @@ impl Multicast {
         }
     }
 
+    /// Whether answers to remote setup messages are waiting to be sent.
+    pub(crate) fn uplink_pending(&self) -> bool {
+        !self.pending_uplinks.is_empty()
+    }
+
     pub(crate) fn setup_send<const N: usize>(
         &mut self,
         mut state: &mut mac::State,
@@ -209,7 +363,7 @@ impl Multicast {
         };
         match &mut state {
             mac::State::Joined(ref mut session) => {
-                let response = session.prepare_buffer::<N>(&send_data, buf);
+                let response = session.prepare_buffer::<N>(&send_data, buf, FOPTS_MAX_LEN);
                 self.pending_uplinks.clear();
                 Ok(response)
             }
@@ -256,6 +410,23 @@ impl From<Response> for async_device::MulticastResponse {
     }
 }
 
+impl From<Response> for nb_device::Response {
+    fn from(r: Response) -> nb_device::Response {
+        match r {
+            // the answer is sent by the application using `send_multicast_answers`
+            Response::NewSession { group_id }
+            | Response::GroupSetupTransmitRequest { group_id } => {
+                nb_device::Response::Multicast(async_device::MulticastResponse::NewSession {
+                    group_id,
+                })
+            }
+            Response::TransmitRequest => nb_device::Response::MulticastAnswerPending,
+            Response::NoUpdate => nb_device::Response::NoUpdate,
+            r => nb_device::Response::Multicast(r.into()),
+        }
+    }
+}
+
 impl Response {
     pub fn is_for_async_mc_response(&self) -> bool {
         matches!(
@@ -0,0 +1,216 @@
+//! Bookkeeping for automatic rejoins of long-lived OTAA devices.
+//!
+//! The [`RejoinPolicy`] describes *when* a device should replace its session with a fresh one,
+//! while [`Rejoin`] tracks the session state needed to evaluate the policy and enforces the
+//! JoinRequest retransmission duty-cycle from LoRaWAN 1.0.4, Section 7:
+//!
+//! - first hour after the first join attempt: at most 36 s of aggregated airtime (1%)
+//! - the following 10 hours: at most 36 s of aggregated airtime per 10 hours (0.1%)
+//! - afterwards: at most 8.7 s of aggregated airtime per 24 hours (0.01%)
+use super::FcntUp;
+use crate::region::constants::{ADR_ACK_DELAY, ADR_ACK_LIMIT};
+
+const HOUR_MS: u64 = 60 * 60 * 1000;
+
+/// Describes the conditions under which an OTAA device automatically rejoins the network.
+///
+/// Every condition is optional, a rejoin is attempted as soon as any of the enabled conditions
+/// holds. The previous session is kept until the new one has been confirmed by a JoinAccept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RejoinPolicy {
+    /// Rejoin once FCntUp of the current session reaches this value. This can also be used to
+    /// rejoin well before the frame counter is exhausted.
+    pub max_uplinks: Option<FcntUp>,
+    /// Rejoin once the current session is older than the given amount of milliseconds. Requires a
+    /// timer providing [`now_ms`](crate::async_device::radio::Timer::now_ms).
+    pub max_session_age_ms: Option<u64>,
+    /// Rejoin after the given amount of consecutive uplinks without any downlink. See
+    /// [`RejoinPolicy::ADR_BACKOFF_UPLINKS`] for a value matching the ADR back-off.
+    pub link_loss_uplinks: Option<u32>,
+    /// Rejoin when the network session has expired.
+    pub on_session_expired: bool,
+}
+
+impl RejoinPolicy {
+    /// Amount of uplinks without downlink after which ADR back-off starts to lower the data rate
+    /// (`ADR_ACK_LIMIT + ADR_ACK_DELAY`).
+    pub const ADR_BACKOFF_UPLINKS: u32 = (ADR_ACK_LIMIT + ADR_ACK_DELAY) as u32;
+}
+
+impl Default for RejoinPolicy {
+    fn default() -> Self {
+        Self {
+            max_uplinks: None,
+            max_session_age_ms: None,
+            link_loss_uplinks: None,
+            on_session_expired: true,
+        }
+    }
+}
+
+/// Reason for an automatic rejoin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum RejoinReason {
+    SessionExpired,
+    MaxUplinks,
+    SessionAge,
+    LinkLoss,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Rejoin {
+    policy: Option<RejoinPolicy>,
+    session_expired: bool,
+    session_start_ms: Option<u64>,
+    uplinks_without_downlink: u32,
+    duty_cycle: JoinDutyCycle,
+}
+
+impl Rejoin {
+    pub(crate) fn set_policy(&mut self, policy: Option<RejoinPolicy>) {
+        self.policy = policy;
+    }
+
+    /// Evaluate the policy for the current session.
+    pub(crate) fn due(&self, fcnt_up: FcntUp, now_ms: Option<u64>) -> Option<RejoinReason> {
+        let policy = self.policy.as_ref()?;
+        if policy.on_session_expired && self.session_expired {
+            return Some(RejoinReason::SessionExpired);
+        }
+        if policy.max_uplinks.is_some_and(|max| fcnt_up >= max) {
+            return Some(RejoinReason::MaxUplinks);
+        }
+        if let (Some(max_age), Some(start), Some(now)) =
+            (policy.max_session_age_ms, self.session_start_ms, now_ms)
+        {
+            if now.saturating_sub(start) >= max_age {
+                return Some(RejoinReason::SessionAge);
+            }
+        }
+        if policy.link_loss_uplinks.is_some_and(|n| self.uplinks_without_downlink >= n) {
+            return Some(RejoinReason::LinkLoss);
+        }
+        None
+    }
+
+    /// Track the outcome of an uplink for link loss and session expiry detection.
+    pub(crate) fn uplink_complete(&mut self, downlink: bool, session_expired: bool) {
+        if downlink {
+            self.uplinks_without_downlink = 0;
+        } else {
+            self.uplinks_without_downlink = self.uplinks_without_downlink.saturating_add(1);
+        }
+        self.session_expired |= session_expired;
+    }
+
+    /// Reset session related state once a new session has been established.
+    pub(crate) fn session_started(&mut self, now_ms: Option<u64>) {
+        self.session_expired = false;
+        self.session_start_ms = now_ms;
+        self.uplinks_without_downlink = 0;
+    }
+
+    pub(crate) fn duty_cycle(&mut self) -> &mut JoinDutyCycle {
+        &mut self.duty_cycle
+    }
+}
+
+/// Tracks the JoinRequest duty-cycle.
+///
+/// When no monotonic clock is available, time is accounted only by the airtime of JoinRequests
+/// and a lower bound of the duration of data uplinks, which are reported to the tracker.
+#[derive(Debug, Default)]
+pub(crate) struct JoinDutyCycle {
+    first_attempt_ms: Option<u64>,
+    next_allowed_ms: u64,
+    elapsed_ms: u64,
+}
+
+impl JoinDutyCycle {
+    /// Returns the amount of milliseconds until the next JoinRequest may be sent.
+    pub(crate) fn backoff_ms(&self, now_ms: Option<u64>) -> u64 {
+        self.next_allowed_ms.saturating_sub(now_ms.unwrap_or(self.elapsed_ms))
+    }
+
+    /// Account for time known to have passed, in case no clock is available.
+    pub(crate) fn elapsed(&mut self, ms: u64) {
+        self.elapsed_ms += ms;
+    }
+
+    /// Record a JoinRequest transmission of the given airtime.
+    pub(crate) fn join_sent(&mut self, now_ms: Option<u64>, airtime_ms: u64) {
+        let now = now_ms.unwrap_or(self.elapsed_ms);
+        let first = *self.first_attempt_ms.get_or_insert(now);
+        let factor = match now.saturating_sub(first) {
+            t if t < HOUR_MS => 100,
+            t if t < 11 * HOUR_MS => 1000,
+            _ => 10000,
+        };
+        self.elapsed_ms = now + airtime_ms;
+        self.next_allowed_ms = now + airtime_ms * factor;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn duty_cycle_phases() {
+        let mut dc = JoinDutyCycle::default();
+        assert_eq!(dc.backoff_ms(Some(0)), 0);
+        // First hour: 1%
+        dc.join_sent(Some(0), 100);
+        assert_eq!(dc.backoff_ms(Some(100)), 9_900);
+        // Next 10 hours: 0.1%
+        dc.join_sent(Some(HOUR_MS), 100);
+        assert_eq!(dc.backoff_ms(Some(HOUR_MS)), 100_000);
+        // Afterwards: 0.01%
+        dc.join_sent(Some(11 * HOUR_MS), 100);
+        assert_eq!(dc.backoff_ms(Some(11 * HOUR_MS)), 1_000_000);
+    }
+
+    #[test]
+    fn duty_cycle_without_clock() {
+        let mut dc = JoinDutyCycle::default();
+        dc.join_sent(None, 100);
+        assert_eq!(dc.backoff_ms(None), 9_900);
+        dc.elapsed(9_900);
+        assert_eq!(dc.backoff_ms(None), 0);
+    }
+
+    #[test]
+    fn policy_triggers() {
+        let mut rejoin = Rejoin::default();
+        assert_eq!(rejoin.due(u32::MAX, Some(0)), None);
+
+        rejoin.set_policy(Some(RejoinPolicy {
+            max_uplinks: Some(10),
+            max_session_age_ms: Some(1000),
+            link_loss_uplinks: Some(2),
+            on_session_expired: true,
+        }));
+        rejoin.session_started(Some(0));
+        assert_eq!(rejoin.due(0, Some(0)), None);
+        assert_eq!(rejoin.due(10, Some(0)), Some(RejoinReason::MaxUplinks));
+        assert_eq!(rejoin.due(0, Some(1000)), Some(RejoinReason::SessionAge));
+        // Without clock, session age can not be evaluated
+        assert_eq!(rejoin.due(0, None), None);
+
+        rejoin.uplink_complete(false, false);
+        rejoin.uplink_complete(true, false);
+        rejoin.uplink_complete(false, false);
+        assert_eq!(rejoin.due(0, Some(0)), None);
+        rejoin.uplink_complete(false, false);
+        assert_eq!(rejoin.due(0, Some(0)), Some(RejoinReason::LinkLoss));
+
+        rejoin.uplink_complete(false, true);
+        assert_eq!(rejoin.due(0, Some(0)), Some(RejoinReason::SessionExpired));
+
+        rejoin.session_started(Some(0));
+        assert_eq!(rejoin.due(0, Some(0)), None);
+    }
+}
@@ -0,0 +1,176 @@
+//! Retransmission of confirmed uplinks which were not acknowledged.
+//!
+//! A retransmission repeats the very same frame (and therefore the same FCntUp) on a newly
+//! selected channel after a random ACK timeout of 1 to 3 seconds has passed since the end of the
+//! RX2 window (LoRaWAN 1.0.4, Section 4.3.1.2 and 19.1).
+use crate::region::constants::ACK_TIMEOUT;
+use lorawan::packet_length::phy::{MHDR_LEN, MIC_LEN};
+use lorawan::types::DR;
+use rand_core::RngCore;
+
+/// Describes how confirmed uplinks without acknowledgment are retransmitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RetransmissionPolicy {
+    /// Maximum amount of transmissions of a confirmed uplink, including the initial one.
+    pub max_attempts: u8,
+    /// Lower the data rate by one step after every second transmission, as long as the frame
+    /// still fits into the maximum payload size of the lower data rate. The configured data rate
+    /// of the device is not modified.
+    pub data_rate_step_down: bool,
+}
+
+impl Default for RetransmissionPolicy {
+    fn default() -> Self {
+        Self { max_attempts: 8, data_rate_step_down: false }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Retransmission {
+    policy: Option<RetransmissionPolicy>,
+    frame: heapless::Vec<u8, 256>,
+    attempts: u8,
+    data_rate: Option<DR>,
+}
+
+impl Retransmission {
+    pub(crate) fn set_policy(&mut self, policy: Option<RetransmissionPolicy>) {
+        self.policy = policy;
+        self.complete();
+        self.attempts = 0;
+    }
+
+    /// Keep a copy of a confirmed frame for later retransmissions.
+    pub(crate) fn uplink_sent(&mut self, frame: &[u8], confirmed: bool) {
+        self.complete();
+        self.attempts = 0;
+        self.data_rate = None;
+        if self.policy.is_some() && confirmed && self.frame.extend_from_slice(frame).is_ok() {
+            self.attempts = 1;
+        }
+    }
+
+    /// Whether the current uplink is to be retransmitted.
+    pub(crate) fn pending(&self) -> bool {
+        self.policy
+            .is_some_and(|policy| !self.frame.is_empty() && self.attempts < policy.max_attempts)
+    }
+
+    /// Amount of transmissions of the most recent confirmed uplink.
+    pub(crate) fn attempts(&self) -> u8 {
+        self.attempts
+    }
+
+    /// Data rate of the retransmission in progress, which may be lower than the configured one.
+    pub(crate) fn data_rate(&self) -> Option<DR> {
+        self.data_rate.filter(|_| !self.frame.is_empty())
+    }
+
+    /// Conclude the current uplink, either due to a downlink or after the last attempt.
+    pub(crate) fn complete(&mut self) {
+        self.frame.clear();
+    }
+
+    /// Provide the frame for the next transmission and the data rate to use for it, starting from
+    /// the configured data rate `dr`. `max_payload_len` provides the maximum MACPayload size of a
+    /// data rate, the step-down stops at the lowest data rate the frame still fits into.
+    pub(crate) fn next_attempt(
+        &mut self,
+        dr: DR,
+        max_payload_len: impl Fn(DR) -> u8,
+    ) -> (&[u8], DR) {
+        let step_down = self.policy.is_some_and(|policy| policy.data_rate_step_down);
+        self.attempts += 1;
+        let mut dr = self.data_rate.unwrap_or(dr);
+        // Transmissions are numbered from 1, step down before the 3rd, 5th, ...
+        if step_down && self.attempts % 2 == 1 && dr as u8 > 0 {
+            let lower = DR::from(dr as u8 - 1);
+            if self.frame.len() <= max_payload_len(lower) as usize + MHDR_LEN + MIC_LEN {
+                dr = lower;
+            }
+        }
+        self.data_rate = Some(dr);
+        (&self.frame, dr)
+    }
+
+    /// Random ACK timeout between 1 and 3 seconds.
+    pub(crate) fn ack_timeout_ms<RNG: RngCore>(rng: &mut RNG) -> u32 {
+        (ACK_TIMEOUT as u32 - 1) * 1000 + rng.next_u32() % 2001
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(feature = "region-eu868")]
+    use crate::region::{Configuration, Region};
+
+    #[test]
+    #[cfg(feature = "region-eu868")]
+    fn attempts() {
+        let mut rt = Retransmission::default();
+        rt.uplink_sent(&[0; 20], true);
+        assert!(!rt.pending());
+
+        rt.set_policy(Some(RetransmissionPolicy { max_attempts: 3, data_rate_step_down: true }));
+        rt.uplink_sent(&[0; 20], false);
+        assert!(!rt.pending());
+        rt.uplink_sent(&[0; 20], true);
+        assert!(rt.pending());
+
+        let region = Configuration::new(Region::EU868);
+        let max_len = |dr| region.get_max_payload_length(dr, false, false);
+        let (frame, dr) = rt.next_attempt(DR::_5, max_len);
+        assert_eq!(frame.len(), 20);
+        assert_eq!(dr, DR::_5);
+        assert!(rt.pending());
+        let (_, dr) = rt.next_attempt(DR::_5, max_len);
+        assert_eq!(dr, DR::_4);
+        assert_eq!(rt.data_rate(), Some(DR::_4));
+        assert_eq!(rt.attempts(), 3);
+        assert!(!rt.pending());
+        rt.complete();
+        assert_eq!(rt.data_rate(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "region-eu868")]
+    fn step_down_stops_at_lowest_fitting_data_rate() {
+        let mut rt = Retransmission::default();
+        rt.set_policy(Some(RetransmissionPolicy { max_attempts: 8, data_rate_step_down: true }));
+        // 64 bytes of MACPayload fit into DR3 (123 bytes) but not into DR2 (59 bytes)
+        rt.uplink_sent(&[0; 69], true);
+        let region = Configuration::new(Region::EU868);
+        let max_len = |dr| region.get_max_payload_length(dr, false, false);
+        let mut data_rates = [DR::_0; 7];
+        for dr in data_rates.iter_mut() {
+            *dr = rt.next_attempt(DR::_5, max_len).1;
+        }
+        // Retransmissions are attempts 2 to 8
+        assert_eq!(data_rates, [DR::_5, DR::_4, DR::_4, DR::_3, DR::_3, DR::_3, DR::_3]);
+    }
+
+    #[test]
+    #[cfg(feature = "region-eu868")]
+    fn no_step_down_for_oversized_frame() {
+        let mut rt = Retransmission::default();
+        rt.set_policy(Some(RetransmissionPolicy { max_attempts: 3, data_rate_step_down: true }));
+        rt.uplink_sent(&[0; 100], true);
+        let region = Configuration::new(Region::EU868);
+        let max_len = |dr| region.get_max_payload_length(dr, false, false);
+        let _ = rt.next_attempt(DR::_3, max_len);
+        let (_, dr) = rt.next_attempt(DR::_3, max_len);
+        assert_eq!(dr, DR::_3);
+    }
+
+    #[test]
+    fn ack_timeout() {
+        let mut rng = crate::Prng::new(0);
+        for _ in 0..100 {
+            let t = Retransmission::ack_timeout_ms(&mut rng);
+            assert!((1000..=3000).contains(&t));
+        }
+    }
+}
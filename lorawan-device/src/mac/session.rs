@@ -1,15 +1,15 @@
 use super::{
     otaa::{DevNonce, NetworkCredentials},
-    uplink, FcntUp, Response, SendData,
+    uplink, DropReason, Event, Events, FcntUp, Response, SendData,
 };
 use crate::radio::RadioBuffer;
 use crate::{region, AppSKey, Downlink, NwkSKey};
 use heapless::Vec;
 use lorawan::maccommandcreator::{
     DevStatusAnsCreator, DlChannelAnsCreator, LinkADRAnsCreator, NewChannelAnsCreator,
-    RXParamSetupAnsCreator, RXTimingSetupAnsCreator,
+    RXParamSetupAnsCreator, RXTimingSetupAnsCreator, TXParamSetupAnsCreator,
 };
-use lorawan::maccommands::{DownlinkMacCommand, MacCommandIterator};
+use lorawan::maccommands::{DownlinkMacCommand, MacCommandIterator, SerializableMacCommand};
 use lorawan::{
     creator::DataPayloadCreator,
     default_crypto::DefaultFactory,
@@ -124,6 +124,7 @@ impl Session {
         configuration: &mut super::Configuration,
         #[cfg(feature = "certification")] certification: &mut super::certification::Certification,
         #[cfg(feature = "multicast")] multicast: &mut super::multicast::Multicast,
+        events: &mut Events,
         rx: &mut RadioBuffer<N>,
         dl: &mut Vec<Downlink, D>,
         max_payload_len: u8,
@@ -141,16 +142,11 @@ impl Session {
                 let payload_len = encrypted_data.as_bytes().len();
                 if payload_len > max_payload_len as usize + MHDR_LEN + MIC_LEN {
                     info!("Dropping oversized payload.");
+                    events.push(Event::DownlinkDropped { reason: DropReason::Oversize });
                     return self.rx2_complete();
                 }
             }
 
-            // If ignore_mac is false, we're dealing with Class A downlink and
-            // therefore can clear uplinks which need to be retained for acknowledgment
-            if !ignore_mac {
-                self.uplink.clear_mac_commands(false);
-            }
-
             #[cfg(feature = "certification")]
             if let Some(port) = encrypted_data.f_port() {
                 if port > 0 {
@@ -165,10 +161,17 @@ impl Session {
             }
             let fcnt = encrypted_data.fhdr().fcnt() as u32;
             let confirmed = encrypted_data.is_confirmed();
-            if encrypted_data.validate_mic(self.nwkskey().inner(), fcnt, &DefaultFactory)
-                && (fcnt > self.fcnt_down || fcnt == 0)
-            {
+            if !encrypted_data.validate_mic(self.nwkskey().inner(), fcnt, &DefaultFactory) {
+                events.push(Event::DownlinkDropped { reason: DropReason::InvalidMic });
+            } else if fcnt <= self.fcnt_down && fcnt != 0 {
+                events.push(Event::DownlinkDropped { reason: DropReason::Replay });
+            } else {
                 self.fcnt_down = fcnt;
+                // If ignore_mac is false, we're dealing with Class A downlink and
+                // therefore can clear uplinks which need to be retained for acknowledgment
+                if !ignore_mac {
+                    self.uplink.downlink_received();
+                }
                 // We can safely unwrap here because we already validated the MIC
                 let decrypted = encrypted_data
                     .decrypt(
@@ -184,6 +187,7 @@ impl Session {
                     self.handle_downlink_macs(
                         configuration,
                         region,
+                        events,
                         MacCommandIterator::<DownlinkMacCommand<'_>>::new(decrypted.fhdr().data()),
                         snr,
                     );
@@ -191,6 +195,7 @@ impl Session {
                         self.handle_downlink_macs(
                             configuration,
                             region,
+                            events,
                             MacCommandIterator::<DownlinkMacCommand<'_>>::new(mac_cmds.data()),
                             snr,
                         );
@@ -213,9 +218,7 @@ impl Session {
                         #[cfg(feature = "certification")]
                         if certification.fport(fport) {
                             use crate::mac::certification::Response::*;
-                            match certification
-                                .handle_message(data, self.fcnt_down.try_into().unwrap())
-                            {
+                            match certification.handle_message(data, self.rx_app_cnt) {
                                 AdrBitChange(adr) => {
                                     self.override_adr = adr;
                                 }
@@ -225,9 +228,41 @@ impl Session {
                                 DutResetReq => {
                                     return Response::DeviceHandler(DeviceEvent::ResetDevice)
                                 }
+                                DutyCycleCtrl(enabled) => {
+                                    return Response::DeviceHandler(DeviceEvent::DutyCycleCtrl {
+                                        enabled,
+                                    })
+                                }
+                                SwitchClass(class) => {
+                                    return Response::DeviceHandler(DeviceEvent::SwitchClass {
+                                        class,
+                                    })
+                                }
                                 LinkCheckReq => {
                                     return Response::LinkCheckReq;
                                 }
+                                DeviceTimeReq => {
+                                    self.uplink.add_mac_command(
+                                        lorawan::maccommands::DeviceTimeReqCreator::new(),
+                                    );
+                                }
+                                PingSlotInfoReq(periodicity) => {
+                                    let mut cmd =
+                                        lorawan::maccommands::PingSlotInfoReqCreator::new();
+                                    // Periodicity is limited to 3 bits by the payload
+                                    let _ = cmd.set_periodicity(periodicity);
+                                    self.uplink.add_mac_command(cmd);
+                                }
+                                RxAppCntReset => {
+                                    self.rx_app_cnt = 0;
+                                }
+                                TxCw { timeout, frequency, tx_power } => {
+                                    return Response::TxContinuousWave {
+                                        frequency,
+                                        tx_power,
+                                        timeout_ms: timeout as u32 * 1000,
+                                    };
+                                }
                                 TxFramesCtrlReq(ftype) => {
                                     // None is a no-op, allowing network to trigger uplinks
                                     if ftype.is_some() {
@@ -245,7 +280,7 @@ impl Session {
                         }
                         #[cfg(feature = "multicast")]
                         if multicast.is_remote_setup_port(fport) {
-                            return multicast.handle_setup_message(data).into();
+                            return multicast.handle_setup_message(region, data).into();
                         }
 
                         // heapless Vec from slice fails only if slice is too large.
@@ -280,6 +315,7 @@ impl Session {
         &mut self,
         data: &SendData<'_>,
         tx_buffer: &mut RadioBuffer<N>,
+        mac_commands_max_len: usize,
     ) -> FcntUp {
         tx_buffer.clear();
         let fcnt = self.fcnt_up;
@@ -310,15 +346,12 @@ impl Session {
             .set_fcnt(fcnt);
 
         let crypto_factory = DefaultFactory;
-        match phy.build(
-            data.data,
-            self.uplink.mac_commands(),
-            &self.nwkskey,
-            &self.appskey,
-            &crypto_factory,
-        ) {
+        // MAC commands which are left out remain pending for a later uplink
+        let mac_commands_len = self.uplink.mac_commands_len_within(mac_commands_max_len);
+        let mac_commands = &self.uplink.mac_commands()[..mac_commands_len];
+        match phy.build(data.data, mac_commands, &self.nwkskey, &self.appskey, &crypto_factory) {
             Ok(packet) => {
-                self.uplink.clear_mac_commands(true);
+                self.uplink.mac_commands_sent(mac_commands_len);
                 tx_buffer.clear();
                 tx_buffer.extend_from_slice(packet).unwrap();
             }
@@ -331,6 +364,7 @@ impl Session {
         &mut self,
         configuration: &mut super::Configuration,
         region: &mut region::Configuration,
+        events: &mut Events,
         cmds: MacCommandIterator<'_, DownlinkMacCommand<'_>>,
         snr: i8,
     ) {
@@ -339,6 +373,14 @@ impl Session {
         let mut cmd_iter = cmds.into_iter().peekable();
         let mut num_adrreq = 0;
         while let Some(cmd) = cmd_iter.next() {
+            let cid = cmd.cid();
+            let applied = |events: &mut Events, applied: bool| {
+                events.push(if applied {
+                    Event::MacCommandApplied { cid }
+                } else {
+                    Event::MacCommandRejected { cid }
+                })
+            };
             match cmd {
                 DevStatusReq(..) => {
                     // TODO: Battery information should come from device/application
@@ -347,10 +389,12 @@ impl Session {
                     let mut cmd = DevStatusAnsCreator::new();
                     let _ = cmd.set_battery(255).set_margin(snr);
                     self.uplink.add_mac_command(cmd);
+                    applied(events, true);
                 }
                 DlChannelReq(payload) => {
                     if region.has_fixed_channel_plan() {
                         // Regions with fixed channel plan ignore this command
+                        applied(events, false);
                         continue;
                     }
                     let (ack_f, ack_c) = region
@@ -359,6 +403,7 @@ impl Session {
                     let mut cmd = DlChannelAnsCreator::new();
                     cmd.set_channel_frequency_ack(ack_f).set_uplink_frequency_exists_ack(ack_c);
                     self.uplink.add_mac_command(cmd);
+                    applied(events, ack_f && ack_c);
                 }
                 LinkADRReq(payload) => {
                     // Contiguous LinkADRReq commands shall be processed in the
@@ -408,6 +453,9 @@ impl Session {
                     let cm_ack = region.channel_mask_validate(&channel_mask, dr);
                     if cm_ack {
                         if let (Some(dr), Some(pw)) = (dr, pw) {
+                            if (dr, pw) != (configuration.data_rate, configuration.tx_power) {
+                                events.push(Event::AdrChange { data_rate: dr, tx_power: pw });
+                            }
                             // TODO: handle nbtrans
                             configuration.data_rate = dr;
                             configuration.tx_power = pw;
@@ -421,6 +469,7 @@ impl Session {
                             .set_data_rate_ack(dr.is_some())
                             .set_tx_power_ack(pw.is_some());
                         self.uplink.add_mac_command(cmd);
+                        applied(events, cm_ack && dr.is_some() && pw.is_some());
                     }
                     num_adrreq = 0;
                 }
@@ -431,10 +480,12 @@ impl Session {
                      * Therefore keep this as a placeholder until a proper
                      * device <-> mac integration has been implemented.
                      */
+                    applied(events, true);
                 }
                 NewChannelReq(payload) => {
                     if region.has_fixed_channel_plan() {
                         // Regions with fixed channel plan ignore this command
+                        applied(events, false);
                         continue;
                     }
                     let (ack_f, ack_d) = region.handle_new_channel(
@@ -446,6 +497,13 @@ impl Session {
                     let mut cmd = NewChannelAnsCreator::new();
                     cmd.set_channel_frequency_ack(ack_f).set_data_rate_range_ack(ack_d);
                     self.uplink.add_mac_command(cmd);
+                    if ack_f && ack_d {
+                        events.push(Event::ChannelAdded {
+                            index: payload.channel_index(),
+                            frequency: payload.frequency().value(),
+                        });
+                    }
+                    applied(events, ack_f && ack_d);
                 }
                 RXParamSetupReq(payload) => {
                     let freq = payload.frequency().value();
@@ -477,17 +535,32 @@ impl Session {
                         .set_channel_ack(freq_ack);
 
                     self.uplink.add_mac_command(cmd);
+                    applied(events, freq_ack && rx2_dr.is_some() && rx1_dr_offset.is_some());
 
-                    // TODO: An end-device that expects to receive Class C
+                    // An end-device that expects to receive Class C
                     // downlink frames will send an uplink frame as soon
                     // as possible after receiving a valid RXParamSetupReq
                     // that modifies RX2 (Frequency or RX2DataRate fields).
+                    // This is detected by `Mac::handle_rx`.
                 }
                 RXTimingSetupReq(payload) => {
                     configuration.rx1_delay = super::del_to_delay_ms(payload.delay());
                     self.uplink.add_mac_command(RXTimingSetupAnsCreator::new());
+                    applied(events, true);
+                }
+                TXParamSetupReq(payload) => {
+                    if !region.supports_tx_param_setup() {
+                        // Other regions ignore this command
+                        applied(events, false);
+                        continue;
+                    }
+                    // TODO: handle MaxEIRP and DownlinkDwellTime
+                    configuration.uplink_dwell_time = payload.uplink_dwell_time();
+                    self.uplink.add_mac_command(TXParamSetupAnsCreator::new());
+                    applied(events, true);
                 }
-                _ => (),
+                // not supported
+                _ => applied(events, false),
             }
         }
     }
@@ -1,13 +1,19 @@
 use lorawan::maccommands::{parse_uplink_mac_commands, SerializableMacCommand, UplinkMacCommand};
-use lorawan::packet_length::phy::mac::fhdr::FOPTS_MAX_LEN;
 
 #[cfg(feature = "serde")]
 mod serde;
 
+/// Capacity for pending MAC commands. Commands exceeding FOpts space are sent in the FRMPayload of
+/// a FPort 0 uplink.
+pub(crate) const MAC_COMMANDS_MAX_LEN: usize = 32;
+
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct Uplink {
-    pending: heapless::Vec<u8, FOPTS_MAX_LEN>,
+    pending: heapless::Vec<u8, MAC_COMMANDS_MAX_LEN>,
     confirmed: bool,
+    /// Length of the sticky answers at the start of `pending` which have been transmitted and
+    /// are retained until a downlink is received.
+    sticky_sent: usize,
 }
 
 impl Uplink {
@@ -22,33 +28,51 @@ impl Uplink {
     }
     pub fn add_mac_command<M: SerializableMacCommand>(&mut self, cmd: M) {
         // Check that there's still enough room for MAC commands
-        if self.pending.len() + cmd.payload_len() < FOPTS_MAX_LEN {
+        if self.pending.len() + 1 + cmd.payload_len() <= MAC_COMMANDS_MAX_LEN {
             let _ = self.pending.push(cmd.cid());
             self.pending.extend_from_slice(cmd.payload_bytes()).unwrap();
         }
     }
-    pub fn clear_mac_commands(&mut self, retain_acks: bool) {
-        // Certain commands have to be retained until their acknowledgment is confirmed
-        if retain_acks {
-            use UplinkMacCommand::*;
-            let mut data: heapless::Vec<u8, FOPTS_MAX_LEN> = heapless::Vec::new();
-            let _: heapless::Vec<_, FOPTS_MAX_LEN> = parse_uplink_mac_commands(&self.pending)
-                .filter(|cmd| {
-                    matches!(cmd, DlChannelAns(_) | RXParamSetupAns(_) | RXTimingSetupAns(_))
-                })
-                .map(|c| {
-                    let _ = data.push(c.cid());
-                    data.extend_from_slice(c.payload_bytes()).unwrap();
-                })
-                .collect();
-            self.pending = data;
-        } else {
-            self.pending.clear();
+    /// Account for the first `len` bytes of pending MAC commands having been transmitted.
+    ///
+    /// Certain answers have to be retained until their acknowledgment is confirmed by a
+    /// downlink, these stay at the start of the pending MAC commands.
+    pub fn mac_commands_sent(&mut self, len: usize) {
+        use UplinkMacCommand::*;
+        let mut data: heapless::Vec<u8, MAC_COMMANDS_MAX_LEN> = heapless::Vec::new();
+        for cmd in parse_uplink_mac_commands(&self.pending[..len]) {
+            if matches!(cmd, DlChannelAns(_) | RXParamSetupAns(_) | RXTimingSetupAns(_)) {
+                let _ = data.push(cmd.cid());
+                data.extend_from_slice(cmd.payload_bytes()).unwrap();
+            }
         }
+        self.sticky_sent = data.len();
+        data.extend_from_slice(&self.pending[len..]).unwrap();
+        self.pending = data;
+    }
+    /// A downlink has been received, which confirms all answers sent so far.
+    pub fn downlink_received(&mut self) {
+        let pending = self.pending.clone();
+        self.pending.clear();
+        self.pending.extend_from_slice(&pending[self.sticky_sent..]).unwrap();
+        self.sticky_sent = 0;
     }
     pub fn mac_commands(&self) -> &[u8] {
         &self.pending
     }
+    /// Length of the leading pending MAC commands which fit into `max_len` bytes without being
+    /// split.
+    pub fn mac_commands_len_within(&self, max_len: usize) -> usize {
+        let mut len = 0;
+        for cmd in parse_uplink_mac_commands(&self.pending) {
+            let cmd_len = 1 + cmd.payload_len();
+            if len + cmd_len > max_len {
+                break;
+            }
+            len += cmd_len;
+        }
+        len
+    }
 }
 
 #[cfg(feature = "defmt-03")]
@@ -66,7 +90,10 @@ impl defmt::Format for Uplink {
 #[cfg(test)]
 mod test {
     use super::*;
-    use lorawan::maccommands::{parse_uplink_mac_commands, LinkADRAnsCreator, UplinkMacCommand};
+    use lorawan::maccommands::{
+        parse_uplink_mac_commands, LinkADRAnsCreator, NewChannelAnsCreator, RXParamSetupAnsCreator,
+        UplinkMacCommand,
+    };
     #[test]
     fn two_link_adr_ans() {
         let mut uplink = Uplink::default();
@@ -77,4 +104,37 @@ mod test {
         assert!(matches!(mac_commands.next().unwrap(), UplinkMacCommand::LinkADRAns(_)));
         assert!(mac_commands.next().is_none());
     }
+
+    #[test]
+    fn exceeding_fopts() {
+        let mut uplink = Uplink::default();
+        for _ in 0..4 {
+            uplink.add_mac_command(LinkADRAnsCreator::new());
+            uplink.add_mac_command(NewChannelAnsCreator::new());
+        }
+        assert_eq!(uplink.mac_commands().len(), 16);
+        assert_eq!(uplink.mac_commands_len_within(15), 14);
+        assert_eq!(uplink.mac_commands_len_within(3), 2);
+        uplink.mac_commands_sent(14);
+        assert_eq!(uplink.mac_commands().len(), 2);
+    }
+
+    #[test]
+    fn sticky_answers_retained_until_downlink() {
+        let mut uplink = Uplink::default();
+        uplink.add_mac_command(RXParamSetupAnsCreator::new());
+        uplink.add_mac_command(LinkADRAnsCreator::new());
+        uplink.mac_commands_sent(4);
+        assert!(matches!(
+            parse_uplink_mac_commands(uplink.mac_commands()).next().unwrap(),
+            UplinkMacCommand::RXParamSetupAns(_)
+        ));
+        uplink.add_mac_command(LinkADRAnsCreator::new());
+        uplink.downlink_received();
+        assert_eq!(uplink.mac_commands().len(), 2);
+        assert!(matches!(
+            parse_uplink_mac_commands(uplink.mac_commands()).next().unwrap(),
+            UplinkMacCommand::LinkADRAns(_)
+        ));
+    }
 }
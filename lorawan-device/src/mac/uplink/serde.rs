@@ -1,5 +1,5 @@
 use crate::mac::uplink::Uplink;
-use lorawan::packet_length::phy::mac::fhdr::FOPTS_MAX_LEN;
+use crate::mac::uplink::MAC_COMMANDS_MAX_LEN;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 #[cfg(feature = "serde")]
@@ -9,7 +9,7 @@ impl Serialize for Uplink {
         let mut state = serializer.serialize_struct("Uplink", 3)?;
         state.serialize_field("confirmed", &self.confirmed)?;
         state.serialize_field("pending_len", &(self.pending.len() as u8))?;
-        let mut full_array = [0u8; FOPTS_MAX_LEN];
+        let mut full_array = [0u8; MAC_COMMANDS_MAX_LEN];
         full_array[..self.pending.len()].copy_from_slice(&self.pending);
         state.serialize_field("pending_data", &full_array)?;
         state.end()
@@ -48,7 +48,8 @@ impl<'de> Deserialize<'de> for Uplink {
             {
                 let mut confirmed: Option<bool> = None;
                 let mut pending_len: Option<u8> = None;
-                let mut pending_data: Option<[u8; FOPTS_MAX_LEN]> = None;
+                // Sessions stored with a FOpts sized buffer are accepted as well
+                let mut pending_data: Option<heapless::Vec<u8, MAC_COMMANDS_MAX_LEN>> = None;
 
                 while let Some(key) = map.next_key::<Field>()? {
                     match key {
@@ -79,7 +80,7 @@ impl<'de> Deserialize<'de> for Uplink {
                 let pending_data =
                     pending_data.ok_or_else(|| de::Error::missing_field("pending_data"))?;
 
-                if pending_len as usize > FOPTS_MAX_LEN {
+                if pending_len as usize > pending_data.len() {
                     return Err(de::Error::custom("pending_len exceeds maximum size"));
                 }
 
@@ -88,7 +89,7 @@ impl<'de> Deserialize<'de> for Uplink {
                     .extend_from_slice(&pending_data[..pending_len as usize])
                     .map_err(|_| de::Error::custom("failed to create heapless::Vec"))?;
 
-                Ok(Uplink { pending, confirmed })
+                Ok(Uplink { pending, confirmed, sticky_sent: 0 })
             }
         }
 
@@ -128,7 +129,7 @@ mod tests {
     #[test]
     fn test_serde_max_size() {
         let mut uplink = Uplink::default();
-        let max_data = [42u8; FOPTS_MAX_LEN];
+        let max_data = [42u8; MAC_COMMANDS_MAX_LEN];
         uplink.pending.extend_from_slice(&max_data).unwrap();
 
         let json = serde_json::to_string(&uplink).unwrap();
@@ -136,4 +137,13 @@ mod tests {
         assert!(!decoded.confirms_downlink());
         assert_eq!(decoded.mac_commands(), &max_data);
     }
+
+    #[test]
+    fn test_serde_fopts_sized_buffer() {
+        let json =
+            r#"{"confirmed":true,"pending_len":2,"pending_data":[3,7,0,0,0,0,0,0,0,0,0,0,0,0,0]}"#;
+        let decoded: Uplink = serde_json::from_str(json).unwrap();
+        assert!(decoded.confirms_downlink());
+        assert_eq!(decoded.mac_commands(), &[3, 7]);
+    }
 }
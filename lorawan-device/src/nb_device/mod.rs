@@ -6,6 +6,13 @@ use super::*;
 use crate::nb_device::radio::PhyRxTx;
 use mac::{Mac, SendData};
 
+#[cfg(feature = "multicast")]
+pub use crate::async_device::{McGroup, MulticastResponse};
+#[cfg(feature = "multicast")]
+use crate::mac::multicast;
+#[cfg(feature = "multicast")]
+use lorawan::{default_crypto::DefaultFactory, keys::McRootKey};
+
 pub(crate) mod state;
 
 pub mod radio;
@@ -37,10 +44,87 @@ where
                 tx_buffer: RadioBuffer::new(),
                 mac: Mac::new(region, R::MAX_RADIO_POWER, R::ANTENNA_GAIN),
                 downlink: Vec::new(),
+                #[cfg(feature = "class-c")]
+                class_c: false,
             },
         }
     }
 
+    /// Enables Class C behavior: after the next uplink, the radio continuously receives in
+    /// between and after the RX windows. Downlinks received this way are provided as
+    /// `Response::DownlinkReceived` when handling the radio event in any state but `SendingData`.
+    #[cfg(feature = "class-c")]
+    pub fn enable_class_c(&mut self) {
+        self.shared.class_c = true;
+    }
+
+    /// Disables Class C behavior. Note that the radio keeps receiving until the next uplink.
+    #[cfg(feature = "class-c")]
+    pub fn disable_class_c(&mut self) {
+        self.shared.class_c = false;
+    }
+
+    /// Sets the port range for frames sent to multicast groups. Warning: this exclusively handles
+    /// these frames in the multicast context and, therefore, unicast frames in this range will not
+    /// be handled. Defaults to `201..=205`.
+    #[cfg(feature = "multicast")]
+    pub fn set_multicast_port_range(&mut self, range: core::ops::RangeInclusive<u8>) {
+        self.shared.mac.multicast.set_range(range);
+    }
+
+    /// Sets the port for remote multicast setup messages used to derive multicast session keys.
+    /// Warning: this exclusively handles these frames in the multicast layer and other application
+    /// frames on this port will be ignored. Defaults to `200`.
+    #[cfg(feature = "multicast")]
+    pub fn set_multicast_remote_setup_port(&mut self, port: u8) {
+        self.shared.mac.multicast.set_remote_setup_port(port);
+    }
+
+    /// Set the McKEKey for multicast session key derivation by providing a McRootKey.
+    #[cfg(feature = "multicast")]
+    pub fn set_multicast_ke_key(&mut self, mc_root_key: McRootKey) {
+        let key = lorawan::keys::McKEKey::derive_from(&DefaultFactory, &mc_root_key);
+        self.shared.mac.multicast.mc_k_e_key = Some(key);
+    }
+
+    /// Sets a multicast session for this device for a specific group.
+    #[cfg(feature = "multicast")]
+    pub fn set_multicast_session(&mut self, group: McGroup, session: multicast::Session) {
+        self.shared.mac.multicast.sessions[group.index()] = Some(session);
+    }
+
+    /// Provides the multicast session of a specific group, if any.
+    #[cfg(feature = "multicast")]
+    pub fn get_multicast_session(&self, group: McGroup) -> Option<&multicast::Session> {
+        self.shared.mac.multicast.sessions[group.index()].as_ref()
+    }
+
+    /// Removes the multicast session of a specific group.
+    #[cfg(feature = "multicast")]
+    pub fn remove_multicast_session(&mut self, group: McGroup) {
+        self.shared.mac.multicast.sessions[group.index()] = None;
+    }
+
+    /// Send the answers to remote multicast setup messages, which are announced by
+    /// `Response::MulticastAnswerPending` and `Response::Multicast(MulticastResponse::NewSession)`.
+    /// The uplink is handled like any other, so this is only possible while ready to send data.
+    #[cfg(feature = "multicast")]
+    pub fn send_multicast_answers(&mut self) -> Result<Response, Error<R>> {
+        let State::Idle(idle) = self.state else {
+            return Err(state::Error::MulticastAnswersWhileBusy.into());
+        };
+        let class_c = self.shared.class_c();
+        let (new_state, result) = idle.send_multicast_answers::<R, RNG, N>(
+            &mut self.shared.mac,
+            &mut self.shared.radio,
+            &mut self.shared.rng,
+            &mut self.shared.tx_buffer,
+            class_c,
+        );
+        self.state = new_state;
+        result
+    }
+
     pub fn join(&mut self, join_mode: JoinMode) -> Result<Response, Error<R>> {
         match join_mode {
             JoinMode::OTAA { deveui, appeui, appkey } => {
@@ -65,6 +149,27 @@ where
         self.shared.mac.configuration.data_rate = datarate
     }
 
+    /// Maximum application payload size in bytes for the current data rate. Larger payloads are
+    /// rejected by [`send`](Self::send) with [`mac::Error::PayloadTooLarge`].
+    pub fn get_max_payload_len(&self) -> u8 {
+        self.shared.mac.max_payload_len()
+    }
+
+    /// Configure retransmissions of confirmed uplinks, or disable them by providing `None`.
+    ///
+    /// When enabled, an unacknowledged confirmed uplink is retransmitted with the same FCntUp:
+    /// after RX2 a `Response::TimeoutRequest` for the ACK timeout is provided instead of
+    /// `Response::NoAck`, which is only provided once the maximum amount of attempts is reached.
+    pub fn set_retransmission_policy(&mut self, policy: Option<mac::RetransmissionPolicy>) {
+        self.shared.mac.retransmission.set_policy(policy);
+    }
+
+    /// Amount of transmissions of the most recent confirmed uplink, if retransmissions are
+    /// enabled.
+    pub fn get_retransmission_attempts(&self) -> u8 {
+        self.shared.mac.retransmission.attempts()
+    }
+
     pub fn ready_to_send_data(&self) -> bool {
         matches!(&self.state, State::Idle(_)) && self.shared.mac.is_joined()
     }
@@ -73,6 +178,30 @@ where
         self.handle_event(Event::SendDataRequest(SendData { data, fport, confirmed }))
     }
 
+    /// Send pending MAC commands in an unconfirmed FPort 0 uplink, without any application data.
+    ///
+    /// Pending MAC commands which do not fit into FOpts alongside the application data of an
+    /// uplink are left for the next uplink, this allows sending them right away. This is
+    /// announced by `Response::MacUplinkPending` or [`mac_uplink_due`](Self::mac_uplink_due).
+    /// If no MAC commands are pending, no uplink is sent and `Response::NoUpdate` is returned.
+    pub fn flush_mac_commands(&mut self) -> Result<Response, Error<R>> {
+        let session = self.shared.mac.get_session();
+        if self.ready_to_send_data()
+            && session.is_some_and(|session| session.uplink.mac_commands().is_empty())
+        {
+            return Ok(Response::NoUpdate);
+        }
+        self.send(&[], 0, false)
+    }
+
+    /// Whether MAC commands did not fit into FOpts of the previous uplink and are to be sent
+    /// using [`flush_mac_commands`](Self::flush_mac_commands). Once the RX windows are complete
+    /// without a downlink, this is announced by `Response::MacUplinkPending` instead of
+    /// `Response::RxComplete`.
+    pub fn mac_uplink_due(&self) -> bool {
+        self.shared.mac.mac_only_uplink_pending()
+    }
+
     pub fn get_fcnt_up(&self) -> Option<u32> {
         self.shared.mac.get_fcnt_up()
     }
@@ -93,13 +222,29 @@ where
         self.shared.downlink.pop()
     }
 
+    /// Metadata of the most recent uplink, including RX window and quality of a downlink received
+    /// in response. It is replaced by the next uplink, so it is to be read once the uplink is
+    /// concluded by a `Response` such as `Response::RxComplete` or `Response::DownlinkReceived`.
+    pub fn get_uplink_metadata(&self) -> Option<mac::UplinkMetadata> {
+        self.shared.mac.uplink_metadata
+    }
+
+    /// Take the oldest buffered MAC event, such as applied MAC commands or dropped downlinks. Up
+    /// to [`mac::EVENT_QUEUE_LEN`] events are buffered, after which the oldest are discarded.
+    #[cfg(feature = "mac-events")]
+    pub fn take_mac_event(&mut self) -> Option<mac::Event> {
+        self.shared.mac.events.pop()
+    }
+
     pub fn handle_event(&mut self, event: Event<'_, R>) -> Result<Response, Error<R>> {
+        let class_c = self.shared.class_c();
         let (new_state, result) = self.state.handle_event::<R, RNG, N, D>(
             &mut self.shared.mac,
             &mut self.shared.radio,
             &mut self.shared.rng,
             &mut self.shared.tx_buffer,
             &mut self.shared.downlink,
+            class_c,
             event,
         );
         self.state = new_state;
@@ -113,6 +258,17 @@ pub(crate) struct Shared<R: PhyRxTx + Timings, RNG: RngCore, const N: usize, con
     pub(crate) tx_buffer: RadioBuffer<N>,
     pub(crate) mac: Mac,
     pub(crate) downlink: Vec<Downlink, D>,
+    #[cfg(feature = "class-c")]
+    pub(crate) class_c: bool,
+}
+
+impl<R: PhyRxTx + Timings, RNG: RngCore, const N: usize, const D: usize> Shared<R, RNG, N, D> {
+    fn class_c(&self) -> bool {
+        #[cfg(feature = "class-c")]
+        return self.class_c;
+        #[cfg(not(feature = "class-c"))]
+        false
+    }
 }
 
 #[derive(Debug)]
@@ -128,6 +284,24 @@ pub enum Response {
     ReadyToSend,
     SessionExpired,
     RxComplete,
+    /// MAC commands which did not fit into FOpts of the uplink are to be sent using
+    /// [`Device::flush_mac_commands`].
+    MacUplinkPending,
+    #[cfg(feature = "multicast")]
+    Multicast(MulticastResponse),
+    /// Continuous wave transmission requested by TxCwReq of the certification protocol, which the
+    /// application is to carry out using the radio: frequency in Hz, TX power in dBm and duration
+    /// in milliseconds.
+    #[cfg(feature = "certification")]
+    TxContinuousWave {
+        frequency: u32,
+        tx_power: i8,
+        timeout_ms: u32,
+    },
+    /// Answers to remote multicast setup messages are to be sent using
+    /// [`Device::send_multicast_answers`].
+    #[cfg(feature = "multicast")]
+    MulticastAnswerPending,
 }
 
 #[derive(Debug)]
@@ -37,6 +37,15 @@ O
 └──────────╫─╫───┘         ║   ║               ║                    ║
 else(Ready)║ ╚═════════════╝   ║               ║                    ║
            ╚═══════════════════╝               ╚════════════════════╝
+
+If a retransmission policy is configured, an unacknowledged confirmed uplink transitions from
+RxWindow2 into "WaitingForAckTimeout" (TimeoutReq) instead, which retransmits the frame upon
+Timeout and continues like SendData.
+
+If Class C is enabled, the radio continuously receives on the RXC configuration (RX2 frequency and
+data rate) while "WaitingForRxWindow" and once the RX windows are complete. Downlinks received in
+RXC are handled as RadioEvents in "Idle", "WaitingForRxWindow" and "WaitingForAckTimeout" without
+a transition (DataDown).
  */
 use super::super::*;
 use super::{
@@ -50,6 +59,7 @@ pub enum State {
     SendingData(SendingData),
     WaitingForRxWindow(WaitingForRxWindow),
     WaitingForRx(WaitingForRx),
+    WaitingForAckTimeout(WaitingForAckTimeout),
 }
 
 macro_rules! into_state {
@@ -64,7 +74,7 @@ macro_rules! into_state {
     )*};
 }
 
-into_state!(Idle, SendingData, WaitingForRxWindow, WaitingForRx);
+into_state!(Idle, SendingData, WaitingForRxWindow, WaitingForRx, WaitingForAckTimeout);
 
 impl Default for State {
     fn default() -> Self {
@@ -90,8 +100,13 @@ pub enum Error {
     TxRequestDuringTx,
     NewSessionWhileWaitingForRx,
     SendDataWhileWaitingForRx,
+    RadioEventWhileWaitingForAckTimeout,
+    NewSessionWhileWaitingForAckTimeout,
+    SendDataWhileWaitingForAckTimeout,
     BufferTooSmall,
     UnexpectedRadioResponse,
+    #[cfg(feature = "multicast")]
+    MulticastAnswersWhileBusy,
 }
 
 impl<R: radio::PhyRxTx> From<Error> for super::Error<R> {
@@ -101,6 +116,7 @@ impl<R: radio::PhyRxTx> From<Error> for super::Error<R> {
 }
 
 impl State {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn handle_event<
         R: radio::PhyRxTx + Timings,
         RNG: RngCore,
@@ -113,13 +129,23 @@ impl State {
         rng: &mut RNG,
         buf: &mut RadioBuffer<N>,
         dl: &mut Vec<Downlink, D>,
+        class_c: bool,
         event: Event<'_, R>,
     ) -> (Self, Result<Response, super::Error<R>>) {
         match self {
-            State::Idle(s) => s.handle_event::<R, RNG, N>(mac, radio, rng, buf, event),
-            State::SendingData(s) => s.handle_event::<R, N>(mac, radio, event),
-            State::WaitingForRxWindow(s) => s.handle_event::<R, N>(mac, radio, event),
-            State::WaitingForRx(s) => s.handle_event::<R, N, D>(mac, radio, buf, event, dl),
+            State::Idle(s) => {
+                s.handle_event::<R, RNG, N, D>(mac, radio, rng, buf, dl, class_c, event)
+            }
+            State::SendingData(s) => s.handle_event::<R, N>(mac, radio, class_c, event),
+            State::WaitingForRxWindow(s) => {
+                s.handle_event::<R, N, D>(mac, radio, buf, dl, class_c, event)
+            }
+            State::WaitingForRx(s) => {
+                s.handle_event::<R, RNG, N, D>(mac, radio, rng, buf, event, dl, class_c)
+            }
+            State::WaitingForAckTimeout(s) => {
+                s.handle_event::<R, RNG, N, D>(mac, radio, rng, buf, dl, class_c, event)
+            }
         }
     }
 }
@@ -128,12 +154,20 @@ impl State {
 pub struct Idle;
 
 impl Idle {
-    pub(crate) fn handle_event<R: radio::PhyRxTx + Timings, RNG: RngCore, const N: usize>(
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn handle_event<
+        R: radio::PhyRxTx + Timings,
+        RNG: RngCore,
+        const N: usize,
+        const D: usize,
+    >(
         self,
         mac: &mut Mac,
         radio: &mut R,
         rng: &mut RNG,
         buf: &mut RadioBuffer<N>,
+        dl: &mut Vec<Downlink, D>,
+        class_c: bool,
         event: Event<'_, R>,
     ) -> (State, Result<Response, super::Error<R>>) {
         enum IntermediateResponse<R: radio::PhyRxTx> {
@@ -148,8 +182,16 @@ impl Idle {
                 IntermediateResponse::RadioTx((Frame::Join, tx_config, dev_nonce as u32))
             }
             Event::TimeoutFired => IntermediateResponse::EarlyReturn(Ok(Response::NoUpdate)),
-            Event::RadioEvent(_radio_event) => {
-                IntermediateResponse::EarlyReturn(Err(Error::RadioEventWhileIdle.into()))
+            Event::RadioEvent(radio_event) => {
+                IntermediateResponse::EarlyReturn(rxc_radio_event::<R, N, D>(
+                    mac,
+                    radio,
+                    buf,
+                    dl,
+                    class_c,
+                    radio_event,
+                    Error::RadioEventWhileIdle,
+                ))
             }
             Event::SendDataRequest(send_data) => {
                 let tx_config = mac.send::<RNG, N>(rng, buf, &send_data);
@@ -164,29 +206,75 @@ impl Idle {
         match response {
             IntermediateResponse::EarlyReturn(response) => (State::Idle(self), response),
             IntermediateResponse::RadioTx((frame, tx_config, fcnt_up)) => {
-                let event: radio::Event<'_, R> =
-                    radio::Event::TxRequest(tx_config, buf.as_ref_for_read());
-                match radio.handle_event(event) {
-                    Ok(response) => {
-                        match response {
-                            // intermediate state where we wait for Join to complete sending
-                            // allows for asynchronous sending
-                            radio::Response::Txing => (
-                                State::SendingData(SendingData { frame }),
-                                Ok(Response::UplinkSending(fcnt_up)),
-                            ),
-                            // directly jump to waiting for RxWindow
-                            // allows for synchronous sending
-                            radio::Response::TxDone(ms) => {
-                                data_rxwindow1_timeout::<R, N>(frame, mac, radio, ms)
-                            }
-                            _ => (State::Idle(self), Err(Error::UnexpectedRadioResponse.into())),
-                        }
-                    }
-                    Err(e) => (State::Idle(self), Err(super::Error::Radio(e))),
+                transmit::<R, N>(frame, tx_config, fcnt_up, mac, radio, buf, class_c, self.into())
+            }
+        }
+    }
+
+    /// Send pending answers to remote multicast setup messages.
+    #[cfg(feature = "multicast")]
+    pub(crate) fn send_multicast_answers<
+        R: radio::PhyRxTx + Timings,
+        RNG: RngCore,
+        const N: usize,
+    >(
+        self,
+        mac: &mut Mac,
+        radio: &mut R,
+        rng: &mut RNG,
+        buf: &mut RadioBuffer<N>,
+        class_c: bool,
+    ) -> (State, Result<Response, super::Error<R>>) {
+        if !mac.multicast.uplink_pending() {
+            return (self.into(), Ok(Response::NoUpdate));
+        }
+        match mac.multicast_setup_send::<RNG, N>(rng, buf) {
+            Ok((tx_config, fcnt_up)) => transmit::<R, N>(
+                Frame::Data,
+                tx_config,
+                fcnt_up,
+                mac,
+                radio,
+                buf,
+                class_c,
+                self.into(),
+            ),
+            Err(e) => (self.into(), Err(e.into())),
+        }
+    }
+}
+
+/// Hand the prepared uplink in `buf` over to the radio. Upon error, `state` is retained.
+#[allow(clippy::too_many_arguments)]
+fn transmit<R: radio::PhyRxTx + Timings, const N: usize>(
+    frame: Frame,
+    tx_config: radio::TxConfig,
+    fcnt_up: u32,
+    mac: &mut Mac,
+    radio: &mut R,
+    buf: &mut RadioBuffer<N>,
+    class_c: bool,
+    state: State,
+) -> (State, Result<Response, super::Error<R>>) {
+    let event: radio::Event<'_, R> = radio::Event::TxRequest(tx_config, buf.as_ref_for_read());
+    match radio.handle_event(event) {
+        Ok(response) => {
+            match response {
+                // intermediate state where we wait for Join to complete sending
+                // allows for asynchronous sending
+                radio::Response::Txing => (
+                    State::SendingData(SendingData { frame }),
+                    Ok(Response::UplinkSending(fcnt_up)),
+                ),
+                // directly jump to waiting for RxWindow
+                // allows for synchronous sending
+                radio::Response::TxDone(ms) => {
+                    data_rxwindow1_timeout::<R, N>(frame, mac, radio, class_c, ms)
                 }
+                _ => (state, Err(Error::UnexpectedRadioResponse.into())),
             }
         }
+        Err(e) => (state, Err(super::Error::Radio(e))),
     }
 }
 
@@ -200,6 +288,7 @@ impl SendingData {
         self,
         mac: &mut Mac,
         radio: &mut R,
+        class_c: bool,
         event: Event<'_, R>,
     ) -> (State, Result<Response, super::Error<R>>) {
         match event {
@@ -211,7 +300,7 @@ impl SendingData {
                         match response {
                             // expect a complete transmit
                             radio::Response::TxDone(ms) => {
-                                data_rxwindow1_timeout::<R, N>(self.frame, mac, radio, ms)
+                                data_rxwindow1_timeout::<R, N>(self.frame, mac, radio, class_c, ms)
                             }
                             // anything other than TxComplete is unexpected
                             _ => {
@@ -239,15 +328,24 @@ pub struct WaitingForRxWindow {
 }
 
 impl WaitingForRxWindow {
-    pub(crate) fn handle_event<R: radio::PhyRxTx + Timings, const N: usize>(
+    pub(crate) fn handle_event<R: radio::PhyRxTx + Timings, const N: usize, const D: usize>(
         self,
         mac: &mut Mac,
         radio: &mut R,
+        buf: &mut RadioBuffer<N>,
+        dl: &mut Vec<Downlink, D>,
+        class_c: bool,
         event: Event<'_, R>,
     ) -> (State, Result<Response, super::Error<R>>) {
         match event {
             // we are waiting for a Timeout
             Event::TimeoutFired => {
+                // stop receiving in RXC before configuring the RX window
+                if class_c {
+                    if let Err(e) = radio.handle_event(radio::Event::CancelRx) {
+                        return (State::WaitingForRxWindow(self), Err(super::Error::Radio(e)));
+                    }
+                }
                 let rf_config = mac.get_rf_config(&self.frame, &self.window.into());
                 let window_start = mac.get_rx_delay(&self.frame, &self.window.into());
                 // configure the radio for the RX
@@ -279,9 +377,17 @@ impl WaitingForRxWindow {
                     Err(e) => (State::WaitingForRxWindow(self), Err(super::Error::Radio(e))),
                 }
             }
-            Event::RadioEvent(_) => (
+            Event::RadioEvent(radio_event) => (
                 State::WaitingForRxWindow(self),
-                Err(Error::RadioEventWhileWaitingForRxWindow.into()),
+                rxc_radio_event::<R, N, D>(
+                    mac,
+                    radio,
+                    buf,
+                    dl,
+                    class_c,
+                    radio_event,
+                    Error::RadioEventWhileWaitingForRxWindow,
+                ),
             ),
             Event::Join(_) => (
                 State::WaitingForRxWindow(self),
@@ -303,13 +409,21 @@ pub struct WaitingForRx {
 }
 
 impl WaitingForRx {
-    pub(crate) fn handle_event<R: radio::PhyRxTx + Timings, const N: usize, const D: usize>(
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn handle_event<
+        R: radio::PhyRxTx + Timings,
+        RNG: RngCore,
+        const N: usize,
+        const D: usize,
+    >(
         self,
         mac: &mut Mac,
         radio: &mut R,
+        rng: &mut RNG,
         buf: &mut RadioBuffer<N>,
         event: Event<'_, R>,
         dl: &mut Vec<Downlink, D>,
+        class_c: bool,
     ) -> (State, Result<Response, super::Error<R>>) {
         match event {
             // we are waiting for the async tx to complete
@@ -333,8 +447,15 @@ impl WaitingForRx {
                                 mac::Response::NoUpdate => {
                                     (State::WaitingForRx(self), Ok(Response::NoUpdate))
                                 }
+                                #[cfg(feature = "multicast")]
+                                mac::Response::Multicast(mac::multicast::Response::NoUpdate) => {
+                                    (State::WaitingForRx(self), Ok(Response::NoUpdate))
+                                }
                                 // Any other type of update indicates we are done receiving. Change to Idle
-                                r => (State::Idle(Idle), Ok(r.into())),
+                                r => {
+                                    mac.downlink_received(self.window.into(), quality);
+                                    after_rx_windows(State::Idle(Idle), mac, radio, class_c, r)
+                                }
                             }
                         }
                         _ => (State::WaitingForRx(self), Ok(Response::NoUpdate)),
@@ -353,19 +474,34 @@ impl WaitingForRx {
                             - mac.get_rx_delay(&self.frame, &Window::_1);
                         let t2 = t1 + time_between_windows;
                         // TODO: jump to RxWindow2 if t2 == now
-                        (
-                            State::WaitingForRxWindow(WaitingForRxWindow {
-                                frame: self.frame,
-                                window: Rx::_2(t2),
-                            }),
-                            Ok(Response::TimeoutRequest(t2)),
-                        )
+                        let state = State::WaitingForRxWindow(WaitingForRxWindow {
+                            frame: self.frame,
+                            window: Rx::_2(t2),
+                        });
+                        match rxc(mac, radio, class_c) {
+                            Ok(()) => (state, Ok(Response::TimeoutRequest(t2))),
+                            Err(e) => (state, Err(super::Error::Radio(e))),
+                        }
                     }
                     // Timeout during second RxWindow leads to giving up
-                    Rx::_2(_) => {
-                        let response = mac.rx2_complete();
-                        (State::Idle(Idle), Ok(response.into()))
-                    }
+                    Rx::_2(t2) => match mac.rx2_complete() {
+                        // ..unless a confirmed uplink is to be retransmitted
+                        mac::Response::Retransmit => {
+                            let t = t2
+                                + radio.get_rx_window_duration_ms()
+                                + mac::Retransmission::ack_timeout_ms(rng);
+                            after_rx_windows(
+                                State::WaitingForAckTimeout(WaitingForAckTimeout),
+                                mac,
+                                radio,
+                                class_c,
+                                Response::TimeoutRequest(t),
+                            )
+                        }
+                        response => {
+                            after_rx_windows(State::Idle(Idle), mac, radio, class_c, response)
+                        }
+                    },
                 }
             }
             Event::Join(_) => {
@@ -378,6 +514,61 @@ impl WaitingForRx {
     }
 }
 
+#[derive(Copy, Clone)]
+pub struct WaitingForAckTimeout;
+
+impl WaitingForAckTimeout {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn handle_event<
+        R: radio::PhyRxTx + Timings,
+        RNG: RngCore,
+        const N: usize,
+        const D: usize,
+    >(
+        self,
+        mac: &mut Mac,
+        radio: &mut R,
+        rng: &mut RNG,
+        buf: &mut RadioBuffer<N>,
+        dl: &mut Vec<Downlink, D>,
+        class_c: bool,
+        event: Event<'_, R>,
+    ) -> (State, Result<Response, super::Error<R>>) {
+        match event {
+            // ACK timeout has passed, retransmit the confirmed uplink
+            Event::TimeoutFired => match mac.retransmit::<RNG, N>(rng, buf) {
+                Ok((tx_config, fcnt_up)) => transmit::<R, N>(
+                    Frame::Data,
+                    tx_config,
+                    fcnt_up,
+                    mac,
+                    radio,
+                    buf,
+                    class_c,
+                    self.into(),
+                ),
+                Err(e) => (State::Idle(Idle), Err(e.into())),
+            },
+            Event::RadioEvent(radio_event) => (
+                self.into(),
+                rxc_radio_event::<R, N, D>(
+                    mac,
+                    radio,
+                    buf,
+                    dl,
+                    class_c,
+                    radio_event,
+                    Error::RadioEventWhileWaitingForAckTimeout,
+                ),
+            ),
+            Event::Join(_) => (self.into(), Err(Error::NewSessionWhileWaitingForAckTimeout.into())),
+            Event::SendDataRequest(_) => {
+                (self.into(), Err(Error::SendDataWhileWaitingForAckTimeout.into()))
+            }
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 enum Rx {
     _1(u32),
@@ -388,12 +579,79 @@ fn data_rxwindow1_timeout<R: radio::PhyRxTx + Timings, const N: usize>(
     frame: Frame,
     mac: &mut Mac,
     radio: &mut R,
+    class_c: bool,
     timestamp_ms: u32,
 ) -> (State, Result<Response, super::Error<R>>) {
+    mac.uplink_transmitted(Some(timestamp_ms.into()));
     let delay = mac.get_rx_delay(&frame, &Window::_1);
     let t1 = (delay as i32 + timestamp_ms as i32 + radio.get_rx_window_offset_ms()) as u32;
-    (
-        State::WaitingForRxWindow(WaitingForRxWindow { frame, window: Rx::_1(t1) }),
-        Ok(Response::TimeoutRequest(t1)),
-    )
+    let state = State::WaitingForRxWindow(WaitingForRxWindow { frame, window: Rx::_1(t1) });
+    // a device only operates in Class C once it has joined
+    match rxc(mac, radio, class_c && matches!(frame, Frame::Data)) {
+        Ok(()) => (state, Ok(Response::TimeoutRequest(t1))),
+        Err(e) => (state, Err(super::Error::Radio(e))),
+    }
+}
+
+/// Transition into `state` once the RX windows are complete, listening in RXC for Class C.
+fn after_rx_windows<R: radio::PhyRxTx>(
+    state: State,
+    mac: &Mac,
+    radio: &mut R,
+    class_c: bool,
+    response: impl Into<Response>,
+) -> (State, Result<Response, super::Error<R>>) {
+    let response = match response.into() {
+        // MAC commands which did not fit into FOpts are to be sent in a dedicated uplink
+        Response::RxComplete if mac.mac_only_uplink_pending() => Response::MacUplinkPending,
+        response => response,
+    };
+    match rxc(mac, radio, class_c) {
+        Ok(()) => (state, Ok(response)),
+        Err(e) => (state, Err(super::Error::Radio(e))),
+    }
+}
+
+/// Continuously receive with the RXC configuration if Class C is enabled. The radio must accept
+/// this request while already receiving and keep receiving until the next request.
+#[allow(unused_variables)]
+fn rxc<R: radio::PhyRxTx>(mac: &Mac, radio: &mut R, class_c: bool) -> Result<(), R::PhyError> {
+    #[cfg(feature = "class-c")]
+    if class_c {
+        radio.handle_event(radio::Event::RxRequest(mac.get_rxc_config().rf))?;
+    }
+    Ok(())
+}
+
+/// Handle a radio event outside of the RX windows. These are only expected while receiving in
+/// RXC, otherwise `error` is provided.
+#[allow(unused_variables)]
+fn rxc_radio_event<R: radio::PhyRxTx, const N: usize, const D: usize>(
+    mac: &mut Mac,
+    radio: &mut R,
+    buf: &mut RadioBuffer<N>,
+    dl: &mut Vec<Downlink, D>,
+    class_c: bool,
+    radio_event: radio::Event<'_, R>,
+    error: Error,
+) -> Result<Response, super::Error<R>> {
+    #[cfg(feature = "class-c")]
+    if class_c {
+        return match radio.handle_event(radio_event).map_err(super::Error::Radio)? {
+            radio::Response::RxDone(quality) => {
+                // copy from radio buffer to mac buffer
+                buf.clear();
+                if let Err(()) = buf.extend_from_slice(radio.get_received_packet().as_ref()) {
+                    return Err(Error::BufferTooSmall.into());
+                }
+                let rf_config = mac.get_rxc_config().rf;
+                let response = mac.handle_rxc::<N, D>(buf, dl, quality.snr(), &rf_config)?;
+                // the radio stops upon reception, keep listening
+                rxc(mac, radio, class_c).map_err(super::Error::Radio)?;
+                Ok(response.into())
+            }
+            _ => Ok(Response::NoUpdate),
+        };
+    }
+    Err(error.into())
 }
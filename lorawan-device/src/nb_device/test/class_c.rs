@@ -0,0 +1,108 @@
+use super::*;
+use crate::nb_device::radio;
+use lorawan::default_crypto::DefaultFactory;
+use lorawan::parser::{DataHeader, DataPayload, PhyPayload};
+
+/// Verify the pending uplink so that Class C downlinks may be received in between RX windows.
+fn verify_uplink<const FCNT_UP: u16>(device: &mut Device<TestRadio, rand_core::OsRng, 255, 4>) {
+    let mut uplink = device.get_radio().take_last_uplink().unwrap();
+    let PhyPayload::Data(DataPayload::Encrypted(data)) = uplink.get_payload() else {
+        panic!("Did not decode PhyPayload::Data!");
+    };
+    assert!(data.validate_mic(&get_key().into(), FCNT_UP as u32, &DefaultFactory));
+    assert_eq!(data.fhdr().fcnt(), FCNT_UP);
+}
+
+#[test]
+fn test_class_c_data_before_rx1() {
+    let mut device = setup_with_session_class_c();
+    let response = device.send(&[1, 2, 3], 3, true).unwrap();
+    assert!(matches!(response, Response::TimeoutRequest(1000)));
+    // We expect FCntUp 1 since the Class C setup sends the first frame
+    verify_uplink::<1>(&mut device);
+
+    // send first downlink before RX1
+    let rxc_config = device.shared.mac.get_rxc_config().rf;
+    assert_eq!(device.get_radio().get_rf_config(), Some(rxc_config));
+    device.get_radio().set_rxtx_handler(class_c_downlink::<1>);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(response, Response::DownlinkReceived(1)));
+
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // begin Rx1
+    assert!(matches!(response, Response::TimeoutRequest(1100)));
+    device.get_radio().set_rxtx_handler(class_c_downlink::<2>);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(response, Response::DownlinkReceived(2)));
+    assert!(device.ready_to_send_data());
+
+    let _ = device.take_downlink().unwrap();
+    let _ = device.take_downlink().unwrap();
+}
+
+#[test]
+fn test_class_c_data_before_rx2() {
+    let mut device = setup_with_session_class_c();
+    let response = device.send(&[1, 2, 3], 3, true).unwrap();
+    assert!(matches!(response, Response::TimeoutRequest(1000)));
+    verify_uplink::<1>(&mut device);
+
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // begin Rx1
+    assert!(matches!(response, Response::TimeoutRequest(1100)));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // end Rx1
+    assert!(matches!(response, Response::TimeoutRequest(2000)));
+
+    // send first downlink before RX2
+    device.get_radio().set_rxtx_handler(class_c_downlink::<1>);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(response, Response::DownlinkReceived(1)));
+
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // begin Rx2
+    assert!(matches!(response, Response::TimeoutRequest(2100)));
+    device.get_radio().set_rxtx_handler(class_c_downlink::<2>);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(response, Response::DownlinkReceived(2)));
+
+    let _ = device.take_downlink().unwrap();
+    let _ = device.take_downlink().unwrap();
+}
+
+#[test]
+fn test_class_c_async_down() {
+    let mut device = setup_with_session_class_c();
+    device.get_radio().set_rxtx_handler(class_c_downlink::<1>);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(response, Response::DownlinkReceived(1)));
+    let downlink = device.take_downlink().unwrap();
+    assert_eq!(downlink.fport, 3);
+    assert_eq!(downlink.data.as_slice(), [1, 2, 3]);
+    // the radio keeps listening after a downlink
+    let rxc_config = device.shared.mac.get_rxc_config().rf;
+    assert_eq!(device.get_radio().get_rf_config(), Some(rxc_config));
+}
+
+#[test]
+fn test_class_c_disabled() {
+    let mut device = setup_with_session_class_c();
+    device.disable_class_c();
+    device.get_radio().set_rxtx_handler(class_c_downlink::<1>);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(())));
+    assert!(matches!(response, Err(Error::State(state::Error::RadioEventWhileIdle))));
+}
+
+#[test]
+#[cfg(feature = "mac-events")]
+fn test_class_c_replay_dropped() {
+    use mac::{DropReason, Event as MacEvent};
+    let mut device = setup_with_session_class_c();
+    while device.take_mac_event().is_some() {}
+    device.get_radio().set_rxtx_handler(class_c_downlink::<1>);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(response, Response::DownlinkReceived(1)));
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(response, Response::NoUpdate));
+    assert_eq!(
+        device.take_mac_event(),
+        Some(MacEvent::DownlinkDropped { reason: DropReason::Replay })
+    );
+    assert!(device.take_mac_event().is_none());
+}
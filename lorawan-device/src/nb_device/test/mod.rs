@@ -4,6 +4,12 @@ use crate::test_util::*;
 use util::*;
 
 use crate::nb_device::Event;
+
+#[cfg(feature = "class-c")]
+mod class_c;
+
+#[cfg(all(feature = "multicast", feature = "class-c"))]
+mod multicast;
 #[test]
 fn test_join_rx1() {
     let mut device = test_device();
@@ -128,3 +134,238 @@ fn test_link_adr_ans() {
     let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
     assert!(matches!(response, Response::DownlinkReceived(1)));
 }
+
+#[test]
+fn test_uplink_metadata() {
+    let mut device = test_device();
+    device.join(get_abp_credentials()).unwrap();
+    let response = device.send(&[0; 1], 1, true).unwrap();
+    assert!(matches!(response, Response::TimeoutRequest(1000)));
+    let metadata = device.get_uplink_metadata().unwrap();
+    let uplink = device.get_radio().take_last_uplink().unwrap();
+    assert_eq!(metadata.frequency, uplink.tx_config.rf.frequency);
+    assert_eq!(metadata.data_rate, Some(region::DR::_0));
+    assert_eq!(metadata.tx_power, uplink.tx_config.pw);
+    // 14 bytes at SF10 BW125
+    assert_eq!(metadata.time_on_air_us, 288_768);
+    assert_eq!(metadata.fcnt_up, Some(0));
+    assert_eq!(metadata.tx_done_ms, Some(0));
+    assert_eq!(metadata.rx_window, None);
+
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // begin Rx1
+    assert!(matches!(response, Response::TimeoutRequest(1100)));
+    device.get_radio().set_rxtx_handler(class_c_downlink::<1>);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(response, Response::DownlinkReceived(1)));
+    let metadata = device.get_uplink_metadata().unwrap();
+    assert_eq!(metadata.rx_window, Some(mac::Window::_1));
+    assert_eq!(metadata.rx_quality, Some(radio::RxQuality::new(0, 0)));
+}
+
+#[test]
+#[cfg(feature = "mac-events")]
+fn test_mac_events() {
+    use mac::Event as MacEvent;
+    let mut device = test_device();
+    device.join(get_otaa_credentials()).unwrap();
+    assert!(matches!(device.take_mac_event(), Some(MacEvent::JoinAttempt { .. })));
+    assert!(device.take_mac_event().is_none());
+
+    let mut device = test_device();
+    device.join(get_abp_credentials()).unwrap();
+    let response = device.send(&[0; 1], 1, true).unwrap();
+    assert!(matches!(response, Response::TimeoutRequest(1000)));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // begin Rx1
+    assert!(matches!(response, Response::TimeoutRequest(1100)));
+    device.get_radio().set_rxtx_handler(handle_data_uplink_with_link_adr_req::<0, 0>);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(response, Response::DownlinkReceived(0)));
+
+    assert_eq!(
+        device.take_mac_event(),
+        Some(MacEvent::AdrChange { data_rate: region::DR::_0, tx_power: Some(21) })
+    );
+    assert_eq!(device.take_mac_event(), Some(MacEvent::MacCommandApplied { cid: 0x03 }));
+    assert_eq!(device.take_mac_event(), Some(MacEvent::MacCommandApplied { cid: 0x03 }));
+    assert!(device.take_mac_event().is_none());
+}
+
+fn uplink_fcnt(uplink: Option<Uplink>) -> u16 {
+    use lorawan::parser::{DataHeader, DataPayload, PhyPayload};
+    match uplink.unwrap().get_payload() {
+        PhyPayload::Data(DataPayload::Encrypted(data)) => data.fhdr().fcnt(),
+        _ => panic!("Did not decode PhyPayload::Data!"),
+    }
+}
+
+#[test]
+fn test_confirmed_uplink_retransmission() {
+    let mut device = test_device();
+    device.set_retransmission_policy(Some(mac::RetransmissionPolicy {
+        max_attempts: 2,
+        data_rate_step_down: false,
+    }));
+    let response = device.join(get_abp_credentials());
+    assert!(matches!(response, Ok(Response::JoinSuccess)));
+    let response = device.send(&[0; 1], 1, true).unwrap();
+    assert!(matches!(response, Response::TimeoutRequest(1000)));
+    assert_eq!(uplink_fcnt(device.get_radio().take_last_uplink()), 0);
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // begin Rx1
+    assert!(matches!(response, Response::TimeoutRequest(1100)));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // end Rx1
+    assert!(matches!(response, Response::TimeoutRequest(2000)));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // being Rx2
+    assert!(matches!(response, Response::TimeoutRequest(2100)));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // end Rx2
+                                                                      // ACK timeout of 1 - 3 seconds after the end of RX2
+    assert!(matches!(response, Response::TimeoutRequest(3100..=5100)));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // retransmit
+    assert!(matches!(response, Response::TimeoutRequest(1000)));
+    assert_eq!(device.get_retransmission_attempts(), 2);
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // begin Rx1
+    assert!(matches!(response, Response::TimeoutRequest(1100)));
+    // Retransmission carries the same FCntUp
+    device.get_radio().set_rxtx_handler(handle_data_uplink_with_link_adr_req::<0, 0>);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(response, Response::DownlinkReceived(0)));
+    assert_eq!(device.get_fcnt_up(), Some(1));
+}
+
+#[test]
+fn test_confirmed_uplink_retransmission_no_ack() {
+    let mut device = test_device();
+    device.set_retransmission_policy(Some(mac::RetransmissionPolicy {
+        max_attempts: 2,
+        data_rate_step_down: false,
+    }));
+    device.join(get_abp_credentials()).unwrap();
+    device.send(&[0; 1], 1, true).unwrap();
+    assert_eq!(uplink_fcnt(device.get_radio().take_last_uplink()), 0);
+    for _ in 0..4 {
+        device.handle_event(Event::TimeoutFired).unwrap();
+    }
+    // retransmit
+    device.handle_event(Event::TimeoutFired).unwrap();
+    assert_eq!(uplink_fcnt(device.get_radio().take_last_uplink()), 0);
+    for _ in 0..3 {
+        device.handle_event(Event::TimeoutFired).unwrap();
+    }
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // end Rx2
+    assert!(matches!(response, Response::NoAck));
+    assert_eq!(device.get_fcnt_up(), Some(1));
+
+    // Next uplink uses the next FCntUp
+    device.send(&[0; 1], 1, true).unwrap();
+    assert_eq!(uplink_fcnt(device.get_radio().take_last_uplink()), 1);
+}
+
+#[test]
+fn test_uplink_payload_too_large() {
+    let mut device = test_device();
+    device.join(get_abp_credentials()).unwrap();
+    let max_len = device.get_max_payload_len();
+    let payload = [0; 256];
+    let response = device.send(&payload[..max_len as usize + 1], 1, false);
+    assert!(matches!(
+        response,
+        Err(Error::Mac(mac::Error::PayloadTooLarge { max_len: m })) if m == max_len
+    ));
+    assert!(device.ready_to_send_data());
+    let response = device.send(&payload[..max_len as usize], 1, false).unwrap();
+    assert!(matches!(response, Response::TimeoutRequest(1000)));
+}
+
+fn uplink_fport(uplink: Option<Uplink>) -> Option<u8> {
+    use lorawan::parser::{DataHeader, DataPayload, PhyPayload};
+    match uplink.unwrap().get_payload() {
+        PhyPayload::Data(DataPayload::Encrypted(data)) => data.f_port(),
+        _ => panic!("Did not decode PhyPayload::Data!"),
+    }
+}
+
+#[test]
+fn test_mac_uplink_pending() {
+    let mut device = test_device();
+    device.join(get_abp_credentials()).unwrap();
+    let response = device.send(&[0; 1], 1, false).unwrap();
+    assert!(matches!(response, Response::TimeoutRequest(1000)));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // begin Rx1
+    assert!(matches!(response, Response::TimeoutRequest(1100)));
+    device.get_radio().set_rxtx_handler(handle_data_uplink_with_link_adr_req::<0, 0>);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(response, Response::DownlinkReceived(0)));
+    assert!(!device.mac_uplink_due());
+
+    // Payload of maximum size leaves no space for the LinkADRAns in FOpts
+    let payload = [0; 256];
+    let max_len = device.get_max_payload_len() as usize;
+    let response = device.send(&payload[..max_len], 1, false).unwrap();
+    assert!(matches!(response, Response::TimeoutRequest(1000)));
+    assert_eq!(uplink_fport(device.get_radio().take_last_uplink()), Some(1));
+    assert!(device.mac_uplink_due());
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // begin Rx1
+    assert!(matches!(response, Response::TimeoutRequest(1100)));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // end Rx1
+    assert!(matches!(response, Response::TimeoutRequest(2000)));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // begin Rx2
+    assert!(matches!(response, Response::TimeoutRequest(2100)));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // end Rx2
+    assert!(matches!(response, Response::MacUplinkPending));
+
+    // MAC commands follow in a dedicated FPort 0 uplink
+    let response = device.flush_mac_commands().unwrap();
+    assert!(matches!(response, Response::TimeoutRequest(1000)));
+    assert_eq!(uplink_fport(device.get_radio().take_last_uplink()), Some(0));
+    assert!(!device.mac_uplink_due());
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // begin Rx1
+    assert!(matches!(response, Response::TimeoutRequest(1100)));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // end Rx1
+    assert!(matches!(response, Response::TimeoutRequest(2000)));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // begin Rx2
+    assert!(matches!(response, Response::TimeoutRequest(2100)));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // end Rx2
+    assert!(matches!(response, Response::RxComplete));
+}
+
+#[test]
+fn test_flush_without_mac_commands() {
+    let mut device = test_device();
+    device.join(get_abp_credentials()).unwrap();
+    let response = device.flush_mac_commands().unwrap();
+    assert!(matches!(response, Response::NoUpdate));
+    assert!(device.get_radio().take_last_uplink().is_none());
+    assert_eq!(device.get_fcnt_up(), Some(0));
+    assert!(device.ready_to_send_data());
+}
+
+#[test]
+#[cfg(feature = "certification")]
+fn test_certification_tx_cw_req() {
+    fn tx_cw_req(_uplink: Option<Uplink>, _config: radio::RfConfig, buf: &mut [u8]) -> usize {
+        use lorawan::creator::DataPayloadCreator;
+        use lorawan::default_crypto::DefaultFactory;
+        let mut phy = DataPayloadCreator::new(buf).unwrap();
+        phy.set_f_port(224);
+        phy.set_dev_addr(&[0; 4]);
+        phy.set_uplink(false);
+        phy.set_fcnt(0);
+        // TxCwReq: 60 s at 868.1 MHz with 14 dBm
+        let payload = [0x7d, 0x3c, 0x00, 0x28, 0x76, 0x84, 0x0e];
+        let finished =
+            phy.build(&payload, [], &get_key().into(), &get_key().into(), &DefaultFactory).unwrap();
+        finished.len()
+    }
+    let mut device = test_device();
+    device.join(get_abp_credentials()).unwrap();
+    let response = device.send(&[0; 1], 1, false).unwrap();
+    assert!(matches!(response, Response::TimeoutRequest(1000)));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // begin Rx1
+    assert!(matches!(response, Response::TimeoutRequest(1100)));
+    device.get_radio().set_rxtx_handler(tx_cw_req);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(
+        response,
+        Response::TxContinuousWave { frequency: 868_100_000, tx_power: 14, timeout_ms: 60_000 }
+    ));
+    assert!(device.ready_to_send_data());
+}
@@ -0,0 +1,95 @@
+use super::*;
+use crate::nb_device::{radio, McGroup, MulticastResponse};
+use crate::test_util::multicast::*;
+use lorawan::keys::McKEKey;
+use lorawan::parser::McAddr;
+
+type TestDevice = Device<TestRadio, rand_core::OsRng, 255, 4>;
+
+/// Receive a remote setup message in RXC and send the answer, verifying it with `verify_answer`.
+fn setup_message(device: &mut TestDevice, request: RxTxHandler, verify_answer: RxTxHandler) {
+    device.get_radio().set_rxtx_handler(request);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(
+        response,
+        Response::Multicast(MulticastResponse::NewSession { .. })
+            | Response::MulticastAnswerPending
+    ));
+
+    let response = device.send_multicast_answers().unwrap();
+    assert!(matches!(response, Response::TimeoutRequest(1000)));
+    // the handler verifies the answer and provides no downlink
+    device.get_radio().set_rxtx_handler(verify_answer);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(response, Response::NoUpdate));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // begin Rx1
+    assert!(matches!(response, Response::TimeoutRequest(1100)));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // end Rx1
+    assert!(matches!(response, Response::TimeoutRequest(2000)));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // begin Rx2
+    assert!(matches!(response, Response::TimeoutRequest(2100)));
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // end Rx2
+    assert!(matches!(response, Response::RxComplete));
+}
+
+#[test]
+fn test_multicast_remote_setup() {
+    let mut device = setup_with_session_class_c();
+    device.shared.mac.multicast.mc_k_e_key = Some(McKEKey::from([0x66; 16]));
+
+    device.get_radio().set_rxtx_handler(handle_multicast_setup_req);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(response, Response::Multicast(MulticastResponse::NewSession { group_id: 1 })));
+
+    let mc_addr = McAddr::from([52, 110, 29, 60]);
+    let session = device.get_multicast_session(McGroup::_1).unwrap();
+    assert_eq!(session.multicast_addr(), mc_addr);
+    assert_eq!(session.fcnt_down, 0x12345678);
+    assert_eq!(session.max_fcnt_down(), 0x87654321);
+
+    let response = device.send_multicast_answers().unwrap();
+    assert!(matches!(response, Response::TimeoutRequest(1000)));
+    let uplink = device.get_radio().take_last_uplink();
+    verify_multicast_setup_ans(uplink, device.shared.mac.get_rxc_config().rf, &mut []);
+
+    // the answer's RX windows are pending
+    let response = device.send_multicast_answers();
+    assert!(matches!(response, Err(Error::State(state::Error::MulticastAnswersWhileBusy))));
+}
+
+#[test]
+fn test_multicast_group_delete() {
+    let mut device = setup_with_session_class_c();
+    device.shared.mac.multicast.mc_k_e_key = Some(McKEKey::from([0x66; 16]));
+
+    setup_message(&mut device, handle_multicast_setup_req, verify_multicast_setup_ans);
+    assert!(device.get_multicast_session(McGroup::_1).is_some());
+    setup_message(&mut device, handle_mc_group_delete_req::<0x01>, verify_mc_group_delete_ans);
+    assert!(device.get_multicast_session(McGroup::_1).is_none());
+
+    // nothing left to answer
+    let response = device.send_multicast_answers().unwrap();
+    assert!(matches!(response, Response::NoUpdate));
+
+    device.get_radio().set_rxtx_handler(handle_regular_downlink_msg::<2>);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(response, Response::DownlinkReceived(2)));
+}
+
+#[test]
+fn test_multicast_invalid_group_delete() {
+    let mut device = setup_with_session_class_c();
+    device.shared.mac.multicast.mc_k_e_key = Some(McKEKey::from([0x66; 16]));
+
+    setup_message(&mut device, handle_multicast_setup_req, verify_multicast_setup_ans);
+    setup_message(
+        &mut device,
+        handle_mc_group_delete_req::<0x03>,
+        verify_mc_group_delete_ans_undefined,
+    );
+    assert!(device.get_multicast_session(McGroup::_1).is_some());
+
+    device.get_radio().set_rxtx_handler(handle_regular_downlink_msg::<2>);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(response, Response::DownlinkReceived(2)));
+}
@@ -12,6 +12,29 @@ pub fn test_device() -> Device<TestRadio, rand_core::OsRng, 255> {
     Device::new(Configuration::new(Region::US915), TestRadio::default(), rand::rngs::OsRng)
 }
 
+/// A device which buffers multiple downlinks, as required for Class C.
+#[cfg(feature = "class-c")]
+pub fn test_device_class_c() -> Device<TestRadio, rand_core::OsRng, 255, 4> {
+    Device::new(Configuration::new(Region::US915), TestRadio::default(), rand::rngs::OsRng)
+}
+
+/// Join using ABP and enable Class C, which takes effect after the first uplink.
+#[cfg(feature = "class-c")]
+pub fn setup_with_session_class_c() -> Device<TestRadio, rand_core::OsRng, 255, 4> {
+    use crate::nb_device::{self, Event as DeviceEvent};
+    let mut device = test_device_class_c();
+    device.join(get_abp_credentials()).unwrap();
+    device.enable_class_c();
+    let response = device.send(&[3, 2, 1], 3, false).unwrap();
+    assert!(matches!(response, nb_device::Response::TimeoutRequest(1000)));
+    let response = device.handle_event(DeviceEvent::TimeoutFired).unwrap(); // begin Rx1
+    assert!(matches!(response, nb_device::Response::TimeoutRequest(1100)));
+    device.get_radio().set_rxtx_handler(handle_class_c_uplink_after_join);
+    let response = device.handle_event(DeviceEvent::RadioEvent(Event::Phy(()))).unwrap();
+    assert!(matches!(response, nb_device::Response::DownlinkReceived(0)));
+    device
+}
+
 #[derive(Debug)]
 pub struct TestRadio {
     current_config: Option<RfConfig>,
@@ -25,6 +48,15 @@ impl TestRadio {
     pub fn set_rxtx_handler(&mut self, handler: RxTxHandler) {
         self.rxtx_handler = Some(handler);
     }
+
+    pub fn take_last_uplink(&mut self) -> Option<Uplink> {
+        self.last_uplink.take()
+    }
+
+    #[cfg(feature = "class-c")]
+    pub fn get_rf_config(&self) -> Option<RfConfig> {
+        self.current_config
+    }
 }
 
 impl Default for TestRadio {
@@ -6,6 +6,58 @@ pub struct RfConfig {
     pub frequency: u32,
     pub bb: BaseBandModulationParams,
     pub max_payload_len: u8,
+    /// FSK modulation to use instead of LoRa, in which case `bb` is irrelevant. Radios must not
+    /// ignore it: `bb` then holds the LoRa parameters of SF7/125 kHz, which are placeholders.
+    pub fsk: Option<FskModulation>,
+}
+
+impl RfConfig {
+    /// Time on air of a frame of `payload_len` bytes, with the preamble and header arguments only
+    /// applying to LoRa.
+    pub fn time_on_air_us(
+        &self,
+        preamble_symbols: Option<u8>,
+        explicit_header: bool,
+        payload_len: u8,
+    ) -> u32 {
+        match self.fsk {
+            Some(fsk) => fsk.time_on_air_us(payload_len),
+            None => self.bb.time_on_air_us(preamble_symbols, explicit_header, payload_len),
+        }
+    }
+}
+
+/// GFSK modulation as used by LoRaWAN FSK data rates.
+///
+/// Frames consist of a 5 byte preamble, the 3 byte sync word `C1 94 C1`, a length byte, the
+/// payload and a CRC-16 (CCITT), with data whitening enabled and Gaussian filtering with BT = 1.0.
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FskModulation {
+    /// Bitrate in bits per second
+    pub bitrate: u32,
+    /// Frequency deviation in Hz
+    pub fdev: u32,
+}
+
+impl FskModulation {
+    /// The 50 kbps FSK data rate of the regional parameters
+    pub const LORAWAN_50KBPS: Self = Self { bitrate: 50_000, fdev: 25_000 };
+    /// Preamble length in bytes
+    pub const PREAMBLE_LENGTH: u8 = 5;
+    /// Sync word
+    pub const SYNC_WORD: [u8; 3] = [0xC1, 0x94, 0xC1];
+
+    /// Time on air of a frame of `payload_len` bytes
+    pub fn time_on_air_us(&self, payload_len: u8) -> u32 {
+        // preamble, sync word, length byte, payload and CRC
+        let bytes = Self::PREAMBLE_LENGTH as u64
+            + Self::SYNC_WORD.len() as u64
+            + 1
+            + payload_len as u64
+            + 2;
+        (bytes * 8 * 1_000_000).div_ceil(self.bitrate as u64) as u32
+    }
 }
 
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
@@ -91,7 +91,7 @@ impl<const DEFAULT_RX2: u32, const OFFSET: u32> DynamicChannelRegion
     }
 }
 
-use super::{Bandwidth, Datarate, SpreadingFactor};
+use super::{Bandwidth, Datarate, FskModulation, SpreadingFactor};
 
 pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     // DR0
@@ -100,6 +100,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 0,
+        fsk: None,
     }),
     // DR1
     Some(Datarate {
@@ -107,6 +108,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 0,
+        fsk: None,
     }),
     // DR2
     Some(Datarate {
@@ -114,6 +116,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 123,
         max_mac_payload_size_with_dwell_time: 19,
+        fsk: None,
     }),
     // DR3
     Some(Datarate {
@@ -121,6 +124,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 123,
         max_mac_payload_size_with_dwell_time: 61,
+        fsk: None,
     }),
     // DR4
     Some(Datarate {
@@ -128,6 +132,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 133,
+        fsk: None,
     }),
     // DR5
     Some(Datarate {
@@ -135,6 +140,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
     // DR6
     Some(Datarate {
@@ -142,9 +148,10 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_250KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
-    // TODO: DR7: FSK: 50 kbps
-    None,
+    // DR7
+    Some(Datarate::fsk(FskModulation::LORAWAN_50KBPS, 250, 250)),
     // DR8..DR14: RFU
     None,
     None,
@@ -4,7 +4,7 @@
 /// 1. DR0 to DR5 (minimum set supported for certification)
 /// 2. DR0 to DR7
 ///
-/// Current status: DR7 (FSK) is unimplemented
+/// Current status: DR0..DR7
 use super::*;
 
 const MAX_EIRP: u8 = 16;
@@ -71,7 +71,7 @@ impl DynamicChannelRegion for EU433Region {
     }
 }
 
-use super::{Bandwidth, Datarate, SpreadingFactor};
+use super::{Bandwidth, Datarate, FskModulation, SpreadingFactor};
 
 pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     // DR0
@@ -80,6 +80,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 0,
+        fsk: None,
     }),
     // DR1
     Some(Datarate {
@@ -87,6 +88,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 0,
+        fsk: None,
     }),
     // DR2
     Some(Datarate {
@@ -94,6 +96,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 123,
         max_mac_payload_size_with_dwell_time: 19,
+        fsk: None,
     }),
     // DR3
     Some(Datarate {
@@ -101,6 +104,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 123,
         max_mac_payload_size_with_dwell_time: 61,
+        fsk: None,
     }),
     // DR4
     Some(Datarate {
@@ -108,6 +112,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 133,
+        fsk: None,
     }),
     // DR5
     Some(Datarate {
@@ -115,6 +120,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
     // DR6
     Some(Datarate {
@@ -122,9 +128,10 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_250KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
-    // TODO: DR7: FSK: 50 kbps
-    None,
+    // DR7
+    Some(Datarate::fsk(FskModulation::LORAWAN_50KBPS, 250, 250)),
     // DR8..DR14: RFU
     None,
     None,
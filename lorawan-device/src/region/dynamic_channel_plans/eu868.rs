@@ -5,7 +5,7 @@
 /// 2. DR0 to DR7
 /// 3. DR0 to DR11 (all data rates implemented)
 ///
-/// Current status: DR0..DR5 (minimum set is supported)
+/// Current status: DR0..DR7 (LR-FHSS is unsupported)
 use super::*;
 
 const MAX_EIRP: u8 = 16;
@@ -74,7 +74,7 @@ impl DynamicChannelRegion for EU868Region {
     }
 }
 
-use super::{Bandwidth, Datarate, SpreadingFactor};
+use super::{Bandwidth, Datarate, FskModulation, SpreadingFactor};
 
 pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     // DR0
@@ -83,6 +83,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 59,
+        fsk: None,
     }),
     // DR1
     Some(Datarate {
@@ -90,6 +91,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 59,
+        fsk: None,
     }),
     // DR2
     Some(Datarate {
@@ -97,6 +99,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 59,
+        fsk: None,
     }),
     // DR3
     Some(Datarate {
@@ -104,6 +107,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 123,
         max_mac_payload_size_with_dwell_time: 123,
+        fsk: None,
     }),
     // DR4
     Some(Datarate {
@@ -111,6 +115,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
     // DR5
     Some(Datarate {
@@ -118,19 +123,18 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
-    None,
-    /*
-    // TODO: DR6: Can be enabled once DR7 is implemented
+    // DR6
     Some(Datarate {
         spreading_factor: SpreadingFactor::_7,
         bandwidth: Bandwidth::_250KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
-    */
-    // TODO: DR7: FSK: 50 kbps
-    None,
+    // DR7
+    Some(Datarate::fsk(FskModulation::LORAWAN_50KBPS, 250, 250)),
     // TODO: DR8: LR-FHSS CR1/3: 137 kHz BW
     None,
     // TODO: DR9: LR-FHSS CR2/3: 137 kHz BW
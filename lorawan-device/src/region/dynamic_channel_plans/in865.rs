@@ -93,7 +93,7 @@ impl DynamicChannelRegion for IN865Region {
     }
 }
 
-use super::{Bandwidth, Datarate, SpreadingFactor};
+use super::{Bandwidth, Datarate, FskModulation, SpreadingFactor};
 
 pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     // DR0
@@ -102,6 +102,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 59,
+        fsk: None,
     }),
     // DR1
     Some(Datarate {
@@ -109,6 +110,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 59,
+        fsk: None,
     }),
     // DR2
     Some(Datarate {
@@ -116,6 +118,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 59,
+        fsk: None,
     }),
     // DR3
     Some(Datarate {
@@ -123,6 +126,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 123,
         max_mac_payload_size_with_dwell_time: 123,
+        fsk: None,
     }),
     // DR4
     Some(Datarate {
@@ -130,6 +134,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
     // DR5
     Some(Datarate {
@@ -137,11 +142,12 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
     // DR6: RFU
     None,
-    // TODO: DR7: FSK: 50 kbps
-    None,
+    // DR7
+    Some(Datarate::fsk(FskModulation::LORAWAN_50KBPS, 250, 250)),
     // DR8..DR14: RFU
     None,
     None,
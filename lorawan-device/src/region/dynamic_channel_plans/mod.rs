@@ -196,7 +196,7 @@ impl<R: DynamicChannelRegion> RegionHandler for DynamicChannelPlan<R> {
     }
 
     fn get_datarate(&self, dr: u8) -> Option<&Datarate> {
-        R::datarates()[dr as usize].as_ref()
+        R::datarates().get(dr as usize)?.as_ref()
     }
 
     fn get_tx_dr_and_frequency<RNG: RngCore>(
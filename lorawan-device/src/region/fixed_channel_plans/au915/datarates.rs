@@ -7,6 +7,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 0,
+        fsk: None,
     }),
     // DR1
     Some(Datarate {
@@ -14,6 +15,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 0,
+        fsk: None,
     }),
     // DR2
     Some(Datarate {
@@ -21,6 +23,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 19,
+        fsk: None,
     }),
     // DR3
     Some(Datarate {
@@ -28,6 +31,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 123,
         max_mac_payload_size_with_dwell_time: 61,
+        fsk: None,
     }),
     // DR4
     Some(Datarate {
@@ -35,6 +39,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 133,
+        fsk: None,
     }),
     // DR5
     Some(Datarate {
@@ -42,6 +47,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
     // DR6
     Some(Datarate {
@@ -49,6 +55,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_500KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
     // TODO: DR7: LR-FHSS CR1/3: 1.523 MHz BW
     None,
@@ -58,6 +65,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_500KHz,
         max_mac_payload_size: 61,
         max_mac_payload_size_with_dwell_time: 61,
+        fsk: None,
     }),
     // DR9
     Some(Datarate {
@@ -65,6 +73,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_500KHz,
         max_mac_payload_size: 137,
         max_mac_payload_size_with_dwell_time: 137,
+        fsk: None,
     }),
     // DR10
     Some(Datarate {
@@ -72,6 +81,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_500KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
     // DR11
     Some(Datarate {
@@ -79,6 +89,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_500KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
     // DR12
     Some(Datarate {
@@ -86,6 +97,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_500KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
     // DR13
     Some(Datarate {
@@ -93,6 +105,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_500KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
     // DR14: RFU
     None,
@@ -171,7 +171,7 @@ impl<F: FixedChannelRegion> RegionHandler for FixedChannelPlan<F> {
     }
 
     fn get_datarate(&self, dr: u8) -> Option<&Datarate> {
-        F::datarates()[dr as usize].as_ref()
+        F::datarates().get(dr as usize)?.as_ref()
     }
 
     fn get_tx_dr_and_frequency<RNG: RngCore>(
@@ -7,6 +7,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 19,
         max_mac_payload_size_with_dwell_time: 19,
+        fsk: None,
     }),
     // DR1
     Some(Datarate {
@@ -14,6 +15,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 61,
         max_mac_payload_size_with_dwell_time: 61,
+        fsk: None,
     }),
     // DR2
     Some(Datarate {
@@ -21,6 +23,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 133,
         max_mac_payload_size_with_dwell_time: 133,
+        fsk: None,
     }),
     // DR3
     Some(Datarate {
@@ -28,6 +31,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_125KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
     // DR4
     Some(Datarate {
@@ -35,6 +39,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_500KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
     // TODO: DR5: LR-FHSS CR1/3: 1.523 MHz BW
     None,
@@ -48,6 +53,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_500KHz,
         max_mac_payload_size: 61,
         max_mac_payload_size_with_dwell_time: 61,
+        fsk: None,
     }),
     // DR9
     Some(Datarate {
@@ -55,6 +61,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_500KHz,
         max_mac_payload_size: 137,
         max_mac_payload_size_with_dwell_time: 137,
+        fsk: None,
     }),
     // DR10
     Some(Datarate {
@@ -62,6 +69,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_500KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
     // DR11
     Some(Datarate {
@@ -69,6 +77,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_500KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
     // DR12
     Some(Datarate {
@@ -76,6 +85,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_500KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
     // DR13
     Some(Datarate {
@@ -83,6 +93,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
         bandwidth: Bandwidth::_500KHz,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
+        fsk: None,
     }),
     // DR14: RFU
     None,
@@ -197,6 +197,33 @@ pub(crate) struct Datarate {
     pub(crate) spreading_factor: SpreadingFactor,
     pub(crate) max_mac_payload_size: u8,
     max_mac_payload_size_with_dwell_time: u8,
+    pub(crate) fsk: Option<FskModulation>,
+}
+
+impl Datarate {
+    /// FSK data rate, for which the LoRa modulation parameters are placeholders.
+    #[cfg(any(
+        feature = "region-as923-1",
+        feature = "region-as923-2",
+        feature = "region-as923-3",
+        feature = "region-as923-4",
+        feature = "region-eu433",
+        feature = "region-eu868",
+        feature = "region-in865"
+    ))]
+    pub(crate) const fn fsk(
+        fsk: FskModulation,
+        max_mac_payload_size: u8,
+        max_mac_payload_size_with_dwell_time: u8,
+    ) -> Self {
+        Self {
+            bandwidth: Bandwidth::_125KHz,
+            spreading_factor: SpreadingFactor::_7,
+            max_mac_payload_size,
+            max_mac_payload_size_with_dwell_time,
+            fsk: Some(fsk),
+        }
+    }
 }
 macro_rules! mut_region_dispatch {
   ($s:expr, $t:tt) => {
@@ -381,6 +408,7 @@ impl Configuration {
                     self.get_coding_rate(),
                 ),
                 max_payload_len: dr.max_mac_payload_size,
+                fsk: dr.fsk,
             },
         }
     }
@@ -389,6 +417,18 @@ impl Configuration {
         region_dispatch!(self, get_datarate, dr)
     }
 
+    /// Find the data rate matching the modulation of a transmission.
+    pub(crate) fn get_datarate_of(&self, rf_config: &RfConfig) -> Option<DR> {
+        (0..NUM_DATARATES).map(DR::from).find(|dr| {
+            self.get_datarate(*dr as u8).is_some_and(|d| match (d.fsk, rf_config.fsk) {
+                (None, None) => {
+                    d.spreading_factor == rf_config.bb.sf && d.bandwidth == rf_config.bb.bw
+                }
+                (fsk, rf_fsk) => fsk == rf_fsk,
+            })
+        })
+    }
+
     pub(crate) fn check_tx_power(&self, tx_power: u8) -> Option<Option<u8>> {
         region_dispatch!(self, check_tx_power, tx_power).map(Some)
     }
@@ -463,6 +503,24 @@ impl Configuration {
         region_dispatch!(self, has_fixed_channel_plan)
     }
 
+    /// Whether the region supports TXParamSetupReq (RP002-1.0.4, AS923 and AU915 only).
+    pub(crate) fn supports_tx_param_setup(&self) -> bool {
+        match self.state.region() {
+            #[cfg(feature = "region-as923-1")]
+            Region::AS923_1 => true,
+            #[cfg(feature = "region-as923-2")]
+            Region::AS923_2 => true,
+            #[cfg(feature = "region-as923-3")]
+            Region::AS923_3 => true,
+            #[cfg(feature = "region-as923-4")]
+            Region::AS923_4 => true,
+            #[cfg(feature = "region-au915")]
+            Region::AU915 => true,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
     pub(crate) fn channel_dl_update(&mut self, index: u8, freq: u32) -> (bool, bool) {
         mut_region_dispatch!(self, channel_dl_update, index, freq)
     }
@@ -622,6 +680,17 @@ mod tests {
         assert!(!r.frequency_valid(872_000_000));
     }
 
+    #[test]
+    #[cfg(all(feature = "region-eu868", feature = "region-us915"))]
+    fn test_get_datarate_out_of_range() {
+        for region in [Region::EU868, Region::US915] {
+            let r = Configuration::new(region);
+            assert!(r.get_datarate(DR::_0 as u8).is_some());
+            assert!(r.get_datarate(DR::_15 as u8).is_none());
+            assert!(r.get_datarate(u8::MAX).is_none());
+        }
+    }
+
     #[test]
     #[cfg(feature = "region-eu868")]
     fn test_rx1_dr_offset_eu868() {
@@ -634,6 +703,28 @@ mod tests {
         assert_eq!(r.get_rx_datarate(DR::_12, 0, &Window::_1), DR::_0);
     }
 
+    #[test]
+    #[cfg(feature = "region-eu868")]
+    fn test_fsk_datarate_eu868() {
+        let r = Configuration::new(Region::EU868);
+        let fsk = r.get_datarate(DR::_7 as u8).unwrap().fsk;
+        assert_eq!(fsk, Some(FskModulation::LORAWAN_50KBPS));
+        let rf = RfConfig {
+            frequency: 868_800_000,
+            bb: BaseBandModulationParams::new(
+                SpreadingFactor::_7,
+                Bandwidth::_125KHz,
+                CodingRate::_4_5,
+            ),
+            max_payload_len: 250,
+            fsk,
+        };
+        assert_eq!(r.get_datarate_of(&rf), Some(DR::_7));
+        assert_eq!(r.get_datarate_of(&RfConfig { fsk: None, ..rf }), Some(DR::_5));
+        // 5 bytes preamble, 3 bytes sync word, length, 10 bytes payload and CRC at 50 kbps
+        assert_eq!(rf.time_on_air_us(Some(8), true, 10), 3_360);
+    }
+
     #[test]
     #[cfg(feature = "region-in865")]
     fn test_rx1_dr_offset_in865() {
@@ -1,8 +1,11 @@
 use super::*;
+use lorawan::creator::DataPayloadCreator;
 use lorawan::maccommandcreator::build_mac_commands;
 use lorawan::maccommands::{
     ChannelMask, DownlinkMacCommand, MacCommandIterator, SerializableMacCommand, UplinkMacCommand,
 };
+#[cfg(feature = "class-c")]
+use lorawan::parser::FCtrl;
 use lorawan::parser::{self, DataHeader};
 use lorawan::{
     default_crypto::DefaultFactory,
@@ -26,8 +29,7 @@ use std::{
 #[derive(Debug, Clone)]
 pub struct Uplink {
     data: Vec<u8>,
-    #[allow(unused)]
-    tx_config: TxConfig,
+    pub tx_config: TxConfig,
 }
 
 impl Uplink {
@@ -231,3 +233,335 @@ pub fn handle_data_uplink_with_link_adr_ans(
         panic!("No uplink passed to handle_data_uplink_with_link_adr_ans")
     }
 }
+
+/// Acknowledge the first uplink of a Class C session.
+#[cfg(feature = "class-c")]
+pub fn handle_class_c_uplink_after_join(
+    uplink: Option<Uplink>,
+    _config: RfConfig,
+    rx_buffer: &mut [u8],
+) -> usize {
+    if let Some(mut uplink) = uplink {
+        if let PhyPayload::Data(DataPayload::Encrypted(data)) = uplink.get_payload() {
+            let fcnt = data.fhdr().fcnt() as u32;
+            assert!(data.validate_mic(&get_key().into(), fcnt, &DefaultFactory));
+            let uplink = data
+                .decrypt(Some(&get_key().into()), Some(&get_key().into()), fcnt, &DefaultFactory)
+                .unwrap();
+            assert_eq!(uplink.fhdr().fcnt(), 0);
+            let mut phy = DataPayloadCreator::new(rx_buffer).unwrap();
+            let mut fctrl = FCtrl::new(0, false);
+            fctrl.set_ack();
+            phy.set_confirmed(false);
+            phy.set_dev_addr(&[0; 4]);
+            phy.set_uplink(false);
+            phy.set_fctrl(&fctrl);
+            // set ack bit
+            let finished =
+                phy.build(&[], [], &get_key().into(), &get_key().into(), &DefaultFactory).unwrap();
+            finished.len()
+        } else {
+            panic!("Did not decode PhyPayload::Data!");
+        }
+    } else {
+        panic!("No uplink passed to handle_class_c_uplink_after_join");
+    }
+}
+
+/// Unsolicited Class C downlink on FPort 3.
+pub fn class_c_downlink<const FCNT_DOWN: u32>(
+    _uplink: Option<Uplink>,
+    _config: RfConfig,
+    rx_buffer: &mut [u8],
+) -> usize {
+    let mut phy = DataPayloadCreator::new(rx_buffer).unwrap();
+    phy.set_f_port(3);
+    phy.set_dev_addr(&[0; 4]);
+    phy.set_uplink(false);
+    phy.set_fcnt(FCNT_DOWN);
+
+    let finished =
+        phy.build(&[1, 2, 3], [], &get_key().into(), &get_key().into(), &DefaultFactory).unwrap();
+    finished.len()
+}
+
+#[cfg(all(feature = "multicast", feature = "class-c"))]
+pub mod multicast {
+    //! Remote multicast setup messages shared by async_device and nb_device tests.
+    use super::*;
+    use lorawan::keys::{AppSKey, McAppSKey, McKEKey, McKey, McNetSKey, NwkSKey};
+    use lorawan::multicast::{
+        parse_uplink_multicast_messages, McClassBSessionReqCreator, McClassCSessionReqCreator,
+        McGroupDeleteReqCreator, McGroupSetupReqCreator, Session, UplinkRemoteSetup,
+    };
+    use lorawan::parser::{FRMPayload, McAddr};
+    use lorawan::types::DR;
+
+    pub fn handle_multicast_setup_req(
+        _uplink: Option<Uplink>,
+        _config: RfConfig,
+        rx_buffer: &mut [u8],
+    ) -> usize {
+        let mut req = McGroupSetupReqCreator::new();
+        let mc_addr = McAddr::from([52, 110, 29, 60]);
+        let mc_key = McKey::from([0x44; 16]);
+        let mcke_key = McKEKey::from([0x66; 16]);
+
+        req.mc_group_id_header(0x01);
+        req.mc_addr(&mc_addr);
+        req.mc_key(&DefaultFactory, &mc_key, &mcke_key);
+        req.min_mc_fcount(0x12345678);
+        req.max_mc_fcount(0x87654321);
+        let setup_req = req.build();
+
+        // Create a downlink frame containing the McGroupSetupReq
+        let mut phy = DataPayloadCreator::new(rx_buffer).unwrap();
+        phy.set_f_port(200); // Remote multicast setup port
+        phy.set_dev_addr(&[0; 4]);
+        phy.set_uplink(false);
+        phy.set_fcnt(0);
+
+        let finished = phy
+            .build(setup_req, [], &get_key().into(), &get_key().into(), &DefaultFactory)
+            .unwrap();
+        finished.len()
+    }
+
+    fn verify_multicast_message(
+        uplink: Option<Uplink>,
+        expected_port: u8,
+        verify_payload: impl FnOnce(&[u8]) -> bool,
+    ) -> usize {
+        let mut uplink = uplink.unwrap();
+        let payload = uplink.get_payload();
+        if let PhyPayload::Data(DataPayload::Encrypted(data)) = payload {
+            let fcnt = data.fhdr().fcnt() as u32;
+            assert!(data.validate_mic(&get_key().into(), fcnt, &DefaultFactory));
+            let uplink = data
+                .decrypt(Some(&get_key().into()), Some(&get_key().into()), fcnt, &DefaultFactory)
+                .unwrap();
+            assert_eq!(uplink.f_port().unwrap(), expected_port);
+
+            if let FRMPayload::Data(ans_data) = uplink.frm_payload() {
+                assert!(verify_payload(ans_data));
+            } else {
+                panic!("Expected data payload");
+            }
+            0
+        } else {
+            panic!("Expected encrypted data payload");
+        }
+    }
+
+    pub fn verify_multicast_setup_ans(
+        uplink: Option<Uplink>,
+        _config: RfConfig,
+        _rx_buffer: &mut [u8],
+    ) -> usize {
+        verify_multicast_message(uplink, 200, |ans_data| {
+            let mut msgs = parse_uplink_multicast_messages(ans_data);
+            let msg = msgs.next().unwrap();
+            if let UplinkRemoteSetup::McGroupSetupAns(ans) = msg {
+                assert_eq!(ans.mc_group_id_header(), 0x01);
+            } else {
+                panic!("Expected McGroupSetupAns");
+            }
+            assert!(msgs.next().is_none());
+            true
+        })
+    }
+
+    pub fn handle_mc_group_delete_req<const GROUP_ID: u8>(
+        _uplink: Option<Uplink>,
+        _config: RfConfig,
+        rx_buffer: &mut [u8],
+    ) -> usize {
+        let mut req = McGroupDeleteReqCreator::new();
+        req.mc_group_id_header(GROUP_ID);
+        let setup_req = req.build();
+
+        // Create a downlink frame containing the McGroupDeleteReq
+        let mut phy = DataPayloadCreator::new(rx_buffer).unwrap();
+        phy.set_f_port(200); // Remote multicast setup port
+        phy.set_dev_addr(&[0; 4]);
+        phy.set_uplink(false);
+        phy.set_fcnt(1);
+
+        let finished = phy
+            .build(setup_req, [], &get_key().into(), &get_key().into(), &DefaultFactory)
+            .unwrap();
+        finished.len()
+    }
+
+    pub fn verify_mc_group_delete_ans(
+        uplink: Option<Uplink>,
+        _config: RfConfig,
+        _rx_buffer: &mut [u8],
+    ) -> usize {
+        verify_multicast_message(uplink, 200, |ans_data| {
+            let mut msgs = parse_uplink_multicast_messages(ans_data);
+            let msg = msgs.next().unwrap();
+            if let UplinkRemoteSetup::McGroupDeleteAns(ans) = msg {
+                assert_eq!(ans.mc_group_id_header(), 0x01);
+                assert!(!ans.mc_group_undefined());
+            } else {
+                panic!("Expected McGroupDeleteAns");
+            }
+            assert!(msgs.next().is_none());
+            true
+        })
+    }
+
+    /// McClassCSessionReq starting right away for 16 s at 923.3 MHz / DR8 (US915 RX2).
+    pub fn handle_mc_class_c_session_req<const GROUP_ID: u8, const FCNT: u32>(
+        _uplink: Option<Uplink>,
+        _config: RfConfig,
+        rx_buffer: &mut [u8],
+    ) -> usize {
+        let mut req = McClassCSessionReqCreator::new();
+        req.mc_group_id_header(GROUP_ID)
+            .session_time(0)
+            .session_timeout(4)
+            .dl_frequency(923_300_000)
+            .data_rate(DR::_8);
+        let session_req = req.build();
+
+        let mut phy = DataPayloadCreator::new(rx_buffer).unwrap();
+        phy.set_f_port(200); // Remote multicast setup port
+        phy.set_dev_addr(&[0; 4]);
+        phy.set_uplink(false);
+        phy.set_fcnt(FCNT);
+
+        let finished = phy
+            .build(session_req, [], &get_key().into(), &get_key().into(), &DefaultFactory)
+            .unwrap();
+        finished.len()
+    }
+
+    /// Expects a McClassCSessionAns with the given status, and TimeToStart = 0 unless an error is
+    /// reported.
+    pub fn verify_mc_class_c_session_ans<const STATUS: u8>(
+        uplink: Option<Uplink>,
+        _config: RfConfig,
+        _rx_buffer: &mut [u8],
+    ) -> usize {
+        verify_multicast_message(uplink, 200, |ans_data| {
+            if STATUS & 0b1_1100 != 0 {
+                ans_data == [0x04, STATUS]
+            } else {
+                ans_data == [0x04, STATUS, 0, 0, 0]
+            }
+        })
+    }
+
+    pub fn handle_mc_class_b_session_req<const GROUP_ID: u8, const FCNT: u32>(
+        _uplink: Option<Uplink>,
+        _config: RfConfig,
+        rx_buffer: &mut [u8],
+    ) -> usize {
+        let mut req = McClassBSessionReqCreator::new();
+        req.mc_group_id_header(GROUP_ID)
+            .session_time(0)
+            .session_timeout(4)
+            .dl_frequency(923_300_000)
+            .data_rate(DR::_8);
+        req.periodicity(3);
+        let session_req = req.build();
+
+        let mut phy = DataPayloadCreator::new(rx_buffer).unwrap();
+        phy.set_f_port(200); // Remote multicast setup port
+        phy.set_dev_addr(&[0; 4]);
+        phy.set_uplink(false);
+        phy.set_fcnt(FCNT);
+
+        let finished = phy
+            .build(session_req, [], &get_key().into(), &get_key().into(), &DefaultFactory)
+            .unwrap();
+        finished.len()
+    }
+
+    /// Expects a McClassBSessionAns with the given status, which always reports an error.
+    pub fn verify_mc_class_b_session_ans<const STATUS: u8>(
+        uplink: Option<Uplink>,
+        _config: RfConfig,
+        _rx_buffer: &mut [u8],
+    ) -> usize {
+        verify_multicast_message(uplink, 200, |ans_data| ans_data == [0x05, STATUS])
+    }
+
+    pub fn handle_regular_downlink_msg<const FCNT: u32>(
+        _uplink: Option<Uplink>,
+        _config: RfConfig,
+        rx_buffer: &mut [u8],
+    ) -> usize {
+        let mut phy = DataPayloadCreator::new(rx_buffer).unwrap();
+        phy.set_f_port(1); // a random fport that's not the multicast port
+        phy.set_dev_addr(&[0; 4]);
+        phy.set_uplink(false);
+        phy.set_fcnt(FCNT);
+
+        let finished = phy
+            .build(&[1, 2, 3], [], &get_key().into(), &get_key().into(), &DefaultFactory)
+            .unwrap();
+        finished.len()
+    }
+
+    pub const MC_ADDR: [u8; 4] = [1, 2, 3, 4];
+    pub const MC_NET_S_KEY: [u8; 16] = [0x11; 16];
+    pub const MC_APP_S_KEY: [u8; 16] = [0x22; 16];
+
+    /// Multicast session for `MC_ADDR` accepting frame counters from `min_fcnt` up to
+    /// (excluding) `max_fcnt`.
+    pub fn multicast_session(min_fcnt: u32, max_fcnt: u32) -> Session {
+        Session::new(
+            McAddr::from(MC_ADDR),
+            McNetSKey::from(MC_NET_S_KEY),
+            McAppSKey::from(MC_APP_S_KEY),
+            min_fcnt,
+            max_fcnt,
+        )
+    }
+
+    /// Multicast downlink to `MC_ADDR` on port 201. The payload is the low byte of the FCnt.
+    pub fn handle_multicast_downlink<const FCNT: u32>(
+        _uplink: Option<Uplink>,
+        _config: RfConfig,
+        rx_buffer: &mut [u8],
+    ) -> usize {
+        let mut phy = DataPayloadCreator::new(rx_buffer).unwrap();
+        phy.set_f_port(201);
+        phy.set_dev_addr(&MC_ADDR);
+        phy.set_uplink(false);
+        phy.set_fcnt(FCNT);
+
+        let finished = phy
+            .build(
+                &[FCNT as u8],
+                [],
+                &NwkSKey::from(MC_NET_S_KEY),
+                &AppSKey::from(MC_APP_S_KEY),
+                &DefaultFactory,
+            )
+            .unwrap();
+        finished.len()
+    }
+
+    pub fn verify_mc_group_delete_ans_undefined(
+        uplink: Option<Uplink>,
+        _config: RfConfig,
+        _rx_buffer: &mut [u8],
+    ) -> usize {
+        verify_multicast_message(uplink, 200, |ans_data| {
+            let mut msgs = parse_uplink_multicast_messages(ans_data);
+            let msg = msgs.next().unwrap();
+            if let UplinkRemoteSetup::McGroupDeleteAns(ans) = msg {
+                assert_eq!(ans.mc_group_id_header(), 0x00);
+                assert!(ans.mc_group_undefined());
+            } else {
+                panic!("Expected McGroupDeleteAns");
+            }
+            assert!(msgs.next().is_none());
+            true
+        })
+    }
+}
@@ -0,0 +1,192 @@
+//! Support for end-to-end tests of applications using the LoRaWAN stack.
+//!
+//! A [`Simulator`] connects an [`async_device::Device`](crate::async_device::Device) to an
+//! in-process [`NetworkServer`] through a simulated radio and timer. Time is virtual: the clock
+//! advances by the time-on-air of transmitted frames and whenever the device waits for the timer,
+//! so that tests complete instantly and deterministically.
+//!
+//! Only the RX1 and RX2 windows are simulated: [`SimRadio`] never completes continuous reception,
+//! so Class C devices do not receive downlinks in RXC and must not rely on `rxc_listen` returning.
+//!
+//! ```
+//! use lorawan_device::async_device::{Device, JoinResponse, SendResponse};
+//! use lorawan_device::region::{Configuration, Region};
+//! use lorawan_device::test_utils::{Gateway, NetworkServer, Simulator};
+//! use lorawan_device::{AppEui, AppKey, DevEui, JoinMode};
+//!
+//! let (deveui, appeui, appkey) = (DevEui::from([1; 8]), AppEui::from([2; 8]), AppKey::from([3; 16]));
+//! let mut ns = NetworkServer::new(Configuration::new(Region::EU868), 0);
+//! ns.add_otaa_device(deveui, appeui, appkey);
+//! ns.add_gateway(Gateway::new(-80, 5));
+//! let sim = Simulator::new(ns);
+//!
+//! let mut device: Device<_, _, _> =
+//!     Device::new_with_seed(Configuration::new(Region::EU868), sim.radio(), sim.timer(), 1);
+//! tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//!     let response = device.join(&JoinMode::OTAA { deveui, appeui, appkey }).await.unwrap();
+//!     assert!(matches!(response, JoinResponse::JoinSuccess));
+//!     device.send(&[1, 2, 3], 2, true).await.unwrap();
+//! });
+//! let uplink = sim.network_server(|ns| ns.take_uplink()).unwrap();
+//! assert_eq!(uplink.data, [1, 2, 3]);
+//! ```
+mod network_server;
+pub use network_server::{
+    Gateway, NetworkServer, ReceivedUplink, ScheduledDownlink, Stats, ADR_HISTORY_LEN,
+    ADR_INSTALLATION_MARGIN_DB,
+};
+
+use crate::async_device::radio::{PhyRxTx, RxConfig, RxQuality, RxStatus, Timer, TxConfig};
+use crate::async_device::Timings;
+use crate::mac::Window;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+struct State {
+    network_server: NetworkServer,
+    now_ms: u64,
+    /// Downlink scheduled in response to the last uplink.
+    downlink: Option<ScheduledDownlink>,
+    /// Receive windows opened since the last uplink.
+    windows: u8,
+}
+
+/// Shared state of a simulation, see the [module documentation](self).
+#[derive(Clone)]
+pub struct Simulator {
+    state: Arc<Mutex<State>>,
+}
+
+impl Simulator {
+    pub fn new(network_server: NetworkServer) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                network_server,
+                now_ms: 0,
+                downlink: None,
+                windows: 0,
+            })),
+        }
+    }
+
+    /// A radio attached to the simulated network.
+    pub fn radio(&self) -> SimRadio {
+        SimRadio { state: self.state.clone(), rx_config: None }
+    }
+
+    /// A timer running on the virtual clock of the simulation.
+    pub fn timer(&self) -> SimTimer {
+        SimTimer { state: self.state.clone(), reset_ms: 0 }
+    }
+
+    /// Access the network server, eg: to queue downlinks or inspect received uplinks.
+    pub fn network_server<T>(&self, f: impl FnOnce(&mut NetworkServer) -> T) -> T {
+        f(&mut self.lock().network_server)
+    }
+
+    /// Current virtual time in milliseconds.
+    pub fn now_ms(&self) -> u64 {
+        self.lock().now_ms
+    }
+
+    /// Advance the virtual clock, eg: to model the application sleeping between uplinks.
+    pub fn advance_ms(&self, ms: u64) {
+        self.lock().now_ms += ms;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+}
+
+/// Simulated [`PhyRxTx`] implementation which transmits into the [`NetworkServer`]. Continuous
+/// reception (RXC) is not supported and never completes.
+pub struct SimRadio {
+    state: Arc<Mutex<State>>,
+    rx_config: Option<RxConfig>,
+}
+
+impl PhyRxTx for SimRadio {
+    type PhyError = ();
+
+    const MAX_RADIO_POWER: u8 = 26;
+
+    async fn tx(&mut self, config: TxConfig, buf: &[u8]) -> Result<u32, Self::PhyError> {
+        let mut state = self.state.lock().unwrap();
+        state.now_ms +=
+            config.rf.time_on_air_us(Some(8), true, buf.len() as u8).div_ceil(1000) as u64;
+        let now_ms = state.now_ms;
+        state.downlink = state.network_server.handle_uplink(now_ms, buf, &config);
+        state.windows = 0;
+        Ok(0)
+    }
+
+    async fn setup_rx(&mut self, config: RxConfig) -> Result<(), Self::PhyError> {
+        self.rx_config = Some(config);
+        Ok(())
+    }
+
+    /// Class C downlinks are not simulated, hence this never completes.
+    async fn rx_continuous(
+        &mut self,
+        _rx_buf: &mut [u8],
+    ) -> Result<(usize, RxQuality), Self::PhyError> {
+        core::future::pending().await
+    }
+
+    async fn rx_single(&mut self, buf: &mut [u8]) -> Result<RxStatus, Self::PhyError> {
+        let mut state = self.state.lock().unwrap();
+        state.windows += 1;
+        let window = match state.windows {
+            1 => Window::_1,
+            _ => Window::_2,
+        };
+        match state.downlink.take() {
+            Some(downlink) if downlink.window == window => {
+                if let Some(config) = self.rx_config {
+                    state.now_ms += config
+                        .rf
+                        .time_on_air_us(Some(8), false, downlink.data.len() as u8)
+                        .div_ceil(1000) as u64;
+                }
+                buf[..downlink.data.len()].copy_from_slice(&downlink.data);
+                Ok(RxStatus::Rx(downlink.data.len(), downlink.quality))
+            }
+            downlink => {
+                state.downlink = downlink;
+                Ok(RxStatus::RxTimeout)
+            }
+        }
+    }
+}
+
+impl Timings for SimRadio {
+    fn get_rx_window_lead_time_ms(&self) -> u32 {
+        0
+    }
+}
+
+/// Simulated [`Timer`] implementation. Awaiting it advances the virtual clock instead of
+/// sleeping.
+pub struct SimTimer {
+    state: Arc<Mutex<State>>,
+    reset_ms: u64,
+}
+
+impl Timer for SimTimer {
+    fn reset(&mut self) {
+        self.reset_ms = self.state.lock().unwrap().now_ms;
+    }
+
+    async fn at(&mut self, millis: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.now_ms = state.now_ms.max(self.reset_ms + millis);
+    }
+
+    async fn delay_ms(&mut self, millis: u64) {
+        self.state.lock().unwrap().now_ms += millis;
+    }
+
+    fn now_ms(&self) -> Option<u64> {
+        Some(self.state.lock().unwrap().now_ms)
+    }
+}
@@ -0,0 +1,573 @@
+//! A minimal, deterministic LoRaWAN network server.
+//!
+//! The network server handles OTAA joins and data uplinks of any number of devices, tracks frame
+//! counters, acknowledges confirmed uplinks, delivers queued downlinks and MAC commands and issues
+//! LinkADRReq based on the link margin reported by its gateways. Packet loss is derived from a
+//! seeded PRNG, so that a simulation run is fully reproducible.
+use crate::async_device::radio::{RxQuality, TxConfig};
+use crate::mac::{NetworkCredentials, Session, Window};
+use crate::region::{self, constants::NUM_DATARATES};
+use crate::{AppEui, AppKey, AppSKey, DevAddr, DevEui, NwkSKey};
+use lora_modulation::{Bandwidth, SpreadingFactor};
+use lorawan::creator::{DataPayloadCreator, JoinAcceptCreator};
+use lorawan::default_crypto::DefaultFactory;
+use lorawan::maccommandcreator::{build_mac_commands, LinkADRReqCreator};
+use lorawan::maccommands::SerializableMacCommand;
+use lorawan::packet_length::phy::mac::fhdr::FOPTS_MAX_LEN;
+use lorawan::parser::{
+    parse, DataHeader, DataPayload, FCtrl, FRMPayload, JoinAcceptPayload, JoinRequestPayload,
+    PhyPayload,
+};
+use lorawan::types::DR;
+use std::{collections::VecDeque, vec::Vec};
+
+/// Amount of uplinks whose SNR is considered by the ADR algorithm.
+pub const ADR_HISTORY_LEN: usize = 4;
+
+/// Link margin in dB kept in reserve by the ADR algorithm.
+pub const ADR_INSTALLATION_MARGIN_DB: i8 = 10;
+
+/// A gateway, described by the quality of its link to the simulated device(s).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gateway {
+    pub rssi: i16,
+    pub snr: i8,
+    /// Whether the gateway is connected to the network server.
+    pub online: bool,
+}
+
+impl Gateway {
+    pub fn new(rssi: i16, snr: i8) -> Self {
+        Self { rssi, snr, online: true }
+    }
+}
+
+/// An uplink accepted by the network server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedUplink {
+    pub dev_addr: DevAddr<[u8; 4]>,
+    pub fcnt: u32,
+    pub fport: Option<u8>,
+    /// Decrypted application payload.
+    pub data: Vec<u8>,
+    /// MAC commands, either from FOpts or from an FPort 0 payload.
+    pub mac_commands: Vec<u8>,
+    pub confirmed: bool,
+    pub data_rate: Option<DR>,
+    pub frequency: u32,
+    /// Quality of the uplink at the best gateway.
+    pub quality: RxQuality,
+    /// Virtual time at the end of the transmission.
+    pub time_ms: u64,
+}
+
+/// A downlink prepared by the network server in response to an uplink.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledDownlink {
+    pub window: Window,
+    pub data: Vec<u8>,
+    pub quality: RxQuality,
+}
+
+/// Counters of simulated traffic.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub joins_accepted: usize,
+    pub uplinks_received: usize,
+    /// Uplinks which were out of gateway coverage or dropped by simulated packet loss.
+    pub uplinks_lost: usize,
+    /// Uplinks which were rejected due to an unknown device, invalid MIC or replayed counter.
+    pub uplinks_rejected: usize,
+    pub downlinks_sent: usize,
+    /// Downlinks dropped by simulated packet loss.
+    pub downlinks_lost: usize,
+}
+
+#[derive(Debug, Clone)]
+struct QueuedDownlink {
+    fport: u8,
+    data: Vec<u8>,
+    confirmed: bool,
+}
+
+#[derive(Debug, Clone)]
+struct DeviceSession {
+    dev_addr: DevAddr<[u8; 4]>,
+    nwkskey: NwkSKey,
+    appskey: AppSKey,
+    fcnt_up: Option<u32>,
+    fcnt_down: u32,
+    downlinks: VecDeque<QueuedDownlink>,
+    mac_commands: Vec<u8>,
+    snr_history: Vec<i8>,
+}
+
+impl DeviceSession {
+    fn new(dev_addr: DevAddr<[u8; 4]>, nwkskey: NwkSKey, appskey: AppSKey) -> Self {
+        Self {
+            dev_addr,
+            nwkskey,
+            appskey,
+            fcnt_up: None,
+            fcnt_down: 0,
+            downlinks: VecDeque::new(),
+            mac_commands: Vec::new(),
+            snr_history: Vec::new(),
+        }
+    }
+
+    /// Restore the 32-bit frame counter from the 16 LSBs transmitted over the air.
+    fn full_fcnt_up(&self, fcnt: u16) -> u32 {
+        match self.fcnt_up {
+            None => fcnt as u32,
+            Some(last) => {
+                let full = (last & !0xFFFF) | fcnt as u32;
+                if full < last {
+                    full.wrapping_add(0x1_0000)
+                } else {
+                    full
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Device {
+    credentials: Option<NetworkCredentials>,
+    dev_nonces: Vec<u16>,
+    session: Option<DeviceSession>,
+}
+
+/// Network server simulator, see the [module documentation](self).
+pub struct NetworkServer {
+    region: region::Configuration,
+    rng: fastrand::Rng,
+    devices: Vec<Device>,
+    gateways: Vec<Gateway>,
+    uplinks: VecDeque<ReceivedUplink>,
+    stats: Stats,
+    uplink_loss: u8,
+    downlink_loss: u8,
+    downlink_window: Window,
+    adr: bool,
+    net_id: [u8; 3],
+    next_dev_addr: u32,
+    app_nonce: u32,
+}
+
+impl NetworkServer {
+    /// Create a network server without any devices or gateways. Packet loss is derived from
+    /// `seed`.
+    pub fn new(region: region::Configuration, seed: u64) -> Self {
+        Self {
+            region,
+            rng: fastrand::Rng::with_seed(seed),
+            devices: Vec::new(),
+            gateways: Vec::new(),
+            uplinks: VecDeque::new(),
+            stats: Stats::default(),
+            uplink_loss: 0,
+            downlink_loss: 0,
+            downlink_window: Window::_1,
+            adr: false,
+            net_id: [0x13, 0, 0],
+            next_dev_addr: 0x2600_0001,
+            app_nonce: 0,
+        }
+    }
+
+    /// Register a device for over-the-air activation.
+    pub fn add_otaa_device(&mut self, deveui: DevEui, appeui: AppEui, appkey: AppKey) {
+        self.devices.push(Device {
+            credentials: Some(NetworkCredentials::new(appeui, deveui, appkey)),
+            dev_nonces: Vec::new(),
+            session: None,
+        });
+    }
+
+    /// Register an activated-by-personalization device.
+    pub fn add_abp_device(
+        &mut self,
+        devaddr: DevAddr<[u8; 4]>,
+        nwkskey: NwkSKey,
+        appskey: AppSKey,
+    ) {
+        self.devices.push(Device {
+            credentials: None,
+            dev_nonces: Vec::new(),
+            session: Some(DeviceSession::new(devaddr, nwkskey, appskey)),
+        });
+    }
+
+    /// Add a gateway and return its index. Without any online gateway, no uplink is received.
+    pub fn add_gateway(&mut self, gateway: Gateway) -> usize {
+        self.gateways.push(gateway);
+        self.gateways.len() - 1
+    }
+
+    pub fn get_mut_gateway(&mut self, index: usize) -> Option<&mut Gateway> {
+        self.gateways.get_mut(index)
+    }
+
+    /// Percentage (0..=100) of uplinks within coverage that are lost.
+    pub fn set_uplink_loss(&mut self, percent: u8) {
+        self.uplink_loss = percent.min(100);
+    }
+
+    /// Percentage (0..=100) of downlinks that are lost.
+    pub fn set_downlink_loss(&mut self, percent: u8) {
+        self.downlink_loss = percent.min(100);
+    }
+
+    /// Select the receive window used for all downlinks.
+    pub fn set_downlink_window(&mut self, window: Window) {
+        self.downlink_window = window;
+    }
+
+    /// Enable adaptive data rate. The ADR bit of uplinks is not evaluated, data rate increases are
+    /// sent to all devices once [`ADR_HISTORY_LEN`] uplinks have been received.
+    pub fn set_adr(&mut self, enabled: bool) {
+        self.adr = enabled;
+    }
+
+    /// Queue application data to be sent in the next receive window of the device.
+    pub fn queue_downlink(
+        &mut self,
+        devaddr: &DevAddr<[u8; 4]>,
+        fport: u8,
+        data: &[u8],
+        confirmed: bool,
+    ) -> bool {
+        match self.session_mut(devaddr) {
+            Some(session) => {
+                session.downlinks.push_back(QueuedDownlink {
+                    fport,
+                    data: data.to_vec(),
+                    confirmed,
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Queue MAC commands to be sent in the next receive window of the device.
+    pub fn queue_mac_commands(
+        &mut self,
+        devaddr: &DevAddr<[u8; 4]>,
+        cmds: &[&dyn SerializableMacCommand],
+    ) -> bool {
+        let mut buf = [0u8; 256];
+        let Ok(len) = build_mac_commands(cmds, &mut buf[..]) else {
+            return false;
+        };
+        match self.session_mut(devaddr) {
+            Some(session) => {
+                session.mac_commands.extend_from_slice(&buf[..len]);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Take the oldest uplink received by the network server.
+    pub fn take_uplink(&mut self) -> Option<ReceivedUplink> {
+        self.uplinks.pop_front()
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Device address assigned to an OTAA device by the latest join.
+    pub fn get_dev_addr(&self, deveui: &DevEui) -> Option<DevAddr<[u8; 4]>> {
+        self.devices
+            .iter()
+            .find(|d| d.credentials.as_ref().is_some_and(|c| c.deveui() == deveui))
+            .and_then(|d| d.session.as_ref().map(|s| s.dev_addr))
+    }
+
+    /// Last FCntUp received from the device.
+    pub fn get_fcnt_up(&self, devaddr: &DevAddr<[u8; 4]>) -> Option<u32> {
+        self.session(devaddr).and_then(|s| s.fcnt_up)
+    }
+
+    /// FCntDown of the next downlink to the device.
+    pub fn get_fcnt_down(&self, devaddr: &DevAddr<[u8; 4]>) -> Option<u32> {
+        self.session(devaddr).map(|s| s.fcnt_down)
+    }
+
+    /// Process a frame transmitted at `time_ms` with the given configuration and return the
+    /// downlink to be received by the device, if any.
+    pub fn handle_uplink(
+        &mut self,
+        time_ms: u64,
+        frame: &[u8],
+        config: &TxConfig,
+    ) -> Option<ScheduledDownlink> {
+        let Some(quality) = self.receive(config) else {
+            self.stats.uplinks_lost += 1;
+            return None;
+        };
+        let mut frame = frame.to_vec();
+        let data = match parse(frame.as_mut_slice()) {
+            Ok(PhyPayload::JoinRequest(join_request)) => self.handle_join_request(join_request),
+            Ok(PhyPayload::Data(DataPayload::Encrypted(data))) => {
+                self.handle_data(time_ms, data, config, quality)
+            }
+            _ => Err(()),
+        };
+        let data = match data {
+            Ok(Some(data)) => data,
+            Ok(None) => return None,
+            Err(()) => {
+                self.stats.uplinks_rejected += 1;
+                return None;
+            }
+        };
+        if self.roll(self.downlink_loss) {
+            self.stats.downlinks_lost += 1;
+            return None;
+        }
+        self.stats.downlinks_sent += 1;
+        Some(ScheduledDownlink { window: self.downlink_window, data, quality })
+    }
+
+    /// Quality of the uplink at the best online gateway, if it was received at all.
+    fn receive(&mut self, config: &TxConfig) -> Option<RxQuality> {
+        let floor = demodulation_floor_db(config.rf.bb.sf);
+        let best =
+            self.gateways.iter().filter(|g| g.online && g.snr >= floor).max_by_key(|g| g.snr)?;
+        let quality = RxQuality::new(best.rssi, best.snr);
+        if self.roll(self.uplink_loss) {
+            return None;
+        }
+        Some(quality)
+    }
+
+    fn roll(&mut self, percent: u8) -> bool {
+        percent > 0 && self.rng.u8(0..100) < percent
+    }
+
+    fn handle_join_request(
+        &mut self,
+        join_request: JoinRequestPayload<&mut [u8]>,
+    ) -> Result<Option<Vec<u8>>, ()> {
+        let device = self
+            .devices
+            .iter_mut()
+            .find(|d| {
+                d.credentials
+                    .as_ref()
+                    .is_some_and(|c| c.deveui().as_ref() == join_request.dev_eui().as_ref())
+            })
+            .ok_or(())?;
+        let credentials = device.credentials.clone().ok_or(())?;
+        if !join_request.validate_mic(credentials.appkey().inner(), &DefaultFactory) {
+            return Err(());
+        }
+        let dev_nonce = join_request.dev_nonce().to_owned();
+        if device.dev_nonces.contains(&u16::from(dev_nonce)) {
+            return Err(());
+        }
+        device.dev_nonces.push(u16::from(dev_nonce));
+
+        self.app_nonce += 1;
+        let dev_addr = DevAddr::from(self.next_dev_addr);
+        self.next_dev_addr += 1;
+        let mut buf = [0u8; 17];
+        let mut phy = JoinAcceptCreator::new(&mut buf[..]).unwrap();
+        let app_nonce = self.app_nonce.to_le_bytes();
+        phy.set_app_nonce(&[app_nonce[0], app_nonce[1], app_nonce[2]]);
+        phy.set_net_id(&self.net_id);
+        phy.set_dev_addr(dev_addr);
+        let join_accept =
+            phy.build(credentials.appkey().inner(), &DefaultFactory).unwrap().to_vec();
+
+        // Derive the session keys the same way the device does
+        let mut copy = join_accept.clone();
+        let Ok(PhyPayload::JoinAccept(JoinAcceptPayload::Encrypted(encrypted))) =
+            parse(copy.as_mut_slice())
+        else {
+            unreachable!()
+        };
+        let decrypted = encrypted.decrypt(credentials.appkey(), &DefaultFactory);
+        let session = Session::derive_new(&decrypted, dev_nonce, &credentials);
+        device.session = Some(DeviceSession::new(dev_addr, session.nwkskey, session.appskey));
+        self.stats.joins_accepted += 1;
+        Ok(Some(join_accept))
+    }
+
+    fn handle_data(
+        &mut self,
+        time_ms: u64,
+        data: lorawan::parser::EncryptedDataPayload<&mut [u8]>,
+        config: &TxConfig,
+        quality: RxQuality,
+    ) -> Result<Option<Vec<u8>>, ()> {
+        if !data.is_uplink() {
+            return Err(());
+        }
+        let data_rate = self.region.get_datarate_of(&config.rf);
+        let max_data_rate = self.max_adr_data_rate();
+        let adr = self.adr;
+        let dev_addr = data.fhdr().dev_addr().to_owned();
+        let session = self.session_mut(&dev_addr).ok_or(())?;
+        let fcnt = session.full_fcnt_up(data.fhdr().fcnt());
+        if session.fcnt_up.is_some_and(|last| fcnt <= last) {
+            return Err(());
+        }
+        let confirmed = data.is_confirmed();
+        let decrypted = data
+            .decrypt_if_mic_ok(
+                session.nwkskey.inner(),
+                session.appskey.inner(),
+                fcnt,
+                &DefaultFactory,
+            )
+            .map_err(|_| ())?;
+        session.fcnt_up = Some(fcnt);
+
+        let mut mac_commands = decrypted.fhdr().data().to_vec();
+        let payload = match decrypted.frm_payload() {
+            FRMPayload::Data(data) => data.to_vec(),
+            FRMPayload::MACCommands(cmds) => {
+                mac_commands.extend_from_slice(cmds.data());
+                Vec::new()
+            }
+            FRMPayload::None => Vec::new(),
+        };
+        let uplink = ReceivedUplink {
+            dev_addr,
+            fcnt,
+            fport: decrypted.f_port(),
+            data: payload,
+            mac_commands,
+            confirmed,
+            data_rate,
+            frequency: config.rf.frequency,
+            quality,
+            time_ms,
+        };
+
+        if adr {
+            if let (Some(current), Some(max)) = (data_rate, max_data_rate) {
+                session.adr(config.rf.bb.sf, current, max, quality.snr());
+            }
+        }
+        let downlink = session.build_downlink(confirmed);
+        self.uplinks.push_back(uplink);
+        self.stats.uplinks_received += 1;
+        Ok(downlink)
+    }
+
+    /// Highest data rate of the region using a 125 kHz LoRa channel.
+    fn max_adr_data_rate(&self) -> Option<DR> {
+        (0..NUM_DATARATES)
+            .filter(|dr| {
+                self.region
+                    .get_datarate(*dr)
+                    .is_some_and(|d| d.fsk.is_none() && d.bandwidth == Bandwidth::_125KHz)
+            })
+            .max()
+            .map(DR::from)
+    }
+
+    fn session(&self, devaddr: &DevAddr<[u8; 4]>) -> Option<&DeviceSession> {
+        self.devices.iter().filter_map(|d| d.session.as_ref()).find(|s| s.dev_addr == *devaddr)
+    }
+
+    fn session_mut(&mut self, devaddr: &DevAddr<[u8; 4]>) -> Option<&mut DeviceSession> {
+        self.devices.iter_mut().filter_map(|d| d.session.as_mut()).find(|s| s.dev_addr == *devaddr)
+    }
+}
+
+impl DeviceSession {
+    /// Record the SNR of an uplink and queue a LinkADRReq once the history is complete and the
+    /// link margin allows to increase the data rate.
+    fn adr(&mut self, sf: SpreadingFactor, current: DR, max: DR, snr: i8) {
+        self.snr_history.push(snr);
+        if self.snr_history.len() < ADR_HISTORY_LEN {
+            return;
+        }
+        let max_snr = self.snr_history.iter().copied().max().unwrap_or(snr);
+        self.snr_history.clear();
+        let margin = i16::from(max_snr)
+            - i16::from(demodulation_floor_db(sf))
+            - i16::from(ADR_INSTALLATION_MARGIN_DB);
+        let steps = (margin.max(0) / 3) as u8;
+        let data_rate = (current as u8).saturating_add(steps).min(max as u8);
+        if data_rate <= current as u8 {
+            return;
+        }
+        let mut req = LinkADRReqCreator::new();
+        req.set_data_rate(data_rate).unwrap();
+        // Keep the current TX power
+        req.set_tx_power(0xF).unwrap();
+        // ChMaskCntl 6: all channels on
+        req.set_redundancy(0x60);
+        let mut buf = [0u8; 5];
+        let len = build_mac_commands(&[&req], &mut buf[..]).unwrap();
+        self.mac_commands.extend_from_slice(&buf[..len]);
+    }
+
+    /// Build the downlink for the receive windows following an uplink, if there is anything to
+    /// be sent.
+    fn build_downlink(&mut self, ack: bool) -> Option<Vec<u8>> {
+        let queued = !self.downlinks.is_empty();
+        let fopts_fit = self.mac_commands.len() <= FOPTS_MAX_LEN;
+        if !queued && self.mac_commands.is_empty() && !ack {
+            return None;
+        }
+        let mut buf = [0u8; 256];
+        let mut phy = DataPayloadCreator::new(&mut buf[..]).unwrap();
+        let mut fctrl = FCtrl::new(0, false);
+        if ack {
+            fctrl.set_ack();
+        }
+        phy.set_uplink(false);
+        phy.set_dev_addr(self.dev_addr);
+        phy.set_fctrl(&fctrl);
+        phy.set_fcnt(self.fcnt_down);
+        let built = if !fopts_fit || !queued && !self.mac_commands.is_empty() {
+            // MAC commands are sent on FPort 0, application data has to wait
+            phy.set_f_port(0);
+            phy.build(&[], &self.mac_commands, &self.nwkskey, &self.appskey, &DefaultFactory)
+        } else if let Some(queued) = self.downlinks.front() {
+            phy.set_f_port(queued.fport);
+            phy.set_confirmed(queued.confirmed);
+            phy.build(
+                &queued.data,
+                &self.mac_commands,
+                &self.nwkskey,
+                &self.appskey,
+                &DefaultFactory,
+            )
+        } else {
+            phy.build(&[], [], &self.nwkskey, &self.appskey, &DefaultFactory)
+        };
+        let frame = built.ok()?.to_vec();
+        // The queued downlink is only dropped once it was built successfully
+        if fopts_fit && queued {
+            self.downlinks.pop_front();
+        }
+        self.mac_commands.clear();
+        self.fcnt_down += 1;
+        Some(frame)
+    }
+}
+
+/// Lowest SNR in dB at which a gateway is able to demodulate a LoRa frame, rounded towards zero.
+fn demodulation_floor_db(sf: SpreadingFactor) -> i8 {
+    match sf {
+        SpreadingFactor::_5 => -2,
+        SpreadingFactor::_6 => -5,
+        SpreadingFactor::_7 => -7,
+        SpreadingFactor::_8 => -10,
+        SpreadingFactor::_9 => -12,
+        SpreadingFactor::_10 => -15,
+        SpreadingFactor::_11 => -17,
+        SpreadingFactor::_12 => -20,
+    }
+}
@@ -10,6 +10,10 @@ use crate::maccommands::Error;
 #[derive(Debug, PartialEq, CommandHandler)]
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 pub enum DownlinkDUTCommand<'a> {
+    /// Request the identifier and version of the certification protocol package
+    #[cmd(cid = 0x00, len = 0)]
+    PackageVersionReq(PackageVersionReqPayload),
+
     /// Request to reset the Microcontroller Unit
     #[cmd(cid = 0x01, len = 0)]
     DutResetReq(DutResetReqPayload),
@@ -18,10 +22,18 @@ pub enum DownlinkDUTCommand<'a> {
     #[cmd(cid = 0x02, len = 0)]
     DutJoinReq(DutJoinReqPayload),
 
+    /// Request to switch to the provided device class
+    #[cmd(cid = 0x03, len = 1)]
+    SwitchClassReq(SwitchClassReqPayload<'a>),
+
     /// Request to activate/deactivate Adaptive Data Rate (ADR)
     #[cmd(cid = 0x04, len = 1)]
     AdrBitChangeReq(AdrBitChangeReqPayload<'a>),
 
+    /// Request to enable/disable regional duty-cycle limitations
+    #[cmd(cid = 0x05, len = 1)]
+    RegionalDutyCycleCtrlReq(RegionalDutyCycleCtrlReqPayload<'a>),
+
     /// Change uplink periodicity to the provided value
     #[cmd(cid = 0x06, len = 1)]
     TxPeriodicityChangeReq(TxPeriodicityChangeReqPayload<'a>),
@@ -39,10 +51,38 @@ pub enum DownlinkDUTCommand<'a> {
     #[cmd(cid = 0x09, len = 0)]
     RxAppCntReq(RxAppCntReqPayload),
 
+    /// Requests the DUT to reset its RxAppCnt value to 0.
+    #[cmd(cid = 0x0a, len = 0)]
+    RxAppCntResetReq(RxAppCntResetReqPayload),
+
     /// Requests the DUT to send a LinkCheckReq MAC command.
     #[cmd(cid = 0x20, len = 0)]
     LinkCheckReq(LinkCheckReqPayload),
 
+    /// Requests the DUT to send a DeviceTimeReq MAC command.
+    #[cmd(cid = 0x21, len = 0)]
+    DeviceTimeReq(DeviceTimeReqPayload),
+
+    /// Requests the DUT to send a PingSlotInfoReq MAC command with the provided periodicity.
+    #[cmd(cid = 0x22, len = 1)]
+    PingSlotInfoReq(PingSlotInfoReqPayload<'a>),
+
+    /// Requests the DUT to provide the number of received beacons.
+    #[cmd(cid = 0x41, len = 0)]
+    BeaconCntReq(BeaconCntReqPayload),
+
+    /// Requests the DUT to reset the number of received beacons.
+    #[cmd(cid = 0x42, len = 0)]
+    BeaconCntRstReq(BeaconCntRstReqPayload),
+
+    /// Request to transmit a continuous wave
+    #[cmd(cid = 0x7d, len = 6)]
+    TxCwReq(TxCwReqPayload<'a>),
+
+    /// Request to permanently disable the certification protocol (`fport = 224`)
+    #[cmd(cid = 0x7e, len = 0)]
+    DutFPort224DisableReq(DutFPort224DisableReqPayload),
+
     /// Request to send firmware version, LoRaWAN version, and regional parameters version
     #[cmd(cid = 0x7f, len = 0)]
     DutVersionsReq(DutVersionsReqPayload),
@@ -51,6 +91,10 @@ pub enum DownlinkDUTCommand<'a> {
 #[derive(Debug, PartialEq, CommandHandler)]
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 pub enum UplinkDUTCommand<'a> {
+    /// Returns the identifier and version of the certification protocol package
+    #[cmd(cid = 0x00, len = 2)]
+    PackageVersionAns(PackageVersionAnsPayload<'a>),
+
     /// Returns data sent by EchoIncPayloadReq, where each byte except the initial CID is incremented by 1
     #[cmd(cid = 0x08)]
     EchoIncPayloadAns(EchoIncPayloadAnsPayload<'a>),
@@ -59,16 +103,47 @@ pub enum UplinkDUTCommand<'a> {
     #[cmd(cid = 0x09, len = 2)]
     RxAppCntAns(RxAppCntAnsPayload<'a>),
 
+    /// Provides information about a received beacon
+    // NB! Variable length payload without any size indication
+    #[cmd(cid = 0x40)]
+    BeaconRxStatusInd(BeaconRxStatusIndPayload<'a>),
+
+    /// Return the number of received beacons.
+    #[cmd(cid = 0x41, len = 2)]
+    BeaconCntAns(BeaconCntAnsPayload<'a>),
+
+    /// Returns firmware version, LoRaWAN version, and regional parameters version
     #[cmd(cid = 0x7f, len = 12)]
     DutVersionsAns(DutVersionsAnsPayload<'a>),
 }
 
+/// Identifier of the certification protocol package (TS009)
+pub const PACKAGE_IDENTIFIER: u8 = 6;
+
+/// Version of the certification protocol package (TS009 v1.0.0)
+pub const PACKAGE_VERSION: u8 = 1;
+
+/// Device class requested by SwitchClassReq
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum DeviceClass {
+    A,
+    B,
+    C,
+}
+
 pub fn parse_downlink_certification_messages(
     data: &[u8],
 ) -> MacCommandIterator<'_, DownlinkDUTCommand<'_>> {
     MacCommandIterator::new(data)
 }
 
+pub fn parse_uplink_certification_messages(
+    data: &[u8],
+) -> MacCommandIterator<'_, UplinkDUTCommand<'_>> {
+    MacCommandIterator::new(data)
+}
+
 impl AdrBitChangeReqPayload<'_> {
     /// Enable/disable ADR
     pub fn adr_enable(&self) -> Result<bool, Error> {
@@ -85,6 +160,161 @@ impl DutVersionsAnsCreator {
         self.data[1..=12].copy_from_slice(&data);
         self
     }
+
+    /// Set firmware, LoRaWAN and regional parameters versions, each of them given as
+    /// `[major, minor, patch, revision]`.
+    pub fn set_versions(
+        &mut self,
+        firmware: [u8; 4],
+        lorawan: [u8; 4],
+        regional_parameters: [u8; 4],
+    ) -> &mut Self {
+        self.data[1..=4].copy_from_slice(&firmware);
+        self.data[5..=8].copy_from_slice(&lorawan);
+        self.data[9..=12].copy_from_slice(&regional_parameters);
+        self
+    }
+}
+
+impl PackageVersionAnsCreator {
+    pub fn set_package(&mut self, identifier: u8, version: u8) -> &mut Self {
+        self.data[1] = identifier;
+        self.data[2] = version;
+        self
+    }
+}
+
+impl PackageVersionAnsPayload<'_> {
+    pub fn identifier(&self) -> u8 {
+        self.0[0]
+    }
+
+    pub fn version(&self) -> u8 {
+        self.0[1]
+    }
+}
+
+impl SwitchClassReqPayload<'_> {
+    pub fn class(&self) -> Result<DeviceClass, Error> {
+        match self.0[0] {
+            0 => Ok(DeviceClass::A),
+            1 => Ok(DeviceClass::B),
+            2 => Ok(DeviceClass::C),
+            _ => Err(Error::RFU),
+        }
+    }
+}
+
+impl RegionalDutyCycleCtrlReqPayload<'_> {
+    /// Enable/disable duty-cycle limitations
+    pub fn duty_cycle_enable(&self) -> Result<bool, Error> {
+        match self.0[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error::RFU),
+        }
+    }
+}
+
+impl PingSlotInfoReqPayload<'_> {
+    /// Ping slot periodicity, ping slots are opened every `2^periodicity` seconds
+    pub fn periodicity(&self) -> u8 {
+        self.0[0] & 0x07
+    }
+}
+
+impl TxCwReqPayload<'_> {
+    /// Duration of the transmission in seconds
+    pub fn timeout(&self) -> u16 {
+        u16::from_le_bytes([self.0[0], self.0[1]])
+    }
+
+    /// Frequency in Hz
+    pub fn frequency(&self) -> u32 {
+        u32::from_le_bytes([self.0[2], self.0[3], self.0[4], 0]) * 100
+    }
+
+    /// Transmit power in dBm
+    pub fn tx_power(&self) -> i8 {
+        self.0[5] as i8
+    }
+}
+
+impl BeaconCntAnsCreator {
+    pub fn set_beacon_cnt(&mut self, value: u16) -> &mut Self {
+        self.data[1..=2].copy_from_slice(&value.to_le_bytes());
+        self
+    }
+}
+
+impl BeaconCntAnsPayload<'_> {
+    pub fn beacon_cnt(&self) -> u16 {
+        u16::from_le_bytes([self.0[0], self.0[1]])
+    }
+}
+
+impl<'a> BeaconRxStatusIndPayload<'a> {
+    pub fn new(data: &'a [u8]) -> Result<Self, Error> {
+        if data.is_empty() {
+            return Err(Error::BufferTooShort);
+        }
+        Ok(BeaconRxStatusIndPayload(data))
+    }
+
+    /// Possible maximum length of the payload not including CID
+    const fn max_len() -> usize {
+        241
+    }
+
+    /// Actual length of the payload
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Beacon reception status, consisting of the received beacon's metadata and contents
+    pub fn payload(&self) -> &[u8] {
+        self.0
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct BeaconRxStatusIndCreator {
+    pub(crate) data: [u8; BeaconRxStatusIndPayload::max_len() + 1],
+    payload_len: usize,
+}
+
+impl Default for BeaconRxStatusIndCreator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BeaconRxStatusIndCreator {
+    pub fn new() -> Self {
+        let mut data = [0; BeaconRxStatusIndPayload::max_len() + 1];
+        data[0] = BeaconRxStatusIndPayload::cid();
+        Self { data, payload_len: 0 }
+    }
+    pub fn build(&self) -> &[u8] {
+        &self.data[..=self.payload_len]
+    }
+    pub const fn cid(&self) -> u8 {
+        BeaconRxStatusIndPayload::cid()
+    }
+    /// Get the length including CID.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.payload_len + 1
+    }
+
+    /// Set the beacon reception status.
+    pub fn payload(&mut self, data: &[u8]) -> &mut Self {
+        self.data[1..=data.len()].copy_from_slice(data);
+        self.payload_len = data.len();
+        self
+    }
 }
 
 impl<'a> EchoIncPayloadAnsPayload<'a> {
@@ -10,6 +10,7 @@ pub enum Error {
     DelayOutOfRange,
     MaxEirpOutOfRange,
     NanoSecondsOutOfRange,
+    PeriodicityOutOfRange,
     BufferTooShort,
 }
 
@@ -589,6 +590,39 @@ impl DeviceTimeAnsCreator {
     }
 }
 
+/// PingSlotInfoReqCreator serves for creating PingSlotInfoReq MacCommand.
+///
+/// # Examples
+///
+/// ```
+/// let mut creator = lorawan::maccommandcreator::PingSlotInfoReqCreator::new();
+/// let res = creator.set_periodicity(7).unwrap().build();
+/// ```
+#[doc(inline)]
+pub use crate::maccommands::PingSlotInfoReqCreator;
+
+impl PingSlotInfoReqCreator {
+    /// Sets the ping slot periodicity, ping slots are opened every `2^periodicity` seconds.
+    pub fn set_periodicity(&mut self, periodicity: u8) -> Result<&mut Self, Error> {
+        if periodicity > 7 {
+            return Err(Error::PeriodicityOutOfRange);
+        }
+        self.data[1] = periodicity;
+        Ok(self)
+    }
+}
+
+/// PingSlotInfoAnsCreator serves for creating PingSlotInfoAns MacCommand.
+///
+/// # Examples
+///
+/// ```
+/// let creator = lorawan::maccommandcreator::PingSlotInfoAnsCreator::new();
+/// let res = creator.build();
+/// ```
+#[doc(inline)]
+pub use crate::maccommands::PingSlotInfoAnsCreator;
+
 pub fn build_mac_commands<T: AsMut<[u8]>>(
     cmds: &[&dyn SerializableMacCommand],
     mut out: T,
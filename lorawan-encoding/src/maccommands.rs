@@ -93,6 +93,10 @@ pub enum DownlinkMacCommand<'a> {
     /// DeviceTimeAns payload handling (LoRaWAN 1.0.3+)
     #[cmd(cid = 0x0D, len = 5)]
     DeviceTimeAns(DeviceTimeAnsPayload<'a>),
+
+    /// PingSlotInfoAns payload handling (LoRaWAN 1.0.3+)
+    #[cmd(cid = 0x10, len = 0)]
+    PingSlotInfoAns(PingSlotInfoAnsPayload),
 }
 
 #[derive(Debug, PartialEq, CommandHandler)]
@@ -141,6 +145,10 @@ pub enum UplinkMacCommand<'a> {
     /// DeviceTimeReq payload handling (LoRaWAN 1.0.3+)
     #[cmd(cid = 0x0D, len = 0)]
     DeviceTimeReq(DeviceTimeReqPayload),
+
+    /// PingSlotInfoReq payload handling (LoRaWAN 1.0.3+)
+    #[cmd(cid = 0x10, len = 1)]
+    PingSlotInfoReq(PingSlotInfoReqPayload<'a>),
 }
 
 macro_rules! create_ack_fn {
@@ -475,3 +483,10 @@ impl DeviceTimeAnsPayload<'_> {
         (self.0[4] as u32) * 3906250
     }
 }
+
+impl PingSlotInfoReqPayload<'_> {
+    /// Ping slot periodicity, ping slots are opened every `2^periodicity` seconds
+    pub fn periodicity(&self) -> u8 {
+        self.0[0] & 0x07
+    }
+}
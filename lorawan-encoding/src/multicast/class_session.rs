@@ -0,0 +1,297 @@
+use crate::multicast::{
+    McClassBSessionAnsCreator, McClassBSessionAnsPayload, McClassBSessionReqCreator,
+    McClassBSessionReqPayload, McClassCSessionAnsCreator, McClassCSessionAnsPayload,
+    McClassCSessionReqCreator, McClassCSessionReqPayload,
+};
+use crate::types::{Frequency, DR};
+
+/*
+ McClassCSessionReq:
+ | McGroupIDHeader | SessionTime | SessionTimeOut | DLFrequ | DR |
+ |       1         |      4      |       1        |    3    |  1 |
+
+ McClassBSessionReq:
+ | McGroupIDHeader | SessionTime | TimeOutPeriodicity | DLFrequ | DR |
+ |       1         |      4      |         1          |    3    |  1 |
+*/
+const SESSION_TIME_OFFSET: usize = 1;
+const TIMEOUT_OFFSET: usize = 5;
+const FREQUENCY_OFFSET: usize = 6;
+const DR_OFFSET: usize = 9;
+
+fn session_time(data: &[u8]) -> u32 {
+    // tolerate unwrap here because we know the length is 4
+    u32::from_le_bytes(data[SESSION_TIME_OFFSET..TIMEOUT_OFFSET].try_into().unwrap())
+}
+
+impl McClassCSessionReqPayload<'_> {
+    pub fn mc_group_id_header(&self) -> u8 {
+        self.0[0] & 0b11
+    }
+
+    /// Start of the session in seconds since the GPS epoch, modulo 2^32.
+    pub fn session_time(&self) -> u32 {
+        session_time(self.0)
+    }
+
+    /// The session lasts for `2^session_timeout` seconds.
+    pub fn session_timeout(&self) -> u8 {
+        self.0[TIMEOUT_OFFSET] & 0x0f
+    }
+
+    pub fn dl_frequency(&self) -> Frequency<'_> {
+        Frequency::new_from_raw(&self.0[FREQUENCY_OFFSET..DR_OFFSET])
+    }
+
+    pub fn data_rate(&self) -> DR {
+        DR::from(self.0[DR_OFFSET])
+    }
+}
+
+impl McClassBSessionReqPayload<'_> {
+    pub fn mc_group_id_header(&self) -> u8 {
+        self.0[0] & 0b11
+    }
+
+    /// Start of the session in seconds since the GPS epoch, modulo 2^32. It is aligned to the
+    /// start of a beacon period.
+    pub fn session_time(&self) -> u32 {
+        session_time(self.0)
+    }
+
+    /// The session lasts for `2^session_timeout` seconds.
+    pub fn session_timeout(&self) -> u8 {
+        self.0[TIMEOUT_OFFSET] & 0x0f
+    }
+
+    /// Ping slot periodicity of the session: a ping slot is opened every `2^periodicity` seconds.
+    pub fn periodicity(&self) -> u8 {
+        (self.0[TIMEOUT_OFFSET] >> 4) & 0x07
+    }
+
+    pub fn dl_frequency(&self) -> Frequency<'_> {
+        Frequency::new_from_raw(&self.0[FREQUENCY_OFFSET..DR_OFFSET])
+    }
+
+    pub fn data_rate(&self) -> DR {
+        DR::from(self.0[DR_OFFSET])
+    }
+}
+
+macro_rules! impl_session_req_creator {
+    ($creator:ident) => {
+        impl $creator {
+            pub fn mc_group_id_header(&mut self, mc_group_id_header: u8) -> &mut Self {
+                self.data[1] = mc_group_id_header & 0b11;
+                self
+            }
+
+            pub fn session_time(&mut self, session_time: u32) -> &mut Self {
+                const OFFSET: usize = 1 + SESSION_TIME_OFFSET;
+                self.data[OFFSET..OFFSET + 4].copy_from_slice(&session_time.to_le_bytes());
+                self
+            }
+
+            pub fn session_timeout(&mut self, session_timeout: u8) -> &mut Self {
+                self.data[1 + TIMEOUT_OFFSET] &= 0xf0;
+                self.data[1 + TIMEOUT_OFFSET] |= session_timeout & 0x0f;
+                self
+            }
+
+            /// Frequency in Hz, in steps of 100 Hz.
+            pub fn dl_frequency(&mut self, frequency: u32) -> &mut Self {
+                const OFFSET: usize = 1 + FREQUENCY_OFFSET;
+                self.data[OFFSET..OFFSET + 3]
+                    .copy_from_slice(&(frequency / 100).to_le_bytes()[..3]);
+                self
+            }
+
+            pub fn data_rate(&mut self, data_rate: DR) -> &mut Self {
+                self.data[1 + DR_OFFSET] = data_rate as u8;
+                self
+            }
+        }
+    };
+}
+
+impl_session_req_creator!(McClassCSessionReqCreator);
+impl_session_req_creator!(McClassBSessionReqCreator);
+
+impl McClassBSessionReqCreator {
+    pub fn periodicity(&mut self, periodicity: u8) -> &mut Self {
+        self.data[1 + TIMEOUT_OFFSET] &= 0x0f;
+        self.data[1 + TIMEOUT_OFFSET] |= (periodicity & 0x07) << 4;
+        self
+    }
+}
+
+/*
+ McClassCSessionAns and McClassBSessionAns:
+ | Status | TimeToStart (optional) |
+ |   1    |           3            |
+
+ Status:
+ | RFU | McGroupUndefined | Freq error | DR error | McGroupIDHeader |
+ | 7:5 |        4         |     3      |    2     |       1:0       |
+
+ TimeToStart is only present if none of the error bits is set. The answer therefore has to be
+ truncated with `build_status_only` when reporting an error.
+*/
+const DR_ERROR: u8 = 0b0100;
+const FREQUENCY_ERROR: u8 = 0b1000;
+const MC_GROUP_UNDEFINED: u8 = 0b1_0000;
+
+macro_rules! impl_session_ans {
+    ($payload:ident, $creator:ident) => {
+        impl $payload<'_> {
+            pub fn mc_group_id_header(&self) -> u8 {
+                self.0[0] & 0b11
+            }
+
+            pub fn dr_error(&self) -> bool {
+                self.0[0] & DR_ERROR != 0
+            }
+
+            pub fn frequency_error(&self) -> bool {
+                self.0[0] & FREQUENCY_ERROR != 0
+            }
+
+            pub fn mc_group_undefined(&self) -> bool {
+                self.0[0] & MC_GROUP_UNDEFINED != 0
+            }
+
+            /// Seconds until the start of the session, present only if the session was accepted.
+            pub fn time_to_start(&self) -> Option<u32> {
+                if self.0[0] & (DR_ERROR | FREQUENCY_ERROR | MC_GROUP_UNDEFINED) != 0 {
+                    return None;
+                }
+                Some(u32::from_le_bytes([self.0[1], self.0[2], self.0[3], 0]))
+            }
+        }
+
+        impl $creator {
+            pub fn mc_group_id_header(&mut self, mc_group_id_header: u8) -> &mut Self {
+                self.data[1] &= !0b11;
+                self.data[1] |= mc_group_id_header & 0b11;
+                self
+            }
+
+            pub fn dr_error(&mut self, error: bool) -> &mut Self {
+                self.set_status_bit(DR_ERROR, error)
+            }
+
+            pub fn frequency_error(&mut self, error: bool) -> &mut Self {
+                self.set_status_bit(FREQUENCY_ERROR, error)
+            }
+
+            pub fn mc_group_undefined(&mut self, undefined: bool) -> &mut Self {
+                self.set_status_bit(MC_GROUP_UNDEFINED, undefined)
+            }
+
+            /// Seconds until the start of the session, saturated to 24 bits.
+            pub fn time_to_start(&mut self, seconds: u32) -> &mut Self {
+                self.data[2..5].copy_from_slice(&seconds.min(0xff_ffff).to_le_bytes()[..3]);
+                self
+            }
+
+            /// Whether the status reports an error, in which case TimeToStart is omitted.
+            pub fn is_error(&self) -> bool {
+                self.data[1] & (DR_ERROR | FREQUENCY_ERROR | MC_GROUP_UNDEFINED) != 0
+            }
+
+            /// Answer without TimeToStart, as sent when the status reports an error.
+            pub fn build_status_only(&self) -> &[u8] {
+                &self.data[..2]
+            }
+
+            fn set_status_bit(&mut self, bit: u8, set: bool) -> &mut Self {
+                if set {
+                    self.data[1] |= bit;
+                } else {
+                    self.data[1] &= !bit;
+                }
+                self
+            }
+        }
+    };
+}
+
+impl_session_ans!(McClassCSessionAnsPayload, McClassCSessionAnsCreator);
+impl_session_ans!(McClassBSessionAnsPayload, McClassBSessionAnsCreator);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multicast::{
+        parse_downlink_multicast_messages, parse_uplink_multicast_messages, DownlinkRemoteSetup,
+        UplinkRemoteSetup,
+    };
+
+    #[test]
+    fn roundtrip_class_c_session_req() {
+        let mut req = McClassCSessionReqCreator::new();
+        req.mc_group_id_header(2)
+            .session_time(0x1234_5678)
+            .session_timeout(9)
+            .dl_frequency(869_525_000)
+            .data_rate(DR::_3);
+        let mut messages = parse_downlink_multicast_messages(req.build());
+        let Some(DownlinkRemoteSetup::McClassCSessionReq(req)) = messages.next() else {
+            panic!("Expected McClassCSessionReq");
+        };
+        assert_eq!(req.mc_group_id_header(), 2);
+        assert_eq!(req.session_time(), 0x1234_5678);
+        assert_eq!(req.session_timeout(), 9);
+        assert_eq!(req.dl_frequency().value(), 869_525_000);
+        assert_eq!(req.data_rate(), DR::_3);
+    }
+
+    #[test]
+    fn roundtrip_class_b_session_req() {
+        let mut req = McClassBSessionReqCreator::new();
+        req.mc_group_id_header(1)
+            .session_time(128 * 1000)
+            .session_timeout(12)
+            .periodicity(5)
+            .dl_frequency(869_525_000)
+            .data_rate(DR::_2);
+        let mut messages = parse_downlink_multicast_messages(req.build());
+        let Some(DownlinkRemoteSetup::McClassBSessionReq(req)) = messages.next() else {
+            panic!("Expected McClassBSessionReq");
+        };
+        assert_eq!(req.mc_group_id_header(), 1);
+        assert_eq!(req.session_time(), 128 * 1000);
+        assert_eq!(req.session_timeout(), 12);
+        assert_eq!(req.periodicity(), 5);
+        assert_eq!(req.dl_frequency().value(), 869_525_000);
+        assert_eq!(req.data_rate(), DR::_2);
+    }
+
+    #[test]
+    fn roundtrip_class_c_session_ans() {
+        let mut ans = McClassCSessionAnsCreator::new();
+        ans.mc_group_id_header(3).time_to_start(0x01_0203);
+        assert!(!ans.is_error());
+        assert_eq!(ans.build(), [0x04, 0x03, 0x03, 0x02, 0x01]);
+        let mut messages = parse_uplink_multicast_messages(ans.build());
+        let Some(UplinkRemoteSetup::McClassCSessionAns(ans)) = messages.next() else {
+            panic!("Expected McClassCSessionAns");
+        };
+        assert_eq!(ans.mc_group_id_header(), 3);
+        assert_eq!(ans.time_to_start(), Some(0x01_0203));
+    }
+
+    #[test]
+    fn class_b_session_ans_error() {
+        let mut ans = McClassBSessionAnsCreator::new();
+        ans.mc_group_id_header(1).dr_error(true).frequency_error(true);
+        assert!(ans.is_error());
+        assert_eq!(ans.build_status_only(), [0x05, 0b1101]);
+        let mut ans = McClassBSessionAnsCreator::new();
+        ans.mc_group_undefined(true);
+        let ans = McClassBSessionAnsPayload::new_from_raw(&ans.build()[1..]);
+        assert!(ans.mc_group_undefined());
+        assert!(!ans.dr_error());
+        assert_eq!(ans.time_to_start(), None);
+    }
+}
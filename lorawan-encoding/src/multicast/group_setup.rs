@@ -6,11 +6,16 @@ use crate::{
     parser::McAddr,
 };
 
-#[derive(Debug)]
+/// Multicast group session, as set up by McGroupSetupReq.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 pub struct Session {
     multicast_addr: McAddr<[u8; 4]>,
     mc_net_s_key: McNetSKey,
     mc_app_s_key: McAppSKey,
+    /// Lowest frame counter accepted for the next frame: `minMcFCount` initially, then the
+    /// counter following the last accepted frame.
     pub fcnt_down: u32,
     max_fcnt_down: u32,
 }
@@ -38,6 +43,27 @@ impl Session {
     pub fn max_fcnt_down(&self) -> u32 {
         self.max_fcnt_down
     }
+
+    /// Full 32-bit frame counter of a frame transmitting the 16 least significant bits `fcnt`,
+    /// provided that it is within `fcnt_down..max_fcnt_down`. Frames which are replayed or exceed
+    /// the lifetime of the group are rejected with `None`.
+    pub fn validate_fcnt(&self, fcnt: u16) -> Option<u32> {
+        let mut fcnt = (self.fcnt_down & 0xffff_0000) | fcnt as u32;
+        if fcnt < self.fcnt_down {
+            fcnt = fcnt.checked_add(0x1_0000)?;
+        }
+        (fcnt < self.max_fcnt_down).then_some(fcnt)
+    }
+
+    /// Records a frame counter validated by [`validate_fcnt`](Self::validate_fcnt) as used.
+    pub fn accept_fcnt(&mut self, fcnt: u32) {
+        self.fcnt_down = fcnt + 1;
+    }
+
+    /// Whether all frame counters of the group have been used.
+    pub fn is_expired(&self) -> bool {
+        self.fcnt_down >= self.max_fcnt_down
+    }
 }
 
 impl McGroupSetupReqPayload<'_> {
@@ -211,4 +237,46 @@ mod tests {
         assert_eq!(mc_group_setup_req.min_mc_fcount(), 0x12345678);
         assert_eq!(mc_group_setup_req.max_mc_fcount(), 0x87654321);
     }
+
+    fn new_session(fcnt_down: u32, max_fcnt_down: u32) -> Session {
+        Session::new(
+            McAddr::from([1, 2, 3, 4]),
+            McNetSKey::from([0; 16]),
+            McAppSKey::from([0; 16]),
+            fcnt_down,
+            max_fcnt_down,
+        )
+    }
+
+    #[test]
+    fn validate_fcnt() {
+        let mut session = new_session(10, 100);
+        assert_eq!(session.validate_fcnt(9), None);
+        assert_eq!(session.validate_fcnt(10), Some(10));
+        session.accept_fcnt(10);
+        assert_eq!(session.validate_fcnt(10), None);
+        assert_eq!(session.validate_fcnt(11), Some(11));
+        assert_eq!(session.validate_fcnt(100), None);
+
+        // Lower 16 bits below the expected counter are taken as a wrap around (a replayed frame
+        // then fails the MIC check)
+        let mut session = new_session(10, 0x2_0000);
+        assert_eq!(session.validate_fcnt(9), Some(0x1_0009));
+        session.accept_fcnt(0xffff);
+        assert_eq!(session.validate_fcnt(0), Some(0x1_0000));
+        assert_eq!(session.validate_fcnt(0xffff), Some(0x1_ffff));
+        session.accept_fcnt(0x1_ffff);
+        assert!(session.is_expired());
+        assert_eq!(session.validate_fcnt(0), None);
+    }
+
+    #[test]
+    fn validate_fcnt_max() {
+        let mut session = new_session(0xffff_fffd, u32::MAX);
+        assert_eq!(session.validate_fcnt(0xffff), None);
+        assert_eq!(session.validate_fcnt(0xfffe), Some(0xffff_fffe));
+        session.accept_fcnt(0xffff_fffe);
+        assert!(session.is_expired());
+        assert_eq!(session.validate_fcnt(0), None);
+    }
 }
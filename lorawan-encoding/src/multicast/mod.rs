@@ -1,3 +1,4 @@
+mod class_session;
 mod group_setup;
 mod group_status;
 pub use group_status::McGroupStatusAnsCreator;
@@ -80,3 +80,91 @@ fn test_echopayloadreq() {
         panic!()
     }
 }
+
+#[test]
+fn test_packageversion() {
+    assert_eq!(
+        parse_downlink_certification_messages(&[0x00]).next(),
+        Some(PackageVersionReq(PackageVersionReqPayload()))
+    );
+    let mut cmd = PackageVersionAnsCreator::new();
+    cmd.set_package(PACKAGE_IDENTIFIER, PACKAGE_VERSION);
+    assert_eq!(cmd.build(), [0x00, 6, 1]);
+
+    let Some(UplinkDUTCommand::PackageVersionAns(ans)) =
+        parse_uplink_certification_messages(cmd.build()).next()
+    else {
+        panic!()
+    };
+    assert_eq!((ans.identifier(), ans.version()), (6, 1));
+}
+
+#[test]
+fn test_switchclassreq() {
+    let classes: Vec<_> =
+        parse_downlink_certification_messages(&[0x03, 0, 0x03, 1, 0x03, 2, 0x03, 3])
+            .map(|cmd| match cmd {
+                SwitchClassReq(payload) => payload.class(),
+                _ => panic!(),
+            })
+            .collect();
+    assert_eq!(
+        classes,
+        [
+            Ok(DeviceClass::A),
+            Ok(DeviceClass::B),
+            Ok(DeviceClass::C),
+            Err(lorawan::maccommands::Error::RFU)
+        ]
+    );
+}
+
+#[test]
+fn test_dutycyclectrlreq() {
+    let mut c = parse_downlink_certification_messages(&[0x05, 0x01, 0x05, 0x00]);
+    let Some(RegionalDutyCycleCtrlReq(enable)) = c.next() else { panic!() };
+    let Some(RegionalDutyCycleCtrlReq(disable)) = c.next() else { panic!() };
+    assert_eq!(enable.duty_cycle_enable(), Ok(true));
+    assert_eq!(disable.duty_cycle_enable(), Ok(false));
+}
+
+#[test]
+fn test_txcwreq() {
+    // 60 s at 868.1 MHz with 14 dBm
+    let data = [0x7d, 60, 0, 0x28, 0x76, 0x84, 14, 0x7e];
+    let mut c = parse_downlink_certification_messages(&data);
+    let Some(TxCwReq(payload)) = c.next() else { panic!() };
+    assert_eq!(payload.timeout(), 60);
+    assert_eq!(payload.frequency(), 868_100_000);
+    assert_eq!(payload.tx_power(), 14);
+    assert_eq!(c.next(), Some(DutFPort224DisableReq(DutFPort224DisableReqPayload())));
+    assert_eq!(c.next(), None);
+}
+
+#[test]
+fn test_class_b_commands() {
+    let data = [0x22, 0x07, 0x41, 0x42];
+    let mut c = parse_downlink_certification_messages(&data);
+    let Some(PingSlotInfoReq(payload)) = c.next() else { panic!() };
+    assert_eq!(payload.periodicity(), 7);
+    assert_eq!(c.next(), Some(BeaconCntReq(BeaconCntReqPayload())));
+    assert_eq!(c.next(), Some(BeaconCntRstReq(BeaconCntRstReqPayload())));
+
+    let mut cmd = BeaconCntAnsCreator::new();
+    cmd.set_beacon_cnt(0x1234);
+    assert_eq!(cmd.build(), [0x41, 0x34, 0x12]);
+
+    let mut cmd = BeaconRxStatusIndCreator::new();
+    cmd.payload(&[1, 2, 3]);
+    let mut c = parse_uplink_certification_messages(cmd.build());
+    let Some(UplinkDUTCommand::BeaconRxStatusInd(payload)) = c.next() else { panic!() };
+    assert_eq!(payload.payload(), [1, 2, 3]);
+    assert_eq!(c.next(), None);
+}
+
+#[test]
+fn test_dutversionsans_versions() {
+    let mut cmd = DutVersionsAnsCreator::new();
+    cmd.set_versions([0, 12, 2, 0], [1, 0, 4, 0], [2, 1, 0, 4]);
+    assert_eq!(cmd.build(), [0x7f, 0, 12, 2, 0, 1, 0, 4, 0, 2, 1, 0, 4]);
+}
@@ -186,6 +186,20 @@ fn test_device_time_ans_creator() {
     assert_eq!(res, [DeviceTimeAnsPayload::cid(), 64, 226, 1, 0, 31]);
 }
 
+#[test]
+fn test_ping_slot_info_req_creator() {
+    let mut creator = PingSlotInfoReqCreator::new();
+    let res = creator.set_periodicity(7).unwrap().build();
+    assert_eq!(res, [PingSlotInfoReqPayload::cid(), 7]);
+    assert!(creator.set_periodicity(8).is_err());
+}
+
+#[test]
+fn test_ping_slot_info_ans_creator() {
+    let creator = PingSlotInfoAnsCreator::new();
+    assert_eq!(creator.build(), [PingSlotInfoAnsPayload::cid()]);
+}
+
 #[test]
 fn test_build_mac_commands() {
     let rx_timing_setup_req =
@@ -267,6 +267,24 @@ fn test_device_time_ans() {
     );
 }
 
+#[test]
+fn test_ping_slot_info_req() {
+    let data = [0xfd];
+    test_helper!(
+        UplinkMacCommand,
+        data,
+        PingSlotInfoReq,
+        PingSlotInfoReqPayload,
+        1,
+        (periodicity, 5),
+    );
+}
+
+#[test]
+fn test_ping_slot_info_ans() {
+    test_helper!(DownlinkMacCommand, PingSlotInfoAns, PingSlotInfoAnsPayload);
+}
+
 #[test]
 fn test_parse_mac_commands_empty_uplink() {
     assert_eq!(parse_uplink_mac_commands(&[]).count(), 0);